@@ -12,13 +12,16 @@ pub const DEFAULT_ICANN_LOCAL_PORT: u16 = 8080;
 pub const DEFAULT_PUBKY_LOCAL_PORT: u16 = 8081;
 
 /// Configuration settings for the Nexus API service
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct ApiConfig {
     pub public_ip: IpAddr,
     pub public_addr: SocketAddr,
     pub pubky_listen_socket: SocketAddr,
     #[serde(default = "default_stack")]
     pub stack: StackConfig,
+    /// Bearer token of the admin endpoints, which are disabled if unset
+    #[serde(default)]
+    pub admin_token: Option<String>,
 }
 
 impl Default for ApiConfig {
@@ -28,10 +31,27 @@ impl Default for ApiConfig {
             public_addr: SocketAddr::from((DEFAULT_LOCAL_IP, DEFAULT_ICANN_LOCAL_PORT)),
             pubky_listen_socket: SocketAddr::from((DEFAULT_LOCAL_IP, DEFAULT_PUBKY_LOCAL_PORT)),
             stack: StackConfig::default(),
+            admin_token: None,
         }
     }
 }
 
+// The admin token is redacted, as the config is logged on start
+impl Debug for ApiConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ApiConfig")
+            .field("public_ip", &self.public_ip)
+            .field("public_addr", &self.public_addr)
+            .field("pubky_listen_socket", &self.pubky_listen_socket)
+            .field("stack", &self.stack)
+            .field(
+                "admin_token",
+                &self.admin_token.as_ref().map(|_| "<redacted>"),
+            )
+            .finish()
+    }
+}
+
 /// Converts a [`DaemonConfig`] into an [`ApiConfig`], extracting only the API-related settings
 /// and the shared application stack
 impl From<DaemonConfig> for ApiConfig {
@@ -26,7 +26,9 @@ pub use api::ApiConfig;
 pub use daemon::DaemonConfig;
 pub use stack::{default_stack, OtlpConfig, StackConfig};
 pub use watcher::WatcherConfig;
-pub use watcher::{DEFAULT_INITIAL_BACKOFF_SECS, DEFAULT_MAX_BACKOFF_SECS};
+pub use watcher::{
+    DEFAULT_ENTITY_CHANGES_MAX_LEN, DEFAULT_INITIAL_BACKOFF_SECS, DEFAULT_MAX_BACKOFF_SECS,
+};
 
 use crate::file::validate_and_expand_path;
 
@@ -1,5 +1,7 @@
 use super::file::ConfigLoader;
 use super::{default_stack, DaemonConfig, StackConfig};
+use crate::models::homeserver::HomeserverPolicy;
+use crate::models::webhook::WebhookConfig;
 use async_trait::async_trait;
 use pubky_app_specs::PubkyId;
 use serde::{Deserialize, Serialize};
@@ -13,12 +15,32 @@ pub const HOMESERVER_PUBKY: &str = "8pinxxgqs41n4aididenw5apqp1urfmzdztr8jt4abrk
 pub const DEFAULT_EVENTS_LIMIT: u32 = 1_000;
 /// Default for [WatcherConfig::monitored_homeservers_limit]
 pub const DEFAULT_MONITORED_HOMESERVERS_LIMIT: usize = 50;
+/// Default for [WatcherConfig::max_concurrent_homeservers]
+pub const DEFAULT_MAX_CONCURRENT_HOMESERVERS: usize = 4;
 /// Default for [WatcherConfig::watcher_sleep]
 pub const DEFAULT_WATCHER_SLEEP: u64 = 5_000;
+/// Default for [WatcherConfig::max_idle_sleep]
+pub const DEFAULT_MAX_IDLE_SLEEP: u64 = 60_000;
 /// Default for [WatcherConfig::initial_backoff_secs]
 pub const DEFAULT_INITIAL_BACKOFF_SECS: u64 = 60;
 /// Default for [WatcherConfig::max_backoff_secs]
 pub const DEFAULT_MAX_BACKOFF_SECS: u64 = 3_600;
+/// Default for [WatcherConfig::retry_sleep]
+pub const DEFAULT_RETRY_SLEEP: u64 = 10_000;
+/// Default for [WatcherConfig::retry_batch_size]
+pub const DEFAULT_RETRY_BATCH_SIZE: usize = 100;
+/// Default for [WatcherConfig::retry_max_attempts]
+pub const DEFAULT_RETRY_MAX_ATTEMPTS: u32 = 10;
+/// Default for [WatcherConfig::migration_check_sleep]
+pub const DEFAULT_MIGRATION_CHECK_SLEEP: u64 = 60_000;
+/// Default for [WatcherConfig::migration_check_batch_size]
+pub const DEFAULT_MIGRATION_CHECK_BATCH_SIZE: usize = 100;
+/// Default for [WatcherConfig::lease_ttl]
+pub const DEFAULT_LEASE_TTL: u64 = 3_660_000;
+/// Default for [WatcherConfig::event_log]
+pub const DEFAULT_EVENT_LOG: bool = false;
+/// Default for [WatcherConfig::entity_changes_max_len]
+pub const DEFAULT_ENTITY_CHANGES_MAX_LEN: usize = 1_000_000;
 // Default moderation service key (test user key, overridden by config.toml value)
 pub const DEFAULT_MODERATION_ID: &str = "uo7jgkykft4885n8cruizwy6khw71mnu5pq3ay9i8pw1ymcn85ko";
 // Moderation service key
@@ -42,14 +64,83 @@ pub struct WatcherConfig {
     pub events_limit: u32,
     /// Maximum number of monitored homeservers
     pub monitored_homeservers_limit: usize,
+    /// Maximum number of homeservers processed in parallel within a run. The default homeserver
+    /// is always started first; `1` processes the homeservers one after the other.
+    #[serde(default = "default_max_concurrent_homeservers")]
+    pub max_concurrent_homeservers: usize,
     /// Sleep between every full run (over all monitored homeservers), in milliseconds
     pub watcher_sleep: u64,
+    /// Ceiling of the polling interval of a homeserver without new events, in milliseconds.
+    ///
+    /// Every run without new events doubles the interval a homeserver is polled at, starting from
+    /// `watcher_sleep`, up to this value. Homeservers returning full `events_limit` pages are
+    /// re-polled right away until drained. Set it to `watcher_sleep` to poll every homeserver
+    /// on every run.
+    #[serde(default = "default_max_idle_sleep")]
+    pub max_idle_sleep: u64,
     /// Initial backoff duration (in seconds) after the first failure of a homeserver
     #[serde(default = "default_initial_backoff_secs")]
     pub initial_backoff_secs: u64,
     /// Maximum backoff duration (in seconds) for a failing homeserver
     #[serde(default = "default_max_backoff_secs")]
     pub max_backoff_secs: u64,
+    /// Sleep between every pass over the retry queue, in milliseconds
+    #[serde(default = "default_retry_sleep")]
+    pub retry_sleep: u64,
+    /// Maximum number of due retry events replayed per pass
+    #[serde(default = "default_retry_batch_size")]
+    pub retry_batch_size: usize,
+    /// Number of failed retries after which an event is moved to the dead-letter index
+    #[serde(default = "default_retry_max_attempts")]
+    pub retry_max_attempts: u32,
+    /// Sleep between every batch of users whose homeserver is re-resolved, in milliseconds.
+    ///
+    /// The homeserver of every indexed user is periodically re-resolved from their pkarr record,
+    /// in batches of `migration_check_batch_size`. Users that moved to a new homeserver get it
+    /// ingested, and their events from the previous homeserver are ignored from then on.
+    #[serde(default = "default_migration_check_sleep")]
+    pub migration_check_sleep: u64,
+    /// Number of users whose homeserver is re-resolved per batch, `0` disables the check,
+    /// see [WatcherConfig::migration_check_sleep]
+    #[serde(default = "default_migration_check_batch_size")]
+    pub migration_check_batch_size: usize,
+    /// Validate the events of the monitored homeservers without writing to the graph or Redis.
+    ///
+    /// The watcher makes a single pass from the stored cursor of each homeserver to its latest
+    /// event, logs a report of the valid events by resource type, the parse failures and the
+    /// `pubky-app-specs` validation errors, and exits.
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Append every processed event line and its fetched blob to a local log under the files path,
+    /// from which `nexusd db rebuild --from-log` can rebuild the graph and Redis indexes.
+    ///
+    /// The log is never trimmed and grows with every event and distinct blob, so it is off by
+    /// default and its disk usage is left to the operator.
+    #[serde(default = "default_event_log")]
+    pub event_log: bool,
+    /// Approximate number of records kept in the stream of the indexed entity changes, the
+    /// oldest being trimmed, see [EntityChange](crate::models::event::EntityChange)
+    #[serde(default = "default_entity_changes_max_len")]
+    pub entity_changes_max_len: usize,
+    /// Share the monitored homeservers with the other watcher instances using the same Redis.
+    ///
+    /// Each instance takes Redis leases on its share of the homeservers, so no homeserver is
+    /// processed twice, and a single elected instance runs the retry processor. The leases of
+    /// a crashed instance expire after `lease_ttl` and are picked up by the others.
+    #[serde(default)]
+    pub sharding: bool,
+    /// Time (ms) after which the homeserver and leader leases of an instance that stopped
+    /// renewing them expire, see [WatcherConfig::sharding]. The watcher raises it above
+    /// `watcher_sleep` plus the timeout of a homeserver run.
+    #[serde(default = "default_lease_ttl")]
+    pub lease_ttl: u64,
+    /// Which homeservers may be discovered through the users referenced by the indexed events
+    /// and through `PUT /v0/ingest/{user_id}`. Denylisted homeservers are no longer polled.
+    #[serde(default)]
+    pub homeserver_policy: HomeserverPolicy,
+    /// URLs the indexed notifications (follows, mentions, replies, tags…) are POSTed to
+    #[serde(default)]
+    pub webhooks: Vec<WebhookConfig>,
     #[serde(default = "default_stack")]
     pub stack: StackConfig,
     // Moderation
@@ -73,9 +164,23 @@ impl Default for WatcherConfig {
             homeserver,
             events_limit: DEFAULT_EVENTS_LIMIT,
             monitored_homeservers_limit: DEFAULT_MONITORED_HOMESERVERS_LIMIT,
+            max_concurrent_homeservers: DEFAULT_MAX_CONCURRENT_HOMESERVERS,
             watcher_sleep: DEFAULT_WATCHER_SLEEP,
+            max_idle_sleep: DEFAULT_MAX_IDLE_SLEEP,
             initial_backoff_secs: DEFAULT_INITIAL_BACKOFF_SECS,
             max_backoff_secs: DEFAULT_MAX_BACKOFF_SECS,
+            retry_sleep: DEFAULT_RETRY_SLEEP,
+            retry_batch_size: DEFAULT_RETRY_BATCH_SIZE,
+            retry_max_attempts: DEFAULT_RETRY_MAX_ATTEMPTS,
+            migration_check_sleep: DEFAULT_MIGRATION_CHECK_SLEEP,
+            migration_check_batch_size: DEFAULT_MIGRATION_CHECK_BATCH_SIZE,
+            dry_run: false,
+            event_log: DEFAULT_EVENT_LOG,
+            entity_changes_max_len: DEFAULT_ENTITY_CHANGES_MAX_LEN,
+            sharding: false,
+            lease_ttl: DEFAULT_LEASE_TTL,
+            homeserver_policy: HomeserverPolicy::default(),
+            webhooks: Vec::new(),
             moderation_id,
             moderated_tags: MODERATED_TAGS.iter().map(|s| s.to_string()).collect(),
         }
@@ -96,6 +201,14 @@ impl From<DaemonConfig> for WatcherConfig {
 #[async_trait]
 impl ConfigLoader<WatcherConfig> for WatcherConfig {}
 
+fn default_max_concurrent_homeservers() -> usize {
+    DEFAULT_MAX_CONCURRENT_HOMESERVERS
+}
+
+fn default_max_idle_sleep() -> u64 {
+    DEFAULT_MAX_IDLE_SLEEP
+}
+
 fn default_initial_backoff_secs() -> u64 {
     DEFAULT_INITIAL_BACKOFF_SECS
 }
@@ -103,3 +216,35 @@ fn default_initial_backoff_secs() -> u64 {
 fn default_max_backoff_secs() -> u64 {
     DEFAULT_MAX_BACKOFF_SECS
 }
+
+fn default_retry_sleep() -> u64 {
+    DEFAULT_RETRY_SLEEP
+}
+
+fn default_retry_batch_size() -> usize {
+    DEFAULT_RETRY_BATCH_SIZE
+}
+
+fn default_retry_max_attempts() -> u32 {
+    DEFAULT_RETRY_MAX_ATTEMPTS
+}
+
+fn default_migration_check_sleep() -> u64 {
+    DEFAULT_MIGRATION_CHECK_SLEEP
+}
+
+fn default_migration_check_batch_size() -> usize {
+    DEFAULT_MIGRATION_CHECK_BATCH_SIZE
+}
+
+fn default_event_log() -> bool {
+    DEFAULT_EVENT_LOG
+}
+
+fn default_entity_changes_max_len() -> usize {
+    DEFAULT_ENTITY_CHANGES_MAX_LEN
+}
+
+fn default_lease_ttl() -> u64 {
+    DEFAULT_LEASE_TTL
+}
@@ -3,5 +3,5 @@ mod pubky;
 mod redis;
 
 pub use neo4j::{get_neo4j_graph, Neo4jConnector, NEO4J_CONNECTOR};
-pub use pubky::{PubkyClientError, PubkyConnector};
-pub use redis::{get_redis_conn, RedisConnector, REDIS_CONNECTOR};
+pub use pubky::{DumpStorage, PubkyClientError, PubkyConnector};
+pub use redis::{get_redis_conn, get_redis_pubsub, RedisConnector, REDIS_CONNECTOR};
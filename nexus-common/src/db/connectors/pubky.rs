@@ -1,11 +1,13 @@
 use pubky::{Pubky, PubkyHttpClient};
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use thiserror::Error;
 use tokio::sync::OnceCell;
 use tracing::debug;
 
 static PUBKY_SINGLETON: OnceCell<Arc<Pubky>> = OnceCell::const_new();
+static DUMP_SINGLETON: OnceCell<Arc<DumpStorage>> = OnceCell::const_new();
 
 #[derive(Debug, Error, Clone, Serialize, Deserialize)]
 pub enum PubkyClientError {
@@ -65,4 +67,102 @@ impl PubkyConnector {
             .await
             .map(|_| ())
     }
+
+    /// Serves the public storage from a local dump directory instead of the homeservers,
+    /// see [DumpStorage]. Once set, [Self::get_dump] takes precedence over the `Pubky` client.
+    pub async fn init_from_dump(dir: PathBuf) -> Result<(), PubkyClientError> {
+        debug!("Serving public storage from the dump at {}", dir.display());
+        DUMP_SINGLETON
+            .get_or_try_init(|| async { Ok(Arc::new(DumpStorage::new(dir))) })
+            .await
+            .map(|_| ())
+    }
+
+    /// Retrieves the dump storage, if the connector was initialised with [Self::init_from_dump]
+    pub fn get_dump() -> Option<Arc<DumpStorage>> {
+        DUMP_SINGLETON.get().cloned()
+    }
+}
+
+/// Read-only stand-in for the public storage of the homeservers, backed by a local directory.
+///
+/// The blob of `pubky://{user_id}/pub/{path}` is read from `{dir}/{user_id}/pub/{path}`.
+#[derive(Debug, Clone)]
+pub struct DumpStorage {
+    dir: PathBuf,
+}
+
+impl DumpStorage {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Root directory of the dump
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Path of the blob of `uri` in the dump, `None` if `uri` is not a `pubky://` URI
+    /// or tries to escape the dump directory
+    pub fn blob_path(&self, uri: &str) -> Option<PathBuf> {
+        let path = uri.strip_prefix("pubky://")?;
+        let mut blob_path = self.dir.clone();
+        for segment in path.split('/') {
+            if segment.is_empty() || segment == "." || segment == ".." {
+                return None;
+            }
+            blob_path.push(segment);
+        }
+        Some(blob_path)
+    }
+
+    /// Reads the blob of `uri` from the dump
+    pub async fn get(&self, uri: &str) -> Result<Vec<u8>, PubkyClientError> {
+        let path = self
+            .blob_path(uri)
+            .ok_or_else(|| PubkyClientError::ClientError(format!("Invalid dump uri {uri}")))?;
+        tokio::fs::read(&path).await.map_err(|e| {
+            PubkyClientError::ClientError(format!(
+                "Fetch resource failed {uri}: {e} ({})",
+                path.display()
+            ))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USER: &str = "4snwyct86m383rsduhw5xgcxpw7c63j3pq8x4ycqikxgik8y64ro";
+
+    #[tokio::test]
+    async fn test_dump_storage_reads_blob_at_uri_path() {
+        let tmp = tempfile::tempdir().unwrap();
+        let posts = tmp.path().join(USER).join("pub/pubky.app/posts");
+        tokio::fs::create_dir_all(&posts).await.unwrap();
+        tokio::fs::write(posts.join("0034A0X7NJ52A"), b"{}")
+            .await
+            .unwrap();
+
+        let dump = DumpStorage::new(tmp.path().to_path_buf());
+        let uri = format!("pubky://{USER}/pub/pubky.app/posts/0034A0X7NJ52A");
+        assert_eq!(dump.get(&uri).await.unwrap(), b"{}");
+        assert!(dump.get(&format!("{uri}X")).await.is_err());
+    }
+
+    #[test]
+    fn test_dump_storage_rejects_escaping_uris() {
+        let dump = DumpStorage::new(PathBuf::from("/dump"));
+        assert!(dump.blob_path("https://example.com/file").is_none());
+        assert!(dump
+            .blob_path(&format!("pubky://{USER}/pub/../../etc"))
+            .is_none());
+        assert_eq!(
+            dump.blob_path(&format!("pubky://{USER}/pub/pubky.app/profile.json")),
+            Some(PathBuf::from(format!(
+                "/dump/{USER}/pub/pubky.app/profile.json"
+            )))
+        );
+    }
 }
@@ -1,5 +1,6 @@
 use crate::db::kv::{RedisError, RedisResult};
 use crate::types::DynError;
+use deadpool_redis::redis::{aio::PubSub, Client};
 use deadpool_redis::{Config, Connection, Pool, Runtime};
 use std::fmt;
 use std::sync::OnceLock;
@@ -7,6 +8,8 @@ use tracing::{debug, info};
 
 pub struct RedisConnector {
     pool: Pool,
+    /// Opens the dedicated connections pub/sub subscriptions need, which pooled ones cannot hold
+    client: Client,
 }
 
 impl RedisConnector {
@@ -32,7 +35,8 @@ impl RedisConnector {
 
         // Create the connection pool. We use the Tokio runtime.
         let pool = cfg.create_pool(Some(Runtime::Tokio1))?;
-        Ok(Self { pool })
+        let client = Client::open(uri)?;
+        Ok(Self { pool, client })
     }
 
     /// Returns a reference to the underlying connection pool.
@@ -58,6 +62,7 @@ impl fmt::Debug for RedisConnector {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("RedisConnector")
             .field("pool", &"deadpool_redis::Pool")
+            .field("client", &"redis::Client")
             .finish()
     }
 }
@@ -78,3 +83,12 @@ pub async fn get_redis_conn() -> RedisResult<Connection> {
         .await
         .map_err(|e| RedisError::ConnectionPoolError(Box::new(e)))
 }
+
+/// Opens a dedicated Redis connection to subscribe to pub/sub channels.
+pub async fn get_redis_pubsub() -> RedisResult<PubSub> {
+    let connector = REDIS_CONNECTOR
+        .get()
+        .ok_or(RedisError::ConnectionNotInitialized)?;
+
+    Ok(connector.client.get_async_pubsub().await?)
+}
@@ -119,3 +119,17 @@ pub fn delete_file(owner_id: &str, file_id: &str) -> Query {
     .param("id", file_id.to_string())
     .param("owner_id", owner_id.to_string())
 }
+
+/// Deletes a feed of a user
+/// # Arguments
+/// * `owner_id` - The unique identifier of the user who owns the feed
+/// * `feed_id` - The unique identifier of the feed
+pub fn delete_feed(owner_id: &str, feed_id: &str) -> Query {
+    Query::new(
+        "delete_feed",
+        "MATCH (f:Feed {id: $id, owner_id: $owner_id})
+         DETACH DELETE f;",
+    )
+    .param("id", feed_id.to_string())
+    .param("owner_id", owner_id.to_string())
+}
@@ -392,8 +392,7 @@ pub fn get_homeserver_by_id(id: &str) -> Query {
     Query::new(
         "get_homeserver_by_id",
         "MATCH (hs:Homeserver {id: $id})
-        WITH hs.id AS id
-        RETURN id",
+        RETURN hs.id AS id, hs.cursor AS cursor",
     )
     .param("id", id)
 }
@@ -408,6 +407,107 @@ pub fn get_all_homeservers() -> Query {
     )
 }
 
+/// Retrieve a page of homeservers with the number of users hosted on them, the homeservers
+/// hosting the most users first
+pub fn get_homeservers_directory(skip: usize, limit: usize) -> Query {
+    Query::new(
+        "get_homeservers_directory",
+        "MATCH (hs:Homeserver)
+        OPTIONAL MATCH (u:User)-[:HOSTED_ON]->(hs)
+        WITH hs, count(u) AS users
+        RETURN hs.id AS id, hs.cursor AS cursor, hs.indexed_at AS indexed_at, users
+        ORDER BY users DESC, id
+        SKIP $skip LIMIT $limit",
+    )
+    .param("skip", skip as i64)
+    .param("limit", limit as i64)
+}
+
+/// Retrieve a homeserver with the number of users hosted on it
+pub fn get_homeserver_directory_entry(id: &str) -> Query {
+    Query::new(
+        "get_homeserver_directory_entry",
+        "MATCH (hs:Homeserver {id: $id})
+        OPTIONAL MATCH (u:User)-[:HOSTED_ON]->(hs)
+        WITH hs, count(u) AS users
+        RETURN hs.id AS id, hs.cursor AS cursor, hs.indexed_at AS indexed_at, users",
+    )
+    .param("id", id)
+}
+
+/// Retrieve the homeserver a user is hosted on
+pub fn get_user_homeserver(user_id: &str) -> Query {
+    Query::new(
+        "get_user_homeserver",
+        "MATCH (u:User {id: $user_id})-[r:HOSTED_ON]->(hs:Homeserver)
+        RETURN hs.id AS homeserver_id, r.indexed_at AS indexed_at",
+    )
+    .param("user_id", user_id)
+}
+
+/// Retrieve a page of users, ordered by ID, with the homeserver they are hosted on, if known
+/// # Arguments
+/// * `after` - Only users with an ID greater than this one are returned
+/// * `limit` - Maximum number of users to return
+pub fn get_users_homeservers(after: &str, limit: usize) -> Query {
+    Query::new(
+        "get_users_homeservers",
+        "MATCH (u:User)
+        WHERE u.id > $after
+        WITH u ORDER BY u.id LIMIT $limit
+        OPTIONAL MATCH (u)-[:HOSTED_ON]->(hs:Homeserver)
+        RETURN u.id AS user_id, hs.id AS homeserver_id
+        ORDER BY user_id",
+    )
+    .param("after", after)
+    .param("limit", limit as i64)
+}
+
+/// Retrieve the `pubky://` URIs of the profile, posts, tags, follows, bookmarks, files and feeds the
+/// graph holds for a user, i.e. the resources of the user's `/pub/pubky.app/` tree that were
+/// indexed. Posts and profiles kept as `[DELETED]` placeholders are left out.
+pub fn get_user_resource_uris(user_id: &str) -> Query {
+    Query::new(
+        "get_user_resource_uris",
+        "MATCH (u:User {id: $user_id})
+        CALL {
+            WITH u
+            WITH u WHERE u.name <> '[DELETED]'
+            RETURN $prefix + 'profile.json' AS uri
+            UNION
+            WITH u
+            MATCH (u)-[:AUTHORED]->(p:Post)
+            WHERE p.content <> '[DELETED]'
+            RETURN $prefix + 'posts/' + p.id AS uri
+            UNION
+            WITH u
+            MATCH (u)-[t:TAGGED]->()
+            WHERE t.app IS NULL
+            RETURN $prefix + 'tags/' + t.id AS uri
+            UNION
+            WITH u
+            MATCH (u)-[:FOLLOWS]->(followee:User)
+            RETURN $prefix + 'follows/' + followee.id AS uri
+            UNION
+            WITH u
+            MATCH (u)-[b:BOOKMARKED]->()
+            RETURN $prefix + 'bookmarks/' + b.id AS uri
+            UNION
+            WITH u
+            MATCH (f:File {owner_id: u.id})
+            WHERE f.uri STARTS WITH $prefix + 'files/'
+            RETURN f.uri AS uri
+            UNION
+            WITH u
+            MATCH (f:Feed {owner_id: u.id})
+            RETURN $prefix + 'feeds/' + f.id AS uri
+        }
+        RETURN collect(uri) AS uris",
+    )
+    .param("user_id", user_id)
+    .param("prefix", format!("pubky://{user_id}/pub/pubky.app/"))
+}
+
 /// Retrieve tags for a user within the viewer's trusted network
 /// # Arguments
 ///
@@ -777,6 +877,36 @@ pub fn get_files_by_ids(key_pair: &[&[&str]]) -> Query {
     .param("pairs", key_pair)
 }
 
+/// Retrieve the feeds of a user, the most recently created first
+/// # Arguments
+/// * `owner_id` - The unique identifier of the user who owns the feeds
+/// * `feed_id` - Only retrieve this feed, if given
+pub fn get_user_feeds(owner_id: &str, feed_id: Option<&str>) -> Query {
+    Query::new(
+        "get_user_feeds",
+        "
+        MATCH (f:Feed {owner_id: $owner_id})
+        WHERE $feed_id IS NULL OR f.id = $feed_id
+        RETURN {
+            id: f.id,
+            owner_id: f.owner_id,
+            uri: 'pubky://' + f.owner_id + '/pub/pubky.app/feeds/' + f.id,
+            name: f.name,
+            tags: f.tags,
+            reach: f.reach,
+            layout: f.layout,
+            sort: f.sort,
+            content: f.content,
+            created_at: f.created_at,
+            indexed_at: f.indexed_at
+        } AS details
+        ORDER BY f.created_at DESC
+        ",
+    )
+    .param("owner_id", owner_id)
+    .param("feed_id", feed_id)
+}
+
 // Build the graph query based on parameters
 pub fn post_stream(
     source: StreamSource,
@@ -1020,8 +1150,10 @@ pub fn user_is_safe_to_delete(user_id: &str) -> Query {
         "user_is_safe_to_delete",
         "
         MATCH (u:User {id: $user_id})
-        // Ensures all relationships to the user (u) are checked, counting as 0 if none exist
+        // Ensures all relationships to the user (u) are checked, counting as 0 if none exist.
+        // The homeserver the user is hosted on does not keep the user around
         OPTIONAL MATCH (u)-[r]-()
+        WHERE type(r) <> 'HOSTED_ON'
         // Checks if the user has any relationships
         WITH u, NOT (COUNT(r) = 0) AS flag
         RETURN flag
@@ -1,7 +1,7 @@
 use crate::db::graph::error::{GraphError, GraphResult};
 use crate::db::graph::Query;
 use crate::models::post::PostRelationships;
-use crate::models::{file::FileDetails, post::PostDetails, user::UserDetails};
+use crate::models::{feed::FeedDetails, file::FileDetails, post::PostDetails, user::UserDetails};
 use pubky_app_specs::{ParsedUri, Resource};
 
 /// Create a user node
@@ -355,14 +355,98 @@ pub fn create_file(file: &FileDetails) -> GraphResult<Query> {
     Ok(query)
 }
 
+/// Create or update a feed of a user
+pub fn create_feed(feed: &FeedDetails) -> GraphResult<Query> {
+    // Enums are stored as their serialized string, e.g. `following`
+    let to_graph_string = |value: serde_json::Result<String>| {
+        value
+            .map(|value| value.trim_matches('"').to_string())
+            .map_err(|e| GraphError::SerializationFailed(Box::new(e)))
+    };
+    let reach = to_graph_string(serde_json::to_string(&feed.reach))?;
+    let layout = to_graph_string(serde_json::to_string(&feed.layout))?;
+    let sort = to_graph_string(serde_json::to_string(&feed.sort))?;
+    let content = match &feed.content {
+        Some(content) => Some(to_graph_string(serde_json::to_string(content))?),
+        None => None,
+    };
+
+    let query = Query::new(
+        "create_feed",
+        "MERGE (f:Feed {id: $id, owner_id: $owner_id})
+         ON CREATE SET f.indexed_at = $indexed_at
+         SET f.name = $name, f.tags = $tags, f.reach = $reach, f.layout = $layout,
+            f.sort = $sort, f.content = $content, f.created_at = $created_at;",
+    )
+    .param("id", feed.id.to_string())
+    .param("owner_id", feed.owner_id.to_string())
+    .param("indexed_at", feed.indexed_at)
+    .param("name", feed.name.to_string())
+    .param("tags", feed.tags.clone())
+    .param("reach", reach)
+    .param("layout", layout)
+    .param("sort", sort)
+    .param("content", content)
+    .param("created_at", feed.created_at);
+
+    Ok(query)
+}
+
 /// Create a homeserver
-pub fn create_homeserver(homeserver_id: &str) -> Query {
+/// # Arguments
+/// * `homeserver_id` - The homeserver ID
+/// * `indexed_at` - Timestamp (ms) recorded as first seen if the homeserver is new
+pub fn create_homeserver(homeserver_id: &str, indexed_at: i64) -> Query {
     Query::new(
         "create_homeserver",
         "MERGE (hs:Homeserver {
           id: $id
         })
+        ON CREATE SET hs.indexed_at = $indexed_at
         RETURN hs;",
     )
     .param("id", homeserver_id)
+    .param("indexed_at", indexed_at)
+}
+
+/// Record the homeserver a user is hosted on, replacing the previous one
+/// # Arguments
+/// * `user_id` - The user, which must already be indexed
+/// * `homeserver_id` - The homeserver, which must already be known
+/// * `indexed_at` - Timestamp (ms) at which the user was found on the homeserver
+pub fn set_user_homeserver(user_id: &str, homeserver_id: &str, indexed_at: i64) -> Query {
+    Query::new(
+        "set_user_homeserver",
+        "MATCH (u:User {id: $user_id}), (hs:Homeserver {id: $homeserver_id})
+        OPTIONAL MATCH (u)-[previous:HOSTED_ON]->(other:Homeserver)
+        WHERE other.id <> $homeserver_id
+        DELETE previous
+        WITH DISTINCT u, hs
+        MERGE (u)-[r:HOSTED_ON]->(hs)
+        ON CREATE SET r.indexed_at = $indexed_at
+        RETURN r.indexed_at AS indexed_at;",
+    )
+    .param("user_id", user_id)
+    .param("homeserver_id", homeserver_id)
+    .param("indexed_at", indexed_at)
+}
+
+/// Checkpoint the event cursor of a homeserver, creating the homeserver if needed
+/// # Arguments
+/// * `homeserver_id` - The homeserver ID
+/// * `cursor` - The cursor to checkpoint
+/// * `indexed_at` - Timestamp (ms) recorded as first seen if the homeserver is new
+pub fn set_homeserver_cursor(homeserver_id: &str, cursor: &str, indexed_at: i64) -> Query {
+    Query::new(
+        "set_homeserver_cursor",
+        "MERGE (hs:Homeserver {
+          id: $id
+        })
+        ON CREATE SET hs.indexed_at = $indexed_at
+        SET hs.cursor = $cursor
+        RETURN hs;",
+    )
+    .param("id", homeserver_id)
+    .param("cursor", cursor)
+    .param("indexed_at", indexed_at)
 }
@@ -77,3 +77,20 @@ pub async fn get_range(
         _ => Ok(Some(result)),
     }
 }
+
+/// Retrieves the number of elements of a Redis list, `0` if it does not exist.
+///
+/// # Arguments
+///
+/// * `prefix` - A string slice representing the prefix for the Redis keys.
+/// * `key` - A string slice representing the key under which the list is stored.
+///
+/// # Errors
+///
+/// Returns an error if the operation fails.
+pub async fn len(prefix: &str, key: &str) -> RedisResult<usize> {
+    let index_key = format!("{prefix}:{key}");
+    let mut redis_conn = get_redis_conn().await?;
+    let len: usize = redis_conn.llen(index_key).await?;
+    Ok(len)
+}
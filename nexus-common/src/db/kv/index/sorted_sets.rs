@@ -42,6 +42,49 @@ pub async fn check_member(prefix: &str, key: &str, member: &str) -> RedisResult<
     Ok(rank)
 }
 
+/// Retrieves the number of elements of a Redis sorted set.
+///
+/// # Arguments
+///
+/// * `prefix` - A string slice representing the prefix for the Redis key.
+/// * `key` - A string slice representing the key under which the sorted set is stored.
+///
+/// # Returns
+///
+/// Returns the `ZCARD` of the sorted set, which is `0` if the set does not exist.
+pub async fn get_size(prefix: &str, key: &str) -> RedisResult<usize> {
+    let index_key = format!("{prefix}:{key}");
+    let mut redis_conn = get_redis_conn().await?;
+    let size: usize = redis_conn.zcard(index_key).await?;
+    Ok(size)
+}
+
+/// Retrieves the number of elements of a Redis sorted set with a score within a range.
+///
+/// # Arguments
+///
+/// * `prefix` - A string slice representing the prefix for the Redis key.
+/// * `key` - A string slice representing the key under which the sorted set is stored.
+/// * `min_score` - The minimum score (inclusive). If `None`, no lower bound is applied.
+/// * `max_score` - The maximum score (inclusive). If `None`, no upper bound is applied.
+///
+/// # Returns
+///
+/// Returns the `ZCOUNT` of the range, which is `0` if the set does not exist.
+pub async fn count_range(
+    prefix: &str,
+    key: &str,
+    min_score: Option<f64>,
+    max_score: Option<f64>,
+) -> RedisResult<usize> {
+    let index_key = format!("{prefix}:{key}");
+    let mut redis_conn = get_redis_conn().await?;
+    let min_score = min_score.unwrap_or(f64::MIN);
+    let max_score = max_score.unwrap_or(f64::MAX);
+    let count: usize = redis_conn.zcount(index_key, min_score, max_score).await?;
+    Ok(count)
+}
+
 /// Adds elements to a Redis sorted set.
 ///
 /// This function adds elements to the specified Redis sorted set. If the set doesn't exist,
@@ -232,18 +275,76 @@ pub async fn _remove(prefix: &str, key: &str, items: &[&str]) -> RedisResult<()>
 /// * `key` - A string slice representing the key under which the sorted set is stored.
 /// * `values` - A slice of string slices representing the elements to be removed from the sorted set.
 ///
+/// # Returns
+///
+/// The number of elements removed, not counting the ones missing from the sorted set.
+///
 /// # Errors
 ///
 /// Returns an error if the operation fails.
-pub async fn del(prefix: &str, key: &str, values: &[&str]) -> RedisResult<()> {
+pub async fn del(prefix: &str, key: &str, values: &[&str]) -> RedisResult<usize> {
     if values.is_empty() {
-        return Ok(());
+        return Ok(0);
     }
 
     let index_key = format!("{prefix}:{key}");
     let mut redis_conn = get_redis_conn().await?;
 
     // Remove the elements from the sorted set
-    let _: () = redis_conn.zrem(index_key, values).await?;
-    Ok(())
+    let removed: usize = redis_conn.zrem(index_key, values).await?;
+    Ok(removed)
+}
+
+/// Removes a whole Redis sorted set and returns its elements, lowest score first.
+///
+/// The read and the removal run in a single `MULTI` transaction, so concurrent callers never
+/// take the same elements.
+///
+/// # Arguments
+///
+/// * `prefix` - A string slice representing the prefix for the Redis keys.
+/// * `key` - A string slice representing the key under which the sorted set is stored.
+///
+/// # Errors
+///
+/// Returns an error if the operation fails.
+pub async fn take_all(prefix: &str, key: &str) -> RedisResult<Vec<String>> {
+    let index_key = format!("{prefix}:{key}");
+    let mut redis_conn = get_redis_conn().await?;
+
+    let (elements, _): (Vec<String>, usize) = redis::pipe()
+        .atomic()
+        .zrange(&index_key, 0, -1)
+        .del(&index_key)
+        .query_async(&mut redis_conn)
+        .await?;
+    Ok(elements)
+}
+
+/// Removes the elements of a Redis sorted set with a score within a range.
+///
+/// # Arguments
+///
+/// * `prefix` - A string slice representing the prefix for the Redis keys.
+/// * `key` - A string slice representing the key under which the sorted set is stored.
+/// * `min_score` - The minimum score (inclusive). If `None`, no lower bound is applied.
+/// * `max_score` - The maximum score (inclusive). If `None`, no upper bound is applied.
+///
+/// # Returns
+///
+/// Returns the number of removed elements, `0` if the set does not exist.
+pub async fn remove_range(
+    prefix: &str,
+    key: &str,
+    min_score: Option<f64>,
+    max_score: Option<f64>,
+) -> RedisResult<usize> {
+    let index_key = format!("{prefix}:{key}");
+    let mut redis_conn = get_redis_conn().await?;
+    let min_score = min_score.unwrap_or(f64::MIN);
+    let max_score = max_score.unwrap_or(f64::MAX);
+    let removed: usize = redis_conn
+        .zrembyscore(index_key, min_score, max_score)
+        .await?;
+    Ok(removed)
 }
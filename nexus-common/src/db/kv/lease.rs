@@ -0,0 +1,87 @@
+use crate::db::get_redis_conn;
+use crate::db::kv::RedisResult;
+use deadpool_redis::redis::{AsyncCommands, Script};
+
+/// Acquires the lease stored at `key` for `owner`, or renews it if `owner` already holds it.
+///
+/// The lease expires after `ttl_ms` unless it is renewed, so a crashed owner
+/// eventually loses it.
+///
+/// # Returns
+/// `true` if `owner` holds the lease, `false` if another owner does
+pub async fn acquire(key: &str, owner: &str, ttl_ms: u64) -> RedisResult<bool> {
+    let mut redis_conn = get_redis_conn().await?;
+    let script = Script::new(
+        r#"
+        if redis.call('GET', KEYS[1]) == ARGV[1] then
+            return redis.call('PEXPIRE', KEYS[1], ARGV[2])
+        end
+        if redis.call('SET', KEYS[1], ARGV[1], 'NX', 'PX', ARGV[2]) then
+            return 1
+        end
+        return 0
+    "#,
+    );
+
+    let acquired: i64 = script
+        .key(key)
+        .arg(owner)
+        .arg(ttl_ms)
+        .invoke_async(&mut redis_conn)
+        .await?;
+    Ok(acquired == 1)
+}
+
+/// Releases the lease stored at `key`, only if `owner` holds it.
+///
+/// # Returns
+/// `true` if the lease was released
+pub async fn release(key: &str, owner: &str) -> RedisResult<bool> {
+    let mut redis_conn = get_redis_conn().await?;
+    let script = Script::new(
+        r#"
+        if redis.call('GET', KEYS[1]) == ARGV[1] then
+            return redis.call('DEL', KEYS[1])
+        end
+        return 0
+    "#,
+    );
+
+    let released: i64 = script
+        .key(key)
+        .arg(owner)
+        .invoke_async(&mut redis_conn)
+        .await?;
+    Ok(released == 1)
+}
+
+/// Returns the current owner of the lease stored at `key`, if any
+pub async fn owner(key: &str) -> RedisResult<Option<String>> {
+    let mut redis_conn = get_redis_conn().await?;
+    Ok(redis_conn.get(key).await?)
+}
+
+/// Records a heartbeat of `member` in the sorted set stored at `key`, scored by `now_ms`,
+/// and drops the members without a heartbeat in the last `ttl_ms`.
+///
+/// # Returns
+/// The number of live members, including `member`
+pub async fn heartbeat(key: &str, member: &str, now_ms: i64, ttl_ms: u64) -> RedisResult<usize> {
+    let mut redis_conn = get_redis_conn().await?;
+    let expired_before = now_ms - ttl_ms as i64;
+
+    let (_, _, live): (i64, i64, usize) = redis::pipe()
+        .zadd(key, member, now_ms)
+        .zrembyscore(key, "-inf", format!("({expired_before}"))
+        .zcard(key)
+        .query_async(&mut redis_conn)
+        .await?;
+    Ok(live)
+}
+
+/// Removes `member` from the sorted set of heartbeats stored at `key`
+pub async fn leave(key: &str, member: &str) -> RedisResult<()> {
+    let mut redis_conn = get_redis_conn().await?;
+    let _: i64 = redis_conn.zrem(key, member).await?;
+    Ok(())
+}
@@ -2,12 +2,17 @@ mod error;
 mod flush;
 mod index;
 mod last_save;
+pub mod lease;
+pub mod pubsub;
+pub mod streams;
 mod traits;
 
 pub use error::{RedisError, RedisResult};
 pub use flush::clear_redis;
 pub use index::json::JsonAction;
+pub use index::lists;
 pub use index::sets;
+pub use index::sorted_sets;
 pub use index::sorted_sets::{ScoreAction, SortOrder};
 pub use last_save::get_last_rdb_save_time;
 pub use traits::RedisOps;
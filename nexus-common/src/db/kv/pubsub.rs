@@ -0,0 +1,168 @@
+use crate::db::kv::RedisResult;
+use crate::db::{get_redis_conn, get_redis_pubsub};
+use deadpool_redis::redis::aio::PubSubSink;
+use deadpool_redis::redis::AsyncCommands;
+use futures::{stream, Stream, StreamExt};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::warn;
+
+/// Number of messages buffered per channel for the local subscribers. A subscriber lagging
+/// further behind misses the oldest ones.
+const CHANNEL_CAPACITY: usize = 256;
+
+/// The subscriber shared by the process, see [Subscriber]
+static SUBSCRIBER: tokio::sync::Mutex<Option<Arc<Subscriber>>> =
+    tokio::sync::Mutex::const_new(None);
+
+/// Publishes `message` on the Redis pub/sub `channel`.
+///
+/// # Returns
+/// The number of subscribers that received the message
+pub async fn publish(channel: &str, message: &str) -> RedisResult<usize> {
+    let mut redis_conn = get_redis_conn().await?;
+    let receivers: usize = redis_conn.publish(channel, message).await?;
+    Ok(receivers)
+}
+
+/// Subscribes to the Redis pub/sub `channel` until the returned stream of messages is dropped.
+///
+/// The subscriptions of the process share a single dedicated connection: the channel is
+/// subscribed on Redis by its first local subscriber and unsubscribed after its last one.
+/// Messages are only received while subscribed: the ones published before are lost. The stream
+/// ends if the connection is lost.
+pub async fn subscribe(channel: &str) -> RedisResult<impl Stream<Item = String> + Send + Unpin> {
+    let subscriber = Subscriber::get().await?;
+    let receiver = subscriber.receiver(channel).await?;
+    let subscription = Subscription {
+        channel: channel.to_string(),
+        subscriber,
+    };
+
+    let messages = stream::unfold(
+        (receiver, subscription),
+        |(mut receiver, subscription)| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(message) => return Some((message, (receiver, subscription))),
+                    Err(RecvError::Lagged(missed)) => {
+                        warn!(
+                            "Subscriber of {} lagged behind, {missed} messages missed",
+                            subscription.channel
+                        );
+                    }
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        },
+    );
+    Ok(Box::pin(messages))
+}
+
+/// Dedicated Redis connection fanning out the messages of the subscribed channels to the local
+/// subscribers of each channel
+struct Subscriber {
+    /// Held while (un)subscribing, so the Redis subscriptions follow the local ones
+    sink: tokio::sync::Mutex<PubSubSink>,
+    channels: Mutex<HashMap<String, broadcast::Sender<String>>>,
+}
+
+impl Subscriber {
+    /// Returns the subscriber of the process, connecting it on first use or after it lost its
+    /// connection
+    async fn get() -> RedisResult<Arc<Self>> {
+        let mut current = SUBSCRIBER.lock().await;
+        if let Some(subscriber) = current.as_ref() {
+            return Ok(subscriber.clone());
+        }
+
+        let (sink, mut messages) = get_redis_pubsub().await?.split();
+        let subscriber = Arc::new(Self {
+            sink: tokio::sync::Mutex::new(sink),
+            channels: Mutex::new(HashMap::new()),
+        });
+        *current = Some(subscriber.clone());
+
+        let fan_out = subscriber.clone();
+        tokio::spawn(async move {
+            while let Some(msg) = messages.next().await {
+                let channel = msg.get_channel_name();
+                let Ok(message) = msg
+                    .get_payload::<String>()
+                    .inspect_err(|e| warn!("Invalid message on {channel}: {e}"))
+                else {
+                    continue;
+                };
+                if let Some(sender) = fan_out.channels().get(channel) {
+                    // Fails only if the last receiver was dropped in the meantime
+                    _ = sender.send(message);
+                }
+            }
+
+            warn!("Redis pub/sub connection lost, closing the subscriptions");
+            let mut current = SUBSCRIBER.lock().await;
+            if current
+                .as_ref()
+                .is_some_and(|subscriber| Arc::ptr_eq(subscriber, &fan_out))
+            {
+                *current = None;
+            }
+            // Dropping the senders ends the streams of the subscribers
+            fan_out.channels().clear();
+        });
+        Ok(subscriber)
+    }
+
+    fn channels(&self) -> std::sync::MutexGuard<'_, HashMap<String, broadcast::Sender<String>>> {
+        self.channels.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Returns a receiver of the messages of `channel`, subscribing to it if needed
+    async fn receiver(&self, channel: &str) -> RedisResult<broadcast::Receiver<String>> {
+        let mut sink = self.sink.lock().await;
+        if let Some(sender) = self.channels().get(channel) {
+            return Ok(sender.subscribe());
+        }
+
+        let (sender, receiver) = broadcast::channel(CHANNEL_CAPACITY);
+        self.channels().insert(channel.to_string(), sender);
+        if let Err(e) = sink.subscribe(channel).await {
+            self.channels().remove(channel);
+            return Err(e.into());
+        }
+        Ok(receiver)
+    }
+
+    /// Unsubscribes from `channel` if it has no local subscriber left
+    async fn release(&self, channel: &str) {
+        let mut sink = self.sink.lock().await;
+        {
+            let mut channels = self.channels();
+            match channels.get(channel) {
+                Some(sender) if sender.receiver_count() == 0 => {
+                    channels.remove(channel);
+                }
+                _ => return,
+            }
+        }
+        if let Err(e) = sink.unsubscribe(channel).await {
+            warn!("Failed to unsubscribe from {channel}: {e}");
+        }
+    }
+}
+
+/// Releases the channel of a stream returned by [subscribe] once it is dropped
+struct Subscription {
+    channel: String,
+    subscriber: Arc<Subscriber>,
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        let channel = std::mem::take(&mut self.channel);
+        let subscriber = self.subscriber.clone();
+        // The receiver of the stream is dropped before the release runs
+        tokio::spawn(async move { subscriber.release(&channel).await });
+    }
+}
@@ -0,0 +1,100 @@
+use crate::db::get_redis_conn;
+use crate::db::kv::RedisResult;
+use deadpool_redis::redis::streams::{StreamMaxlen, StreamReadOptions, StreamReadReply};
+use deadpool_redis::redis::AsyncCommands;
+use std::collections::HashMap;
+
+/// An entry of a Redis stream
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamEntry {
+    /// Id assigned by Redis, `<milliseconds>-<sequence>`
+    pub id: String,
+    pub fields: HashMap<String, String>,
+}
+
+/// Appends an entry to the Redis stream `key`, trimming the stream to about `max_len` entries.
+///
+/// # Returns
+/// The id of the new entry
+pub async fn add(key: &str, fields: &[(&str, &str)], max_len: usize) -> RedisResult<String> {
+    let mut redis_conn = get_redis_conn().await?;
+    let id: String = redis_conn
+        .xadd_maxlen(key, StreamMaxlen::Approx(max_len), "*", fields)
+        .await?;
+    Ok(id)
+}
+
+/// Creates the consumer `group` of the Redis stream `key`, creating the stream if missing.
+///
+/// # Arguments
+/// * `start_id` - Id after which the group starts reading: `0` for the whole stream, `$` for
+///   the entries added from now on
+///
+/// # Returns
+/// Whether the group was created, `false` if it already existed
+pub async fn create_group(key: &str, group: &str, start_id: &str) -> RedisResult<bool> {
+    let mut redis_conn = get_redis_conn().await?;
+    let created: Result<(), _> = redis_conn
+        .xgroup_create_mkstream(key, group, start_id)
+        .await;
+    match created {
+        Ok(()) => Ok(true),
+        Err(e) if e.code() == Some("BUSYGROUP") => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Reads entries of the Redis stream `key` as `consumer` of the consumer `group`.
+///
+/// # Arguments
+/// * `id` - `>` for the entries never delivered to the group, which become pending for the
+///   consumer until acknowledged with [ack]; `0` for the entries pending for the consumer
+/// * `count` - Maximum number of entries to read
+/// * `block_ms` - If set, waits up to this many milliseconds for new entries when there are none
+pub async fn read_group(
+    key: &str,
+    group: &str,
+    consumer: &str,
+    id: &str,
+    count: usize,
+    block_ms: Option<usize>,
+) -> RedisResult<Vec<StreamEntry>> {
+    let mut options = StreamReadOptions::default()
+        .group(group, consumer)
+        .count(count);
+    if let Some(block_ms) = block_ms {
+        options = options.block(block_ms);
+    }
+
+    let mut redis_conn = get_redis_conn().await?;
+    let reply: Option<StreamReadReply> = redis_conn.xread_options(&[key], &[id], &options).await?;
+
+    let entries = reply
+        .into_iter()
+        .flat_map(|reply| reply.keys)
+        .flat_map(|stream| stream.ids)
+        .map(|entry| StreamEntry {
+            fields: entry
+                .map
+                .keys()
+                .filter_map(|field| Some((field.clone(), entry.get::<String>(field)?)))
+                .collect(),
+            id: entry.id,
+        })
+        .collect();
+    Ok(entries)
+}
+
+/// Acknowledges entries of the Redis stream `key` read by the consumer `group`, so they are no
+/// longer pending.
+///
+/// # Returns
+/// The number of acknowledged entries
+pub async fn ack(key: &str, group: &str, ids: &[&str]) -> RedisResult<usize> {
+    if ids.is_empty() {
+        return Ok(0);
+    }
+    let mut redis_conn = get_redis_conn().await?;
+    let acked: usize = redis_conn.xack(key, group, ids).await?;
+    Ok(acked)
+}
@@ -546,6 +546,45 @@ pub trait RedisOps: Serialize + DeserializeOwned + Send + Sync {
         sorted_sets::check_member(prefix, &key, &member_key).await
     }
 
+    /// Retrieves the number of elements of a Redis sorted set using the provided key parts.
+    ///
+    /// # Arguments
+    ///
+    /// * `key_parts` - A slice of string slices that represent the parts used to form the key under which the sorted set is stored.
+    /// * `prefix` - An optional string representing the prefix for the Redis keys. Defaults to `Sorted`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the operation fails, such as if the Redis connection is unavailable.
+    async fn get_sorted_set_size(key_parts: &[&str], prefix: Option<&str>) -> RedisResult<usize> {
+        let prefix = prefix.unwrap_or(SORTED_PREFIX);
+        let key = key_parts.join(":");
+        sorted_sets::get_size(prefix, &key).await
+    }
+
+    /// Retrieves the number of elements of a Redis sorted set with a score within a range.
+    ///
+    /// # Arguments
+    ///
+    /// * `key_parts` - A slice of string slices that represent the parts used to form the key under which the sorted set is stored.
+    /// * `min_score` - The minimum score (inclusive). If `None`, no lower bound is applied.
+    /// * `max_score` - The maximum score (inclusive). If `None`, no upper bound is applied.
+    /// * `prefix` - An optional string representing the prefix for the Redis keys. Defaults to `Sorted`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the operation fails, such as if the Redis connection is unavailable.
+    async fn count_sorted_set_range(
+        key_parts: &[&str],
+        min_score: Option<f64>,
+        max_score: Option<f64>,
+        prefix: Option<&str>,
+    ) -> RedisResult<usize> {
+        let prefix = prefix.unwrap_or(SORTED_PREFIX);
+        let key = key_parts.join(":");
+        sorted_sets::count_range(prefix, &key, min_score, max_score).await
+    }
+
     /// Adds elements to a Redis sorted set using the provided key parts.
     ///
     /// This method adds elements to a Redis sorted set under the key generated from the provided `key_parts`.
@@ -610,6 +649,29 @@ pub trait RedisOps: Serialize + DeserializeOwned + Send + Sync {
         Self::put_score_index_sorted_set(key_parts, member, ScoreAction::Decrement(1.0)).await
     }
 
+    /// Removes the elements of a Redis sorted set with a score within a range.
+    ///
+    /// # Arguments
+    ///
+    /// * `key_parts` - A slice of string slices that represent the parts used to form the key under which the sorted set is stored.
+    /// * `min_score` - The minimum score (inclusive). If `None`, no lower bound is applied.
+    /// * `max_score` - The maximum score (inclusive). If `None`, no upper bound is applied.
+    /// * `prefix` - An optional string representing the prefix for the Redis keys. Defaults to `Sorted`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the operation fails, such as if the Redis connection is unavailable.
+    async fn remove_sorted_set_range(
+        key_parts: &[&str],
+        min_score: Option<f64>,
+        max_score: Option<f64>,
+        prefix: Option<&str>,
+    ) -> RedisResult<usize> {
+        let prefix = prefix.unwrap_or(SORTED_PREFIX);
+        let key = key_parts.join(":");
+        sorted_sets::remove_range(prefix, &key, min_score, max_score).await
+    }
+
     /// Removes elements from a Redis sorted set using the provided key parts.
     ///
     /// This method removes the specified elements from the Redis sorted set identified by the key generated
@@ -637,7 +699,8 @@ pub trait RedisOps: Serialize + DeserializeOwned + Send + Sync {
         // Create the key by joining the key parts
         let key = key_parts.join(":");
         // Call the sorted_sets::del function to remove the items from the sorted set
-        sorted_sets::del(prefix, &key, items).await
+        sorted_sets::del(prefix, &key, items).await?;
+        Ok(())
     }
 
     /// Retrieves a range of elements from a Redis sorted set using the provided key parts.
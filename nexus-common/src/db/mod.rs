@@ -6,8 +6,8 @@ pub mod reindex;
 
 pub use config::*;
 pub use connectors::{
-    get_neo4j_graph, get_redis_conn, Neo4jConnector, PubkyClientError, PubkyConnector,
-    RedisConnector, NEO4J_CONNECTOR, REDIS_CONNECTOR,
+    get_neo4j_graph, get_redis_conn, get_redis_pubsub, DumpStorage, Neo4jConnector,
+    PubkyClientError, PubkyConnector, RedisConnector, NEO4J_CONNECTOR, REDIS_CONNECTOR,
 };
 pub use graph::error::{GraphError, GraphResult};
 pub use graph::exec::*;
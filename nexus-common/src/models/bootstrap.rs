@@ -42,6 +42,8 @@ pub struct Bootstrap {
     pub indexed: bool,
     /// Latest notifications
     pub notifications: Vec<Notification>,
+    /// Number of notifications more recent than the last read timestamp of the user
+    pub unread_notifications: usize,
 }
 
 /// IDs of objects relevant to the bootstrap payload, for example
@@ -289,6 +291,7 @@ impl Bootstrap {
                 },
             )
             .await?;
+            self.unread_notifications = Notification::unread_count(viewer_id).await?;
         }
         Ok(())
     }
@@ -3,6 +3,7 @@ use thiserror::Error;
 use crate::{
     db::{kv::RedisError, GraphError},
     media::processors::MediaProcessorError,
+    models::homeserver::AdmissionRejection,
 };
 
 #[derive(Error, Debug)]
@@ -21,6 +22,13 @@ pub enum ModelError {
     #[error("FileOperationFailed")]
     FileOperationFailed(#[from] std::io::Error),
 
+    /// Homeserver refused by the homeserver admission policy
+    #[error("Homeserver {homeserver_id} rejected: {reason}")]
+    HomeserverRejected {
+        homeserver_id: String,
+        reason: AdmissionRejection,
+    },
+
     #[error("Generic: {0}")]
     Generic(String),
 }
@@ -0,0 +1,295 @@
+use super::{Event, EventType};
+use crate::config::DEFAULT_ENTITY_CHANGES_MAX_LEN;
+use crate::db::kv::streams::{self, StreamEntry};
+use crate::db::kv::RedisResult;
+use chrono::Utc;
+use pubky_app_specs::{ParsedUri, Resource};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Redis stream of the [EntityChange]s
+pub const ENTITY_CHANGES_STREAM: &str = "Entity:Changes";
+/// Approximate number of changes kept in the stream, the oldest being trimmed,
+/// see [EntityChange::set_max_len]
+static ENTITY_CHANGES_MAX_LEN: AtomicUsize = AtomicUsize::new(DEFAULT_ENTITY_CHANGES_MAX_LEN);
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EntityType {
+    User,
+    Post,
+    Follow,
+    Bookmark,
+    Tag,
+    File,
+    Feed,
+    LastRead,
+}
+
+impl EntityType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EntityType::User => "user",
+            EntityType::Post => "post",
+            EntityType::Follow => "follow",
+            EntityType::Bookmark => "bookmark",
+            EntityType::Tag => "tag",
+            EntityType::File => "file",
+            EntityType::Feed => "feed",
+            EntityType::LastRead => "last_read",
+        }
+    }
+
+    fn parse(entity: &str) -> Option<Self> {
+        [
+            EntityType::User,
+            EntityType::Post,
+            EntityType::Follow,
+            EntityType::Bookmark,
+            EntityType::Tag,
+            EntityType::File,
+            EntityType::Feed,
+            EntityType::LastRead,
+        ]
+        .into_iter()
+        .find(|entity_type| entity_type.as_str() == entity)
+    }
+}
+
+/// Compact record of an entity indexed or deleted by the watcher, appended to the
+/// [ENTITY_CHANGES_STREAM] Redis stream.
+///
+/// Unlike the raw homeserver event lines, changes are only recorded once their event was
+/// successfully handled. Consumers read them through Redis consumer groups, e.g. to invalidate
+/// caches across API instances, or to feed search indexers and analytics.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EntityChange {
+    pub entity: EntityType,
+    /// Id of the entity among the resources of its user, the user id itself for `user` and
+    /// `last_read`, the followee id for `follow`
+    pub id: String,
+    /// User owning the entity
+    pub user_id: String,
+    pub uri: String,
+    pub operation: EventType,
+    /// Timestamp (ms) at which the change was indexed
+    pub timestamp: i64,
+}
+
+impl EntityChange {
+    /// The change applied by a handled event, if its resource is an indexed entity
+    pub fn from_event(event: &Event) -> Option<Self> {
+        Self::from_uri(&event.parsed_uri, &event.uri, event.event_type.clone())
+    }
+
+    /// The change applied to the entity at `uri`, if its resource is an indexed entity,
+    /// e.g. the content deleted by a moderation tag
+    pub fn from_uri(parsed_uri: &ParsedUri, uri: &str, operation: EventType) -> Option<Self> {
+        let user_id = parsed_uri.user_id.to_string();
+        let (entity, id) = match &parsed_uri.resource {
+            Resource::User => (EntityType::User, user_id.clone()),
+            Resource::Post(id) => (EntityType::Post, id.clone()),
+            Resource::Follow(id) => (EntityType::Follow, id.to_string()),
+            Resource::Bookmark(id) => (EntityType::Bookmark, id.clone()),
+            Resource::Tag(id) => (EntityType::Tag, id.clone()),
+            Resource::File(id) => (EntityType::File, id.clone()),
+            Resource::Feed(id) => (EntityType::Feed, id.clone()),
+            Resource::LastRead => (EntityType::LastRead, user_id.clone()),
+            Resource::Mute(_) | Resource::Blob(_) | Resource::Unknown => return None,
+        };
+        Some(Self::new(entity, id, user_id, uri, operation))
+    }
+
+    /// A change indexed now, e.g. of a universal tag, whose URI is not a [ParsedUri]
+    pub fn new(
+        entity: EntityType,
+        id: String,
+        user_id: String,
+        uri: &str,
+        operation: EventType,
+    ) -> Self {
+        Self {
+            entity,
+            id,
+            user_id,
+            uri: uri.to_string(),
+            operation,
+            timestamp: Utc::now().timestamp_millis(),
+        }
+    }
+
+    /// Appends the change to the stream
+    ///
+    /// # Returns
+    /// The id of the stream entry
+    pub async fn append(&self) -> RedisResult<String> {
+        let timestamp = self.timestamp.to_string();
+        let fields = [
+            ("entity", self.entity.as_str()),
+            ("id", self.id.as_str()),
+            ("user_id", self.user_id.as_str()),
+            ("uri", self.uri.as_str()),
+            ("operation", Self::operation_str(&self.operation)),
+            ("timestamp", timestamp.as_str()),
+        ];
+        let max_len = ENTITY_CHANGES_MAX_LEN.load(Ordering::Relaxed);
+        streams::add(ENTITY_CHANGES_STREAM, &fields, max_len).await
+    }
+
+    /// Sets the approximate number of changes kept in the stream by the next appends,
+    /// see [WatcherConfig::entity_changes_max_len](crate::config::WatcherConfig::entity_changes_max_len)
+    pub fn set_max_len(max_len: usize) {
+        ENTITY_CHANGES_MAX_LEN.store(max_len, Ordering::Relaxed);
+    }
+
+    /// Creates the consumer `group`, reading the whole stream if `from_start`, otherwise only
+    /// the changes appended from now on. Does nothing if the group already exists.
+    ///
+    /// # Returns
+    /// Whether the group was created
+    pub async fn create_group(group: &str, from_start: bool) -> RedisResult<bool> {
+        let start_id = if from_start { "0" } else { "$" };
+        streams::create_group(ENTITY_CHANGES_STREAM, group, start_id).await
+    }
+
+    /// Reads up to `count` changes never delivered to the consumer `group`, waiting up to
+    /// `block_ms` for new ones if set. They stay pending for `consumer` until acknowledged with
+    /// [EntityChange::ack].
+    ///
+    /// # Returns
+    /// The changes with their stream entry ids, oldest first
+    pub async fn read_group(
+        group: &str,
+        consumer: &str,
+        count: usize,
+        block_ms: Option<usize>,
+    ) -> RedisResult<Vec<(String, Self)>> {
+        let entries =
+            streams::read_group(ENTITY_CHANGES_STREAM, group, consumer, ">", count, block_ms)
+                .await?;
+        Ok(Self::from_entries(entries))
+    }
+
+    /// Reads up to `count` changes delivered to `consumer` but not acknowledged yet, e.g. to
+    /// resume after a crash
+    pub async fn read_pending(
+        group: &str,
+        consumer: &str,
+        count: usize,
+    ) -> RedisResult<Vec<(String, Self)>> {
+        let entries =
+            streams::read_group(ENTITY_CHANGES_STREAM, group, consumer, "0", count, None).await?;
+        Ok(Self::from_entries(entries))
+    }
+
+    /// Acknowledges processed changes by their stream entry ids
+    pub async fn ack(group: &str, ids: &[&str]) -> RedisResult<usize> {
+        streams::ack(ENTITY_CHANGES_STREAM, group, ids).await
+    }
+
+    fn from_entries(entries: Vec<StreamEntry>) -> Vec<(String, Self)> {
+        entries
+            .into_iter()
+            .filter_map(|entry| match Self::from_fields(&entry) {
+                Some(change) => Some((entry.id, change)),
+                None => {
+                    tracing::warn!("Invalid entity change {}: {:?}", entry.id, entry.fields);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    fn from_fields(entry: &StreamEntry) -> Option<Self> {
+        let field = |name: &str| entry.fields.get(name).cloned();
+        let operation = match field("operation")?.as_str() {
+            "put" => EventType::Put,
+            "del" => EventType::Del,
+            _ => return None,
+        };
+        Some(Self {
+            entity: EntityType::parse(&field("entity")?)?,
+            id: field("id")?,
+            user_id: field("user_id")?,
+            uri: field("uri")?,
+            operation,
+            timestamp: field("timestamp")?.parse().ok()?,
+        })
+    }
+
+    fn operation_str(operation: &EventType) -> &'static str {
+        match operation {
+            EventType::Put => "put",
+            EventType::Del => "del",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::event::ParseResult;
+    use std::path::PathBuf;
+
+    const USER_ID: &str = "operrr8wsbpr3ue9d4qj41ge1kcc6r7fdiy6o3ugjrrhi4y77rdo";
+
+    fn event(line: &str) -> Event {
+        match Event::parse_event(line, PathBuf::from("/tmp")).unwrap() {
+            ParseResult::Parsed(event) => event,
+            other => panic!("Unexpected parse result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn change_from_event() {
+        let post = event(&format!(
+            "PUT pubky://{USER_ID}/pub/pubky.app/posts/0032SSN7Q4EVG"
+        ));
+        let change = EntityChange::from_event(&post).unwrap();
+        assert_eq!(change.entity, EntityType::Post);
+        assert_eq!(change.id, "0032SSN7Q4EVG");
+        assert_eq!(change.user_id, USER_ID);
+        assert_eq!(change.operation, EventType::Put);
+
+        let user = event(&format!("DEL pubky://{USER_ID}/pub/pubky.app/profile.json"));
+        let change = EntityChange::from_event(&user).unwrap();
+        assert_eq!(change.entity, EntityType::User);
+        assert_eq!(change.id, USER_ID);
+        assert_eq!(change.operation, EventType::Del);
+    }
+
+    #[test]
+    fn change_from_moderated_uri() {
+        let uri = format!("pubky://{USER_ID}/pub/pubky.app/posts/0032SSN7Q4EVG");
+        let parsed_uri = ParsedUri::try_from(uri.as_str()).unwrap();
+        let change = EntityChange::from_uri(&parsed_uri, &uri, EventType::Del).unwrap();
+        assert_eq!(change.entity, EntityType::Post);
+        assert_eq!(change.id, "0032SSN7Q4EVG");
+        assert_eq!(change.uri, uri);
+        assert_eq!(change.operation, EventType::Del);
+    }
+
+    #[test]
+    fn change_fields_round_trip() {
+        let post = event(&format!(
+            "DEL pubky://{USER_ID}/pub/pubky.app/posts/0032SSN7Q4EVG"
+        ));
+        let change = EntityChange::from_event(&post).unwrap();
+        let timestamp = change.timestamp.to_string();
+        let entry = StreamEntry {
+            id: "1-0".to_string(),
+            fields: [
+                ("entity", change.entity.as_str()),
+                ("id", change.id.as_str()),
+                ("user_id", change.user_id.as_str()),
+                ("uri", change.uri.as_str()),
+                ("operation", "del"),
+                ("timestamp", timestamp.as_str()),
+            ]
+            .into_iter()
+            .map(|(field, value)| (field.to_string(), value.to_string()))
+            .collect(),
+        };
+        assert_eq!(EntityChange::from_fields(&entry), Some(change));
+    }
+}
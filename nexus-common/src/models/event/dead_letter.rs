@@ -0,0 +1,154 @@
+use crate::db::kv::{sorted_sets, RedisResult, SortOrder};
+use crate::db::RedisOps;
+use async_trait::async_trait;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::EventProcessorError;
+
+pub const DEAD_LETTER_PREFIX: &str = "DeadLetter";
+pub const DEAD_LETTER_EVENTS_INDEX: [&str; 1] = ["events"];
+pub const DEAD_LETTER_STATE_INDEX: [&str; 1] = ["state"];
+
+/// An event that exhausted its retries (or failed with a non-retryable error while being retried).
+///
+/// Dead-lettered events are never replayed automatically. Operators can inspect them and,
+/// once the underlying issue is fixed, move them back to the retry queue.
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone)]
+pub struct DeadLetterEvent {
+    /// Retry queue index key of the event, e.g. `PUT:{user_id}:{resource}:{id}`
+    pub index_key: String,
+    /// Original event line as received from the homeserver, if known
+    pub event_line: Option<String>,
+    /// Homeserver the event was polled from, if known
+    pub homeserver: Option<String>,
+    /// Last error returned while processing the event
+    #[schema(value_type = Object)]
+    pub error: EventProcessorError,
+    /// Human readable form of [`Self::error`]
+    pub reason: String,
+    /// Number of retries made before the event was dead-lettered
+    pub retry_count: u32,
+    /// Timestamp (ms) of the first failure
+    pub first_failed_at: i64,
+    /// Timestamp (ms) of the last failure
+    pub last_failed_at: i64,
+    /// Timestamp (ms) at which the event was moved to the dead-letter index
+    pub dead_lettered_at: i64,
+}
+
+#[async_trait]
+impl RedisOps for DeadLetterEvent {
+    async fn prefix() -> String {
+        String::from(DEAD_LETTER_PREFIX)
+    }
+}
+
+impl DeadLetterEvent {
+    pub fn new(
+        index_key: String,
+        event_line: Option<String>,
+        homeserver: Option<String>,
+        error: EventProcessorError,
+        retry_count: u32,
+        first_failed_at: i64,
+    ) -> Self {
+        let now = Utc::now().timestamp_millis();
+        Self {
+            index_key,
+            event_line,
+            homeserver,
+            reason: error.to_string(),
+            error,
+            retry_count,
+            first_failed_at,
+            last_failed_at: now,
+            dead_lettered_at: now,
+        }
+    }
+
+    /// Stores the event in the dead-letter sorted set, scored by [`Self::dead_lettered_at`],
+    /// and its details in the dead-letter JSON index
+    pub async fn put_to_index(&self) -> RedisResult<()> {
+        Self::put_index_sorted_set(
+            &DEAD_LETTER_EVENTS_INDEX,
+            &[(self.dead_lettered_at as f64, &self.index_key)],
+            Some(DEAD_LETTER_PREFIX),
+            None,
+        )
+        .await?;
+
+        let index = &[DEAD_LETTER_STATE_INDEX, [&self.index_key]].concat();
+        self.put_index_json(index, None, None).await
+    }
+
+    /// Retrieves a dead-lettered event by its index key
+    pub async fn get_from_index(index_key: &str) -> RedisResult<Option<Self>> {
+        let index: &Vec<&str> = &[DEAD_LETTER_STATE_INDEX, [index_key]].concat();
+        Self::try_from_index_json(index, None).await
+    }
+
+    /// Lists dead-lettered events, most recently dead-lettered first
+    pub async fn list(skip: usize, limit: usize) -> RedisResult<Vec<Self>> {
+        let index_keys = Self::list_keys(skip, limit).await?;
+        if index_keys.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let state_keys: Vec<Vec<&str>> = index_keys
+            .iter()
+            .map(|index_key| [DEAD_LETTER_STATE_INDEX, [index_key.as_str()]].concat())
+            .collect();
+        let key_parts_list: Vec<&[&str]> = state_keys.iter().map(|key| key.as_slice()).collect();
+
+        let events = Self::try_from_index_multiple_json(&key_parts_list).await?;
+        Ok(events.into_iter().flatten().collect())
+    }
+
+    /// Lists the index keys of dead-lettered events, most recently dead-lettered first
+    pub async fn list_keys(skip: usize, limit: usize) -> RedisResult<Vec<String>> {
+        let keys = Self::try_from_index_sorted_set(
+            &DEAD_LETTER_EVENTS_INDEX,
+            None,
+            None,
+            Some(skip),
+            Some(limit),
+            SortOrder::Descending,
+            Some(DEAD_LETTER_PREFIX),
+        )
+        .await?
+        .unwrap_or_default();
+
+        Ok(keys.into_iter().map(|(index_key, _)| index_key).collect())
+    }
+
+    /// Removes a dead-lettered event from both the sorted set and the JSON index
+    ///
+    /// # Returns
+    /// The number of removed events, `0` if the event was not dead-lettered
+    pub async fn remove_from_index(index_key: &str) -> RedisResult<usize> {
+        let key = DEAD_LETTER_EVENTS_INDEX.join(":");
+        let removed = sorted_sets::del(DEAD_LETTER_PREFIX, &key, &[index_key]).await?;
+
+        let index: &Vec<&str> = &[DEAD_LETTER_STATE_INDEX, [index_key]].concat();
+        Self::remove_from_index_multiple_json(&[index]).await?;
+        Ok(removed)
+    }
+
+    /// Removes every dead-lettered event and returns how many were removed
+    pub async fn purge() -> RedisResult<usize> {
+        const PURGE_BATCH_SIZE: usize = 500;
+
+        let mut purged = 0;
+        loop {
+            let index_keys = Self::list_keys(0, PURGE_BATCH_SIZE).await?;
+            if index_keys.is_empty() {
+                return Ok(purged);
+            }
+            for index_key in &index_keys {
+                purged += Self::remove_from_index(index_key).await?;
+            }
+        }
+    }
+}
@@ -67,6 +67,9 @@ impl From<ModelError> for EventProcessorError {
             ModelError::FileOperationFailed(source) => {
                 EventProcessorError::InternalError(source.to_string())
             }
+            rejected @ ModelError::HomeserverRejected { .. } => {
+                EventProcessorError::Generic(rejected.to_string())
+            }
             ModelError::Generic(message) => EventProcessorError::Generic(message),
         }
     }
@@ -118,4 +121,32 @@ impl EventProcessorError {
     pub fn generic(source: impl std::fmt::Display) -> Self {
         Self::Generic(source.to_string())
     }
+
+    /// Name of the error variant, used as a low-cardinality metric attribute
+    pub fn variant_name(&self) -> &'static str {
+        match self {
+            Self::GraphQueryFailed(_) => "GraphQueryFailed",
+            Self::MissingDependency { .. } => "MissingDependency",
+            Self::IndexOperationFailed(_) => "IndexOperationFailed",
+            Self::SkipIndexing => "SkipIndexing",
+            Self::InvalidEventLine(_) => "InvalidEventLine",
+            Self::SpecValidation(_) => "SpecValidation",
+            Self::PubkyClientError(_) => "PubkyClientError",
+            Self::MediaProcessorError(_) => "MediaProcessorError",
+            Self::InternalError(_) => "InternalError",
+            Self::StaticSaveFailed(_) => "StaticSaveFailed",
+            Self::Generic(_) => "Generic",
+        }
+    }
+
+    /// Whether re-running the same event can succeed later.
+    ///
+    /// `InvalidEventLine` and `SpecValidation` are deterministic: the same input
+    /// always produces the same error, so they must never be scheduled for retry.
+    pub fn is_retryable(&self) -> bool {
+        !matches!(
+            self,
+            EventProcessorError::InvalidEventLine(_) | EventProcessorError::SpecValidation(_)
+        )
+    }
 }
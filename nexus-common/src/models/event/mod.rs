@@ -1,12 +1,34 @@
+mod change;
+mod dead_letter;
 mod errors;
+mod stored;
+mod tail;
 
-use crate::db::{kv::RedisResult, RedisOps};
+use crate::db::kv::{lists, pubsub, RedisError, RedisResult};
+use crate::db::RedisOps;
+use chrono::Utc;
+use futures::{stream, Stream};
 use pubky_app_specs::{ParsedUri, Resource};
 use serde::{Deserialize, Serialize};
 use std::{fmt, path::PathBuf};
-use tracing::{debug, error};
+use tail::EventTail;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, error, warn};
 
+pub use change::{EntityChange, EntityType, ENTITY_CHANGES_STREAM};
+pub use dead_letter::DeadLetterEvent;
 pub use errors::EventProcessorError;
+pub use stored::{EventFilter, StoredEvent};
+
+/// Key of the events list, under the `Event` prefix
+const EVENTS_KEY: &str = "Events";
+/// Redis pub/sub channel on which the stored event lines are published
+const EVENTS_CHANNEL: &str = "Event:Channel";
+/// Number of stored events read at once when filtering them
+const EVENTS_SCAN_BATCH_SIZE: usize = 1000;
+/// Maximum number of stored events scanned by a filtered read, so a selective filter does not
+/// scan the whole list in one request
+const EVENTS_MAX_SCANNED: usize = 10_000;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum EventType {
@@ -20,7 +42,7 @@ pub enum EventType {
 pub enum ParseResult {
     /// Successfully parsed into a known, actionable event.
     Parsed(Event),
-    /// Known resource type that Nexus does not handle (e.g. LastRead, Feed, Blob).
+    /// Known resource type that Nexus does not handle (e.g. Blob).
     Skipped,
     /// URI was not recognised by pubky-app-specs. This may be an app-specific
     /// path (e.g. `/pub/mapky/tags/...`) or a genuinely malformed URI.
@@ -68,6 +90,10 @@ pub struct Event {
 
     /// Original event line as received from the homeserver.
     event_line: String,
+
+    /// Homeserver the event was read from, if known.
+    #[serde(default)]
+    pub homeserver: Option<String>,
 }
 
 impl RedisOps for Event {}
@@ -120,9 +146,7 @@ impl Event {
                 )))
             }
             // Known resources not handled by Nexus
-            Resource::LastRead | Resource::Feed(_) | Resource::Blob(_) => {
-                return Ok(ParseResult::Skipped)
-            }
+            Resource::Blob(_) => return Ok(ParseResult::Skipped),
             _ => (),
         };
 
@@ -134,35 +158,135 @@ impl Event {
             parsed_uri,
             files_path,
             event_line,
+            homeserver: None,
         }))
     }
 
-    /// Stores event line in Redis as part of the events list.
+    /// Records the homeserver the event was read from, stored along with the event line.
+    pub fn with_homeserver(mut self, homeserver: &str) -> Self {
+        self.homeserver = Some(homeserver.to_string());
+        self
+    }
+
+    /// Original event line as received from the homeserver.
+    pub fn event_line(&self) -> &str {
+        &self.event_line
+    }
+
+    /// Stores event line in Redis as part of the events list, with the current timestamp and its
+    /// homeserver, and publishes it to the followers of [Event::follow].
     #[tracing::instrument(name = "event.index.write", skip_all)]
     pub async fn store_event(&self) -> RedisResult<()> {
-        self.put_index_list(&["Events"]).await
+        let stored = StoredEvent {
+            line: self.event_line.clone(),
+            timestamp: Some(Utc::now().timestamp_millis()),
+            homeserver: self.homeserver.clone(),
+        };
+        let entry = serde_json::to_string(&stored)
+            .map_err(|e| RedisError::SerializationFailed(Box::new(e)))?;
+        lists::put(&Self::prefix().await, EVENTS_KEY, &[&entry]).await?;
+
+        // The event is stored, followers catch up on the next read
+        if let Err(e) = pubsub::publish(EVENTS_CHANNEL, &self.event_line).await {
+            warn!("Failed to publish event {}: {e}", self.uri);
+        }
+        Ok(())
     }
 
     pub async fn get_events_from_redis(
         cursor: Option<u64>,
         limit: usize,
     ) -> RedisResult<(Vec<String>, u64)> {
+        let (events, next_cursor) =
+            Self::get_stored_events(cursor, limit, &EventFilter::default()).await?;
+        let lines = events.into_iter().map(|(_, event)| event.line).collect();
+        Ok((lines, next_cursor))
+    }
+
+    /// Reads up to `limit` stored events selected by `filter`, from the `cursor` index of the
+    /// events list.
+    ///
+    /// Filtered reads scan at most [EVENTS_MAX_SCANNED] events: fewer than `limit` events may be
+    /// returned before the end of the list, reading on from the returned cursor.
+    ///
+    /// # Returns
+    /// The events with their index in the list, and the cursor to read the next events from
+    pub async fn get_stored_events(
+        cursor: Option<u64>,
+        limit: usize,
+        filter: &EventFilter,
+    ) -> RedisResult<(Vec<(u64, StoredEvent)>, u64)> {
         let start = cursor.unwrap_or(0);
-        // Clamp to usize::MAX: on 32-bit targets u64 can exceed usize; the LRANGE
-        // would return empty results for such a large index either way.
-        let start_u = usize::try_from(start).unwrap_or(usize::MAX);
-        let result = Event::try_from_index_list(&["Events"], Some(start_u), Some(limit)).await;
-
-        let events = match result {
-            Ok(r) => r.unwrap_or_default(),
-            Err(error) => {
-                error!("IndexReadFailed: Failed to read from list due to Redis error: {error}");
-                return Err(error);
-            }
-        };
+        let mut next_cursor = start;
+        let mut events = Vec::new();
+        let mut scanned = 0;
 
-        let next_cursor = start + events.len() as u64;
+        while events.len() < limit && scanned < EVENTS_MAX_SCANNED {
+            let batch_size = match filter.is_empty() {
+                true => limit,
+                false => EVENTS_SCAN_BATCH_SIZE.min(EVENTS_MAX_SCANNED - scanned),
+            };
+            // Clamp to usize::MAX: on 32-bit targets u64 can exceed usize; the LRANGE
+            // would return empty results for such a large index either way.
+            let start_u = usize::try_from(next_cursor).unwrap_or(usize::MAX);
+            let result =
+                Event::try_from_index_list(&[EVENTS_KEY], Some(start_u), Some(batch_size)).await;
+            let entries = match result {
+                Ok(r) => r.unwrap_or_default(),
+                Err(error) => {
+                    error!("IndexReadFailed: Failed to read from list due to Redis error: {error}");
+                    return Err(error);
+                }
+            };
+
+            let read = entries.len();
+            for entry in entries {
+                let index = next_cursor;
+                next_cursor += 1;
+                let event = StoredEvent::from_entry(entry);
+                if filter.matches(&event) {
+                    events.push((index, event));
+                    if events.len() == limit {
+                        break;
+                    }
+                }
+            }
+            scanned += read;
+            if read < batch_size {
+                break;
+            }
+        }
 
         Ok((events, next_cursor))
     }
+
+    /// Number of events in the events list, the cursor after the last stored event
+    pub async fn count_stored_events() -> RedisResult<u64> {
+        let len = lists::len(&Self::prefix().await, EVENTS_KEY).await?;
+        Ok(len as u64)
+    }
+
+    /// Follows the events as they are stored, until the stream is dropped.
+    ///
+    /// The new events are read once per process and broadcast to its followers. The stream ends
+    /// if it lags too far behind or the connection to Redis is lost.
+    ///
+    /// # Returns
+    /// The index of the first event of the stream, the events before it being read with
+    /// [Event::get_stored_events], and the stream of the events with their index
+    pub async fn follow(
+    ) -> RedisResult<(u64, impl Stream<Item = (u64, StoredEvent)> + Send + Unpin)> {
+        let (cursor, receiver) = EventTail::follow().await?;
+        let events = stream::unfold(receiver, |mut receiver| async move {
+            match receiver.recv().await {
+                Ok(event) => Some((event, receiver)),
+                Err(RecvError::Lagged(missed)) => {
+                    warn!("Event follower lagged behind, {missed} events missed");
+                    None
+                }
+                Err(RecvError::Closed) => None,
+            }
+        });
+        Ok((cursor, Box::pin(events)))
+    }
 }
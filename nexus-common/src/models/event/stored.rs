@@ -0,0 +1,143 @@
+use super::EventType;
+use pubky_app_specs::ParsedUri;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Event line as stored in the events list, with when and from where it was indexed
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone, PartialEq)]
+pub struct StoredEvent {
+    /// Event line as received from the homeserver, e.g. `PUT pubky://<pk>/<path>`
+    pub line: String,
+    /// Timestamp (ms) at which the event was stored, missing for the events stored before it
+    /// was recorded
+    pub timestamp: Option<i64>,
+    /// Homeserver the event was read from, missing for imported events and the events stored
+    /// before it was recorded
+    pub homeserver: Option<String>,
+}
+
+impl StoredEvent {
+    /// Parses an entry of the events list: a JSON record or, for the entries stored before the
+    /// records, the bare event line
+    pub fn from_entry(entry: String) -> Self {
+        if entry.starts_with('{') {
+            if let Ok(stored) = serde_json::from_str(&entry) {
+                return stored;
+            }
+        }
+        Self {
+            line: entry,
+            timestamp: None,
+            homeserver: None,
+        }
+    }
+
+    /// Type and URI of the event line
+    fn parts(&self) -> Option<(EventType, &str)> {
+        let (event_type, uri) = self.line.split_once(' ')?;
+        let event_type = match event_type {
+            "PUT" => EventType::Put,
+            "DEL" => EventType::Del,
+            _ => return None,
+        };
+        Some((event_type, uri))
+    }
+}
+
+/// Selects the stored events by user, resource and type. Empty filters select every event.
+#[derive(Default, Debug, Clone)]
+pub struct EventFilter {
+    pub user_id: Option<String>,
+    /// Resource name as in the homeserver paths, e.g. `posts`, `follows` or `profile.json`
+    pub resource: Option<String>,
+    pub event_type: Option<EventType>,
+}
+
+impl EventFilter {
+    pub fn is_empty(&self) -> bool {
+        self.user_id.is_none() && self.resource.is_none() && self.event_type.is_none()
+    }
+
+    pub fn matches(&self, event: &StoredEvent) -> bool {
+        if self.is_empty() {
+            return true;
+        }
+        let Some((event_type, uri)) = event.parts() else {
+            return false;
+        };
+        if self.event_type.as_ref().is_some_and(|t| *t != event_type) {
+            return false;
+        }
+        if self.user_id.is_none() && self.resource.is_none() {
+            return true;
+        }
+        let Ok(parsed_uri) = ParsedUri::try_from(uri) else {
+            return false;
+        };
+        self.user_id
+            .as_ref()
+            .is_none_or(|user_id| *user_id == *parsed_uri.user_id)
+            && self
+                .resource
+                .as_ref()
+                .is_none_or(|resource| *resource == parsed_uri.resource.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USER_ID: &str = "operrr8wsbpr3ue9d4qj41ge1kcc6r7fdiy6o3ugjrrhi4y77rdo";
+
+    fn stored(line: &str) -> StoredEvent {
+        StoredEvent::from_entry(line.to_string())
+    }
+
+    #[test]
+    fn parse_records_and_bare_lines() {
+        let line = format!("PUT pubky://{USER_ID}/pub/pubky.app/profile.json");
+        let record = StoredEvent {
+            line: line.clone(),
+            timestamp: Some(1000),
+            homeserver: Some("homeserver".to_string()),
+        };
+        let entry = serde_json::to_string(&record).unwrap();
+        assert_eq!(StoredEvent::from_entry(entry), record);
+
+        let bare = stored(&line);
+        assert_eq!(bare.line, line);
+        assert_eq!(bare.timestamp, None);
+        assert_eq!(bare.homeserver, None);
+    }
+
+    #[test]
+    fn filter_by_user_resource_and_type() {
+        let post = stored(&format!(
+            "PUT pubky://{USER_ID}/pub/pubky.app/posts/0032SSN7Q4EVG"
+        ));
+        let profile = stored(&format!("DEL pubky://{USER_ID}/pub/pubky.app/profile.json"));
+        assert!(EventFilter::default().matches(&post));
+
+        let filter = EventFilter {
+            user_id: Some(USER_ID.to_string()),
+            resource: Some("posts".to_string()),
+            ..Default::default()
+        };
+        assert!(filter.matches(&post));
+        assert!(!filter.matches(&profile));
+
+        let filter = EventFilter {
+            event_type: Some(EventType::Del),
+            ..Default::default()
+        };
+        assert!(!filter.matches(&post));
+        assert!(filter.matches(&profile));
+
+        let filter = EventFilter {
+            user_id: Some("other".to_string()),
+            ..Default::default()
+        };
+        assert!(!filter.matches(&post));
+    }
+}
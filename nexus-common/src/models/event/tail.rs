@@ -0,0 +1,122 @@
+use super::{Event, EventFilter, StoredEvent, EVENTS_CHANNEL};
+use crate::db::kv::{pubsub, RedisResult};
+use futures::StreamExt;
+use std::sync::{Arc, Mutex, MutexGuard};
+use tokio::sync::broadcast;
+use tracing::warn;
+
+/// Number of stored events read at once by the tail
+const TAIL_BATCH_SIZE: usize = 100;
+/// Number of events buffered for the local followers. A follower lagging further behind misses
+/// the oldest ones.
+const TAIL_CAPACITY: usize = 1024;
+
+/// The tail shared by the process, see [EventTail]
+static TAIL: tokio::sync::Mutex<Option<Arc<EventTail>>> = tokio::sync::Mutex::const_new(None);
+
+/// Reads the events as they are stored and broadcasts them to the local followers.
+///
+/// Every event published on the events channel triggers a single read of the newly stored
+/// events, whatever the number of followers. The tail stops once it has no follower left.
+pub(super) struct EventTail {
+    state: Mutex<TailState>,
+}
+
+struct TailState {
+    /// Index of the next event to read
+    cursor: u64,
+    sender: broadcast::Sender<(u64, StoredEvent)>,
+}
+
+impl EventTail {
+    /// Follows the tail of the process, starting it on first use.
+    ///
+    /// # Returns
+    /// The index from which the stored events are broadcast, and the receiver of the events with
+    /// their index
+    pub(super) async fn follow() -> RedisResult<(u64, broadcast::Receiver<(u64, StoredEvent)>)> {
+        let mut current = TAIL.lock().await;
+        if let Some(tail) = current.as_ref() {
+            let state = tail.state();
+            return Ok((state.cursor, state.sender.subscribe()));
+        }
+
+        // Subscribed before counting, so no event is stored unnoticed in between
+        let mut published = pubsub::subscribe(EVENTS_CHANNEL).await?;
+        let cursor = Event::count_stored_events().await?;
+        let (sender, receiver) = broadcast::channel(TAIL_CAPACITY);
+        let tail = Arc::new(Self {
+            state: Mutex::new(TailState { cursor, sender }),
+        });
+        *current = Some(tail.clone());
+
+        tokio::spawn(async move {
+            while published.next().await.is_some() {
+                if tail.release_if_unfollowed().await {
+                    return;
+                }
+                tail.read_new().await;
+            }
+            warn!("Events channel closed, closing the event followers");
+            tail.release().await;
+        });
+        Ok((cursor, receiver))
+    }
+
+    fn state(&self) -> MutexGuard<'_, TailState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Reads the events stored since the last read and broadcasts them
+    async fn read_new(&self) {
+        loop {
+            let cursor = self.state().cursor;
+            let read =
+                Event::get_stored_events(Some(cursor), TAIL_BATCH_SIZE, &EventFilter::default())
+                    .await;
+            let (events, next_cursor) = match read {
+                Ok(read) => read,
+                // Read again on the next publish
+                Err(e) => {
+                    warn!("Failed to read the stored events from cursor {cursor}: {e}");
+                    return;
+                }
+            };
+
+            let full_batch = events.len() == TAIL_BATCH_SIZE;
+            let mut state = self.state();
+            for event in events {
+                // Fails only if there is no follower
+                _ = state.sender.send(event);
+            }
+            state.cursor = next_cursor;
+            if !full_batch {
+                return;
+            }
+        }
+    }
+
+    /// Stops following the events channel if the tail has no follower left
+    ///
+    /// # Returns
+    /// Whether the tail was released
+    async fn release_if_unfollowed(self: &Arc<Self>) -> bool {
+        // Locked first, so no follower subscribes in between
+        let mut current = TAIL.lock().await;
+        if self.state().sender.receiver_count() > 0 {
+            return false;
+        }
+        if current.as_ref().is_some_and(|tail| Arc::ptr_eq(tail, self)) {
+            *current = None;
+        }
+        true
+    }
+
+    /// Makes room for a new tail, dropping the sender of this one ends its followers
+    async fn release(self: &Arc<Self>) {
+        let mut current = TAIL.lock().await;
+        if current.as_ref().is_some_and(|tail| Arc::ptr_eq(tail, self)) {
+            *current = None;
+        }
+    }
+}
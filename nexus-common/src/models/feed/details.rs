@@ -0,0 +1,234 @@
+use crate::db::kv::{RedisResult, SortOrder};
+use crate::db::{exec_single_row, fetch_all_rows_from_graph, queries, GraphResult, RedisOps};
+use crate::models::error::ModelResult;
+use crate::models::post::{PostStream, StreamSource};
+use crate::types::{Pagination, StreamSorting};
+use chrono::Utc;
+use pubky_app_specs::{
+    feed_uri_builder, PubkyAppFeed, PubkyAppFeedLayout, PubkyAppFeedReach, PubkyAppFeedSort,
+    PubkyAppPostKind,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Feeds of a user, scored by their creation timestamp
+const USER_FEEDS_KEY_PART: &str = "Feeds";
+
+/// A feed saved by a user: a named post stream configuration, so clients get the same
+/// results on every device.
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone, PartialEq)]
+pub struct FeedDetails {
+    pub id: String,
+    pub owner_id: String,
+    pub uri: String,
+    pub name: String,
+    /// Only posts tagged with one of these labels are part of the feed
+    pub tags: Option<Vec<String>>,
+    pub reach: PubkyAppFeedReach,
+    pub layout: PubkyAppFeedLayout,
+    pub sort: PubkyAppFeedSort,
+    /// Only posts of this kind are part of the feed
+    pub content: Option<PubkyAppPostKind>,
+    pub created_at: i64,
+    pub indexed_at: i64,
+}
+
+impl RedisOps for FeedDetails {}
+
+impl FeedDetails {
+    pub fn from_homeserver(homeserver_feed: PubkyAppFeed, owner_id: &str, feed_id: &str) -> Self {
+        let config = homeserver_feed.feed;
+        Self {
+            id: feed_id.to_string(),
+            owner_id: owner_id.to_string(),
+            uri: feed_uri_builder(owner_id.to_string(), feed_id.to_string()),
+            name: homeserver_feed.name,
+            tags: config.tags,
+            reach: config.reach,
+            layout: config.layout,
+            sort: config.sort,
+            content: config.content,
+            created_at: homeserver_feed.created_at,
+            indexed_at: Utc::now().timestamp_millis(),
+        }
+    }
+
+    /// Retrieves a feed of a user, first trying Redis, then Neo4j
+    pub async fn get_by_id(owner_id: &str, feed_id: &str) -> ModelResult<Option<Self>> {
+        if let Some(feed) = Self::try_from_index_json(&[owner_id, feed_id], None).await? {
+            return Ok(Some(feed));
+        }
+        match Self::get_from_graph(owner_id, Some(feed_id)).await?.pop() {
+            Some(feed) => {
+                feed.put_to_index().await?;
+                Ok(Some(feed))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Lists the feeds of a user, the most recently created first
+    pub async fn get_by_owner(
+        owner_id: &str,
+        skip: Option<usize>,
+        limit: Option<usize>,
+    ) -> ModelResult<Vec<Self>> {
+        let feed_ids = Self::try_from_index_sorted_set(
+            &[USER_FEEDS_KEY_PART, owner_id],
+            None,
+            None,
+            skip,
+            limit,
+            SortOrder::Descending,
+            None,
+        )
+        .await?;
+
+        let Some(feed_ids) = feed_ids else {
+            // The feeds of the user are not indexed yet
+            let feeds = Self::get_from_graph(owner_id, None).await?;
+            for feed in &feeds {
+                feed.put_to_index().await?;
+            }
+            let skip = skip.unwrap_or(0);
+            let limit = limit.unwrap_or(feeds.len());
+            return Ok(feeds.into_iter().skip(skip).take(limit).collect());
+        };
+
+        let key_parts: Vec<[&str; 2]> = feed_ids
+            .iter()
+            .map(|(feed_id, _)| [owner_id, feed_id.as_str()])
+            .collect();
+        let key_parts: Vec<&[&str]> = key_parts.iter().map(|parts| &parts[..]).collect();
+        let feeds = Self::try_from_index_multiple_json(&key_parts).await?;
+        Ok(feeds.into_iter().flatten().collect())
+    }
+
+    /// Retrieves the feeds of a user from the graph, or only `feed_id` if given
+    pub async fn get_from_graph(owner_id: &str, feed_id: Option<&str>) -> GraphResult<Vec<Self>> {
+        let query = queries::get::get_user_feeds(owner_id, feed_id);
+        let rows = fetch_all_rows_from_graph(query).await?;
+        rows.into_iter()
+            .map(|row| Ok(row.get("details")?))
+            .collect()
+    }
+
+    pub async fn put_to_graph(&self) -> GraphResult<()> {
+        exec_single_row(queries::put::create_feed(self)?).await
+    }
+
+    pub async fn put_to_index(&self) -> RedisResult<()> {
+        self.put_index_json(&[&self.owner_id, &self.id], None, None)
+            .await?;
+        Self::put_index_sorted_set(
+            &[USER_FEEDS_KEY_PART, &self.owner_id],
+            &[(self.created_at as f64, &self.id)],
+            None,
+            None,
+        )
+        .await
+    }
+
+    pub async fn delete(&self) -> ModelResult<()> {
+        exec_single_row(queries::del::delete_feed(&self.owner_id, &self.id)).await?;
+        Self::remove_from_index_multiple_json(&[&[&self.owner_id, &self.id]]).await?;
+        Self::remove_from_index_sorted_set(
+            None,
+            &[USER_FEEDS_KEY_PART, &self.owner_id],
+            &[&self.id],
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Deletes all the feeds of a user, e.g. along with the user
+    pub async fn delete_by_owner(owner_id: &str) -> ModelResult<()> {
+        for feed in Self::get_from_graph(owner_id, None).await? {
+            feed.delete().await?;
+        }
+        Ok(())
+    }
+
+    /// Source of the posts of the feed, the reach being taken from the owner of the feed
+    pub fn stream_source(&self) -> StreamSource {
+        let observer_id = self.owner_id.clone();
+        match self.reach {
+            PubkyAppFeedReach::Following => StreamSource::Following { observer_id },
+            PubkyAppFeedReach::Followers => StreamSource::Followers { observer_id },
+            PubkyAppFeedReach::Friends => StreamSource::Friends { observer_id },
+            PubkyAppFeedReach::All => StreamSource::All,
+        }
+    }
+
+    pub fn stream_sorting(&self) -> StreamSorting {
+        match self.sort {
+            PubkyAppFeedSort::Recent => StreamSorting::Timeline,
+            PubkyAppFeedSort::Popularity => StreamSorting::TotalEngagement,
+        }
+    }
+
+    /// Runs the configuration of the feed through [PostStream::get_posts]
+    pub async fn get_posts(
+        &self,
+        pagination: Pagination,
+        viewer_id: Option<&str>,
+    ) -> ModelResult<Option<PostStream>> {
+        PostStream::get_posts(
+            self.stream_source(),
+            pagination,
+            SortOrder::Descending,
+            self.stream_sorting(),
+            viewer_id,
+            self.tags.clone(),
+            self.content.clone(),
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USER_ID: &str = "8pinxxgqs41n4aididenw5apqp1urfmzdztr8jt4abrkdn435ewo";
+
+    fn new_feed(reach: PubkyAppFeedReach, sort: PubkyAppFeedSort) -> FeedDetails {
+        let homeserver_feed = PubkyAppFeed::new(
+            Some(vec!["Rust ".to_string()]),
+            reach,
+            PubkyAppFeedLayout::Columns,
+            sort,
+            Some(PubkyAppPostKind::Short),
+            "My feed".to_string(),
+        );
+        FeedDetails::from_homeserver(homeserver_feed, USER_ID, "FEEDID")
+    }
+
+    #[test]
+    fn feed_from_homeserver() {
+        let feed = new_feed(PubkyAppFeedReach::All, PubkyAppFeedSort::Recent);
+        assert_eq!(
+            feed.uri,
+            format!("pubky://{USER_ID}/pub/pubky.app/feeds/FEEDID")
+        );
+        assert_eq!(feed.tags, Some(vec!["rust".to_string()]));
+        assert_eq!(feed.content, Some(PubkyAppPostKind::Short));
+    }
+
+    #[test]
+    fn feed_stream_params() {
+        let feed = new_feed(PubkyAppFeedReach::Friends, PubkyAppFeedSort::Popularity);
+        assert!(matches!(
+            feed.stream_source(),
+            StreamSource::Friends { observer_id } if observer_id == USER_ID
+        ));
+        assert!(matches!(
+            feed.stream_sorting(),
+            StreamSorting::TotalEngagement
+        ));
+
+        let feed = new_feed(PubkyAppFeedReach::All, PubkyAppFeedSort::Recent);
+        assert!(matches!(feed.stream_source(), StreamSource::All));
+        assert!(matches!(feed.stream_sorting(), StreamSorting::Timeline));
+    }
+}
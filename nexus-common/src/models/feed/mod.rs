@@ -0,0 +1,3 @@
+mod details;
+
+pub use details::FeedDetails;
@@ -1,5 +1,6 @@
 use crate::db::exec_single_row;
 use crate::db::fetch_key_from_graph;
+use crate::db::fetch_row_from_graph;
 use crate::db::kv::RedisError;
 use crate::db::kv::RedisResult;
 use crate::db::queries;
@@ -10,23 +11,34 @@ use crate::models::error::ModelError;
 use crate::models::error::ModelResult;
 use crate::models::user::UserDetails;
 
+use chrono::Utc;
 use pubky_app_specs::ParsedUri;
 use pubky_app_specs::PubkyId;
 use serde::{Deserialize, Serialize};
 use tracing::{info, warn};
 
+mod policy;
+mod status;
+mod view;
+pub use policy::{AdmissionRejection, HomeserverPolicy, RejectedHomeserver};
+pub use status::HomeserverStatus;
+pub use view::HomeserverView;
+
+/// Cursor of a homeserver that was never polled
+pub const DEFAULT_CURSOR: &str = "0000000000000";
+
 /// Represents a homeserver with its public key, URL, and cursor.
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Homeserver {
     pub id: PubkyId,
 
-    // Cursor lives in Redis only (not the graph). Redis snapshots can
-    // lag, so a crash may lose recent advancement — the watcher then
-    // resumes from whatever cursor survived in the snapshot and catches
-    // up from the homeserver. We do NOT re-index from scratch on partial
-    // loss; `persist_if_unknown` only re-seeds cursor=0 when the
-    // Homeserver key is missing from Redis entirely (e.g. wiped volume),
-    // never on a routine crash.
+    // Cursor is read from Redis and checkpointed to the graph after every
+    // processed batch (`put_cursor_to_graph`). Redis snapshots can lag, so
+    // a crash may lose recent advancement in Redis; when the Homeserver key
+    // is missing from Redis entirely (e.g. wiped volume), `get_by_id` and
+    // `persist_if_unknown` recover the last graph checkpoint instead of
+    // re-indexing from cursor=0. Only a homeserver that was never
+    // checkpointed is re-seeded with the default cursor.
     pub cursor: String,
 }
 
@@ -37,10 +49,15 @@ impl Homeserver {
     pub fn new(id: PubkyId) -> Self {
         Homeserver {
             id,
-            cursor: "0000000000000".to_string(),
+            cursor: DEFAULT_CURSOR.to_string(),
         }
     }
 
+    /// Whether the cursor has advanced past [`DEFAULT_CURSOR`]
+    pub fn has_checkpoint(&self) -> bool {
+        self.cursor != DEFAULT_CURSOR
+    }
+
     /// Creates a new homeserver instance with the specified cursor
     pub fn try_from_cursor<T: Into<String>>(id: PubkyId, cursor: T) -> ModelResult<Self> {
         let cursor = cursor.into();
@@ -55,20 +72,39 @@ impl Homeserver {
 
     /// Stores this homeserver in the graph.
     pub async fn put_to_graph(&self) -> GraphResult<()> {
-        let query = queries::put::create_homeserver(&self.id);
+        let query = queries::put::create_homeserver(&self.id, Utc::now().timestamp_millis());
+        exec_single_row(query).await
+    }
+
+    /// Checkpoints the cursor of this homeserver in the graph.
+    pub async fn put_cursor_to_graph(&self) -> GraphResult<()> {
+        let query = queries::put::set_homeserver_cursor(
+            &self.id,
+            &self.cursor,
+            Utc::now().timestamp_millis(),
+        );
         exec_single_row(query).await
     }
 
     /// Retrieves a homeserver from Neo4j.
     ///
-    /// Note that the cursor in the returned homeserver will have the default value, as it is not persisted in the graph.
+    /// The cursor is the last checkpoint written by [`Self::put_cursor_to_graph`],
+    /// or the default value if the homeserver was never checkpointed.
     pub async fn get_from_graph(id: &str) -> GraphResult<Option<Homeserver>> {
         let query = queries::get::get_homeserver_by_id(id);
 
-        let maybe_id = fetch_key_from_graph(query, "id").await?;
-        let maybe_hs = maybe_id.map(Homeserver::new);
+        let Some(row) = fetch_row_from_graph(query).await? else {
+            return Ok(None);
+        };
+
+        let id: PubkyId = row.get("id")?;
+        let maybe_cursor: Option<String> = row.get("cursor").unwrap_or(None);
+        let homeserver = match maybe_cursor {
+            Some(cursor) if !cursor.is_empty() => Homeserver { id, cursor },
+            _ => Homeserver::new(id),
+        };
 
-        Ok(maybe_hs)
+        Ok(Some(homeserver))
     }
 
     /// Retrieves the homeserver from Redis.
@@ -87,13 +123,26 @@ impl Homeserver {
     }
 
     pub async fn get_by_id(homeserver_id: PubkyId) -> ModelResult<Option<Homeserver>> {
-        // No graph fallback. A cache miss (or a Redis read error silently
-        // surfaced as `Ok(None)` by `json::get`) is treated as a failure
-        // and propagated to the caller, which puts the homeserver into
-        // backoff. The previous behaviour rebuilt from the graph (which
-        // does not store the cursor) and wrote cursor=0 back to Redis,
-        // silently overwriting the real value on a transient Redis hiccup.
-        Ok(Self::get_from_index(&homeserver_id).await?)
+        if let Some(homeserver) = Self::get_from_index(&homeserver_id).await? {
+            return Ok(Some(homeserver));
+        }
+
+        // Index miss: recover from the cursor checkpointed in the graph.
+        // Without a checkpoint the miss (or a Redis read error silently
+        // surfaced as `Ok(None)` by `json::get`) is treated as a failure and
+        // propagated to the caller, which puts the homeserver into backoff,
+        // rather than writing cursor=0 over the real value in Redis.
+        match Self::get_from_graph(&homeserver_id).await? {
+            Some(homeserver) if homeserver.has_checkpoint() => {
+                warn!(
+                    "Homeserver {homeserver_id} missing from index; recovering cursor {} from graph",
+                    homeserver.cursor
+                );
+                homeserver.put_to_index().await?;
+                Ok(Some(homeserver))
+            }
+            _ => Ok(None),
+        }
     }
 
     /// Ensures the homeserver is recorded in both the graph and the index.
@@ -101,26 +150,27 @@ impl Homeserver {
     /// - First-time install (missing from both): writes a fresh
     ///   [`Homeserver::new`] to graph and index.
     /// - Asymmetric state (present in graph, missing from index): re-seeds the
-    ///   index with the default cursor and logs a warning. This is the
-    ///   self-heal path for a wiped Redis volume sitting alongside a persisted
-    ///   Neo4j volume — common in dev/testnet workflows where operators tear
-    ///   down one store but not the other. The watcher will then re-index
-    ///   from `cursor=0`, which is the same recovery the system performs
-    ///   after a Redis crash by design.
+    ///   index with the cursor checkpointed in the graph and logs a warning.
+    ///   This is the self-heal path for a wiped Redis volume sitting alongside
+    ///   a persisted Neo4j volume — common in dev/testnet workflows where
+    ///   operators tear down one store but not the other. A homeserver that
+    ///   was never checkpointed is re-seeded with the default cursor and
+    ///   re-indexed from `cursor=0`.
     /// - Both already present: no-op.
     ///
     /// Safe to call repeatedly. Relies on `get_from_index` returning a
     /// reliable `Ok(None)` for genuine cache misses; if the read errors, the
     /// error propagates and the re-seed never fires.
     pub async fn persist_if_unknown(homeserver_id: PubkyId) -> ModelResult<()> {
-        let in_graph = Self::get_from_graph(&homeserver_id).await?.is_some();
+        let from_graph = Self::get_from_graph(&homeserver_id).await?;
+        let in_graph = from_graph.is_some();
         let in_index = Self::get_from_index(&homeserver_id).await?.is_some();
 
         if in_graph && in_index {
             return Ok(());
         }
 
-        let homeserver = Homeserver::new(homeserver_id.clone());
+        let homeserver = from_graph.unwrap_or_else(|| Homeserver::new(homeserver_id.clone()));
 
         if !in_graph {
             info!("Persisting new homeserver to graph: {homeserver_id}");
@@ -131,7 +181,8 @@ impl Homeserver {
             if in_graph {
                 warn!(
                     "Homeserver {homeserver_id} present in graph but missing from index; \
-                     re-seeding index with default cursor — watcher will re-index from 0"
+                     re-seeding index with graph cursor {}",
+                    homeserver.cursor
                 );
             } else {
                 info!("Persisting new homeserver to index: {homeserver_id}");
@@ -171,6 +222,9 @@ impl Homeserver {
 
     /// If a referenced user is using a new, unknown homeserver, this method triggers ingestion of that homeserver.
     ///
+    /// The homeserver is only ingested if the [`HomeserverPolicy`] admits it, otherwise it is
+    /// recorded as a [`RejectedHomeserver`] and [`ModelError::HomeserverRejected`] is returned.
+    ///
     /// ### Arguments
     ///
     /// - `referenced_user_id`: The `PubkyId` of the referenced user
@@ -195,10 +249,52 @@ impl Homeserver {
         };
 
         let hs_pk = PubkyId::from(ref_post_author_hs);
-        Self::persist_if_unknown(hs_pk.clone())
+        Self::maybe_ingest(hs_pk, referenced_user_id).await
+    }
+
+    /// Ingests a homeserver a user was resolved to, unless it is already known, if the
+    /// [`HomeserverPolicy`] admits it.
+    ///
+    /// ### Arguments
+    ///
+    /// - `hs_id`: The homeserver to ingest
+    /// - `referenced_user_id`: The user hosted on the homeserver, recorded if it is rejected
+    pub async fn maybe_ingest(hs_id: PubkyId, referenced_user_id: &PubkyId) -> ModelResult<()> {
+        Self::admit(&hs_id, referenced_user_id).await?;
+        Self::persist_if_unknown(hs_id.clone())
             .await
-            .inspect(|_| tracing::info!("Ingested homeserver {hs_pk}"))
-            .inspect_err(|e| tracing::error!("Failed to ingest homeserver {hs_pk}: {e}"))
+            .inspect(|_| tracing::info!("Ingested homeserver {hs_id}"))
+            .inspect_err(|e| tracing::error!("Failed to ingest homeserver {hs_id}: {e}"))
+    }
+
+    /// Checks a homeserver against the [`HomeserverPolicy`] published by the watcher, unless it
+    /// is already known. Rejections are recorded as [`RejectedHomeserver`].
+    async fn admit(hs_id: &PubkyId, referenced_user_id: &PubkyId) -> ModelResult<()> {
+        let Some(policy) = HomeserverPolicy::get_from_index().await? else {
+            return Ok(());
+        };
+        if Self::get_from_graph(hs_id).await?.is_some() {
+            return Ok(());
+        }
+
+        let known_hs_ids = match policy.max_discovered {
+            Some(_) => {
+                fetch_key_from_graph(queries::get::get_all_homeservers(), "homeservers_list")
+                    .await?
+                    .unwrap_or_default()
+            }
+            None => Vec::new(),
+        };
+        let Err(reason) = policy.admit(hs_id, &known_hs_ids) else {
+            return Ok(());
+        };
+
+        tracing::warn!("Homeserver {hs_id} of user {referenced_user_id} rejected: {reason}");
+        RejectedHomeserver::record(hs_id, reason, referenced_user_id).await?;
+        Err(ModelError::HomeserverRejected {
+            homeserver_id: hs_id.to_string(),
+            reason,
+        })
     }
 }
 
@@ -325,4 +421,72 @@ mod tests {
         );
         Ok(())
     }
+
+    #[tokio_shared_rt::test(shared)]
+    async fn test_put_cursor_to_get_from_graph() -> Result<(), DynError> {
+        StackManager::setup(&StackConfig::default()).await?;
+
+        let keys = Keypair::random();
+        let id = PubkyId::try_from(&keys.public_key().to_z32())?;
+
+        // Never checkpointed: default cursor
+        Homeserver::new(id.clone()).put_to_graph().await?;
+        let from_graph = Homeserver::get_from_graph(&id).await?.unwrap();
+        assert_eq!(from_graph.cursor, DEFAULT_CURSOR);
+        assert!(!from_graph.has_checkpoint());
+
+        Homeserver::try_from_cursor(id.clone(), "1234567890123")?
+            .put_cursor_to_graph()
+            .await?;
+        let from_graph = Homeserver::get_from_graph(&id).await?.unwrap();
+        assert_eq!(from_graph.cursor, "1234567890123");
+        Ok(())
+    }
+
+    // Redis wiped after the watcher checkpointed a cursor: the index must be
+    // re-seeded from the graph checkpoint instead of cursor=0.
+    #[tokio_shared_rt::test(shared)]
+    async fn test_persist_if_unknown_reseeds_index_with_graph_cursor() -> Result<(), DynError> {
+        StackManager::setup(&StackConfig::default()).await?;
+
+        let keys = Keypair::random();
+        let id = PubkyId::try_from(&keys.public_key().to_z32())?;
+
+        Homeserver::try_from_cursor(id.clone(), "1234567890123")?
+            .put_cursor_to_graph()
+            .await?;
+
+        Homeserver::persist_if_unknown(id.clone()).await?;
+
+        let from_index = Homeserver::get_from_index(&id)
+            .await?
+            .expect("index should be re-seeded after persist_if_unknown");
+        assert_eq!(from_index.cursor, "1234567890123");
+        Ok(())
+    }
+
+    #[tokio_shared_rt::test(shared)]
+    async fn test_get_by_id_recovers_cursor_from_graph() -> Result<(), DynError> {
+        StackManager::setup(&StackConfig::default()).await?;
+
+        let keys = Keypair::random();
+        let id = PubkyId::try_from(&keys.public_key().to_z32())?;
+
+        // Without a checkpoint, an index miss is still reported as not found
+        Homeserver::new(id.clone()).put_to_graph().await?;
+        assert!(Homeserver::get_by_id(id.clone()).await?.is_none());
+
+        Homeserver::try_from_cursor(id.clone(), "1234567890123")?
+            .put_cursor_to_graph()
+            .await?;
+
+        let homeserver = Homeserver::get_by_id(id.clone())
+            .await?
+            .expect("homeserver should be recovered from the graph checkpoint");
+        assert_eq!(homeserver.cursor, "1234567890123");
+
+        let from_index = Homeserver::get_from_index(&id).await?.unwrap();
+        assert_eq!(from_index.cursor, "1234567890123");
+        Ok(())
+    }
 }
@@ -0,0 +1,230 @@
+use crate::db::kv::{RedisResult, SortOrder};
+use crate::db::RedisOps;
+use chrono::Utc;
+use pubky_app_specs::PubkyId;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use utoipa::ToSchema;
+
+/// Rejected homeservers, scored by the timestamp of their last rejection
+pub const REJECTED_HOMESERVERS_KEY_PARTS: [&str; 2] = ["Homeservers", "Rejected"];
+
+/// Key under which the watcher publishes its active policy
+const ACTIVE_POLICY_KEY_PARTS: [&str; 1] = ["Active"];
+
+/// Which homeservers may be discovered and ingested through the users referencing them,
+/// i.e. by [`Homeserver::maybe_ingest_for_user`](super::Homeserver::maybe_ingest_for_user).
+///
+/// Configured in the watcher, which publishes it to Redis on start, so the API applies the
+/// same policy to `PUT /v0/ingest/{user_id}`.
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone, Default, PartialEq)]
+pub struct HomeserverPolicy {
+    /// If not empty, only these homeservers may be discovered
+    #[serde(default)]
+    pub allowlist: Vec<PubkyId>,
+    /// Homeservers that are never discovered, and no longer polled if they already are
+    #[serde(default)]
+    pub denylist: Vec<PubkyId>,
+    /// Number of known homeservers, the default one included, from which no more are
+    /// discovered. No limit if unset.
+    #[serde(default)]
+    pub max_discovered: Option<usize>,
+}
+
+/// Reason why a homeserver was refused by the [`HomeserverPolicy`]
+#[derive(Serialize, Deserialize, ToSchema, Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AdmissionRejection {
+    #[error("homeserver is denylisted")]
+    Denylisted,
+    #[error("homeserver is not allowlisted")]
+    NotAllowlisted,
+    #[error("limit of discovered homeservers reached")]
+    DiscoveryLimitReached,
+}
+
+impl RedisOps for HomeserverPolicy {}
+
+impl HomeserverPolicy {
+    pub fn is_denylisted(&self, hs_id: &str) -> bool {
+        self.denylist.iter().any(|id| id.as_ref() == hs_id)
+    }
+
+    pub fn is_allowlisted(&self, hs_id: &str) -> bool {
+        self.allowlist.iter().any(|id| id.as_ref() == hs_id)
+    }
+
+    /// Decides whether an unknown homeserver may be ingested
+    ///
+    /// # Arguments
+    /// * `hs_id` - The homeserver to admit
+    /// * `known_hs_ids` - The homeservers already known
+    pub fn admit(&self, hs_id: &str, known_hs_ids: &[String]) -> Result<(), AdmissionRejection> {
+        if self.is_denylisted(hs_id) {
+            return Err(AdmissionRejection::Denylisted);
+        }
+        if self.is_allowlisted(hs_id) {
+            return Ok(());
+        }
+        if !self.allowlist.is_empty() {
+            return Err(AdmissionRejection::NotAllowlisted);
+        }
+        match self.max_discovered {
+            Some(max_discovered) if known_hs_ids.len() >= max_discovered => {
+                Err(AdmissionRejection::DiscoveryLimitReached)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Retrieves the policy published by the watcher, if any
+    pub async fn get_from_index() -> RedisResult<Option<Self>> {
+        Self::try_from_index_json(&ACTIVE_POLICY_KEY_PARTS, None).await
+    }
+
+    /// Publishes this policy, replacing the previous one
+    pub async fn put_to_index(&self) -> RedisResult<()> {
+        self.put_index_json(&ACTIVE_POLICY_KEY_PARTS, None, None)
+            .await
+    }
+}
+
+/// A homeserver refused by the [`HomeserverPolicy`], kept so operators can review the
+/// homeservers users are on and adjust the policy.
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone)]
+pub struct RejectedHomeserver {
+    pub id: String,
+    /// Reason of the last rejection
+    pub reason: AdmissionRejection,
+    /// User whose homeserver was resolved to this one, at the last rejection
+    pub referenced_by: String,
+    /// Timestamp (ms) of the first rejection
+    pub first_rejected_at: i64,
+    /// Timestamp (ms) of the last rejection
+    pub last_rejected_at: i64,
+    /// Number of times the homeserver was rejected
+    pub attempts: u32,
+}
+
+impl RedisOps for RejectedHomeserver {}
+
+impl RejectedHomeserver {
+    /// Records a rejection of the homeserver `id`, resolved from the user `referenced_by`
+    pub async fn record(
+        id: &str,
+        reason: AdmissionRejection,
+        referenced_by: &str,
+    ) -> RedisResult<Self> {
+        let now = Utc::now().timestamp_millis();
+        let previous = Self::get_from_index(id).await?;
+
+        let rejected = Self {
+            id: id.to_string(),
+            reason,
+            referenced_by: referenced_by.to_string(),
+            first_rejected_at: previous
+                .as_ref()
+                .map_or(now, |previous| previous.first_rejected_at),
+            last_rejected_at: now,
+            attempts: previous.map_or(0, |previous| previous.attempts) + 1,
+        };
+        rejected.put_to_index().await?;
+        Ok(rejected)
+    }
+
+    /// Retrieves a rejected homeserver from Redis.
+    pub async fn get_from_index(id: &str) -> RedisResult<Option<Self>> {
+        Self::try_from_index_json(&[id], None).await
+    }
+
+    /// Stores this rejected homeserver in Redis.
+    pub async fn put_to_index(&self) -> RedisResult<()> {
+        self.put_index_json(&[&self.id], None, None).await?;
+        Self::put_index_sorted_set(
+            &REJECTED_HOMESERVERS_KEY_PARTS,
+            &[(self.last_rejected_at as f64, &self.id)],
+            None,
+            None,
+        )
+        .await
+    }
+
+    /// Lists the rejected homeservers, the most recently rejected first.
+    pub async fn list(skip: usize, limit: usize) -> RedisResult<Vec<Self>> {
+        let ids = Self::try_from_index_sorted_set(
+            &REJECTED_HOMESERVERS_KEY_PARTS,
+            None,
+            None,
+            Some(skip),
+            Some(limit),
+            SortOrder::Descending,
+            None,
+        )
+        .await?
+        .unwrap_or_default();
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let key_parts: Vec<[&str; 1]> = ids.iter().map(|(id, _)| [id.as_str()]).collect();
+        let key_parts_list: Vec<&[&str]> = key_parts.iter().map(|key| key.as_slice()).collect();
+
+        let rejected = Self::try_from_index_multiple_json(&key_parts_list).await?;
+        Ok(rejected.into_iter().flatten().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HS_A: &str = "8um71us3fyw6h8wbcxb5ar3rwusy1a6u49956ikzojg3gcwd1dty";
+    const HS_B: &str = "8pinxxgqs41n4aididenw5apqp1urfmzdztr8jt4abrkdn435ewo";
+    const HS_C: &str = "uo7jgkykft4885n8cruizwy6khw71mnu5pq3ay9i8pw1ymcn85ko";
+
+    fn pubky_id(id: &str) -> PubkyId {
+        PubkyId::try_from(id).unwrap()
+    }
+
+    #[test]
+    fn test_default_policy_admits_any_homeserver() {
+        let policy = HomeserverPolicy::default();
+        assert_eq!(policy.admit(HS_A, &[HS_B.into(), HS_C.into()]), Ok(()));
+    }
+
+    #[test]
+    fn test_denylist_wins_over_allowlist() {
+        let policy = HomeserverPolicy {
+            allowlist: vec![pubky_id(HS_A)],
+            denylist: vec![pubky_id(HS_A)],
+            max_discovered: None,
+        };
+        assert_eq!(policy.admit(HS_A, &[]), Err(AdmissionRejection::Denylisted));
+    }
+
+    #[test]
+    fn test_allowlist_restricts_discovery() {
+        let policy = HomeserverPolicy {
+            allowlist: vec![pubky_id(HS_A)],
+            ..Default::default()
+        };
+        assert_eq!(policy.admit(HS_A, &[]), Ok(()));
+        assert_eq!(
+            policy.admit(HS_B, &[]),
+            Err(AdmissionRejection::NotAllowlisted)
+        );
+    }
+
+    #[test]
+    fn test_discovery_limit() {
+        let policy = HomeserverPolicy {
+            max_discovered: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(policy.admit(HS_A, &[HS_B.into()]), Ok(()));
+        assert_eq!(
+            policy.admit(HS_A, &[HS_B.into(), HS_C.into()]),
+            Err(AdmissionRejection::DiscoveryLimitReached)
+        );
+    }
+}
@@ -0,0 +1,115 @@
+use crate::db::kv::{RedisResult, SortOrder};
+use crate::db::RedisOps;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::Homeserver;
+
+/// Homeservers with a status, scored by the timestamp of their last successful poll
+pub const HOMESERVER_STATUS_KEY_PARTS: [&str; 2] = ["Homeservers", "Status"];
+
+/// Watcher progress for a single homeserver, as of its last event processor run.
+///
+/// Written by the watcher after every run and exposed read-only by the API, so that
+/// homeservers that silently stopped progressing can be spotted.
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone, Default)]
+pub struct HomeserverStatus {
+    pub id: String,
+    /// Last cursor stored for the homeserver
+    pub cursor: Option<String>,
+    /// Timestamp (ms) of the last run
+    pub last_run_at: i64,
+    /// Outcome of the last run, e.g. `Ok`, `Error`, `Timeout`
+    pub last_run_status: String,
+    /// Timestamp (ms) of the last successful poll, if any
+    pub last_success_at: Option<i64>,
+    /// Failed runs since the last successful one
+    pub consecutive_failures: u32,
+    /// Timestamp (ms) until which the homeserver is skipped, if it is backing off
+    pub backoff_until: Option<i64>,
+    /// Event lines processed in the last run
+    pub events_processed: usize,
+}
+
+impl RedisOps for HomeserverStatus {}
+
+impl HomeserverStatus {
+    /// Records the outcome of an event processor run for a homeserver.
+    ///
+    /// The cursor is read from the [`Homeserver`] index, and the last successful poll is
+    /// kept from the previous status when the run failed.
+    pub async fn record_run(
+        id: &str,
+        last_run_status: impl Into<String>,
+        success: bool,
+        consecutive_failures: u32,
+        backoff_until: Option<i64>,
+        events_processed: usize,
+    ) -> RedisResult<Self> {
+        let now = Utc::now().timestamp_millis();
+        let last_success_at = match success {
+            true => Some(now),
+            false => Self::get_from_index(id)
+                .await?
+                .and_then(|status| status.last_success_at),
+        };
+        let cursor = Homeserver::get_from_index(id).await?.map(|hs| hs.cursor);
+
+        let status = Self {
+            id: id.to_string(),
+            cursor,
+            last_run_at: now,
+            last_run_status: last_run_status.into(),
+            last_success_at,
+            consecutive_failures,
+            backoff_until,
+            events_processed,
+        };
+        status.put_to_index().await?;
+        Ok(status)
+    }
+
+    /// Retrieves the status of a homeserver from Redis.
+    pub async fn get_from_index(id: &str) -> RedisResult<Option<Self>> {
+        Self::try_from_index_json(&[id], None).await
+    }
+
+    /// Stores this status in Redis.
+    pub async fn put_to_index(&self) -> RedisResult<()> {
+        self.put_index_json(&[&self.id], None, None).await?;
+
+        let score = self.last_success_at.unwrap_or_default() as f64;
+        Self::put_index_sorted_set(
+            &HOMESERVER_STATUS_KEY_PARTS,
+            &[(score, &self.id)],
+            None,
+            None,
+        )
+        .await
+    }
+
+    /// Lists homeserver statuses, the ones with the oldest successful poll first.
+    pub async fn list(skip: usize, limit: usize) -> RedisResult<Vec<Self>> {
+        let ids = Self::try_from_index_sorted_set(
+            &HOMESERVER_STATUS_KEY_PARTS,
+            None,
+            None,
+            Some(skip),
+            Some(limit),
+            SortOrder::Ascending,
+            None,
+        )
+        .await?
+        .unwrap_or_default();
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let key_parts: Vec<[&str; 1]> = ids.iter().map(|(id, _)| [id.as_str()]).collect();
+        let key_parts_list: Vec<&[&str]> = key_parts.iter().map(|key| key.as_slice()).collect();
+
+        let statuses = Self::try_from_index_multiple_json(&key_parts_list).await?;
+        Ok(statuses.into_iter().flatten().collect())
+    }
+}
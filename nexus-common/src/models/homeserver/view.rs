@@ -0,0 +1,69 @@
+use crate::db::{fetch_all_rows_from_graph, fetch_row_from_graph, queries, GraphResult, RedisOps};
+use crate::models::error::ModelResult;
+use neo4rs::Row;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::Homeserver;
+
+/// Directory entry of a known homeserver: how many indexed users it hosts, when it was first
+/// seen and how far the watcher got in its events.
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone, PartialEq)]
+pub struct HomeserverView {
+    pub id: String,
+    /// Number of indexed users hosted on the homeserver
+    pub users: u64,
+    /// Timestamp (ms) at which the homeserver was first seen, unknown for homeservers
+    /// discovered before it was recorded
+    pub first_seen_at: Option<i64>,
+    /// Current event cursor of the homeserver
+    pub cursor: Option<String>,
+}
+
+impl HomeserverView {
+    /// Retrieves a homeserver by ID
+    pub async fn get_by_id(id: &str) -> ModelResult<Option<Self>> {
+        let query = queries::get::get_homeserver_directory_entry(id);
+        let Some(row) = fetch_row_from_graph(query).await? else {
+            return Ok(None);
+        };
+        Ok(Some(Self::from_row(row)?.with_index_cursor().await?))
+    }
+
+    /// Lists the known homeservers, the ones hosting the most users first
+    pub async fn list(skip: usize, limit: usize) -> ModelResult<Vec<Self>> {
+        let query = queries::get::get_homeservers_directory(skip, limit);
+        let mut homeservers = fetch_all_rows_from_graph(query)
+            .await?
+            .into_iter()
+            .map(Self::from_row)
+            .collect::<GraphResult<Vec<_>>>()?;
+
+        // The current cursors of the whole page are read from the index at once
+        let ids: Vec<&str> = homeservers.iter().map(|hs| hs.id.as_str()).collect();
+        let indexed = Homeserver::mget(&ids).await?;
+        for (view, indexed) in homeservers.iter_mut().zip(indexed) {
+            if let Some(homeserver) = indexed {
+                view.cursor = Some(homeserver.cursor);
+            }
+        }
+        Ok(homeservers)
+    }
+
+    fn from_row(row: Row) -> GraphResult<Self> {
+        Ok(Self {
+            id: row.get("id")?,
+            users: row.get::<i64>("users")? as u64,
+            first_seen_at: row.get("indexed_at").unwrap_or(None),
+            cursor: row.get("cursor").unwrap_or(None),
+        })
+    }
+
+    /// The graph only holds the last checkpoint, the current cursor is in the index
+    async fn with_index_cursor(mut self) -> ModelResult<Self> {
+        if let Some(homeserver) = Homeserver::get_from_index(&self.id).await? {
+            self.cursor = Some(homeserver.cursor);
+        }
+        Ok(self)
+    }
+}
@@ -1,6 +1,7 @@
 pub mod bootstrap;
 pub mod error;
 pub mod event;
+pub mod feed;
 pub mod file;
 pub mod follow;
 pub mod homeserver;
@@ -10,6 +11,7 @@ pub mod resource;
 pub mod tag;
 pub mod traits;
 pub mod user;
+pub mod webhook;
 
 /// Create tuples with a 0.0 score for each element, forcing the sorted set to support lexicographical search
 fn create_zero_score_tuples(strings: &[String]) -> Vec<(f64, &str)> {
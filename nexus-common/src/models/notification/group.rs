@@ -0,0 +1,267 @@
+use super::{Notification, NotificationBody, NotificationType};
+use crate::db::kv::RedisResult;
+use crate::types::Pagination;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use utoipa::ToSchema;
+
+/// Default time window of a group, in milliseconds: one day
+pub const DEFAULT_GROUP_WINDOW: i64 = 24 * 60 * 60 * 1000;
+/// Maximum number of actors listed by a group, see [NotificationGroup::actors_count] for the others
+pub const MAX_GROUP_ACTORS: usize = 10;
+
+/// Notifications of the same type about the same target (post and/or tag label), within the
+/// same time window, so clients can show "X and 12 others tagged your post with 'rust'".
+#[derive(Serialize, Deserialize, ToSchema, Clone, Debug)]
+pub struct NotificationGroup {
+    #[serde(rename = "type")]
+    pub notification_type: NotificationType,
+    /// Post the notifications are about, if any
+    pub post_uri: Option<String>,
+    pub tag_label: Option<String>,
+    /// Distinct users whose actions triggered the notifications, the most recent first, up to
+    /// [MAX_GROUP_ACTORS]
+    pub actors: Vec<String>,
+    /// Number of distinct users whose actions triggered the notifications, listed or not
+    pub actors_count: usize,
+    /// Number of notifications in the group
+    pub count: usize,
+    /// Timestamp of the most recent notification of the group
+    pub timestamp: i64,
+    /// Timestamp of the oldest notification of the group
+    pub oldest_timestamp: i64,
+    /// Body of the most recent notification of the group
+    pub latest: NotificationBody,
+}
+
+/// Identifies the group of a notification: its type, target and time window
+type GroupKey = (NotificationType, Option<String>, Option<String>, i64);
+
+/// Groups notifications visited the most recent first. As the time windows are aligned on
+/// multiples of the window length, a group is complete once an older window is reached.
+struct Grouper {
+    window: i64,
+    groups: Vec<NotificationGroup>,
+    keys: HashMap<GroupKey, usize>,
+    /// Distinct actors of each group, including the ones beyond [MAX_GROUP_ACTORS]
+    actors: Vec<HashSet<String>>,
+    /// Time window of the last pushed notification
+    current_window: i64,
+}
+
+impl Grouper {
+    fn new(window: i64) -> Self {
+        Self {
+            window,
+            groups: Vec::new(),
+            keys: HashMap::new(),
+            actors: Vec::new(),
+            current_window: i64::MAX,
+        }
+    }
+
+    fn push(&mut self, notification: Notification) {
+        let (post_uri, tag_label) = NotificationGroup::target(&notification.body);
+        self.current_window = notification.timestamp.div_euclid(self.window);
+        let key = (
+            notification.body.notification_type(),
+            post_uri.clone(),
+            tag_label.clone(),
+            self.current_window,
+        );
+
+        let actor = notification.body.actor().to_string();
+        match self.keys.get(&key) {
+            Some(&index) => {
+                let group = &mut self.groups[index];
+                if self.actors[index].insert(actor.clone()) {
+                    group.actors_count += 1;
+                    if group.actors.len() < MAX_GROUP_ACTORS {
+                        group.actors.push(actor);
+                    }
+                }
+                group.count += 1;
+                group.oldest_timestamp = notification.timestamp;
+            }
+            None => {
+                self.keys.insert(key, self.groups.len());
+                self.actors.push(HashSet::from([actor.clone()]));
+                self.groups.push(NotificationGroup {
+                    notification_type: notification.body.notification_type(),
+                    post_uri,
+                    tag_label,
+                    actors: vec![actor],
+                    actors_count: 1,
+                    count: 1,
+                    timestamp: notification.timestamp,
+                    oldest_timestamp: notification.timestamp,
+                    latest: notification.body,
+                });
+            }
+        }
+    }
+
+    /// Number of leading groups that no notification older than the pushed ones can join
+    fn complete_groups(&self) -> usize {
+        self.groups
+            .iter()
+            .position(|group| group.timestamp.div_euclid(self.window) == self.current_window)
+            .unwrap_or(self.groups.len())
+    }
+}
+
+impl NotificationGroup {
+    /// Groups notifications sorted the most recent first, within time windows of `window`
+    /// milliseconds
+    pub fn group(notifications: impl IntoIterator<Item = Notification>, window: i64) -> Vec<Self> {
+        let mut grouper = Grouper::new(window);
+        notifications
+            .into_iter()
+            .for_each(|notification| grouper.push(notification));
+        grouper.groups
+    }
+
+    /// Lists the notification groups of the user, the most recent first. Skip and limit apply to
+    /// the groups, while the timestamp range and `types` select the notifications to group.
+    pub async fn get_by_id(
+        user_id: &str,
+        pagination: Pagination,
+        types: Option<&[NotificationType]>,
+        window: i64,
+    ) -> RedisResult<Vec<Self>> {
+        let skip = pagination.skip.unwrap_or(0);
+        let limit = pagination.limit.unwrap_or(20);
+
+        let mut grouper = Grouper::new(window);
+        Notification::scan(user_id, &pagination, |notification| {
+            let notification_type = notification.body.notification_type();
+            if types.is_none_or(|types| types.contains(&notification_type)) {
+                grouper.push(notification);
+            }
+            grouper.complete_groups() < skip + limit
+        })
+        .await?;
+        Ok(grouper.groups.into_iter().skip(skip).take(limit).collect())
+    }
+
+    /// Post and tag label the notification is about
+    fn target(body: &NotificationBody) -> (Option<String>, Option<String>) {
+        match body {
+            NotificationBody::Follow { .. }
+            | NotificationBody::NewFriend { .. }
+            | NotificationBody::LostFriend { .. } => (None, None),
+            NotificationBody::TagPost {
+                tag_label,
+                post_uri,
+                ..
+            }
+            | NotificationBody::UntagPost {
+                tag_label,
+                post_uri,
+                ..
+            } => (Some(post_uri.clone()), Some(tag_label.clone())),
+            NotificationBody::TagProfile { tag_label, .. }
+            | NotificationBody::UntagProfile { tag_label, .. } => (None, Some(tag_label.clone())),
+            NotificationBody::Reply {
+                parent_post_uri, ..
+            } => (Some(parent_post_uri.clone()), None),
+            NotificationBody::Repost { embed_uri, .. } => (Some(embed_uri.clone()), None),
+            NotificationBody::Mention { post_uri, .. } => (Some(post_uri.clone()), None),
+            NotificationBody::PostDeleted { deleted_uri, .. } => (Some(deleted_uri.clone()), None),
+            NotificationBody::PostEdited { edited_uri, .. } => (Some(edited_uri.clone()), None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: i64 = 60 * 60 * 1000;
+    const POST_URI: &str = "pubky://author/pub/pubky.app/posts/0032SSN7Q4EVG";
+
+    fn tag_post(timestamp: i64, tagged_by: &str, tag_label: &str) -> Notification {
+        Notification {
+            timestamp,
+            body: NotificationBody::TagPost {
+                tagged_by: tagged_by.to_string(),
+                tag_label: tag_label.to_string(),
+                post_uri: POST_URI.to_string(),
+            },
+        }
+    }
+
+    fn follow(timestamp: i64, followed_by: &str) -> Notification {
+        Notification {
+            timestamp,
+            body: NotificationBody::Follow {
+                followed_by: followed_by.to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn group_by_type_target_and_window() {
+        let notifications = vec![
+            tag_post(DEFAULT_GROUP_WINDOW + 5 * HOUR, "carol", "rust"),
+            follow(DEFAULT_GROUP_WINDOW + 4 * HOUR, "dave"),
+            tag_post(DEFAULT_GROUP_WINDOW + 3 * HOUR, "bob", "rust"),
+            tag_post(DEFAULT_GROUP_WINDOW + 2 * HOUR, "bob", "nexus"),
+            tag_post(DEFAULT_GROUP_WINDOW + HOUR, "alice", "rust"),
+            // Previous window
+            tag_post(DEFAULT_GROUP_WINDOW - HOUR, "erin", "rust"),
+        ];
+        let groups = NotificationGroup::group(notifications, DEFAULT_GROUP_WINDOW);
+
+        assert_eq!(groups.len(), 4);
+        assert_eq!(groups[0].notification_type, NotificationType::TagPost);
+        assert_eq!(groups[0].post_uri.as_deref(), Some(POST_URI));
+        assert_eq!(groups[0].tag_label.as_deref(), Some("rust"));
+        assert_eq!(groups[0].actors, vec!["carol", "bob", "alice"]);
+        assert_eq!(groups[0].count, 3);
+        assert_eq!(groups[0].timestamp, DEFAULT_GROUP_WINDOW + 5 * HOUR);
+        assert_eq!(groups[0].oldest_timestamp, DEFAULT_GROUP_WINDOW + HOUR);
+
+        assert_eq!(groups[1].notification_type, NotificationType::Follow);
+        assert_eq!(groups[1].post_uri, None);
+        assert_eq!(groups[2].tag_label.as_deref(), Some("nexus"));
+        assert_eq!(groups[3].actors, vec!["erin"]);
+    }
+
+    #[test]
+    fn group_counts_repeated_actors_once() {
+        let notifications = vec![follow(3, "alice"), follow(2, "bob"), follow(1, "alice")];
+        let groups = NotificationGroup::group(notifications, DEFAULT_GROUP_WINDOW);
+
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].actors, vec!["alice", "bob"]);
+        assert_eq!(groups[0].actors_count, 2);
+        assert_eq!(groups[0].count, 3);
+    }
+
+    #[test]
+    fn group_lists_a_bounded_number_of_actors() {
+        let notifications = (0..MAX_GROUP_ACTORS as i64 + 5)
+            .rev()
+            .map(|i| follow(i, &format!("user{i}")))
+            .chain([follow(0, "user0")]);
+        let groups = NotificationGroup::group(notifications, DEFAULT_GROUP_WINDOW);
+
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].actors.len(), MAX_GROUP_ACTORS);
+        assert_eq!(groups[0].actors[0], format!("user{}", MAX_GROUP_ACTORS + 4));
+        assert_eq!(groups[0].actors_count, MAX_GROUP_ACTORS + 5);
+        assert_eq!(groups[0].count, MAX_GROUP_ACTORS + 6);
+    }
+
+    #[test]
+    fn complete_groups_once_an_older_window_is_reached() {
+        let mut grouper = Grouper::new(DEFAULT_GROUP_WINDOW);
+        grouper.push(follow(DEFAULT_GROUP_WINDOW + HOUR, "alice"));
+        grouper.push(tag_post(DEFAULT_GROUP_WINDOW, "bob", "rust"));
+        assert_eq!(grouper.complete_groups(), 0);
+
+        grouper.push(follow(DEFAULT_GROUP_WINDOW - HOUR, "carol"));
+        assert_eq!(grouper.complete_groups(), 2);
+    }
+}
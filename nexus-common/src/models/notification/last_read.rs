@@ -0,0 +1,31 @@
+use crate::db::kv::RedisResult;
+use crate::db::RedisOps;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Timestamp up to which a user has read their notifications, as published on their homeserver
+/// at `/pub/pubky.app/last_read`
+#[derive(Serialize, Deserialize, ToSchema, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LastRead {
+    /// Timestamp (ms) of the last read notification
+    pub timestamp: i64,
+}
+
+impl RedisOps for LastRead {}
+
+impl LastRead {
+    /// Retrieves the last read timestamp of a user
+    pub async fn get_from_index(user_id: &str) -> RedisResult<Option<Self>> {
+        Self::try_from_index_json(&[user_id], None).await
+    }
+
+    /// Stores the last read timestamp of a user
+    pub async fn put_to_index(&self, user_id: &str) -> RedisResult<()> {
+        self.put_index_json(&[user_id], None, None).await
+    }
+
+    /// Removes the last read timestamp of a user, making all their notifications unread
+    pub async fn del_from_index(user_id: &str) -> RedisResult<()> {
+        Self::remove_from_index_multiple_json(&[&[user_id]]).await
+    }
+}
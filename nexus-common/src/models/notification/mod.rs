@@ -1,13 +1,29 @@
-use crate::db::kv::{RedisError, RedisResult, SortOrder};
+use crate::db::kv::{pubsub, RedisError, RedisResult, SortOrder};
 use crate::db::{fetch_all_rows_from_graph, queries, RedisOps};
 use crate::models::error::ModelResult;
+use crate::models::webhook::Webhooks;
 use crate::types::Pagination;
 use chrono::Utc;
+use futures::{Stream, StreamExt};
 use neo4rs::Row;
 use pubky_app_specs::{bookmark_uri_builder, post_uri_builder, tag_uri_builder, PubkyId};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
+mod group;
+mod last_read;
+mod preferences;
+
+pub use group::{NotificationGroup, DEFAULT_GROUP_WINDOW, MAX_GROUP_ACTORS};
+pub use last_read::LastRead;
+pub use preferences::NotificationPreferences;
+
+/// Prefix of the pub/sub channel on which the new notifications of a user are published
+const NOTIFICATION_CHANNEL_PREFIX: &str = "Notification:Channel";
+
+/// Number of notifications read at once when scanning the notifications of a user
+const SCAN_BATCH_SIZE: usize = 100;
+
 #[derive(Serialize, Deserialize, ToSchema, Clone, Debug, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum PostChangedSource {
@@ -24,7 +40,7 @@ pub enum PostChangedType {
     Deleted,
 }
 
-#[derive(Serialize, Deserialize, ToSchema, Default, Debug)]
+#[derive(Serialize, Deserialize, ToSchema, Default, Debug, Clone)]
 pub struct Notification {
     pub timestamp: i64,
     pub body: NotificationBody,
@@ -88,6 +104,61 @@ pub enum NotificationBody {
     },
 }
 
+/// The `type` of a [NotificationBody], to filter notifications by
+#[derive(Serialize, Deserialize, ToSchema, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationType {
+    Follow,
+    NewFriend,
+    LostFriend,
+    TagPost,
+    TagProfile,
+    UntagPost,
+    UntagProfile,
+    Reply,
+    Repost,
+    Mention,
+    PostDeleted,
+    PostEdited,
+}
+
+impl NotificationBody {
+    pub fn notification_type(&self) -> NotificationType {
+        match self {
+            NotificationBody::Follow { .. } => NotificationType::Follow,
+            NotificationBody::NewFriend { .. } => NotificationType::NewFriend,
+            NotificationBody::LostFriend { .. } => NotificationType::LostFriend,
+            NotificationBody::TagPost { .. } => NotificationType::TagPost,
+            NotificationBody::TagProfile { .. } => NotificationType::TagProfile,
+            NotificationBody::UntagPost { .. } => NotificationType::UntagPost,
+            NotificationBody::UntagProfile { .. } => NotificationType::UntagProfile,
+            NotificationBody::Reply { .. } => NotificationType::Reply,
+            NotificationBody::Repost { .. } => NotificationType::Repost,
+            NotificationBody::Mention { .. } => NotificationType::Mention,
+            NotificationBody::PostDeleted { .. } => NotificationType::PostDeleted,
+            NotificationBody::PostEdited { .. } => NotificationType::PostEdited,
+        }
+    }
+
+    /// The user whose action triggered the notification
+    pub fn actor(&self) -> &str {
+        match self {
+            NotificationBody::Follow { followed_by } => followed_by,
+            NotificationBody::NewFriend { followed_by } => followed_by,
+            NotificationBody::LostFriend { unfollowed_by } => unfollowed_by,
+            NotificationBody::TagPost { tagged_by, .. } => tagged_by,
+            NotificationBody::TagProfile { tagged_by, .. } => tagged_by,
+            NotificationBody::UntagPost { untagged_by, .. } => untagged_by,
+            NotificationBody::UntagProfile { untagged_by, .. } => untagged_by,
+            NotificationBody::Reply { replied_by, .. } => replied_by,
+            NotificationBody::Repost { reposted_by, .. } => reposted_by,
+            NotificationBody::Mention { mentioned_by, .. } => mentioned_by,
+            NotificationBody::PostDeleted { deleted_by, .. } => deleted_by,
+            NotificationBody::PostEdited { edited_by, .. } => edited_by,
+        }
+    }
+}
+
 type QueryFunction = fn(&str, &str) -> crate::db::graph::Query;
 type ExtractFunction = Box<dyn Fn(&Row) -> (String, String) + Send>;
 
@@ -109,19 +180,78 @@ impl Notification {
         }
     }
 
-    /// Stores the `NotificationBody` in the sorted set for the user using the timestamp as the score.
+    /// Stores the `NotificationBody` in the sorted set for the user using the timestamp as the
+    /// score, unless the user muted it in their [NotificationPreferences], then delivers the
+    /// notification to the [Webhooks].
     async fn put_to_index(&self, user_id: &str) -> RedisResult<()> {
-        let notification_body_json = serde_json::to_string(&self.body)
+        let preferences = NotificationPreferences::get_by_id(user_id).await?;
+        self.put_to_index_with(user_id, &preferences).await
+    }
+
+    /// Same as [Self::put_to_index], with the preferences of the user already read
+    async fn put_to_index_with(
+        &self,
+        user_id: &str,
+        preferences: &NotificationPreferences,
+    ) -> RedisResult<()> {
+        if preferences.allows(user_id, &self.body).await? {
+            let notification_body_json = serde_json::to_string(&self.body)
+                .map_err(|e| RedisError::SerializationFailed(Box::new(e)))?;
+            let score = self.timestamp as f64;
+
+            Notification::put_index_sorted_set(
+                &["Notification", user_id],
+                &[(score, notification_body_json.as_str())],
+                None,
+                None,
+            )
+            .await?;
+
+            // Clients missing the push catch up from the list. The publish future is boxed to keep
+            // the futures of the event handlers shallow.
+            if let Err(e) = Box::pin(self.publish(user_id)).await {
+                tracing::warn!("Failed to publish notification for {user_id}: {e}");
+            }
+        }
+
+        // Dispatched once indexed, so a failed write retried by the watcher is not delivered twice
+        Box::pin(Webhooks::dispatch(user_id, self)).await;
+        Ok(())
+    }
+
+    /// Identifies the notification among the ones of the user: its timestamp followed by a hash
+    /// of its body, as notifications sharing a timestamp differ by their body
+    pub fn id(&self) -> String {
+        let body_json = serde_json::to_vec(&self.body).unwrap_or_default();
+        let hash = blake3::hash(&body_json);
+        format!("{}-{}", self.timestamp, &hash.to_hex()[..16])
+    }
+
+    /// Publishes the notification on the channel of the user, to push it to their connected clients
+    async fn publish(&self, user_id: &str) -> RedisResult<()> {
+        let notification_json = serde_json::to_string(self)
             .map_err(|e| RedisError::SerializationFailed(Box::new(e)))?;
-        let score = self.timestamp as f64;
+        pubsub::publish(&Self::channel(user_id), &notification_json).await?;
+        Ok(())
+    }
 
-        Notification::put_index_sorted_set(
-            &["Notification", user_id],
-            &[(score, notification_body_json.as_str())],
-            None,
-            None,
-        )
-        .await
+    /// Subscribes to the notifications of the user as they are indexed, until the stream is
+    /// dropped. Notifications indexed before subscribing are not part of the stream.
+    pub async fn subscribe(user_id: &str) -> RedisResult<impl Stream<Item = Self> + Send + Unpin> {
+        let messages = pubsub::subscribe(&Self::channel(user_id)).await?;
+        Ok(Box::pin(messages.filter_map(|message| async move {
+            serde_json::from_str::<Notification>(&message)
+                .inspect_err(|e| {
+                    tracing::warn!(
+                        "Failed to deserialize notification, body: {message}, reason: {e}"
+                    )
+                })
+                .ok()
+        })))
+    }
+
+    fn channel(user_id: &str) -> String {
+        format!("{NOTIFICATION_CHANNEL_PREFIX}:{user_id}")
     }
 
     /// Lists notifications from the sorted set for the user, based on skip and limit, or timestamp range.
@@ -130,6 +260,62 @@ impl Notification {
         let skip = pagination.skip.unwrap_or(0);
         let limit = pagination.limit.unwrap_or(20);
 
+        let (notifications, _) = Self::get_page(user_id, &pagination, skip, limit).await?;
+        Ok(notifications)
+    }
+
+    /// Lists the notifications of the user with one of the given `types`, paginated like
+    /// [Notification::get_by_id]
+    pub async fn get_by_types(
+        user_id: &str,
+        pagination: Pagination,
+        types: &[NotificationType],
+    ) -> RedisResult<Vec<Self>> {
+        let skip = pagination.skip.unwrap_or(0);
+        let limit = pagination.limit.unwrap_or(20);
+
+        let mut matching = Vec::new();
+        Self::scan(user_id, &pagination, |notification| {
+            if types.contains(&notification.body.notification_type()) {
+                matching.push(notification);
+            }
+            matching.len() < skip + limit
+        })
+        .await?;
+        Ok(matching.into_iter().skip(skip).take(limit).collect())
+    }
+
+    /// Visits the notifications of the user within the timestamp range of the pagination, the most
+    /// recent first, until `visit` returns false. Skip and limit are ignored.
+    pub(crate) async fn scan(
+        user_id: &str,
+        pagination: &Pagination,
+        mut visit: impl FnMut(Self) -> bool,
+    ) -> RedisResult<()> {
+        let mut skip = 0;
+        loop {
+            let (notifications, read) =
+                Self::get_page(user_id, pagination, skip, SCAN_BATCH_SIZE).await?;
+            for notification in notifications {
+                if !visit(notification) {
+                    return Ok(());
+                }
+            }
+            if read < SCAN_BATCH_SIZE {
+                return Ok(());
+            }
+            skip += read;
+        }
+    }
+
+    /// Reads a page of notifications within the timestamp range of the pagination, along with the
+    /// number of entries read, which includes the ones that failed to deserialize
+    async fn get_page(
+        user_id: &str,
+        pagination: &Pagination,
+        skip: usize,
+        limit: usize,
+    ) -> RedisResult<(Vec<Self>, usize)> {
         let notifications = Notification::try_from_index_sorted_set(
             &["Notification", user_id],
             pagination.start,
@@ -139,30 +325,67 @@ impl Notification {
             SortOrder::Descending, // StreamSorting in descending order by score (timestamp)
             None,
         )
-        .await?;
+        .await?
+        .unwrap_or_default();
 
+        let read = notifications.len();
         let mut result = Vec::new();
 
-        if let Some(notifications) = notifications {
-            for (notification_body_str, score) in notifications {
-                match serde_json::from_str::<NotificationBody>(&notification_body_str) {
-                    Ok(body) => {
-                        let notification = Notification {
-                            timestamp: score as i64,
-                            body,
-                        };
-                        result.push(notification);
-                    }
-                    Err(e) => {
-                        tracing::warn!(
-                            "Failed to deserialize notification, body: {notification_body_str}, reason: {e}"
-                        );
-                    }
+        for (notification_body_str, score) in notifications {
+            match serde_json::from_str::<NotificationBody>(&notification_body_str) {
+                Ok(body) => {
+                    let notification = Notification {
+                        timestamp: score as i64,
+                        body,
+                    };
+                    result.push(notification);
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to deserialize notification, body: {notification_body_str}, reason: {e}"
+                    );
                 }
             }
         }
 
-        Ok(result)
+        Ok((result, read))
+    }
+
+    /// Lists the notifications of the user that are more recent than their [LastRead] timestamp,
+    /// narrowing the timestamp range of the pagination accordingly.
+    pub async fn get_unread_by_id(user_id: &str, pagination: Pagination) -> RedisResult<Vec<Self>> {
+        let pagination = Self::unread_pagination(user_id, pagination).await?;
+        Self::get_by_id(user_id, pagination).await
+    }
+
+    /// Narrows the timestamp range of the pagination to the notifications of the user that are
+    /// more recent than their [LastRead] timestamp
+    pub async fn unread_pagination(
+        user_id: &str,
+        mut pagination: Pagination,
+    ) -> RedisResult<Pagination> {
+        if let Some(lower_bound) = Self::unread_lower_bound(user_id).await? {
+            pagination.end = Some(
+                pagination
+                    .end
+                    .map_or(lower_bound, |end| end.max(lower_bound)),
+            );
+        }
+        Ok(pagination)
+    }
+
+    /// Counts the notifications of the user that are more recent than their [LastRead] timestamp.
+    /// All notifications are unread if the user never published one.
+    pub async fn unread_count(user_id: &str) -> RedisResult<usize> {
+        let lower_bound = Self::unread_lower_bound(user_id).await?;
+        Notification::count_sorted_set_range(&["Notification", user_id], lower_bound, None, None)
+            .await
+    }
+
+    /// Lowest score of an unread notification of the user, if they read any
+    async fn unread_lower_bound(user_id: &str) -> RedisResult<Option<f64>> {
+        let last_read = LastRead::get_from_index(user_id).await?;
+        Ok(last_read.map(|last_read| (last_read.timestamp + 1) as f64))
     }
 
     pub async fn new_follow(user_id: &str, followee_id: &str, new_friend: bool) -> RedisResult<()> {
@@ -399,6 +622,7 @@ impl Notification {
             ),
         ];
 
+        let mut notifications = Vec::new();
         for (query_fn, post_changed_source, extract_fn) in notification_types {
             let query = query_fn(author_id, post_id);
             let rows = fetch_all_rows_from_graph(query).await?;
@@ -426,10 +650,19 @@ impl Notification {
                     },
                 };
 
-                let notification = Notification::new(notification_body);
-                notification.put_to_index(&user_id).await?;
+                notifications.push((user_id, Notification::new(notification_body)));
             }
         }
+
+        // The preferences of all the notified users are read at once
+        let user_ids: Vec<&str> = notifications
+            .iter()
+            .map(|(user_id, _)| user_id.as_str())
+            .collect();
+        let preferences = NotificationPreferences::get_by_ids(&user_ids).await?;
+        for ((user_id, notification), preferences) in notifications.iter().zip(&preferences) {
+            notification.put_to_index_with(user_id, preferences).await?;
+        }
         Ok(())
     }
 }
@@ -0,0 +1,147 @@
+use super::{NotificationBody, NotificationType, PostChangedSource};
+use crate::db::kv::RedisResult;
+use crate::db::RedisOps;
+use crate::models::follow::{Following, UserFollows};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Notifications a user does not want to receive. Muted notifications are not stored at all, so
+/// they are missing from the notification list, the unread count and the pushed notifications.
+#[derive(Serialize, Deserialize, ToSchema, Default, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct NotificationPreferences {
+    /// Types of notifications the user is never notified of
+    pub muted_types: Vec<NotificationType>,
+    /// Sources of the `post_edited` notifications the user is not notified of, e.g. `bookmark`
+    /// to ignore the edits of bookmarked posts
+    pub muted_edit_sources: Vec<PostChangedSource>,
+    /// Sources of the `post_deleted` notifications the user is not notified of
+    pub muted_delete_sources: Vec<PostChangedSource>,
+    /// Only notify the tags and untags (of posts and profile) made by users the user follows
+    pub tags_from_following_only: bool,
+}
+
+impl RedisOps for NotificationPreferences {}
+
+impl NotificationPreferences {
+    /// Retrieves the preferences of a user, the defaults muting nothing
+    pub async fn get_by_id(user_id: &str) -> RedisResult<Self> {
+        Ok(Self::try_from_index_json(&[user_id], None)
+            .await?
+            .unwrap_or_default())
+    }
+
+    /// Retrieves the preferences of several users at once, e.g. for a notification fanned out
+    /// to all of them
+    pub async fn get_by_ids(user_ids: &[&str]) -> RedisResult<Vec<Self>> {
+        let key_parts: Vec<[&str; 1]> = user_ids.iter().map(|user_id| [*user_id]).collect();
+        let key_parts: Vec<&[&str]> = key_parts.iter().map(|parts| &parts[..]).collect();
+        let preferences = Self::try_from_index_multiple_json(&key_parts).await?;
+        Ok(preferences
+            .into_iter()
+            .map(Option::unwrap_or_default)
+            .collect())
+    }
+
+    /// Stores the preferences of a user
+    pub async fn put_to_index(&self, user_id: &str) -> RedisResult<()> {
+        self.put_index_json(&[user_id], None, None).await
+    }
+
+    /// Removes the preferences of a user, restoring the defaults
+    pub async fn del_from_index(user_id: &str) -> RedisResult<()> {
+        Self::remove_from_index_multiple_json(&[&[user_id]]).await
+    }
+
+    /// Whether the user holding these preferences should receive the notification
+    pub async fn allows(&self, user_id: &str, body: &NotificationBody) -> RedisResult<bool> {
+        if self.mutes(body) {
+            return Ok(false);
+        }
+        if self.tags_from_following_only && Self::is_tag(body) {
+            return Following::check_in_index(user_id, body.actor()).await;
+        }
+        Ok(true)
+    }
+
+    /// Whether the notification is muted by its type or source, regardless of its actor
+    pub fn mutes(&self, body: &NotificationBody) -> bool {
+        if self.muted_types.contains(&body.notification_type()) {
+            return true;
+        }
+        match body {
+            NotificationBody::PostEdited { edit_source, .. } => {
+                self.muted_edit_sources.contains(edit_source)
+            }
+            NotificationBody::PostDeleted { delete_source, .. } => {
+                self.muted_delete_sources.contains(delete_source)
+            }
+            _ => false,
+        }
+    }
+
+    fn is_tag(body: &NotificationBody) -> bool {
+        matches!(
+            body,
+            NotificationBody::TagPost { .. }
+                | NotificationBody::TagProfile { .. }
+                | NotificationBody::UntagPost { .. }
+                | NotificationBody::UntagProfile { .. }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn post_edited(edit_source: PostChangedSource) -> NotificationBody {
+        NotificationBody::PostEdited {
+            edit_source,
+            edited_by: "author".to_string(),
+            edited_uri: "pubky://author/pub/pubky.app/posts/0032SSN7Q4EVG".to_string(),
+            linked_uri: "pubky://user/pub/pubky.app/bookmarks/BOOKMARKID".to_string(),
+        }
+    }
+
+    #[test]
+    fn defaults_mute_nothing() {
+        let preferences = NotificationPreferences::default();
+        let untag = NotificationBody::UntagProfile {
+            untagged_by: "tagger".to_string(),
+            tag_label: "rust".to_string(),
+        };
+        assert!(!preferences.mutes(&untag));
+        assert!(!preferences.mutes(&post_edited(PostChangedSource::Bookmark)));
+    }
+
+    #[test]
+    fn mutes_types_and_sources() {
+        let preferences = NotificationPreferences {
+            muted_types: vec![NotificationType::UntagPost, NotificationType::UntagProfile],
+            muted_edit_sources: vec![PostChangedSource::Bookmark],
+            ..Default::default()
+        };
+        let untag = NotificationBody::UntagProfile {
+            untagged_by: "tagger".to_string(),
+            tag_label: "rust".to_string(),
+        };
+        let tag = NotificationBody::TagProfile {
+            tagged_by: "tagger".to_string(),
+            tag_label: "rust".to_string(),
+        };
+        assert!(preferences.mutes(&untag));
+        assert!(!preferences.mutes(&tag));
+        assert!(preferences.mutes(&post_edited(PostChangedSource::Bookmark)));
+        assert!(!preferences.mutes(&post_edited(PostChangedSource::Reply)));
+    }
+
+    #[test]
+    fn missing_fields_default() {
+        let preferences: NotificationPreferences =
+            serde_json::from_str(r#"{"muted_types":["mention"]}"#).unwrap();
+        assert_eq!(preferences.muted_types, vec![NotificationType::Mention]);
+        assert!(preferences.muted_edit_sources.is_empty());
+        assert!(!preferences.tags_from_following_only);
+    }
+}
@@ -0,0 +1,107 @@
+use crate::db::kv::RedisResult;
+use crate::db::{fetch_all_rows_from_graph, fetch_row_from_graph, queries, GraphResult, RedisOps};
+use crate::models::error::ModelResult;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Homeserver a user is hosted on: the one their profile was first indexed from, until their
+/// published pkarr record is resolved to another one.
+///
+/// Stored as a `HOSTED_ON` relationship in the graph and indexed in Redis, where the watcher
+/// reads it to ignore the events of users that migrated away from the polled homeserver.
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone, PartialEq)]
+pub struct UserHomeserver {
+    pub homeserver_id: String,
+    /// Timestamp (ms) at which the user was found on this homeserver
+    pub indexed_at: i64,
+}
+
+impl RedisOps for UserHomeserver {}
+
+impl UserHomeserver {
+    /// Retrieves the homeserver of a user, first trying Redis, then Neo4j
+    pub async fn get_by_id(user_id: &str) -> ModelResult<Option<Self>> {
+        if let Some(user_homeserver) = Self::get_from_index(user_id).await? {
+            return Ok(Some(user_homeserver));
+        }
+        match Self::get_from_graph(user_id).await? {
+            Some(user_homeserver) => {
+                user_homeserver.put_to_index(user_id).await?;
+                Ok(Some(user_homeserver))
+            }
+            None => Ok(None),
+        }
+    }
+
+    pub async fn get_from_graph(user_id: &str) -> GraphResult<Option<Self>> {
+        let query = queries::get::get_user_homeserver(user_id);
+        let Some(row) = fetch_row_from_graph(query).await? else {
+            return Ok(None);
+        };
+        Ok(Some(Self {
+            homeserver_id: row.get("homeserver_id")?,
+            indexed_at: row.get("indexed_at").unwrap_or_default(),
+        }))
+    }
+
+    /// Retrieves a page of the indexed users, ordered by ID, with the homeserver they are
+    /// hosted on, if known
+    ///
+    /// # Arguments
+    /// * `after` - Only users with an ID greater than this one are returned, `""` for the first page
+    /// * `limit` - Maximum number of users to return
+    pub async fn get_page_from_graph(
+        after: &str,
+        limit: usize,
+    ) -> GraphResult<Vec<(String, Option<String>)>> {
+        let query = queries::get::get_users_homeservers(after, limit);
+        let rows = fetch_all_rows_from_graph(query).await?;
+        rows.into_iter()
+            .map(|row| Ok((row.get("user_id")?, row.get("homeserver_id")?)))
+            .collect()
+    }
+
+    pub async fn get_from_index(user_id: &str) -> RedisResult<Option<Self>> {
+        Self::try_from_index_json(&[user_id], None).await
+    }
+
+    pub async fn put_to_index(&self, user_id: &str) -> RedisResult<()> {
+        self.put_index_json(&[user_id], None, None).await
+    }
+
+    /// Restores the homeserver of a user from the graph to Redis
+    pub async fn reindex(user_id: &str) -> ModelResult<()> {
+        if let Some(user_homeserver) = Self::get_from_graph(user_id).await? {
+            user_homeserver.put_to_index(user_id).await?;
+        }
+        Ok(())
+    }
+
+    /// Records that the user is now hosted on `homeserver_id`, replacing the previous homeserver.
+    ///
+    /// # Returns
+    /// The recorded homeserver, `None` if the user or the homeserver is not in the graph
+    pub async fn put(user_id: &str, homeserver_id: &str) -> ModelResult<Option<Self>> {
+        let query = queries::put::set_user_homeserver(
+            user_id,
+            homeserver_id,
+            Utc::now().timestamp_millis(),
+        );
+        let Some(row) = fetch_row_from_graph(query).await? else {
+            return Ok(None);
+        };
+        let user_homeserver = Self {
+            homeserver_id: homeserver_id.to_string(),
+            indexed_at: row.get("indexed_at")?,
+        };
+        user_homeserver.put_to_index(user_id).await?;
+        Ok(Some(user_homeserver))
+    }
+
+    /// Removes the homeserver of a deleted user from Redis. The graph relationship is
+    /// deleted together with the user node.
+    pub async fn delete(user_id: &str) -> RedisResult<()> {
+        Self::remove_from_index_multiple_json(&[&[user_id]]).await
+    }
+}
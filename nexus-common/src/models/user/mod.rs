@@ -1,8 +1,10 @@
 mod counts;
 mod details;
+mod homeserver;
 //mod id;
 mod influencers;
 mod relationship;
+mod resync;
 mod search;
 mod stream;
 mod tags;
@@ -10,8 +12,10 @@ mod view;
 
 pub use counts::UserCounts;
 pub use details::UserDetails;
+pub use homeserver::UserHomeserver;
 pub use influencers::Influencers;
 pub use relationship::Relationship;
+pub use resync::{ResyncStatus, UserResyncRequest, USER_RESYNC_QUEUE_KEY_PARTS};
 pub use search::{UserSearch, USER_NAME_KEY_PARTS};
 pub use stream::{
     UserIdStream, UserStream, UserStreamInput, UserStreamSource, USER_INFLUENCERS_KEY_PARTS,
@@ -0,0 +1,243 @@
+use crate::db::kv::{RedisResult, SortOrder};
+use crate::db::RedisOps;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Users waiting for a resync, scored by the timestamp of their request
+pub const USER_RESYNC_QUEUE_KEY_PARTS: [&str; 2] = ["Resync", "Queue"];
+/// Users whose resync is running, scored by the timestamp at which it started
+pub const USER_RESYNC_RUNNING_KEY_PARTS: [&str; 2] = ["Resync", "Running"];
+/// Time (ms) after which a running resync is considered abandoned, e.g. by a watcher that
+/// crashed mid-run, and can be queued again
+pub const USER_RESYNC_STALE_AFTER_MS: i64 = 60 * 60 * 1000;
+
+/// State of a [`UserResyncRequest`]
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ResyncStatus {
+    /// Waiting for the watcher
+    Queued,
+    /// Being run by the watcher
+    Running,
+    Done,
+    Failed,
+}
+
+/// Request to resync a user from their homeserver, i.e. to converge the graph with the
+/// `/pub/pubky.app/` tree of the user.
+///
+/// Queued through the admin API and run by the watcher, which updates the request as it goes.
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone)]
+pub struct UserResyncRequest {
+    pub user_id: String,
+    pub status: ResyncStatus,
+    /// Timestamp (ms) of the request
+    pub requested_at: i64,
+    /// Timestamp (ms) at which the watcher started the resync
+    #[serde(default)]
+    pub started_at: Option<i64>,
+    /// Timestamp (ms) at which the resync completed or failed
+    pub finished_at: Option<i64>,
+    /// Number of resources on the homeserver that were missing from the graph
+    pub put: usize,
+    /// Number of resources in the graph that are no longer on the homeserver
+    pub deleted: usize,
+    /// Error of a failed resync
+    pub error: Option<String>,
+}
+
+impl RedisOps for UserResyncRequest {}
+
+impl UserResyncRequest {
+    /// Queues a resync of the user, unless one is already queued or running and not stale
+    pub async fn enqueue(user_id: &str) -> RedisResult<Self> {
+        if let Some(request) = Self::get_from_index(user_id).await? {
+            let now = Utc::now().timestamp_millis();
+            if request.status == ResyncStatus::Queued
+                || (request.status == ResyncStatus::Running && !request.is_stale(now))
+            {
+                return Ok(request);
+            }
+        }
+
+        let request = Self {
+            user_id: user_id.to_string(),
+            status: ResyncStatus::Queued,
+            requested_at: Utc::now().timestamp_millis(),
+            started_at: None,
+            finished_at: None,
+            put: 0,
+            deleted: 0,
+            error: None,
+        };
+        request.put_to_index().await?;
+        Ok(request)
+    }
+
+    /// Retrieves the last resync request of a user
+    pub async fn get_from_index(user_id: &str) -> RedisResult<Option<Self>> {
+        Self::try_from_index_json(&[user_id], None).await
+    }
+
+    /// Whether the request has been running for longer than [`USER_RESYNC_STALE_AFTER_MS`]
+    pub fn is_stale(&self, now: i64) -> bool {
+        self.status == ResyncStatus::Running
+            && self
+                .started_at
+                .is_none_or(|started_at| now - started_at > USER_RESYNC_STALE_AFTER_MS)
+    }
+
+    /// Stores this request, keeping it in the queue only while it is queued and in the running
+    /// set only while it is running
+    pub async fn put_to_index(&self) -> RedisResult<()> {
+        self.put_index_json(&[&self.user_id], None, None).await?;
+        let (add_to, remove_from) = match self.status {
+            ResyncStatus::Queued => (
+                Some((&USER_RESYNC_QUEUE_KEY_PARTS, self.requested_at)),
+                vec![&USER_RESYNC_RUNNING_KEY_PARTS],
+            ),
+            ResyncStatus::Running => (
+                Some((
+                    &USER_RESYNC_RUNNING_KEY_PARTS,
+                    self.started_at.unwrap_or(self.requested_at),
+                )),
+                vec![&USER_RESYNC_QUEUE_KEY_PARTS],
+            ),
+            ResyncStatus::Done | ResyncStatus::Failed => (
+                None,
+                vec![&USER_RESYNC_QUEUE_KEY_PARTS, &USER_RESYNC_RUNNING_KEY_PARTS],
+            ),
+        };
+        for key_parts in remove_from {
+            Self::remove_from_index_sorted_set(None, key_parts, &[&self.user_id]).await?;
+        }
+        if let Some((key_parts, score)) = add_to {
+            Self::put_index_sorted_set(key_parts, &[(score as f64, &self.user_id)], None, None)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Queues again the resyncs that have been running for longer than
+    /// [`USER_RESYNC_STALE_AFTER_MS`], left behind by a watcher that stopped mid-run
+    ///
+    /// # Returns
+    /// The number of requests queued again
+    pub async fn requeue_stale() -> RedisResult<usize> {
+        let now = Utc::now().timestamp_millis();
+        let user_ids = Self::try_from_index_sorted_set(
+            &USER_RESYNC_RUNNING_KEY_PARTS,
+            Some((now - USER_RESYNC_STALE_AFTER_MS) as f64),
+            None,
+            None,
+            None,
+            SortOrder::Ascending,
+            None,
+        )
+        .await?
+        .unwrap_or_default();
+
+        let mut count = 0;
+        for (user_id, _) in user_ids {
+            match Self::get_from_index(&user_id).await? {
+                Some(mut request) if request.is_stale(now) => {
+                    request.status = ResyncStatus::Queued;
+                    request.started_at = None;
+                    request.put_to_index().await?;
+                    count += 1;
+                }
+                // Finished or restarted in the meantime
+                Some(request) => request.put_to_index().await?,
+                None => {
+                    Self::remove_from_index_sorted_set(
+                        None,
+                        &USER_RESYNC_RUNNING_KEY_PARTS,
+                        &[&user_id],
+                    )
+                    .await?
+                }
+            }
+        }
+        Ok(count)
+    }
+
+    /// Lists the queued requests, the oldest first
+    pub async fn queued(limit: usize) -> RedisResult<Vec<Self>> {
+        let user_ids = Self::try_from_index_sorted_set(
+            &USER_RESYNC_QUEUE_KEY_PARTS,
+            None,
+            None,
+            None,
+            Some(limit),
+            SortOrder::Ascending,
+            None,
+        )
+        .await?
+        .unwrap_or_default();
+
+        let mut requests = Vec::with_capacity(user_ids.len());
+        for (user_id, _) in user_ids {
+            if let Some(request) = Self::get_from_index(&user_id).await? {
+                requests.push(request);
+            }
+        }
+        Ok(requests)
+    }
+
+    /// Marks the request as being run
+    pub async fn start(&mut self) -> RedisResult<()> {
+        self.status = ResyncStatus::Running;
+        self.started_at = Some(Utc::now().timestamp_millis());
+        self.put_to_index().await
+    }
+
+    /// Records the outcome of the resync: the number of resources put and deleted, or the error
+    pub async fn finish(&mut self, result: Result<(usize, usize), String>) -> RedisResult<()> {
+        match result {
+            Ok((put, deleted)) => {
+                self.status = ResyncStatus::Done;
+                self.put = put;
+                self.deleted = deleted;
+            }
+            Err(error) => {
+                self.status = ResyncStatus::Failed;
+                self.error = Some(error);
+            }
+        }
+        self.finished_at = Some(Utc::now().timestamp_millis());
+        self.put_to_index().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(status: ResyncStatus, started_at: Option<i64>) -> UserResyncRequest {
+        UserResyncRequest {
+            user_id: "user".to_string(),
+            status,
+            requested_at: 0,
+            started_at,
+            finished_at: None,
+            put: 0,
+            deleted: 0,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_is_stale() {
+        let now = 10 * USER_RESYNC_STALE_AFTER_MS;
+        let recent = Some(now - 1000);
+        let old = Some(now - USER_RESYNC_STALE_AFTER_MS - 1);
+
+        assert!(!request(ResyncStatus::Running, recent).is_stale(now));
+        assert!(request(ResyncStatus::Running, old).is_stale(now));
+        // Running requests stored before `started_at` was recorded
+        assert!(request(ResyncStatus::Running, None).is_stale(now));
+        assert!(!request(ResyncStatus::Queued, old).is_stale(now));
+        assert!(!request(ResyncStatus::Done, old).is_stale(now));
+    }
+}
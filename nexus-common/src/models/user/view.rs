@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
-use super::{Relationship, UserCounts, UserDetails};
+use super::{Relationship, UserCounts, UserDetails, UserHomeserver};
 use crate::db::RedisOps;
 use crate::models::error::ModelResult;
 use crate::models::tag::traits::TagCollection;
@@ -15,6 +15,9 @@ pub struct UserView {
     pub counts: UserCounts,
     pub tags: Vec<TagDetails>,
     pub relationship: Relationship,
+    /// ID of the homeserver the user is hosted on, if known
+    #[serde(default)]
+    pub homeserver: Option<String>,
 }
 
 impl UserView {
@@ -24,11 +27,13 @@ impl UserView {
         viewer_id: Option<&str>,
         depth: Option<u8>,
     ) -> ModelResult<Option<Self>> {
-        // Perform all operations concurrently
-        let (details, counts, relationship) = tokio::try_join!(
+        // Perform all operations concurrently. The homeserver is only read from the index, as most
+        // users have none and a graph fallback would query Neo4j on every view of them
+        let (details, counts, relationship, homeserver) = tokio::try_join!(
             UserDetails::get_by_id(user_id),
             UserCounts::get_by_id(user_id),
             Relationship::get_by_id(user_id, viewer_id),
+            async { Ok(UserHomeserver::get_from_index(user_id).await?) },
         )?;
 
         let Some(details) = details else {
@@ -52,6 +57,7 @@ impl UserView {
             counts,
             relationship,
             tags,
+            homeserver: homeserver.map(|homeserver| homeserver.homeserver_id),
         }))
     }
 
@@ -64,9 +70,12 @@ impl UserView {
         viewer_id: Option<&str>,
         depth: Option<u8>,
     ) -> ModelResult<Vec<Option<Self>>> {
-        // Use mget to fetch all user details and counts in bulk
-        let (details_list, counts_list): (Vec<Option<UserDetails>>, Vec<Option<UserCounts>>) =
-            tokio::try_join!(UserDetails::mget(user_ids), UserCounts::mget(user_ids))?;
+        // Use mget to fetch all user details, counts and homeservers in bulk
+        let (details_list, counts_list, homeservers_list) = tokio::try_join!(
+            UserDetails::mget(user_ids),
+            UserCounts::mget(user_ids),
+            UserHomeserver::mget(user_ids)
+        )?;
 
         let mut user_views = Vec::with_capacity(user_ids.len());
 
@@ -97,6 +106,9 @@ impl UserView {
                 counts,
                 relationship,
                 tags,
+                homeserver: homeservers_list[i]
+                    .as_ref()
+                    .map(|homeserver| homeserver.homeserver_id.clone()),
             }));
         }
 
@@ -0,0 +1,71 @@
+use crate::models::notification::NotificationType;
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+use std::time::Duration;
+
+/// Default for [WebhookConfig::max_attempts]
+pub const DEFAULT_WEBHOOK_MAX_ATTEMPTS: u32 = 5;
+/// Default for [WebhookConfig::initial_backoff_ms]
+pub const DEFAULT_WEBHOOK_INITIAL_BACKOFF_MS: u64 = 1_000;
+/// Upper bound for the delay between two delivery attempts, in milliseconds
+pub const WEBHOOK_MAX_BACKOFF_MS: u64 = 3_600_000;
+
+/// A URL the indexed notifications are POSTed to, see [Webhooks](super::Webhooks)
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct WebhookConfig {
+    /// Identifies the webhook in the delivery log
+    pub name: String,
+    pub url: String,
+    /// Key of the HMAC-SHA256 signature of the payloads
+    pub secret: String,
+    /// Types of the notifications delivered to the webhook, all of them if empty
+    #[serde(default)]
+    pub events: Vec<NotificationType>,
+    /// Number of delivery attempts after which a payload is logged as failed
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+    /// Delay before the first retry, in milliseconds, doubled after every failed attempt up to
+    /// [WEBHOOK_MAX_BACKOFF_MS]
+    #[serde(default = "default_initial_backoff_ms")]
+    pub initial_backoff_ms: u64,
+}
+
+impl WebhookConfig {
+    /// Whether notifications of this type are delivered to the webhook
+    pub fn matches(&self, notification_type: NotificationType) -> bool {
+        self.events.is_empty() || self.events.contains(&notification_type)
+    }
+
+    /// Delay before the next attempt after `failed_attempts` failed ones:
+    /// `min(initial_backoff_ms * 2^(failed_attempts - 1), MAX)`
+    pub fn backoff(&self, failed_attempts: u32) -> Duration {
+        let factor = 2u64.saturating_pow(failed_attempts.saturating_sub(1));
+        let backoff_ms = self
+            .initial_backoff_ms
+            .saturating_mul(factor)
+            .min(WEBHOOK_MAX_BACKOFF_MS);
+        Duration::from_millis(backoff_ms)
+    }
+}
+
+// The secret is redacted, as the config is logged on start
+impl Debug for WebhookConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebhookConfig")
+            .field("name", &self.name)
+            .field("url", &self.url)
+            .field("secret", &"<redacted>")
+            .field("events", &self.events)
+            .field("max_attempts", &self.max_attempts)
+            .field("initial_backoff_ms", &self.initial_backoff_ms)
+            .finish()
+    }
+}
+
+fn default_max_attempts() -> u32 {
+    DEFAULT_WEBHOOK_MAX_ATTEMPTS
+}
+
+fn default_initial_backoff_ms() -> u64 {
+    DEFAULT_WEBHOOK_INITIAL_BACKOFF_MS
+}
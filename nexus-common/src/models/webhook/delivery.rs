@@ -0,0 +1,88 @@
+use crate::db::kv::{RedisError, RedisResult, SortOrder};
+use crate::db::RedisOps;
+use crate::models::notification::NotificationType;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Deliveries older than this, in milliseconds, are dropped from the log: one week
+const DELIVERY_LOG_RETENTION: i64 = 7 * 24 * 60 * 60 * 1000;
+
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    /// The webhook answered with a 2xx status
+    Delivered,
+    /// Every attempt failed
+    Failed,
+}
+
+/// Outcome of the delivery of a payload to a webhook, logged per webhook and scored by the
+/// time of the last attempt
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone)]
+pub struct WebhookDelivery {
+    /// Id of the delivered payload
+    pub id: String,
+    #[serde(rename = "type")]
+    pub event_type: NotificationType,
+    /// User who received the notification
+    pub user_id: String,
+    pub status: DeliveryStatus,
+    pub attempts: u32,
+    /// HTTP status of the last attempt, if the webhook answered
+    pub status_code: Option<u16>,
+    /// Error of the last attempt, if it failed
+    pub error: Option<String>,
+    /// Timestamp (ms) of the last attempt
+    pub timestamp: i64,
+}
+
+impl RedisOps for WebhookDelivery {}
+
+impl WebhookDelivery {
+    /// Lists the deliveries of a webhook, the most recent first
+    pub async fn get_by_webhook(
+        webhook: &str,
+        skip: Option<usize>,
+        limit: Option<usize>,
+    ) -> RedisResult<Vec<Self>> {
+        let deliveries = Self::try_from_index_sorted_set(
+            &["Webhook", "Deliveries", webhook],
+            None,
+            None,
+            skip,
+            limit,
+            SortOrder::Descending,
+            None,
+        )
+        .await?
+        .unwrap_or_default();
+
+        Ok(deliveries
+            .into_iter()
+            .filter_map(|(delivery, _)| {
+                serde_json::from_str(&delivery)
+                    .inspect_err(|e| tracing::warn!("Failed to deserialize webhook delivery: {e}"))
+                    .ok()
+            })
+            .collect())
+    }
+
+    /// Logs the delivery, dropping the deliveries of the webhook past the retention period
+    pub async fn put_to_index(&self, webhook: &str) -> RedisResult<()> {
+        let key_parts = ["Webhook", "Deliveries", webhook];
+        let delivery_json = serde_json::to_string(self)
+            .map_err(|e| RedisError::SerializationFailed(Box::new(e)))?;
+        Self::put_index_sorted_set(
+            &key_parts,
+            &[(self.timestamp as f64, delivery_json.as_str())],
+            None,
+            None,
+        )
+        .await?;
+
+        let expired = (Utc::now().timestamp_millis() - DELIVERY_LOG_RETENTION) as f64;
+        Self::remove_sorted_set_range(&key_parts, None, Some(expired), None).await?;
+        Ok(())
+    }
+}
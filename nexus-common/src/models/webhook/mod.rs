@@ -0,0 +1,350 @@
+use crate::db::kv::RedisResult;
+use crate::models::notification::{Notification, NotificationType};
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+mod config;
+mod delivery;
+mod pending;
+
+pub use config::{
+    WebhookConfig, DEFAULT_WEBHOOK_INITIAL_BACKOFF_MS, DEFAULT_WEBHOOK_MAX_ATTEMPTS,
+    WEBHOOK_MAX_BACKOFF_MS,
+};
+pub use delivery::{DeliveryStatus, WebhookDelivery};
+pub use pending::PendingDelivery;
+
+/// Header with the HMAC-SHA256 signature of the request body, as `sha256=<hex digest>`
+pub const SIGNATURE_HEADER: &str = "X-Nexus-Signature";
+/// Header with the id of the payload, the same across the delivery attempts
+pub const PAYLOAD_ID_HEADER: &str = "X-Nexus-Webhook-Id";
+
+/// Timeout of a delivery attempt
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+/// Delay after which a delivery whose attempt did not complete, as the process stopped, is
+/// attempted again
+const ATTEMPT_LEASE: Duration = Duration::from_secs(60);
+/// Maximum number of pending deliveries attempted per [Webhooks::run_pending]
+const PENDING_BATCH_SIZE: usize = 100;
+
+static WEBHOOKS: OnceLock<Webhooks> = OnceLock::new();
+
+/// Body POSTed to the webhooks for every indexed notification
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WebhookPayload {
+    /// Unique id of the payload, for receivers to deduplicate retried deliveries
+    pub id: String,
+    #[serde(rename = "type")]
+    pub event_type: NotificationType,
+    /// User who received the notification
+    pub user_id: String,
+    pub notification: Notification,
+}
+
+impl WebhookPayload {
+    /// The id is derived from the notification: its recipient, its timestamp and its body,
+    /// which holds its type and names the source user and URIs. The timestamp tells apart the
+    /// repeated events with the same body, e.g. a follow after an unfollow.
+    pub fn new(user_id: &str, notification: &Notification) -> Self {
+        let event_type = notification.body.notification_type();
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(user_id.as_bytes());
+        hasher.update(&notification.timestamp.to_be_bytes());
+        hasher.update(
+            serde_json::to_string(&notification.body)
+                .unwrap_or_default()
+                .as_bytes(),
+        );
+        Self {
+            id: hasher.finalize().to_hex()[..32].to_string(),
+            event_type,
+            user_id: user_id.to_string(),
+            notification: notification.clone(),
+        }
+    }
+}
+
+/// Signs a payload with the secret of a webhook, as sent in the [SIGNATURE_HEADER]
+pub fn sign(secret: &str, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(body.as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Outbound webhooks, to which the watcher POSTs the notifications it indexes.
+///
+/// Deliveries run in the background and are retried with an exponential backoff. They are
+/// persisted as [PendingDelivery] until they succeed or their attempts are exhausted, and their
+/// outcome is logged in [WebhookDelivery]. Notifications muted by their recipient are delivered too, as
+/// the webhooks serve downstream services rather than users.
+pub struct Webhooks {
+    client: reqwest::Client,
+    webhooks: Vec<WebhookConfig>,
+}
+
+impl Webhooks {
+    /// Registers the webhooks notifications are delivered to. Only the first call takes effect.
+    pub fn init(webhooks: &[WebhookConfig]) {
+        if webhooks.is_empty() || WEBHOOKS.get().is_some() {
+            return;
+        }
+        let client = match reqwest::Client::builder().timeout(DELIVERY_TIMEOUT).build() {
+            Ok(client) => client,
+            Err(e) => {
+                error!("Failed to build the webhooks HTTP client, webhooks are disabled: {e}");
+                return;
+            }
+        };
+        let webhooks = webhooks
+            .iter()
+            .filter(|webhook| match reqwest::Url::parse(&webhook.url) {
+                Ok(_) => true,
+                Err(e) => {
+                    error!("Ignoring webhook {} with an invalid url: {e}", webhook.name);
+                    false
+                }
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        info!("Delivering notifications to {} webhook(s)", webhooks.len());
+        let _ = WEBHOOKS.set(Self { client, webhooks });
+    }
+
+    /// Delivers the notification of the user to the matching webhooks, in the background.
+    /// Does nothing if no webhook is registered.
+    pub async fn dispatch(user_id: &str, notification: &Notification) {
+        let Some(webhooks) = WEBHOOKS.get() else {
+            return;
+        };
+        let payload = WebhookPayload::new(user_id, notification);
+        let matching = webhooks
+            .webhooks
+            .iter()
+            .filter(|webhook| webhook.matches(payload.event_type));
+
+        for webhook in matching {
+            let pending = PendingDelivery::new(&webhook.name, payload.clone());
+            // Picked up by [Self::run_pending] only if the attempt below is interrupted
+            if let Err(e) = pending.schedule(lease_end()).await {
+                error!(
+                    "Failed to persist delivery {} to webhook {}: {e}",
+                    payload.id, webhook.name
+                );
+            }
+            tokio::spawn(Self::attempt(
+                webhooks.client.clone(),
+                webhook.clone(),
+                pending,
+            ));
+        }
+    }
+
+    /// Attempts the pending deliveries whose next attempt is due, in the background: the retries
+    /// of the failed attempts and the deliveries interrupted by a restart.
+    ///
+    /// # Returns
+    /// The number of attempts started
+    pub async fn run_pending() -> RedisResult<usize> {
+        let Some(webhooks) = WEBHOOKS.get() else {
+            return Ok(0);
+        };
+        let due =
+            PendingDelivery::get_due(Utc::now().timestamp_millis(), PENDING_BATCH_SIZE).await?;
+
+        let mut started = 0;
+        for pending in due {
+            let Some(webhook) = webhooks
+                .webhooks
+                .iter()
+                .find(|webhook| webhook.name == pending.webhook)
+            else {
+                warn!(
+                    "Dropping delivery {} to the unregistered webhook {}",
+                    pending.payload.id, pending.webhook
+                );
+                pending.remove().await?;
+                continue;
+            };
+            // Leased to the attempt, so the next run does not start it again
+            pending.schedule(lease_end()).await?;
+            tokio::spawn(Self::attempt(
+                webhooks.client.clone(),
+                webhook.clone(),
+                pending,
+            ));
+            started += 1;
+        }
+        Ok(started)
+    }
+
+    /// POSTs the payload to the webhook. A failed attempt is scheduled again after the backoff
+    /// until the attempts are exhausted, otherwise the delivery is logged.
+    async fn attempt(
+        client: reqwest::Client,
+        webhook: WebhookConfig,
+        mut pending: PendingDelivery,
+    ) {
+        let payload = &pending.payload;
+        let body = match serde_json::to_string(payload) {
+            Ok(body) => body,
+            Err(e) => {
+                error!("Failed to serialize webhook payload {}: {e}", payload.id);
+                return;
+            }
+        };
+        let response = client
+            .post(&webhook.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, sign(&webhook.secret, &body))
+            .header(PAYLOAD_ID_HEADER, &payload.id)
+            .body(body)
+            .send()
+            .await;
+
+        pending.attempts += 1;
+        let status = match response {
+            Ok(response) if response.status().is_success() => {
+                pending.status_code = Some(response.status().as_u16());
+                pending.error = None;
+                DeliveryStatus::Delivered
+            }
+            Ok(response) => {
+                pending.status_code = Some(response.status().as_u16());
+                pending.error = Some(format!("Unexpected status {}", response.status()));
+                DeliveryStatus::Failed
+            }
+            Err(e) => {
+                pending.status_code = None;
+                pending.error = Some(e.to_string());
+                DeliveryStatus::Failed
+            }
+        };
+
+        if status == DeliveryStatus::Failed && pending.attempts < webhook.max_attempts.max(1) {
+            let backoff_ms = webhook.backoff(pending.attempts).as_millis() as i64;
+            let due_at = Utc::now().timestamp_millis().saturating_add(backoff_ms);
+            if let Err(e) = pending.schedule(due_at).await {
+                error!(
+                    "Failed to schedule the retry of delivery {} to webhook {}: {e}",
+                    pending.payload.id, webhook.name
+                );
+            }
+            return;
+        }
+
+        if status == DeliveryStatus::Failed {
+            warn!(
+                "Failed to deliver payload {} to webhook {} after {} attempts: {:?}",
+                pending.payload.id, webhook.name, pending.attempts, pending.error
+            );
+        }
+        let delivery = WebhookDelivery {
+            id: pending.payload.id.clone(),
+            event_type: pending.payload.event_type,
+            user_id: pending.payload.user_id.clone(),
+            status,
+            attempts: pending.attempts,
+            status_code: pending.status_code,
+            error: pending.error.clone(),
+            timestamp: Utc::now().timestamp_millis(),
+        };
+        if let Err(e) = delivery.put_to_index(&webhook.name).await {
+            error!(
+                "Failed to log delivery {} to webhook {}: {e}",
+                delivery.id, webhook.name
+            );
+        }
+        if let Err(e) = pending.remove().await {
+            error!(
+                "Failed to remove delivery {} to webhook {}: {e}",
+                delivery.id, webhook.name
+            );
+        }
+    }
+}
+
+/// Due time of a delivery leased to a running attempt
+fn lease_end() -> i64 {
+    Utc::now().timestamp_millis() + ATTEMPT_LEASE.as_millis() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::notification::NotificationBody;
+
+    fn follow(timestamp: i64) -> Notification {
+        Notification {
+            timestamp,
+            body: NotificationBody::Follow {
+                followed_by: "follower".to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn sign_with_hmac_sha256() {
+        assert_eq!(
+            sign("key", "The quick brown fox jumps over the lazy dog"),
+            "sha256=f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+        );
+    }
+
+    #[test]
+    fn payload_id_identifies_the_notification() {
+        let payload = WebhookPayload::new("user", &follow(1000));
+        assert_eq!(payload.id.len(), 32);
+        assert_eq!(payload.event_type, NotificationType::Follow);
+        assert_eq!(payload.id, WebhookPayload::new("user", &follow(1000)).id);
+        assert_ne!(payload.id, WebhookPayload::new("other", &follow(1000)).id);
+    }
+
+    #[test]
+    fn repeated_notifications_get_distinct_payload_ids() {
+        // e.g. follow, unfollow and follow again
+        let first = WebhookPayload::new("user", &follow(1000));
+        let second = WebhookPayload::new("user", &follow(2000));
+        assert_ne!(first.id, second.id);
+
+        // so their pending deliveries do not overwrite each other
+        let first = PendingDelivery::new("moderation", first);
+        let second = PendingDelivery::new("moderation", second);
+        assert_ne!(first.index_key(), second.index_key());
+    }
+
+    #[test]
+    fn webhook_matches_configured_events() {
+        let mut webhook: WebhookConfig = serde_json::from_str(
+            r#"{"name": "moderation", "url": "http://localhost:9000", "secret": "s3cr3t"}"#,
+        )
+        .unwrap();
+        assert_eq!(webhook.max_attempts, DEFAULT_WEBHOOK_MAX_ATTEMPTS);
+        assert!(webhook.matches(NotificationType::Reply));
+
+        webhook.events = vec![NotificationType::Mention, NotificationType::Follow];
+        assert!(webhook.matches(NotificationType::Follow));
+        assert!(!webhook.matches(NotificationType::Reply));
+        assert!(!format!("{webhook:?}").contains("s3cr3t"));
+    }
+
+    #[test]
+    fn webhook_backoff_doubles_up_to_the_cap() {
+        let webhook: WebhookConfig = serde_json::from_str(
+            r#"{"name": "moderation", "url": "http://localhost:9000", "secret": "s3cr3t"}"#,
+        )
+        .unwrap();
+        assert_eq!(webhook.backoff(1), Duration::from_millis(1_000));
+        assert_eq!(webhook.backoff(2), Duration::from_millis(2_000));
+        assert_eq!(webhook.backoff(4), Duration::from_millis(8_000));
+        assert_eq!(
+            webhook.backoff(u32::MAX),
+            Duration::from_millis(WEBHOOK_MAX_BACKOFF_MS)
+        );
+    }
+}
@@ -0,0 +1,116 @@
+use super::WebhookPayload;
+use crate::db::kv::{RedisResult, SortOrder};
+use crate::db::RedisOps;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+pub const WEBHOOK_PENDING_PREFIX: &str = "WebhookPending";
+/// Keys of the pending deliveries, scored with the timestamp (ms) at which their next attempt is due
+pub const WEBHOOK_PENDING_DELIVERIES_INDEX: [&str; 1] = ["deliveries"];
+pub const WEBHOOK_PENDING_STATE_INDEX: [&str; 1] = ["state"];
+
+/// Delivery of a payload to a webhook waiting for its next attempt.
+///
+/// Persisted in Redis like the retry queue of the watcher, so the deliveries in flight survive
+/// a restart.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PendingDelivery {
+    /// Name of the webhook the payload is delivered to
+    pub webhook: String,
+    pub payload: WebhookPayload,
+    /// Number of failed attempts
+    pub attempts: u32,
+    /// HTTP status of the last attempt, if the webhook answered
+    pub status_code: Option<u16>,
+    /// Error of the last attempt, if it failed
+    pub error: Option<String>,
+}
+
+#[async_trait]
+impl RedisOps for PendingDelivery {
+    async fn prefix() -> String {
+        String::from(WEBHOOK_PENDING_PREFIX)
+    }
+}
+
+impl PendingDelivery {
+    pub fn new(webhook: &str, payload: WebhookPayload) -> Self {
+        Self {
+            webhook: webhook.to_string(),
+            payload,
+            attempts: 0,
+            status_code: None,
+            error: None,
+        }
+    }
+
+    /// Identifies the delivery in the index, as `"{webhook}:{payload id}"`
+    pub(super) fn index_key(&self) -> String {
+        format!("{}:{}", self.webhook, self.payload.id)
+    }
+
+    /// Stores the delivery, due at the given timestamp (ms)
+    pub async fn schedule(&self, due_at: i64) -> RedisResult<()> {
+        let index_key = self.index_key();
+        self.put_index_json(
+            &[WEBHOOK_PENDING_STATE_INDEX, [&index_key]].concat(),
+            None,
+            None,
+        )
+        .await?;
+
+        Self::put_index_sorted_set(
+            &WEBHOOK_PENDING_DELIVERIES_INDEX,
+            &[(due_at as f64, index_key.as_str())],
+            Some(WEBHOOK_PENDING_PREFIX),
+            None,
+        )
+        .await
+    }
+
+    /// Removes the delivery once it succeeded or its attempts are exhausted
+    pub async fn remove(&self) -> RedisResult<()> {
+        Self::remove_by_index_key(&self.index_key()).await
+    }
+
+    async fn remove_by_index_key(index_key: &str) -> RedisResult<()> {
+        Self::remove_from_index_sorted_set(
+            Some(WEBHOOK_PENDING_PREFIX),
+            &WEBHOOK_PENDING_DELIVERIES_INDEX,
+            &[index_key],
+        )
+        .await?;
+
+        let index: &Vec<&str> = &[WEBHOOK_PENDING_STATE_INDEX, [index_key]].concat();
+        Self::remove_from_index_multiple_json(&[index]).await
+    }
+
+    /// Retrieves the deliveries whose next attempt is due, oldest first
+    /// # Arguments
+    /// * `now` - Timestamp (ms) used as the upper bound of the due window
+    /// * `limit` - Maximum number of deliveries to return
+    pub async fn get_due(now: i64, limit: usize) -> RedisResult<Vec<Self>> {
+        let due = Self::try_from_index_sorted_set(
+            &WEBHOOK_PENDING_DELIVERIES_INDEX,
+            Some(now as f64),
+            None,
+            None,
+            Some(limit),
+            SortOrder::Ascending,
+            Some(WEBHOOK_PENDING_PREFIX),
+        )
+        .await?
+        .unwrap_or_default();
+
+        let mut deliveries = Vec::with_capacity(due.len());
+        for (index_key, _) in due {
+            let index = &[WEBHOOK_PENDING_STATE_INDEX, [&index_key]].concat();
+            match Self::try_from_index_json(index, None).await? {
+                Some(delivery) => deliveries.push(delivery),
+                // The state is gone, nothing left to deliver
+                None => Self::remove_by_index_key(&index_key).await?,
+            }
+        }
+        Ok(deliveries)
+    }
+}
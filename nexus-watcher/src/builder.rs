@@ -1,5 +1,8 @@
 use crate::dispatcher::EventDispatcher;
-use crate::service::NexusWatcher;
+use crate::service::{
+    rebuild_from_log, EventImport, EventProcessorRunner, EventReplay, ImportSummary, NexusWatcher,
+    RebuildSummary, ReplaySummary, ResyncSummary, UserResync,
+};
 use nexus_common::db::{DatabaseConfig, PubkyConnector};
 use nexus_common::plugin::{NexusPlugin, PluginContext};
 use nexus_common::types::DynError;
@@ -123,12 +126,57 @@ impl NexusWatcherBuilder {
                 .await?;
         }
 
-        let dispatcher = if self.plugins.is_empty() {
-            None
-        } else {
-            Some(Arc::new(EventDispatcher::new(self.plugins)))
-        };
+        let dispatcher = EventDispatcher::from_plugins(self.plugins);
 
         NexusWatcher::start(shutdown_rx, self.config, dispatcher).await
     }
+
+    /// Initializes the service stack and replays a range of homeserver events,
+    /// see [EventReplay]. The replay stops early on Ctrl-C.
+    pub async fn replay(self, replay: EventReplay) -> Result<ReplaySummary, DynError> {
+        self.init_stack().await?;
+
+        let dispatcher = EventDispatcher::from_plugins(self.plugins);
+
+        let runner =
+            EventProcessorRunner::from_config(&self.config, create_shutdown_rx(), dispatcher);
+        replay.run(&runner).await
+    }
+
+    /// Initializes the service stack and resyncs a single user from their homeserver,
+    /// see [UserResync]
+    pub async fn resync(self, resync: UserResync) -> Result<ResyncSummary, DynError> {
+        self.init_stack().await?;
+
+        let dispatcher = EventDispatcher::from_plugins(self.plugins);
+
+        let runner =
+            EventProcessorRunner::from_config(&self.config, create_shutdown_rx(), dispatcher);
+        resync.run(&runner).await
+    }
+
+    /// Opens the DB connections, serves the blobs from the dump directory instead of the
+    /// homeservers and imports its events, see [EventImport]. The import stops early on Ctrl-C.
+    pub async fn import(self, import: EventImport) -> Result<ImportSummary, DynError> {
+        StackManager::setup(&self.config.stack).await?;
+        PubkyConnector::init_from_dump(import.dir.clone()).await?;
+
+        let dispatcher = EventDispatcher::from_plugins(self.plugins);
+
+        let runner =
+            EventProcessorRunner::from_config(&self.config, create_shutdown_rx(), dispatcher);
+        import.run(&runner).await
+    }
+
+    /// Opens the DB connections, without any homeserver client, and rebuilds the indexes
+    /// from the local event log, see [rebuild_from_log]. The rebuild stops early on Ctrl-C.
+    pub async fn rebuild_from_log(self) -> Result<RebuildSummary, DynError> {
+        StackManager::setup(&self.config.stack).await?;
+
+        // The plugins are not run, only used to tell their events apart
+        let dispatcher = EventDispatcher::from_plugins(self.plugins);
+        let runner =
+            EventProcessorRunner::from_config(&self.config, create_shutdown_rx(), dispatcher);
+        rebuild_from_log(&runner).await
+    }
 }
@@ -2,10 +2,11 @@
 //! prefix, intercepting them *before* `Event::parse_event()` so
 //! `pubky-app-specs` never sees domain-specific URIs.
 
-use nexus_common::db::PubkyConnector;
+use crate::metrics::metrics;
 use nexus_common::models::event::EventProcessorError;
 use nexus_common::plugin::{NexusPlugin, PluginContext};
 use std::sync::Arc;
+use std::time::Instant;
 use tracing::{debug, warn};
 
 pub struct EventDispatcher {
@@ -40,6 +41,11 @@ impl EventDispatcher {
         Self { plugins }
     }
 
+    /// The dispatcher of the registered plugins, `None` if there are none
+    pub fn from_plugins(plugins: Vec<Arc<dyn NexusPlugin>>) -> Option<Arc<Self>> {
+        (!plugins.is_empty()).then(|| Arc::new(Self::new(plugins)))
+    }
+
     /// Returns `Ok(true)` if one or more registered plugins handled this event
     /// line, `Ok(false)` if no plugin matched (caller should fall through to
     /// social watcher), or `Err` if any plugin failed after claiming the event
@@ -54,50 +60,15 @@ impl EventDispatcher {
     ///
     /// Event line format: `"PUT pubky://user_id/pub/..."` or `"DEL pubky://..."`.
     pub async fn try_dispatch(&self, line: &str) -> Result<bool, EventProcessorError> {
-        if self.plugins.is_empty() {
+        let Some(Route {
+            event_type,
+            uri,
+            matching,
+        }) = self.route(line)
+        else {
             return Ok(false);
-        }
-
-        // Split "PUT pubky://..." → (event_type, uri)
-        let mut parts = line.splitn(2, ' ');
-        let event_type = match parts.next() {
-            Some(t) => t,
-            None => return Ok(false),
-        };
-        let uri = match parts.next() {
-            Some(u) => u.trim(),
-            None => return Ok(false),
-        };
-
-        // Extract the /pub/{domain}.app/... path from pubky://{user_id}/pub/...
-        let path = match extract_pub_path(uri) {
-            Some(p) => p,
-            None => return Ok(false),
         };
 
-        // Collect all plugins whose namespace prefix matches this path.
-        let matching: Vec<_> = self
-            .plugins
-            .iter()
-            .filter(|p| path.starts_with(p.manifest().namespace))
-            .collect();
-
-        if matching.is_empty() {
-            return Ok(false);
-        }
-
-        // App-specific files/blobs/tags use universal Nexus handling. Let them
-        // fall through instead of requiring every plugin to duplicate core logic.
-        let resource_suffix = path
-            .strip_prefix(matching[0].manifest().namespace)
-            .unwrap_or(path);
-        if resource_suffix.starts_with("files/")
-            || resource_suffix.starts_with("blobs/")
-            || resource_suffix.starts_with("tags/")
-        {
-            return Ok(false);
-        }
-
         let user_id = match extract_user_id(uri) {
             Some(u) => u,
             None => {
@@ -119,25 +90,80 @@ impl EventDispatcher {
 
             let ctx = PluginContext::for_plugin(plugin.as_ref());
 
-            match event_type {
+            let t0 = Instant::now();
+            let result = match event_type {
                 "PUT" => {
                     plugin
                         .handle_put(uri, data.as_deref().unwrap(), &user_id, &ctx)
                         .await
-                        .map_err(EventProcessorError::generic)?;
-                }
-                "DEL" => {
-                    plugin
-                        .handle_del(uri, &user_id, &ctx)
-                        .await
-                        .map_err(EventProcessorError::generic)?;
                 }
+                "DEL" => plugin.handle_del(uri, &user_id, &ctx).await,
                 _ => return Ok(false),
-            }
+            };
+            metrics().record_plugin_dispatch(manifest.name, t0.elapsed());
+            result.map_err(EventProcessorError::generic)?;
         }
 
         Ok(true) // at least one plugin handled the event
     }
+
+    /// Whether a registered plugin would claim this event line in [Self::try_dispatch],
+    /// without fetching its blob or running the plugin.
+    pub fn matches(&self, line: &str) -> bool {
+        self.route(line).is_some()
+    }
+
+    /// Splits the event line into its event type and URI, and collects the plugins
+    /// whose namespace prefix matches its path. Returns `None` if no plugin claims the event.
+    fn route<'a>(&'a self, line: &'a str) -> Option<Route<'a>> {
+        if self.plugins.is_empty() {
+            return None;
+        }
+
+        // Split "PUT pubky://..." → (event_type, uri)
+        let mut parts = line.splitn(2, ' ');
+        let event_type = parts.next()?;
+        let uri = parts.next()?.trim();
+
+        // Extract the /pub/{domain}.app/... path from pubky://{user_id}/pub/...
+        let path = extract_pub_path(uri)?;
+
+        // Collect all plugins whose namespace prefix matches this path.
+        let matching: Vec<_> = self
+            .plugins
+            .iter()
+            .filter(|p| path.starts_with(p.manifest().namespace))
+            .collect();
+
+        if matching.is_empty() {
+            return None;
+        }
+
+        // App-specific files/blobs/tags use universal Nexus handling. Let them
+        // fall through instead of requiring every plugin to duplicate core logic.
+        let resource_suffix = path
+            .strip_prefix(matching[0].manifest().namespace)
+            .unwrap_or(path);
+        if resource_suffix.starts_with("files/")
+            || resource_suffix.starts_with("blobs/")
+            || resource_suffix.starts_with("tags/")
+        {
+            return None;
+        }
+
+        Some(Route {
+            event_type,
+            uri,
+            matching,
+        })
+    }
+}
+
+/// An event line claimed by one or more plugins, see [EventDispatcher::route]
+struct Route<'a> {
+    event_type: &'a str,
+    uri: &'a str,
+    matching: Vec<&'a Arc<dyn NexusPlugin>>,
 }
 
 /// Extract `/pub/{domain}.app/...` from `pubky://{user_id}/pub/...`.
@@ -160,25 +186,10 @@ fn extract_user_id(uri: &str) -> Option<String> {
 }
 
 async fn fetch_blob(uri: &str) -> Result<Vec<u8>, EventProcessorError> {
-    let pubky = PubkyConnector::get()?;
-    let response = pubky.public_storage().get(uri).await?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let body = response
-            .text()
-            .await
-            .unwrap_or_else(|_| "<unable to read body>".to_string());
-
-        let err_msg = format!("Fetch resource failed {uri}: HTTP {status} - {body}");
-        return Err(EventProcessorError::client_error(err_msg))?;
-    }
-
-    response
-        .bytes()
-        .await
-        .map(|bytes| bytes.to_vec())
-        .map_err(|e| EventProcessorError::client_error(e.to_string()))
+    let t0 = Instant::now();
+    let blob = crate::events::fetch_blob(uri).await;
+    metrics().record_blob_fetch(t0.elapsed());
+    blob
 }
 
 #[cfg(test)]
@@ -296,6 +307,15 @@ mod tests {
         assert!(matches!(result, Ok(false)));
     }
 
+    #[test]
+    fn test_matches_mirrors_try_dispatch() {
+        let dispatcher = EventDispatcher::new(vec![Arc::new(MockPlugin) as Arc<dyn NexusPlugin>]);
+        assert!(dispatcher.matches("PUT pubky://abc123/pub/mock.app/items/id1"));
+        assert!(!dispatcher.matches("PUT pubky://abc123/pub/other.app/items/id1"));
+        assert!(!dispatcher.matches("PUT pubky://abc123/pub/mock.app/tags/tag1"));
+        assert!(!EventDispatcher::new(vec![]).matches("PUT pubky://abc123/pub/mock.app/items/id1"));
+    }
+
     #[test]
     fn test_plugins_sorted_longest_namespace_first() {
         // The sort key is namespace length — verify the comparator directly.
@@ -0,0 +1,35 @@
+use crate::events::EventProcessorError;
+
+use nexus_common::models::feed::FeedDetails;
+use pubky_app_specs::{PubkyAppFeed, PubkyId};
+use tracing::debug;
+
+#[tracing::instrument(name = "feed.put", skip_all, fields(user_id = %user_id, feed_id = %feed_id))]
+pub async fn sync_put(
+    user_id: PubkyId,
+    feed: PubkyAppFeed,
+    feed_id: String,
+) -> Result<(), EventProcessorError> {
+    debug!("Indexing new feed: {} -> {}", user_id, feed_id);
+    let feed_details = FeedDetails::from_homeserver(feed, &user_id, &feed_id);
+
+    // SAVE TO GRAPH
+    feed_details
+        .put_to_graph()
+        .await
+        .map_err(EventProcessorError::graph_query_failed)?;
+
+    // SAVE TO INDEX
+    feed_details.put_to_index().await?;
+    Ok(())
+}
+
+#[tracing::instrument(name = "feed.del", skip_all, fields(user_id = %user_id, feed_id = %feed_id))]
+pub async fn del(user_id: PubkyId, feed_id: String) -> Result<(), EventProcessorError> {
+    debug!("Deleting feed: {} -> {}", user_id, feed_id);
+    match FeedDetails::get_by_id(&user_id, &feed_id).await? {
+        Some(feed_details) => feed_details.delete().await?,
+        None => debug!("Feed {} of user {} is not indexed", feed_id, user_id),
+    }
+    Ok(())
+}
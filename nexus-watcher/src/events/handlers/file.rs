@@ -1,6 +1,6 @@
-use crate::events::EventProcessorError;
+use crate::events::{fetch_blob, EventProcessorError};
 
-use nexus_common::db::PubkyConnector;
+use nexus_common::db::PubkyClientError;
 use nexus_common::media::FileVariant;
 use nexus_common::media::VariantController;
 use nexus_common::models::file::Blob;
@@ -52,13 +52,29 @@ async fn ingest(
     pubkyapp_file: &PubkyAppFile,
     files_path: PathBuf,
 ) -> Result<FileMeta, EventProcessorError> {
-    let pubky = PubkyConnector::get()?;
-    let response = pubky.public_storage().get(&pubkyapp_file.src).await?;
-
-    let blob = response
-        .bytes()
-        .await
-        .map_err(|e| EventProcessorError::client_error(e.to_string()))?;
+    let blob = match fetch_blob(&pubkyapp_file.src).await {
+        Ok(blob) => blob,
+        // Offline rebuild from the event log: the blob was saved to disk when the event was first indexed
+        Err(EventProcessorError::PubkyClientError(PubkyClientError::NotInitialized)) => {
+            let main_path = files_path
+                .join(user_id.to_string())
+                .join(file_id)
+                .join(FileVariant::Main.to_string());
+            if let Ok(blob) = tokio::fs::read(&main_path).await {
+                debug!("Reusing the stored file blob at {}", main_path.display());
+                return ingest_raw(
+                    user_id,
+                    file_id,
+                    &pubkyapp_file.content_type,
+                    blob,
+                    files_path,
+                )
+                .await;
+            }
+            return Err(PubkyClientError::NotInitialized.into());
+        }
+        Err(e) => return Err(e),
+    };
     let pubky_app_object = PubkyAppObject::from_uri(&pubkyapp_file.src, &blob)
         .map_err(EventProcessorError::generic)?;
 
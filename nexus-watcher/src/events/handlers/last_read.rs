@@ -0,0 +1,29 @@
+use crate::events::EventProcessorError;
+
+use nexus_common::models::notification::LastRead;
+use pubky_app_specs::{PubkyAppLastRead, PubkyId};
+use tracing::debug;
+
+#[tracing::instrument(name = "last_read.put", skip_all, fields(user_id = %user_id))]
+pub async fn sync_put(
+    user_id: PubkyId,
+    last_read: PubkyAppLastRead,
+) -> Result<(), EventProcessorError> {
+    debug!(
+        "Indexing last read timestamp of user {}: {}",
+        user_id, last_read.timestamp
+    );
+    LastRead {
+        timestamp: last_read.timestamp,
+    }
+    .put_to_index(&user_id)
+    .await?;
+    Ok(())
+}
+
+#[tracing::instrument(name = "last_read.del", skip_all, fields(user_id = %user_id))]
+pub async fn del(user_id: PubkyId) -> Result<(), EventProcessorError> {
+    debug!("Deleting last read timestamp of user {}", user_id);
+    LastRead::del_from_index(&user_id).await?;
+    Ok(())
+}
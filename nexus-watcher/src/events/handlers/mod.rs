@@ -1,6 +1,8 @@
 pub mod bookmark;
+pub mod feed;
 pub mod file;
 pub mod follow;
+pub mod last_read;
 pub mod post;
 pub mod tag;
 pub mod universal_file;
@@ -1,6 +1,5 @@
 use std::path::{Path, PathBuf};
 
-use nexus_common::db::PubkyConnector;
 use nexus_common::models::event::{EventProcessorError, EventType};
 use nexus_common::models::file::FileDetails;
 use nexus_common::models::traits::Collection;
@@ -8,6 +7,7 @@ use pubky_app_specs::{PubkyAppFile, PubkyId, APP_PATH, PROTOCOL, PUBLIC_PATH};
 use tracing::debug;
 
 use super::file;
+use crate::events::fetch_blob;
 
 /// Info extracted from an app-specific file path:
 /// `pubky://<user_id>/pub/<app>/files/<file_id>`.
@@ -35,50 +35,21 @@ pub async fn try_handle(
 }
 
 async fn handle_put(info: AppFileInfo, files_path: PathBuf) -> Result<(), EventProcessorError> {
-    let pubky = PubkyConnector::get()?;
-    let response = pubky.public_storage().get(&info.uri).await?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let body = response
-            .text()
-            .await
-            .unwrap_or_else(|_| "<unable to read body>".to_string());
-        return Err(EventProcessorError::client_error(format!(
-            "Fetch universal file failed {}: HTTP {status} - {body}",
-            info.uri
-        )));
-    }
-
-    let file_json = response
-        .bytes()
-        .await
-        .map_err(|e| EventProcessorError::client_error(e.to_string()))?;
-
-    let app_file: PubkyAppFile = serde_json::from_slice(&file_json).map_err(|e| {
-        EventProcessorError::generic(format!(
-            "Failed to deserialize universal file at {}: {e}",
-            info.uri
-        ))
-    })?;
+    let file_json = fetch_blob(&info.uri).await?;
+    let app_file = parse_blob(&info.uri, &file_json)?;
 
     debug!(
         "Ingesting universal file {}/{} (src={})",
         info.user_id, info.file_id, app_file.src
     );
 
-    let pubky = PubkyConnector::get()?;
-    let blob_response = pubky.public_storage().get(&app_file.src).await?;
-    let raw_bytes = blob_response
-        .bytes()
-        .await
-        .map_err(|e| EventProcessorError::client_error(e.to_string()))?;
+    let raw_bytes = fetch_blob(&app_file.src).await?;
 
     let file_meta = file::ingest_raw(
         &info.user_id,
         &info.file_id,
         &app_file.content_type,
-        raw_bytes.to_vec(),
+        raw_bytes,
         files_path,
     )
     .await?;
@@ -108,7 +79,16 @@ async fn handle_del(info: AppFileInfo, files_path: PathBuf) -> Result<(), EventP
     file::del(&info.user_id, info.file_id, files_path).await
 }
 
-fn try_parse_app_file_path(uri: &str) -> Option<AppFileInfo> {
+/// Deserializes the blob of a universal file as a [PubkyAppFile]
+pub(crate) fn parse_blob(uri: &str, blob: &[u8]) -> Result<PubkyAppFile, EventProcessorError> {
+    serde_json::from_slice(blob).map_err(|e| {
+        EventProcessorError::generic(format!(
+            "Failed to deserialize universal file at {uri}: {e}"
+        ))
+    })
+}
+
+pub(crate) fn try_parse_app_file_path(uri: &str) -> Option<AppFileInfo> {
     let rest = match uri.get(..PROTOCOL.len()) {
         Some(prefix) if prefix.eq_ignore_ascii_case(PROTOCOL) => &uri[PROTOCOL.len()..],
         _ => return None,
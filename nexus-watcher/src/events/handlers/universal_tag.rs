@@ -1,9 +1,9 @@
-use nexus_common::db::PubkyConnector;
-use nexus_common::models::event::{EventProcessorError, EventType};
+use nexus_common::models::event::{EntityChange, EntityType, EventProcessorError, EventType};
 use pubky_app_specs::{PubkyAppTag, PubkyId, APP_PATH, PROTOCOL, PUBLIC_PATH};
 use tracing::debug;
 
 use super::tag;
+use crate::events::fetch_blob;
 
 /// Info extracted from a universal tag path: `pubky://<user_id>/pub/<app>/tags/<tag_id>`
 pub struct AppTagInfo {
@@ -19,9 +19,13 @@ pub struct AppTagInfo {
 ///
 /// Returns `None` if the URI isn't an app-specific tag path.
 /// Returns `Some(Ok(()))` on success or `Some(Err(...))` on processing failure.
+///
+/// The change of the tag is appended to the stream of [EntityChange]s if `record_change`, as
+/// for the other events, see [handle_fetched](crate::events::handle_fetched).
 pub async fn try_handle(
     event_type: &EventType,
     uri: &str,
+    record_change: bool,
 ) -> Option<Result<(), EventProcessorError>> {
     let info = try_parse_app_tag_path(uri)?;
 
@@ -30,41 +34,36 @@ pub async fn try_handle(
         event_type, info.uri, info.app
     );
 
-    Some(match event_type {
+    let change = record_change.then(|| tag_change(event_type, &info));
+    let result = match event_type {
         EventType::Put => handle_put(info).await,
         EventType::Del => handle_del(info).await,
-    })
+    };
+    if let (Ok(()), Some(change)) = (&result, change) {
+        crate::events::append_change(&change).await;
+    }
+    Some(result)
 }
 
-async fn handle_put(info: AppTagInfo) -> Result<(), EventProcessorError> {
-    // Fetch the tag blob from the homeserver
-    let pubky = PubkyConnector::get()?;
-    let response = pubky.public_storage().get(&info.uri).await?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let body = response
-            .text()
-            .await
-            .unwrap_or_else(|_| "<unable to read body>".to_string());
-        return Err(EventProcessorError::client_error(format!(
-            "Fetch universal tag failed {}: HTTP {status} - {body}",
-            info.uri
-        )));
-    }
+/// The change applied by an event on the universal tag at `uri`, if it is one
+pub(crate) fn change(event_type: &EventType, uri: &str) -> Option<EntityChange> {
+    try_parse_app_tag_path(uri).map(|info| tag_change(event_type, &info))
+}
 
-    let blob = response
-        .bytes()
-        .await
-        .map_err(|e| EventProcessorError::client_error(e.to_string()))?;
+fn tag_change(event_type: &EventType, info: &AppTagInfo) -> EntityChange {
+    EntityChange::new(
+        EntityType::Tag,
+        info.tag_id.clone(),
+        info.user_id.to_string(),
+        &info.uri,
+        event_type.clone(),
+    )
+}
 
-    // Deserialize as PubkyAppTag — if it's not a valid tag, this fails cleanly
-    let app_tag: PubkyAppTag = serde_json::from_slice(&blob).map_err(|e| {
-        EventProcessorError::generic(format!(
-            "Failed to deserialize universal tag at {}: {e}",
-            info.uri
-        ))
-    })?;
+async fn handle_put(info: AppTagInfo) -> Result<(), EventProcessorError> {
+    // Fetch the tag blob from the homeserver
+    let blob = fetch_blob(&info.uri).await?;
+    let app_tag = parse_blob(&info.uri, &blob)?;
 
     tag::sync_put_resource(app_tag, info.user_id, info.tag_id, info.app).await
 }
@@ -73,6 +72,14 @@ async fn handle_del(info: AppTagInfo) -> Result<(), EventProcessorError> {
     tag::del(&info.uri).await
 }
 
+/// Deserializes the blob of a universal tag as a [PubkyAppTag], failing cleanly if it is not
+/// a valid tag
+pub(crate) fn parse_blob(uri: &str, blob: &[u8]) -> Result<PubkyAppTag, EventProcessorError> {
+    serde_json::from_slice(blob).map_err(|e| {
+        EventProcessorError::generic(format!("Failed to deserialize universal tag at {uri}: {e}"))
+    })
+}
+
 /// Try to parse a URI as an app-specific tag path.
 ///
 /// Matches: `pubky://<user_id>/pub/<app>/tags/<tag_id>`
@@ -80,7 +87,7 @@ async fn handle_del(info: AppTagInfo) -> Result<(), EventProcessorError> {
 /// - Not a pubky:// URI
 /// - Not a */tags/* path
 /// - App is `APP_PATH` (handled by the standard event flow)
-pub(super) fn try_parse_app_tag_path(uri: &str) -> Option<AppTagInfo> {
+pub(crate) fn try_parse_app_tag_path(uri: &str) -> Option<AppTagInfo> {
     // Case-insensitive scheme check per RFC 3986 (safe UTF-8 access)
     let rest = match uri.get(..PROTOCOL.len()) {
         Some(prefix) if prefix.eq_ignore_ascii_case(PROTOCOL) => &uri[PROTOCOL.len()..],
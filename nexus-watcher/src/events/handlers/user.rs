@@ -5,8 +5,10 @@ use nexus_common::db::{
     exec_single_row, execute_graph_operation, queries, OperationOutcome, RedisOps,
 };
 use nexus_common::models::{
+    feed::FeedDetails,
+    notification::{LastRead, NotificationPreferences},
     traits::Collection,
-    user::{UserCounts, UserDetails, UserSearch, USER_DELETED_SENTINEL},
+    user::{UserCounts, UserDetails, UserHomeserver, UserSearch, USER_DELETED_SENTINEL},
 };
 use pubky_app_specs::{PubkyAppUser, PubkyId};
 use tracing::debug;
@@ -75,12 +77,21 @@ pub async fn del(user_id: PubkyId) -> Result<(), EventProcessorError> {
             let indexing_results = nexus_common::traced_join!(
                 tracing::info_span!("index.delete");
                 UserDetails::remove_from_index_multiple_json(&key_parts_list),
-                UserCounts::delete(&user_id)
+                UserCounts::delete(&user_id),
+                UserHomeserver::delete(&user_id),
+                LastRead::del_from_index(&user_id),
+                NotificationPreferences::del_from_index(&user_id)
             );
             indexing_results.0?;
             indexing_results.1?;
+            indexing_results.2?;
+            indexing_results.3?;
+            indexing_results.4?;
 
-            // 3. Graph deletion LAST
+            // 3. Feeds are only linked to the user by their `owner_id`
+            FeedDetails::delete_by_owner(&user_id).await?;
+
+            // 4. Graph deletion LAST
             exec_single_row(queries::del::delete_user(&user_id))
                 .await
                 .map_err(EventProcessorError::graph_query_failed)?;
@@ -0,0 +1,308 @@
+//! Local append-only log of the raw event lines processed by the watcher.
+//!
+//! Every event line is appended to a segment under `{files_path}/event_log/segments`, one JSON
+//! [EventLogEntry] per line, together with the homeserver it was polled from, its cursor and
+//! the hash of its fetched blob. Blobs are stored once per content hash under
+//! `{files_path}/event_log/blobs`, so the indexes can be rebuilt without contacting any homeserver.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tokio::fs::{self, File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+/// Directory of the event log, relative to the files path
+pub const EVENT_LOG_DIR: &str = "event_log";
+const SEGMENTS_DIR: &str = "segments";
+const BLOBS_DIR: &str = "blobs";
+const SEGMENT_EXTENSION: &str = "jsonl";
+/// A new segment is started once the current one reaches this size
+const SEGMENT_MAX_BYTES: u64 = 64 * 1024 * 1024;
+
+/// An event line as recorded in the event log
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EventLogEntry {
+    /// Timestamp (ms) at which the event was logged
+    pub logged_at: i64,
+    /// Homeserver the event was polled from, if known
+    pub homeserver: Option<String>,
+    /// Cursor to resume from without reading the event again, see [event_cursors]. `None` for
+    /// replayed retry events.
+    pub cursor: Option<String>,
+    /// Raw event line, e.g. `PUT pubky://{user_id}/pub/pubky.app/posts/{post_id}`
+    pub line: String,
+    /// blake3 hash of the fetched blob, `None` for DEL events and blobs that were not fetched
+    pub blob_hash: Option<String>,
+}
+
+/// Cursor of each event line of a page read from the cursor `start`, i.e. the cursor to resume
+/// from without reading the event again.
+///
+/// A page only carries the cursor of its last event. Homeserver cursors are increasing event ids,
+/// so when a page holds as many events as its cursor is past `start`, the ids of its events
+/// follow each other from `start`. Otherwise only the cursor of the last event is known, and the
+/// other events get `start`, from which resuming reads them again rather than missing them.
+pub fn event_cursors(start: &str, lines: &[String]) -> Vec<String> {
+    let events = lines
+        .iter()
+        .filter(|line| !line.starts_with("cursor: "))
+        .count();
+    let Some(end) = lines
+        .iter()
+        .rev()
+        .find_map(|line| line.strip_prefix("cursor: "))
+    else {
+        return vec![start.to_string(); events];
+    };
+
+    if let (Ok(first), Ok(last)) = (start.parse::<u64>(), end.parse::<u64>()) {
+        if last.checked_sub(first) == Some(events as u64) {
+            return (first + 1..=last).map(|id| id.to_string()).collect();
+        }
+    }
+    let mut cursors = vec![start.to_string(); events];
+    if let Some(last) = cursors.last_mut() {
+        *last = end.to_string();
+    }
+    cursors
+}
+
+/// Segment currently appended to
+struct Segment {
+    index: u64,
+    size: u64,
+    file: File,
+}
+
+/// Append-only, segmented log of the processed event lines and their blobs
+pub struct EventLog {
+    dir: PathBuf,
+    /// Opened lazily on the first append
+    segment: Mutex<Option<Segment>>,
+}
+
+impl EventLog {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            segment: Mutex::new(None),
+        }
+    }
+
+    /// The event log stored under `files_path`
+    pub fn from_files_path(files_path: &Path) -> Self {
+        Self::new(files_path.join(EVENT_LOG_DIR))
+    }
+
+    /// Appends an event line to the current segment, storing its blob if any
+    ///
+    /// # Arguments
+    /// * `homeserver` - Homeserver the event was polled from
+    /// * `cursor` - Cursor of the event, see [event_cursors]
+    /// * `line` - Raw event line
+    /// * `blob` - Blob fetched for a PUT event
+    pub async fn append(
+        &self,
+        homeserver: Option<&str>,
+        cursor: Option<&str>,
+        line: &str,
+        blob: Option<&[u8]>,
+    ) -> io::Result<()> {
+        let blob_hash = match blob {
+            Some(blob) => Some(self.put_blob(blob).await?),
+            None => None,
+        };
+        let entry = EventLogEntry {
+            logged_at: Utc::now().timestamp_millis(),
+            homeserver: homeserver.map(String::from),
+            cursor: cursor.map(String::from),
+            line: line.to_string(),
+            blob_hash,
+        };
+        let mut record = serde_json::to_vec(&entry)?;
+        record.push(b'\n');
+
+        let mut guard = self.segment.lock().await;
+        let segment = match guard.take() {
+            Some(segment) if segment.size < SEGMENT_MAX_BYTES => segment,
+            Some(segment) => self.open_segment(segment.index + 1).await?,
+            None => self.open_segment(self.last_segment_index().await?).await?,
+        };
+        let segment = guard.insert(segment);
+        segment.file.write_all(&record).await?;
+        segment.file.flush().await?;
+        segment.size += record.len() as u64;
+        Ok(())
+    }
+
+    /// Paths of all the segments, oldest first
+    pub async fn segments(&self) -> io::Result<Vec<PathBuf>> {
+        Ok(self
+            .segment_indexes()
+            .await?
+            .into_iter()
+            .map(|index| self.segment_path(index))
+            .collect())
+    }
+
+    /// Reads the entries of a segment, in the order they were appended
+    pub async fn read_segment(path: &Path) -> io::Result<Vec<EventLogEntry>> {
+        let content = fs::read_to_string(path).await?;
+        content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(line).map_err(io::Error::from))
+            .collect()
+    }
+
+    /// Reads a blob stored by [Self::append]
+    pub async fn read_blob(&self, hash: &str) -> io::Result<Vec<u8>> {
+        fs::read(self.blob_path(hash)).await
+    }
+
+    /// Stores a blob under its content hash, once, and returns the hash
+    async fn put_blob(&self, blob: &[u8]) -> io::Result<String> {
+        let hash = blake3::hash(blob).to_hex().to_string();
+        let path = self.blob_path(&hash);
+        if !fs::try_exists(&path).await? {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).await?;
+            }
+            // Write to a temporary file first so a crash never leaves a truncated blob behind
+            let tmp_path = path.with_extension("tmp");
+            fs::write(&tmp_path, blob).await?;
+            fs::rename(&tmp_path, &path).await?;
+        }
+        Ok(hash)
+    }
+
+    async fn open_segment(&self, index: u64) -> io::Result<Segment> {
+        let path = self.segment_path(index);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .await?;
+        let size = file.metadata().await?.len();
+        Ok(Segment { index, size, file })
+    }
+
+    /// Index of the latest segment, `0` if the log is empty
+    async fn last_segment_index(&self) -> io::Result<u64> {
+        Ok(self.segment_indexes().await?.pop().unwrap_or(0))
+    }
+
+    /// Indexes of the existing segments, in ascending order
+    async fn segment_indexes(&self) -> io::Result<Vec<u64>> {
+        let mut indexes = Vec::new();
+        let mut entries = match fs::read_dir(self.dir.join(SEGMENTS_DIR)).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(indexes),
+            Err(e) => return Err(e),
+        };
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some(SEGMENT_EXTENSION) {
+                continue;
+            }
+            if let Some(index) = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.parse().ok())
+            {
+                indexes.push(index);
+            }
+        }
+        indexes.sort_unstable();
+        Ok(indexes)
+    }
+
+    fn segment_path(&self, index: u64) -> PathBuf {
+        self.dir
+            .join(SEGMENTS_DIR)
+            .join(format!("{index:010}.{SEGMENT_EXTENSION}"))
+    }
+
+    /// Blobs are spread over subdirectories named after the first two characters of their hash
+    fn blob_path(&self, hash: &str) -> PathBuf {
+        let prefix = hash.get(..2).unwrap_or(hash);
+        self.dir.join(BLOBS_DIR).join(prefix).join(hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LINE: &str =
+        "PUT pubky://4snwyct86m383rsduhw5xgcxpw7c63j3pq8x4ycqikxgik8y64ro/pub/pubky.app/posts/0034A0X7NJ52A";
+
+    #[tokio::test]
+    async fn test_append_and_read_back() {
+        let tmp = tempfile::tempdir().unwrap();
+        let event_log = EventLog::from_files_path(tmp.path());
+
+        event_log
+            .append(Some("hs"), Some("42"), LINE, Some(b"{\"content\":\"hi\"}"))
+            .await
+            .unwrap();
+        event_log
+            .append(Some("hs"), Some("42"), LINE, None)
+            .await
+            .unwrap();
+
+        let segments = event_log.segments().await.unwrap();
+        assert_eq!(segments.len(), 1);
+
+        let entries = EventLog::read_segment(&segments[0]).await.unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].line, LINE);
+        assert_eq!(entries[0].cursor.as_deref(), Some("42"));
+        assert!(entries[1].blob_hash.is_none());
+
+        let hash = entries[0].blob_hash.as_deref().unwrap();
+        let blob = event_log.read_blob(hash).await.unwrap();
+        assert_eq!(blob, b"{\"content\":\"hi\"}");
+    }
+
+    #[test]
+    fn test_event_cursors() {
+        let page = |lines: &[&str]| lines.iter().map(|l| l.to_string()).collect::<Vec<_>>();
+
+        // Consecutive event ids
+        let lines = page(&[LINE, LINE, LINE, "cursor: 13"]);
+        assert_eq!(event_cursors("10", &lines), ["11", "12", "13"]);
+
+        // A gap in the event ids, only the last one is known
+        let lines = page(&[LINE, LINE, "cursor: 15"]);
+        assert_eq!(event_cursors("10", &lines), ["10", "15"]);
+
+        // No cursor line or non-numeric cursors
+        assert_eq!(event_cursors("10", &page(&[LINE, LINE])), ["10", "10"]);
+        let lines = page(&[LINE, LINE, "cursor: b"]);
+        assert_eq!(event_cursors("a", &lines), ["a", "b"]);
+        assert!(event_cursors("10", &[]).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_appends_to_the_latest_segment_after_reopening() {
+        let tmp = tempfile::tempdir().unwrap();
+        EventLog::from_files_path(tmp.path())
+            .append(None, None, LINE, None)
+            .await
+            .unwrap();
+
+        let event_log = EventLog::from_files_path(tmp.path());
+        event_log.append(None, None, LINE, None).await.unwrap();
+
+        let segments = event_log.segments().await.unwrap();
+        assert_eq!(segments.len(), 1);
+        assert_eq!(EventLog::read_segment(&segments[0]).await.unwrap().len(), 2);
+    }
+}
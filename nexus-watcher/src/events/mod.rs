@@ -1,52 +1,89 @@
+use crate::metrics::metrics;
 use nexus_common::db::PubkyConnector;
-use nexus_common::models::event::{Event, EventProcessorError, EventType};
+use nexus_common::models::event::{EntityChange, Event, EventProcessorError, EventType};
 use pubky_app_specs::{PubkyAppObject, Resource};
 use std::sync::Arc;
-use tracing::debug;
+use std::time::Instant;
+use tracing::{debug, warn};
 
 pub mod handlers;
+pub mod log;
 mod moderation;
 pub mod retry;
 
 pub use moderation::Moderation;
 
 pub async fn handle(event: &Event, moderation: Arc<Moderation>) -> Result<(), EventProcessorError> {
+    let blob = fetch_put_blob(event).await?;
+    handle_fetched(event, blob.as_deref(), moderation, true).await
+}
+
+/// Fetches the blob of a PUT event from the homeserver. DEL events have no blob.
+pub async fn fetch_put_blob(event: &Event) -> Result<Option<Vec<u8>>, EventProcessorError> {
     match event.event_type {
-        EventType::Put => handle_put_event(event, moderation).await,
-        EventType::Del => handle_del_event(event).await,
+        EventType::Put => {
+            let t0 = Instant::now();
+            let blob = fetch_event_blob(event).await;
+            metrics().record_blob_fetch(t0.elapsed());
+            blob.map(Some)
+        }
+        EventType::Del => Ok(None),
+    }
+}
+
+/// Indexes an event whose blob was already fetched, e.g. with [fetch_put_blob]
+/// or read back from the [log::EventLog].
+///
+/// The change of the entity is appended to the stream of [EntityChange]s if `record_change`,
+/// which only the live and retried events do: rebuilt, replayed and imported events were
+/// already streamed when first indexed, or are bulk loads consumers are not expected to follow.
+pub async fn handle_fetched(
+    event: &Event,
+    blob: Option<&[u8]>,
+    moderation: Arc<Moderation>,
+    record_change: bool,
+) -> Result<(), EventProcessorError> {
+    let change = match (&event.event_type, blob) {
+        (EventType::Put, Some(blob)) => handle_put_event(event, blob, moderation).await,
+        (EventType::Put, None) => Err(EventProcessorError::InvalidEventLine(format!(
+            "Missing blob of PUT event {}",
+            event.uri
+        ))),
+        (EventType::Del, _) => handle_del_event(event)
+            .await
+            .map(|()| EntityChange::from_event(event)),
     }?;
 
     event.store_event().await?;
+    if let (true, Some(change)) = (record_change, change) {
+        append_change(&change).await;
+    }
     Ok(())
 }
 
+/// Appends the change of an indexed entity to the stream of [EntityChange]s
+pub(crate) async fn append_change(change: &EntityChange) {
+    // The entity is indexed, consumers missing a change is not worth a retry
+    if let Err(e) = change.append().await {
+        warn!(
+            "Failed to append the change of {} to the stream: {e}",
+            change.uri
+        );
+    }
+}
+
+/// Handles a PUT event by dispatching to the appropriate handler.
+///
+/// # Returns
+/// The change applied by the event: the object of the event is indexed, except for a
+/// moderation tag, which deletes the tagged content instead
 pub async fn handle_put_event(
     event: &Event,
+    blob: &[u8],
     moderation: Arc<Moderation>,
-) -> Result<(), EventProcessorError> {
+) -> Result<Option<EntityChange>, EventProcessorError> {
     debug!("Handling PUT event for URI: {}", event.uri);
 
-    let pubky = PubkyConnector::get()?;
-    let response = pubky.public_storage().get(&event.uri).await?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let body = response
-            .text()
-            .await
-            .unwrap_or_else(|_| "<unable to read body>".to_string());
-
-        let err_msg = format!(
-            "Fetch resource failed {}: HTTP {status} - {body}",
-            event.uri
-        );
-        return Err(EventProcessorError::client_error(err_msg))?;
-    }
-
-    let blob = response
-        .bytes()
-        .await
-        .map_err(|e| EventProcessorError::client_error(e.to_string()))?;
     let resource = event.parsed_uri.resource.clone();
 
     // Use the new importer from pubky-app-specs.
@@ -54,7 +91,7 @@ pub async fn handle_put_event(
     // not be retried (a re-run produces the same error). Classify them as
     // `SpecValidation` so the retry queue stays clean — the load-bearing
     // counterpart to the `Unknown` forwards-compat variant in pubky-app-specs.
-    let pubky_object = PubkyAppObject::from_resource(&resource, &blob)
+    let pubky_object = PubkyAppObject::from_resource(&resource, blob)
         .map_err(|e| EventProcessorError::SpecValidation(e.to_string()))?;
 
     let user_id = event.parsed_uri.user_id.clone();
@@ -76,7 +113,7 @@ pub async fn handle_put_event(
         }
         (PubkyAppObject::Tag(tag), Resource::Tag(tag_id)) => {
             if moderation.should_delete(&tag, user_id.clone()).await {
-                Moderation::apply_moderation(tag, event.files_path.clone()).await?
+                return Moderation::apply_moderation(tag, event.files_path.clone()).await;
             } else {
                 handlers::tag::sync_put(tag, user_id, tag_id).await?
             }
@@ -91,9 +128,48 @@ pub async fn handle_put_event(
             )
             .await?
         }
+        (PubkyAppObject::Feed(feed), Resource::Feed(feed_id)) => {
+            handlers::feed::sync_put(user_id, feed, feed_id).await?
+        }
+        (PubkyAppObject::LastRead(last_read), Resource::LastRead) => {
+            handlers::last_read::sync_put(user_id, last_read).await?
+        }
         other => debug!("Event type not handled, Resource: {other:?}"),
     }
-    Ok(())
+    Ok(EntityChange::from_event(event))
+}
+
+/// Fetches the blob of a PUT event from the homeserver.
+pub(crate) async fn fetch_event_blob(event: &Event) -> Result<Vec<u8>, EventProcessorError> {
+    fetch_blob(&event.uri).await
+}
+
+/// Fetches the blob at `uri` from the public storage of its homeserver, or from the dump
+/// directory if the connector was initialised with [PubkyConnector::init_from_dump]
+pub(crate) async fn fetch_blob(uri: &str) -> Result<Vec<u8>, EventProcessorError> {
+    if let Some(dump) = PubkyConnector::get_dump() {
+        return Ok(dump.get(uri).await?);
+    }
+
+    let pubky = PubkyConnector::get()?;
+    let response = pubky.public_storage().get(uri).await?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "<unable to read body>".to_string());
+
+        let err_msg = format!("Fetch resource failed {uri}: HTTP {status} - {body}");
+        return Err(EventProcessorError::client_error(err_msg))?;
+    }
+
+    response
+        .bytes()
+        .await
+        .map(|bytes| bytes.to_vec())
+        .map_err(|e| EventProcessorError::client_error(e.to_string()))
 }
 
 /// Handles a DEL event by dispatching to the appropriate handler.
@@ -115,6 +191,8 @@ pub async fn handle_del_event(event: &Event) -> Result<(), EventProcessorError>
         Resource::File(file_id) => {
             handlers::file::del(&user_id, file_id.clone(), event.files_path.clone()).await?
         }
+        Resource::Feed(feed_id) => handlers::feed::del(user_id, feed_id.clone()).await?,
+        Resource::LastRead => handlers::last_read::del(user_id).await?,
         other => debug!("DEL event type not handled for resource: {other:?}"),
     }
     Ok(())
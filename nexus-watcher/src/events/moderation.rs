@@ -1,7 +1,8 @@
 use std::path::PathBuf;
 
 use crate::events::handlers;
-use nexus_common::models::event::EventProcessorError;
+use crate::events::handlers::universal_tag;
+use nexus_common::models::event::{EntityChange, EventProcessorError, EventType};
 use pubky_app_specs::{ParsedUri, PubkyAppTag, PubkyId, Resource};
 use tracing::info;
 
@@ -17,26 +18,38 @@ impl Moderation {
         tagger_id == self.id && self.tags.contains(&tag.label)
     }
 
+    /// Deletes the content targeted by a moderation tag
+    ///
+    /// # Returns
+    /// The deletion of the moderated entity, if it is one
     #[tracing::instrument(name = "moderation.apply", skip_all)]
     pub async fn apply_moderation(
         moderator_tag: PubkyAppTag,
         files_path: PathBuf,
-    ) -> Result<(), EventProcessorError> {
+    ) -> Result<Option<EntityChange>, EventProcessorError> {
         let lahel = moderator_tag.label;
         let moderated_uri = &moderator_tag.uri;
 
         // ParsedUri does not handle app-specific tag storage paths (Universal Tags), so they must be intercepted first.
         if handlers::tag::is_tag_storage_uri(&moderator_tag.uri) {
             info!("Moderation tag '{lahel}' detected. Deleting moderated tag {moderated_uri}",);
-            return handlers::tag::del(moderated_uri).await;
+            handlers::tag::del(moderated_uri).await?;
+            let change = match ParsedUri::try_from(moderated_uri.as_str()) {
+                Ok(parsed_uri) => {
+                    EntityChange::from_uri(&parsed_uri, moderated_uri, EventType::Del)
+                }
+                Err(_) => universal_tag::change(&EventType::Del, moderated_uri),
+            };
+            return Ok(change);
         }
 
         // Parse the embeded URI to extract author_id and post_id using parse_tagged_post_uri
         let parsed_uri = ParsedUri::try_from(moderator_tag.uri.as_str())
             .map_err(EventProcessorError::generic)?;
+        let change = EntityChange::from_uri(&parsed_uri, moderated_uri, EventType::Del);
         let user_id = parsed_uri.user_id;
 
-        match parsed_uri.resource {
+        let moderated = match parsed_uri.resource {
             Resource::Post(post_id) => {
                 info!("Moderation tag '{lahel}' detected. Deleting post {user_id}:{post_id}");
                 handlers::post::sync_del(user_id, post_id).await
@@ -53,7 +66,8 @@ impl Moderation {
                 info!("Moderation tag '{lahel}' detected. Deleting file {user_id}:{file_id}");
                 handlers::file::del(&user_id, file_id, files_path).await
             }
-            _ => Ok(()),
-        }
+            _ => return Ok(None),
+        };
+        moderated.map(|()| change)
     }
 }
@@ -1,6 +1,7 @@
 use async_trait::async_trait;
 use chrono::Utc;
-use nexus_common::db::kv::RedisResult;
+use nexus_common::db::kv::{sorted_sets, RedisResult, SortOrder};
+use nexus_common::models::event::{DeadLetterEvent, EventType};
 use pubky_app_specs::ParsedUri;
 use serde::{Deserialize, Serialize};
 
@@ -11,6 +12,10 @@ use crate::events::EventProcessorError;
 pub const RETRY_MANAGER_PREFIX: &str = "RetryManager";
 pub const RETRY_MANAGER_EVENTS_INDEX: [&str; 1] = ["events"];
 pub const RETRY_MANAGER_STATE_INDEX: [&str; 1] = ["state"];
+/// Reverse index from a dependency key to the events waiting for it to be indexed
+pub const RETRY_MANAGER_DEPENDENCY_INDEX: [&str; 1] = ["dependency"];
+/// Upper bound for the delay between two retries of the same event
+pub const RETRY_MAX_BACKOFF_SECS: u64 = 3_600;
 
 /// Represents an event in the retry queue and it is used to manage events that have failed
 /// to process and need to be retried
@@ -21,6 +26,16 @@ pub struct RetryEvent {
     /// The type of error that caused the event to fail
     /// This determines how the event should be processed during the retry process
     pub error_type: EventProcessorError,
+    /// Original event line as received from the homeserver, replayed by the [`super::processor::RetryProcessor`].
+    /// Entries written before this field existed have no line and cannot be replayed
+    #[serde(default)]
+    pub event_line: Option<String>,
+    /// Homeserver the event was polled from
+    #[serde(default)]
+    pub homeserver: Option<String>,
+    /// Timestamp (ms) of the first failure
+    #[serde(default)]
+    pub first_failed_at: i64,
 }
 
 #[async_trait]
@@ -31,13 +46,76 @@ impl RedisOps for RetryEvent {
 }
 
 impl RetryEvent {
-    pub fn new(error_type: EventProcessorError) -> Self {
+    pub fn new(
+        error_type: EventProcessorError,
+        event_line: impl Into<String>,
+        homeserver: &str,
+    ) -> Self {
         Self {
             retry_count: 0,
             error_type,
+            event_line: Some(event_line.into()),
+            homeserver: Some(homeserver.to_string()),
+            first_failed_at: Utc::now().timestamp_millis(),
         }
     }
 
+    /// Builds the dead-letter entry for this event, keeping its last error and failure timestamps
+    pub fn into_dead_letter(self, index_key: &str) -> DeadLetterEvent {
+        DeadLetterEvent::new(
+            index_key.to_string(),
+            self.event_line,
+            self.homeserver,
+            self.error_type,
+            self.retry_count,
+            self.first_failed_at,
+        )
+    }
+
+    /// Moves a dead-lettered event back to the retry queue with a fresh retry count.
+    /// The event is due immediately, so the next [`super::processor::RetryProcessor`] pass replays it
+    pub async fn requeue_dead_letter(dead_letter: DeadLetterEvent) -> RedisResult<()> {
+        let retry_event = Self {
+            retry_count: 0,
+            error_type: dead_letter.error,
+            event_line: dead_letter.event_line,
+            homeserver: dead_letter.homeserver,
+            first_failed_at: dead_letter.first_failed_at,
+        };
+        retry_event
+            .schedule(&dead_letter.index_key, Utc::now().timestamp_millis())
+            .await?;
+
+        DeadLetterEvent::remove_from_index(&dead_letter.index_key).await?;
+        Ok(())
+    }
+
+    /// Delay before the next attempt, or `None` if the error must never be retried.
+    ///
+    /// Each error variant has its own base delay, doubled on every retry and capped
+    /// at [`RETRY_MAX_BACKOFF_SECS`]: `min(base * 2^retry_count, MAX)`.
+    pub fn backoff_secs(&self) -> Option<u64> {
+        let base_secs: u64 = match self.error_type {
+            EventProcessorError::InvalidEventLine(_) | EventProcessorError::SpecValidation(_) => {
+                return None
+            }
+            // Storage hiccups usually clear up quickly
+            EventProcessorError::GraphQueryFailed(_)
+            | EventProcessorError::IndexOperationFailed(_) => 5,
+            EventProcessorError::MediaProcessorError(_)
+            | EventProcessorError::StaticSaveFailed(_)
+            | EventProcessorError::InternalError(_)
+            | EventProcessorError::Generic(_) => 10,
+            // The homeserver may be unreachable for a while
+            EventProcessorError::PubkyClientError(_) => 30,
+            // Waits for another event (parent post, followee, PUT of the deleted object) to be indexed
+            EventProcessorError::MissingDependency { .. } | EventProcessorError::SkipIndexing => 60,
+        };
+
+        let factor = 2u64.saturating_pow(self.retry_count);
+        Some(base_secs.saturating_mul(factor).min(RETRY_MAX_BACKOFF_SECS))
+    }
+
     /// It processes a homeserver URI and extracts specific components to form a index key
     /// in the format `"{pubkyId}:{repository_model}:{event_id}"`
     /// # Parameters
@@ -67,28 +145,112 @@ impl RetryEvent {
         }
     }
 
+    /// Returns the dependency key other events use to wait for the event behind `index_key`.
+    /// Only PUT events can satisfy a dependency
+    pub fn dependency_key(index_key: &str) -> Option<&str> {
+        let (event_type, dependency_key) = index_key.split_once(':')?;
+        (event_type == EventType::Put.to_string()).then_some(dependency_key)
+    }
+
     /// Stores an event in both a sorted set and a JSON index in Redis.
-    /// It adds an event line to a Redis sorted set with a timestamp-based score
-    /// and also stores the event details in a separate JSON index for retrieval.
+    /// It adds the event index key to a Redis sorted set, scored with the timestamp (ms) at which
+    /// the next attempt is due, and also stores the event details in a separate JSON index for retrieval.
     /// # Arguments
-    /// * `event_line` - A `String` representing the event line to be indexed.
+    /// * `index_key` - A `String` representing the index key of the event to be indexed.
     #[tracing::instrument(name = "retry.index.write", skip_all)]
-    pub async fn put_to_index(&self, event_line: String) -> RedisResult<()> {
+    pub async fn put_to_index(&self, index_key: String) -> RedisResult<()> {
+        let delay_ms = self.backoff_secs().unwrap_or(RETRY_MAX_BACKOFF_SECS) * 1_000;
+        let due_at = Utc::now().timestamp_millis() + delay_ms as i64;
+        self.schedule(&index_key, due_at).await
+    }
+
+    /// Stores the event in the retry queue, due at the given timestamp (ms).
+    /// Events blocked by a `MissingDependency` are also registered under each of their
+    /// dependency keys, so they can be replayed as soon as the dependency is indexed
+    async fn schedule(&self, index_key: &str, due_at: i64) -> RedisResult<()> {
         Self::put_index_sorted_set(
             &RETRY_MANAGER_EVENTS_INDEX,
-            // NOTE: Don't know if we should use now timestamp or the event timestamp
-            &[(Utc::now().timestamp_millis() as f64, &event_line)],
+            &[(due_at as f64, index_key)],
             Some(RETRY_MANAGER_PREFIX),
             None,
         )
         .await?;
 
-        let index = &[RETRY_MANAGER_STATE_INDEX, [&event_line]].concat();
+        let index = &[RETRY_MANAGER_STATE_INDEX, [index_key]].concat();
         self.put_index_json(index, None, None).await?;
 
+        if let EventProcessorError::MissingDependency { dependency } = &self.error_type {
+            let now = Utc::now().timestamp_millis() as f64;
+            for dependency_key in dependency {
+                let index = &[RETRY_MANAGER_DEPENDENCY_INDEX, [dependency_key]].concat();
+                Self::put_index_sorted_set(
+                    index,
+                    &[(now, index_key)],
+                    Some(RETRY_MANAGER_PREFIX),
+                    None,
+                )
+                .await?;
+            }
+        }
+
         Ok(())
     }
 
+    /// Atomically removes and returns the index keys of the events waiting for a dependency,
+    /// oldest first. Returned keys may point to events that already left the queue
+    /// # Arguments
+    /// * `dependency_key` - A `&str` in the same `"{pubkyId}:{repository_model}:{event_id}"` format
+    ///   as [`Self::generate_index_key`], e.g. the key of a parent post or of a followee profile
+    #[tracing::instrument(name = "retry.index.dependents", skip_all)]
+    pub async fn take_dependents(dependency_key: &str) -> RedisResult<Vec<String>> {
+        let key = [RETRY_MANAGER_DEPENDENCY_INDEX, [dependency_key]]
+            .concat()
+            .join(":");
+        // Read and removed at once, so concurrent processors never replay the same dependents
+        sorted_sets::take_all(RETRY_MANAGER_PREFIX, &key).await
+    }
+
+    /// Removes an event from both the sorted set and the JSON index in Redis
+    /// # Arguments
+    /// * `index_key` - A `&str` representing the index key of the event to remove
+    #[tracing::instrument(name = "retry.index.del", skip_all)]
+    pub async fn remove_from_index(index_key: &str) -> RedisResult<()> {
+        Self::remove_from_index_sorted_set(
+            Some(RETRY_MANAGER_PREFIX),
+            &RETRY_MANAGER_EVENTS_INDEX,
+            &[index_key],
+        )
+        .await?;
+
+        let index: &Vec<&str> = &[RETRY_MANAGER_STATE_INDEX, [index_key]].concat();
+        Self::remove_from_index_multiple_json(&[index]).await
+    }
+
+    /// Retrieves the index keys of the events whose next attempt is due, oldest first
+    /// # Arguments
+    /// * `now` - Timestamp (ms) used as the upper bound of the due window
+    /// * `limit` - Maximum number of index keys to return
+    pub async fn get_due(now: i64, limit: usize) -> RedisResult<Vec<String>> {
+        let due = Self::try_from_index_sorted_set(
+            &RETRY_MANAGER_EVENTS_INDEX,
+            Some(now as f64),
+            None,
+            None,
+            Some(limit),
+            SortOrder::Ascending,
+            Some(RETRY_MANAGER_PREFIX),
+        )
+        .await?
+        .unwrap_or_default();
+
+        Ok(due.into_iter().map(|(index_key, _)| index_key).collect())
+    }
+
+    /// Retrieves the number of events waiting in the retry queue
+    pub async fn queue_depth() -> RedisResult<usize> {
+        Self::get_sorted_set_size(&RETRY_MANAGER_EVENTS_INDEX, Some(RETRY_MANAGER_PREFIX)).await
+    }
+
     /// Checks if a specific event exists in the Redis sorted set
     /// # Arguments
     /// * `event_index` - A `&str` representing the event index to check
@@ -114,3 +276,81 @@ impl RetryEvent {
         Self::try_from_index_json(index, None).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn retry_event(error_type: EventProcessorError, retry_count: u32) -> RetryEvent {
+        RetryEvent {
+            retry_count,
+            ..RetryEvent::new(
+                error_type,
+                "PUT pubky://user/pub/pubky.app/profile.json",
+                "homeserver",
+            )
+        }
+    }
+
+    #[test]
+    fn test_backoff_secs_skips_non_retryable_errors() {
+        let invalid = retry_event(EventProcessorError::InvalidEventLine("bad".into()), 0);
+        assert_eq!(invalid.backoff_secs(), None);
+
+        let spec = retry_event(EventProcessorError::SpecValidation("bad".into()), 0);
+        assert_eq!(spec.backoff_secs(), None);
+    }
+
+    #[test]
+    fn test_backoff_secs_doubles_per_retry() {
+        let error = EventProcessorError::GraphQueryFailed("down".into());
+        assert_eq!(retry_event(error.clone(), 0).backoff_secs(), Some(5));
+        assert_eq!(retry_event(error.clone(), 1).backoff_secs(), Some(10));
+        assert_eq!(retry_event(error, 3).backoff_secs(), Some(40));
+    }
+
+    #[test]
+    fn test_backoff_secs_depends_on_error_variant() {
+        let missing = retry_event(EventProcessorError::missing_dependencies(vec![]), 0);
+        let generic = retry_event(EventProcessorError::Generic("oops".into()), 0);
+        assert!(missing.backoff_secs() > generic.backoff_secs());
+    }
+
+    #[test]
+    fn test_into_dead_letter_keeps_failure_context() {
+        let event = retry_event(EventProcessorError::SkipIndexing, 7);
+        let first_failed_at = event.first_failed_at;
+
+        let dead_letter = event.into_dead_letter("DEL:user:profile.json");
+        assert_eq!(dead_letter.index_key, "DEL:user:profile.json");
+        assert_eq!(dead_letter.homeserver.as_deref(), Some("homeserver"));
+        assert_eq!(dead_letter.retry_count, 7);
+        assert_eq!(dead_letter.first_failed_at, first_failed_at);
+        assert!(matches!(
+            dead_letter.error,
+            EventProcessorError::SkipIndexing
+        ));
+    }
+
+    #[test]
+    fn test_dependency_key_only_for_put_events() {
+        assert_eq!(
+            RetryEvent::dependency_key("PUT:user:posts:0032QB10HCRHG"),
+            Some("user:posts:0032QB10HCRHG")
+        );
+        assert_eq!(
+            RetryEvent::dependency_key("PUT:user:profile.json"),
+            Some("user:profile.json")
+        );
+        assert_eq!(
+            RetryEvent::dependency_key("DEL:user:posts:0032QB10HCRHG"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_backoff_secs_is_capped() {
+        let event = retry_event(EventProcessorError::SkipIndexing, 64);
+        assert_eq!(event.backoff_secs(), Some(RETRY_MAX_BACKOFF_SECS));
+    }
+}
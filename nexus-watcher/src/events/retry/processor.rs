@@ -0,0 +1,285 @@
+use chrono::Utc;
+use nexus_common::models::event::{Event, EventProcessorError, ParseResult};
+use nexus_common::WatcherConfig;
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::watch::Receiver;
+use tracing::{debug, error, info, warn};
+
+use super::event::RetryEvent;
+use crate::dispatcher::EventDispatcher;
+use crate::events::handlers::{universal_file, universal_tag};
+use crate::events::log::EventLog;
+use crate::events::{fetch_put_blob, handle_fetched, Moderation};
+use crate::metrics::metrics;
+
+/// Replays the events stored in the `RetryManager` queue once their backoff window has elapsed.
+///
+/// Successful events are removed from the queue. Failed events are rescheduled with an
+/// increased `retry_count`, until they fail with a non-retryable error or exhaust
+/// [WatcherConfig::retry_max_attempts], in which case they are moved to the dead-letter index.
+pub struct RetryProcessor {
+    /// See [WatcherConfig::retry_batch_size]
+    pub batch_size: usize,
+    /// See [WatcherConfig::retry_max_attempts]
+    pub max_attempts: u32,
+    pub files_path: PathBuf,
+    pub moderation: Arc<Moderation>,
+    pub shutdown_rx: Receiver<bool>,
+    /// Domain plugin dispatcher, so plugin events are replayed through their plugin
+    pub dispatcher: Option<Arc<EventDispatcher>>,
+    /// Event log the blobs fetched by successful replays are appended to
+    pub event_log: Option<Arc<EventLog>>,
+}
+
+impl RetryProcessor {
+    /// Creates a new instance from the provided configuration
+    pub fn from_config(
+        config: &WatcherConfig,
+        shutdown_rx: Receiver<bool>,
+        dispatcher: Option<Arc<EventDispatcher>>,
+        event_log: Option<Arc<EventLog>>,
+    ) -> Self {
+        Self {
+            batch_size: config.retry_batch_size,
+            max_attempts: config.retry_max_attempts,
+            files_path: config.stack.files_path.clone(),
+            moderation: Arc::new(Moderation {
+                id: config.moderation_id.clone(),
+                tags: config.moderated_tags.clone(),
+            }),
+            shutdown_rx,
+            dispatcher,
+            event_log,
+        }
+    }
+
+    /// Replays every event of the retry queue whose next attempt is due
+    #[tracing::instrument(name = "retry.run", skip_all)]
+    pub async fn run(&self) -> Result<(), EventProcessorError> {
+        metrics().record_retry_queue_depth(RetryEvent::queue_depth().await?);
+
+        let now = Utc::now().timestamp_millis();
+        let due_keys = RetryEvent::get_due(now, self.batch_size).await?;
+
+        if due_keys.is_empty() {
+            debug!("No retry events due");
+            return Ok(());
+        }
+
+        info!("Retrying {} failed events", due_keys.len());
+        for index_key in due_keys {
+            if *self.shutdown_rx.borrow() {
+                debug!("Shutdown detected, exiting retry loop");
+                break;
+            }
+            // A failure on one event must not hold back the rest of the due batch
+            if let Err(e) = self.retry(&index_key).await {
+                error!("Failed to retry {index_key}: {e}");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Replays a single event of the retry queue and updates its retry state.
+    /// If the event gets indexed, the events that were waiting for it are replayed as well
+    /// # Arguments
+    /// * `index_key` - A `&str` representing the index key of the event in the retry queue
+    #[tracing::instrument(name = "retry.event", skip(self))]
+    pub async fn retry(&self, index_key: &str) -> Result<(), EventProcessorError> {
+        if self.retry_event(index_key).await? {
+            if let Some(dependency_key) = RetryEvent::dependency_key(index_key) {
+                self.retry_dependents(dependency_key).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Replays, right away, the events blocked by a `MissingDependency` on `dependency_key`,
+    /// oldest first.
+    ///
+    /// Dependents that get indexed release their own dependents in turn, so a chain of
+    /// replies written before their root post is indexed in a single pass. A dependent that
+    /// cannot be replayed, e.g. on a Redis error, is put back in the retry queue and the
+    /// others are still replayed.
+    /// # Arguments
+    /// * `dependency_key` - A `&str` representing the key of the resource that was just indexed,
+    ///   as built by [`RetryEvent::generate_index_key_from_uri`]
+    #[tracing::instrument(name = "retry.dependents", skip(self))]
+    pub async fn retry_dependents(&self, dependency_key: &str) -> Result<(), EventProcessorError> {
+        let mut pending = VecDeque::from(RetryEvent::take_dependents(dependency_key).await?);
+
+        while let Some(index_key) = pending.pop_front() {
+            if *self.shutdown_rx.borrow() {
+                debug!("Shutdown detected, exiting dependents retry loop");
+                pending.push_front(index_key);
+                break;
+            }
+
+            debug!("Dependency {dependency_key} indexed, retrying {index_key}");
+            match self.retry_dependent(&index_key).await {
+                Ok(released) => pending.extend(released),
+                Err(e) => {
+                    error!("Failed to retry {index_key} after {dependency_key} was indexed: {e}");
+                    Self::requeue(&index_key).await;
+                }
+            }
+        }
+
+        // Taken from the dependency index but not replayed
+        for index_key in pending {
+            Self::requeue(&index_key).await;
+        }
+        Ok(())
+    }
+
+    /// Replays an event released by its dependency
+    ///
+    /// # Returns
+    /// The events released in turn, if it got indexed
+    async fn retry_dependent(&self, index_key: &str) -> Result<Vec<String>, EventProcessorError> {
+        // The event may have been indexed by a scheduled retry in the meantime
+        if RetryEvent::check_uri(index_key).await?.is_none() {
+            return Ok(Vec::new());
+        }
+        if !self.retry_event(index_key).await? {
+            return Ok(Vec::new());
+        }
+        match RetryEvent::dependency_key(index_key) {
+            Some(key) => Ok(RetryEvent::take_dependents(key).await?),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Puts an event taken from the dependency index back in the retry queue, so it is
+    /// replayed on schedule
+    async fn requeue(index_key: &str) {
+        match RetryEvent::get_from_index(index_key).await {
+            Ok(Some(retry_event)) => {
+                if let Err(e) = retry_event.put_to_index(index_key.to_string()).await {
+                    error!("Failed to put {index_key} back in the retry queue: {e}");
+                }
+            }
+            // The event left the queue in the meantime
+            Ok(None) => {}
+            Err(e) => error!("Failed to put {index_key} back in the retry queue: {e}"),
+        }
+    }
+
+    /// Replays a single event of the retry queue and returns whether it was indexed
+    async fn retry_event(&self, index_key: &str) -> Result<bool, EventProcessorError> {
+        let Some(mut retry_event) = RetryEvent::get_from_index(index_key).await? else {
+            warn!("Retry state missing for {index_key}, removing it from the queue");
+            RetryEvent::remove_from_index(index_key).await?;
+            return Ok(false);
+        };
+
+        let Some(event_line) = retry_event.event_line.clone() else {
+            warn!("Retry event {index_key} has no event line and cannot be replayed");
+            Self::dead_letter(index_key, retry_event).await?;
+            return Ok(false);
+        };
+
+        match self
+            .replay(&event_line, retry_event.homeserver.as_deref())
+            .await
+        {
+            Ok(()) => {
+                info!(
+                    "Retry event {index_key} indexed after {} retries",
+                    retry_event.retry_count + 1
+                );
+                RetryEvent::remove_from_index(index_key).await?;
+                return Ok(true);
+            }
+            Err(e) if !e.is_retryable() => {
+                error!("Retry event {index_key} failed with a non-retryable error: {e}");
+                retry_event.error_type = e;
+                Self::dead_letter(index_key, retry_event).await?;
+            }
+            Err(e) => {
+                retry_event.retry_count += 1;
+                retry_event.error_type = e;
+                warn!(
+                    "Retry event {index_key} failed again ({} retries): {}",
+                    retry_event.retry_count, retry_event.error_type
+                );
+                if retry_event.retry_count >= self.max_attempts {
+                    Self::dead_letter(index_key, retry_event).await?;
+                } else {
+                    retry_event.put_to_index(index_key.to_string()).await?;
+                }
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Moves an event from the retry queue to the dead-letter index
+    async fn dead_letter(
+        index_key: &str,
+        retry_event: RetryEvent,
+    ) -> Result<(), EventProcessorError> {
+        error!(
+            "Moving event {index_key} to the dead-letter index after {} retries: {}",
+            retry_event.retry_count, retry_event.error_type
+        );
+        retry_event
+            .into_dead_letter(index_key)
+            .put_to_index()
+            .await?;
+        RetryEvent::remove_from_index(index_key).await?;
+        Ok(())
+    }
+
+    /// Runs an event line through the same path as the homeserver event processor:
+    /// domain plugins first, then the social handlers and the universal tag/file handlers
+    async fn replay(
+        &self,
+        line: &str,
+        homeserver: Option<&str>,
+    ) -> Result<(), EventProcessorError> {
+        if let Some(ref dispatcher) = self.dispatcher {
+            if dispatcher.try_dispatch(line).await? {
+                return Ok(());
+            }
+        }
+
+        match Event::parse_event(line, self.files_path.clone())? {
+            ParseResult::Parsed(event) => {
+                let event = match homeserver {
+                    Some(homeserver) => event.with_homeserver(homeserver),
+                    None => event,
+                };
+                let blob = fetch_put_blob(&event).await?;
+                // The event line was logged without its blob when it first failed
+                if let (Some(event_log), Some(blob)) = (&self.event_log, &blob) {
+                    if let Err(e) = event_log.append(homeserver, None, line, Some(blob)).await {
+                        error!("Failed to append event line to the event log: {e}");
+                    }
+                }
+                handle_fetched(&event, blob.as_deref(), self.moderation.clone(), true).await
+            }
+            ParseResult::Skipped => Ok(()),
+            ParseResult::UnrecognizedUri {
+                event_type,
+                uri,
+                reason,
+            } => {
+                if let Some(result) = universal_tag::try_handle(&event_type, &uri, true).await {
+                    return result;
+                }
+                if let Some(result) =
+                    universal_file::try_handle(&event_type, &uri, &self.files_path).await
+                {
+                    return result;
+                }
+                Err(EventProcessorError::InvalidEventLine(format!(
+                    "Cannot parse event URI: {reason}"
+                )))
+            }
+        }
+    }
+}
@@ -17,6 +17,7 @@
 mod builder;
 pub mod dispatcher;
 pub mod events;
+mod metrics;
 pub mod service;
 #[cfg(feature = "testing")]
 pub mod testing;
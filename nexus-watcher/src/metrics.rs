@@ -0,0 +1,123 @@
+//! OpenTelemetry metrics of the watcher pipeline.
+//!
+//! Instruments are created from the global meter provider registered by
+//! [`nexus_common::StackManager::setup_metrics`], so they are exported next to the
+//! Neo4j metrics. When no OTLP endpoint is configured they are no-ops.
+
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use nexus_common::models::event::{EventProcessorError, EventType};
+use opentelemetry::metrics::{Counter, Gauge, Histogram};
+use opentelemetry::{global, KeyValue};
+
+/// The OpenTelemetry meter name used by all watcher metrics.
+const METER_NAME: &str = "nexus.watcher";
+
+static WATCHER_METRICS: OnceLock<WatcherMetrics> = OnceLock::new();
+
+/// Shared OpenTelemetry metric instruments for the watcher pipeline.
+pub struct WatcherMetrics {
+    /// Events indexed successfully, by resource type and event type.
+    events_processed: Counter<u64>,
+    /// Events that failed to index, by resource type and error variant.
+    event_failures: Counter<u64>,
+    /// Time spent by a domain plugin handling an event, by plugin name.
+    plugin_dispatch_duration: Histogram<f64>,
+    /// Time spent fetching an event blob from the homeserver.
+    blob_fetch_duration: Histogram<f64>,
+    /// Number of event lines returned by a homeserver poll.
+    poll_batch_size: Histogram<u64>,
+    /// Number of events waiting in the retry queue.
+    retry_queue_depth: Gauge<u64>,
+}
+
+/// Returns the watcher metric instruments, creating them on first use.
+///
+/// The first call must happen after the stack is set up, otherwise the
+/// instruments are bound to the no-op provider.
+pub fn metrics() -> &'static WatcherMetrics {
+    WATCHER_METRICS.get_or_init(WatcherMetrics::new)
+}
+
+impl WatcherMetrics {
+    fn new() -> Self {
+        let meter = global::meter(METER_NAME);
+        Self {
+            events_processed: meter
+                .u64_counter("watcher.events.processed")
+                .with_description("Total number of homeserver events indexed successfully")
+                .build(),
+            event_failures: meter
+                .u64_counter("watcher.events.failures")
+                .with_description("Total number of homeserver events that failed to index")
+                .build(),
+            plugin_dispatch_duration: meter
+                .f64_histogram("watcher.plugin.dispatch_duration")
+                .with_description(
+                    "Time spent by a domain plugin handling an event, in milliseconds",
+                )
+                .with_unit("ms")
+                .build(),
+            blob_fetch_duration: meter
+                .f64_histogram("watcher.blob.fetch_duration")
+                .with_description(
+                    "Time spent fetching an event blob from the homeserver, in milliseconds",
+                )
+                .with_unit("ms")
+                .build(),
+            poll_batch_size: meter
+                .u64_histogram("watcher.poll.batch_size")
+                .with_description("Number of event lines returned per homeserver poll")
+                .with_unit("{event}")
+                .build(),
+            retry_queue_depth: meter
+                .u64_gauge("watcher.retry.queue_depth")
+                .with_description("Number of events waiting in the retry queue")
+                .with_unit("{event}")
+                .build(),
+        }
+    }
+
+    /// Records a successfully indexed event.
+    pub fn record_event(&self, resource: &str, event_type: &EventType) {
+        self.events_processed.add(
+            1,
+            &[
+                KeyValue::new("resource", resource.to_string()),
+                KeyValue::new("event_type", event_type.to_string()),
+            ],
+        );
+    }
+
+    /// Records an event that failed to index.
+    pub fn record_failure(&self, resource: &str, error: &EventProcessorError) {
+        self.event_failures.add(
+            1,
+            &[
+                KeyValue::new("resource", resource.to_string()),
+                KeyValue::new("error", error.variant_name()),
+            ],
+        );
+    }
+
+    pub fn record_plugin_dispatch(&self, plugin: &'static str, duration: Duration) {
+        self.plugin_dispatch_duration.record(
+            duration.as_secs_f64() * 1000.0,
+            &[KeyValue::new("plugin", plugin)],
+        );
+    }
+
+    pub fn record_blob_fetch(&self, duration: Duration) {
+        self.blob_fetch_duration
+            .record(duration.as_secs_f64() * 1000.0, &[]);
+    }
+
+    pub fn record_poll_batch_size(&self, size: usize) {
+        self.poll_batch_size.record(size as u64, &[]);
+    }
+
+    pub fn record_retry_queue_depth(&self, depth: usize) {
+        self.retry_queue_depth.record(depth as u64, &[]);
+    }
+}
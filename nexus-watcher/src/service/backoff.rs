@@ -1,18 +1,38 @@
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
 
+use chrono::Utc;
 use tracing::info;
 
 struct BackoffState {
+    failures: u32,
     next_backoff_secs: u64,
     backoff_until: Instant,
 }
 
+/// Polling cadence of a homeserver, derived from the size of its last pages
+struct PollState {
+    /// Number of consecutive runs without new events
+    idle_polls: u32,
+    /// The last page was full, more events are waiting
+    draining: bool,
+    next_poll_at: Instant,
+}
+
 /// Tracks per-homeserver failure counts and exponential backoff windows.
+///
+/// With [HomeserverBackoff::with_poll_cadence], it also adapts how often each homeserver is
+/// polled to its recent event volume: homeservers returning full pages are re-polled right away
+/// until drained, homeservers without new events are polled less and less often.
 pub struct HomeserverBackoff {
     initial_backoff_secs: u64,
     max_backoff_secs: u64,
     state: HashMap<String, BackoffState>,
+    /// Regular polling interval, zero if the cadence is not adaptive
+    poll_interval: Duration,
+    /// Ceiling of the polling interval of idle homeservers
+    max_poll_interval: Duration,
+    polls: HashMap<String, PollState>,
 }
 
 impl HomeserverBackoff {
@@ -25,6 +45,82 @@ impl HomeserverBackoff {
             initial_backoff_secs,
             max_backoff_secs,
             state: HashMap::new(),
+            poll_interval: Duration::ZERO,
+            max_poll_interval: Duration::ZERO,
+            polls: HashMap::new(),
+        }
+    }
+
+    /// Adapts the polling cadence of each homeserver to its event volume.
+    ///
+    /// # Parameters
+    /// * `poll_interval` - Interval between two regular runs
+    /// * `max_poll_interval` - Ceiling of the interval a homeserver without new events is polled at.
+    ///   Each empty run doubles it, starting from `poll_interval`.
+    pub fn with_poll_cadence(
+        mut self,
+        poll_interval: Duration,
+        max_poll_interval: Duration,
+    ) -> Self {
+        self.poll_interval = poll_interval;
+        self.max_poll_interval = max_poll_interval.max(poll_interval);
+        self
+    }
+
+    /// Returns `true` if the homeserver is due for polling.
+    ///
+    /// A homeserver counts as due within half a poll interval of its next poll, so the jitter of
+    /// the run ticks does not postpone it by a whole interval.
+    pub fn is_due(&self, hs_id: &str) -> bool {
+        match self.polls.get(hs_id) {
+            Some(ps) => Instant::now() + self.poll_interval / 2 >= ps.next_poll_at,
+            None => true,
+        }
+    }
+
+    /// Returns `true` if a homeserver returned a full page in its last run and should be
+    /// re-polled right away.
+    pub fn is_draining(&self) -> bool {
+        self.polls.values().any(|ps| ps.draining)
+    }
+
+    /// Forgets the polling cadence of the homeservers left out of the current run.
+    ///
+    /// A homeserver dropped from the run list (lease lost, denylisted, over the monitoring
+    /// limit) is never polled again by this instance, so its last page must not keep the
+    /// watcher draining.
+    pub fn retain_polls(&mut self, hs_ids: &[String]) {
+        self.polls.retain(|hs_id, _| hs_ids.contains(hs_id));
+    }
+
+    /// Schedules the next poll of a homeserver after a successful run.
+    ///
+    /// # Parameters
+    /// * `events_processed` - Number of event lines of the run
+    /// * `page_full` - Whether the run returned a full page, i.e. more events are waiting
+    pub fn record_poll(&mut self, hs_id: &str, events_processed: usize, page_full: bool) {
+        let now = Instant::now();
+        let state = self.polls.entry(hs_id.to_string()).or_insert(PollState {
+            idle_polls: 0,
+            draining: false,
+            next_poll_at: now,
+        });
+
+        state.draining = page_full;
+        if page_full {
+            state.idle_polls = 0;
+            state.next_poll_at = now;
+        } else if events_processed > 0 {
+            state.idle_polls = 0;
+            state.next_poll_at = now + self.poll_interval;
+        } else {
+            state.idle_polls = state.idle_polls.saturating_add(1);
+            let factor = 2u32.saturating_pow(state.idle_polls.min(16));
+            let interval = self
+                .poll_interval
+                .saturating_mul(factor)
+                .min(self.max_poll_interval);
+            state.next_poll_at = now + interval;
         }
     }
 
@@ -36,6 +132,21 @@ impl HomeserverBackoff {
         }
     }
 
+    /// Number of failed runs since the last successful one.
+    pub fn consecutive_failures(&self, hs_id: &str) -> u32 {
+        self.state.get(hs_id).map_or(0, |bs| bs.failures)
+    }
+
+    /// Wall-clock timestamp (ms) at which the current backoff window ends, if any.
+    pub fn backoff_until(&self, hs_id: &str) -> Option<i64> {
+        let remaining = self
+            .state
+            .get(hs_id)?
+            .backoff_until
+            .checked_duration_since(Instant::now())?;
+        Some(Utc::now().timestamp_millis() + remaining.as_millis() as i64)
+    }
+
     /// Resets backoff state for a homeserver after a successful run.
     pub fn record_success(&mut self, hs_id: &str) {
         self.state.remove(hs_id);
@@ -48,13 +159,17 @@ impl HomeserverBackoff {
         let initial = self.initial_backoff_secs;
         let max = self.max_backoff_secs;
         let entry = self.state.entry(hs_id.to_string()).or_insert(BackoffState {
+            failures: 0,
             next_backoff_secs: initial,
             backoff_until: Instant::now(),
         });
 
+        entry.failures = entry.failures.saturating_add(1);
         let backoff_secs = entry.next_backoff_secs;
         entry.backoff_until = Instant::now() + Duration::from_secs(backoff_secs);
         entry.next_backoff_secs = (backoff_secs * 2).min(max);
+        // Once the backoff window ends, the homeserver is polled right away
+        self.polls.remove(hs_id);
 
         info!("Homeserver {hs_id} backed off for {backoff_secs}s");
     }
@@ -77,6 +192,73 @@ mod tests {
         }
     }
 
+    fn cadence() -> HomeserverBackoff {
+        HomeserverBackoff::default()
+            .with_poll_cadence(Duration::from_secs(5), Duration::from_secs(60))
+    }
+
+    #[test]
+    fn new_homeserver_is_due() {
+        assert!(cadence().is_due("hs1"));
+    }
+
+    #[test]
+    fn full_page_is_repolled_right_away() {
+        let mut backoff = cadence();
+        backoff.record_poll("hs1", 100, true);
+        assert!(backoff.is_due("hs1"));
+        assert!(backoff.is_draining());
+
+        backoff.record_poll("hs1", 10, false);
+        assert!(!backoff.is_due("hs1"));
+        assert!(!backoff.is_draining());
+    }
+
+    #[test]
+    fn homeserver_leaving_the_run_stops_draining() {
+        let mut backoff = cadence();
+        backoff.record_poll("hs1", 100, true);
+        backoff.record_poll("hs2", 10, false);
+        assert!(backoff.is_draining());
+
+        backoff.retain_polls(&["hs2".to_string()]);
+        assert!(!backoff.is_draining());
+        assert!(backoff.is_due("hs1"));
+        assert!(!backoff.is_due("hs2"));
+    }
+
+    #[test]
+    fn idle_homeserver_is_polled_less_often_up_to_the_ceiling() {
+        let mut backoff = cadence();
+        let next_poll_in = |backoff: &HomeserverBackoff| {
+            backoff.polls["hs1"]
+                .next_poll_at
+                .saturating_duration_since(Instant::now())
+        };
+
+        backoff.record_poll("hs1", 0, false);
+        assert!(next_poll_in(&backoff) > Duration::from_secs(9));
+        assert!(!backoff.is_due("hs1"));
+
+        for _ in 0..10 {
+            backoff.record_poll("hs1", 0, false);
+        }
+        assert!(next_poll_in(&backoff) <= Duration::from_secs(60));
+        assert!(next_poll_in(&backoff) > Duration::from_secs(59));
+
+        // New events restore the regular cadence
+        backoff.record_poll("hs1", 3, false);
+        assert!(next_poll_in(&backoff) <= Duration::from_secs(5));
+    }
+
+    #[test]
+    fn default_cadence_is_not_adaptive() {
+        let mut backoff = HomeserverBackoff::default();
+        backoff.record_poll("hs1", 0, false);
+        backoff.record_poll("hs1", 0, false);
+        assert!(backoff.is_due("hs1"));
+    }
+
     #[test]
     fn new_homeserver_is_not_skipped() {
         let backoff = HomeserverBackoff::default();
@@ -98,6 +280,22 @@ mod tests {
         assert!(!backoff.should_skip("hs1"));
     }
 
+    #[test]
+    fn failures_are_counted_until_success() {
+        let mut backoff = HomeserverBackoff::default();
+        assert_eq!(backoff.consecutive_failures("hs1"), 0);
+        assert!(backoff.backoff_until("hs1").is_none());
+
+        backoff.record_failure("hs1");
+        backoff.record_failure("hs1");
+        assert_eq!(backoff.consecutive_failures("hs1"), 2);
+        assert!(backoff.backoff_until("hs1").unwrap() > Utc::now().timestamp_millis());
+
+        backoff.record_success("hs1");
+        assert_eq!(backoff.consecutive_failures("hs1"), 0);
+        assert!(backoff.backoff_until("hs1").is_none());
+    }
+
     #[test]
     fn independent_homeservers() {
         let mut backoff = HomeserverBackoff::default();
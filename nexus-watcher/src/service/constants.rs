@@ -3,3 +3,5 @@ pub const WATCHER_CONFIG_FILE_NAME: &str = "watcher-config.toml";
 ///  Per-homeserver hard timeout (seconds)
 // TODO: Set timeout maybe from the config file
 pub const PROCESSING_TIMEOUT_SECS: u64 = 3_600;
+/// Maximum number of queued user resyncs run per retry pass
+pub const USER_RESYNC_BATCH_SIZE: usize = 10;
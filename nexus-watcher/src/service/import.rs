@@ -0,0 +1,137 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use nexus_common::models::homeserver::{Homeserver, DEFAULT_CURSOR};
+use nexus_common::types::DynError;
+use tokio::fs;
+use tracing::{debug, info};
+
+use crate::service::processor_runner::EventProcessorRunner;
+use crate::service::stats::{EventOutcomes, ResourceOutcomes};
+
+/// Directory of the event-line files, relative to the dump directory
+pub const DUMP_EVENTS_DIR: &str = "events";
+
+/// Imports event lines and their blobs from a local dump directory, without contacting any
+/// homeserver. The dump is laid out as:
+///
+/// ```text
+/// {dir}/events/*          files of event lines, as returned by `/events`, imported in file name order
+/// {dir}/{user_id}/pub/... blob of each PUT event, at the path of its `pubky://{user_id}/pub/...` URI
+/// ```
+///
+/// The event lines go through the same [`EventProcessor`](crate::service::EventProcessor) path as
+/// the homeserver events, with the blobs served by the dump, see
+/// [`PubkyConnector::init_from_dump`](nexus_common::db::PubkyConnector::init_from_dump).
+/// Cursor lines are ignored, so no homeserver cursor is moved, the imported events are not
+/// appended to the event log, and the events of users hosted on another homeserver are imported too.
+#[derive(Debug, Clone)]
+pub struct EventImport {
+    pub dir: PathBuf,
+}
+
+/// Result of an [`EventImport`]
+#[derive(Debug, Default)]
+pub struct ImportSummary {
+    /// Number of event-line files read
+    pub files: usize,
+    /// Number of event lines read
+    pub events: usize,
+    /// Outcome of the imported events, by resource type
+    pub outcomes: BTreeMap<String, ResourceOutcomes>,
+}
+
+impl EventImport {
+    /// Imports the dump using event processors built by `runner`
+    pub async fn run(&self, runner: &EventProcessorRunner) -> Result<ImportSummary, DynError> {
+        let outcomes = EventOutcomes::default();
+        let mut summary = ImportSummary::default();
+
+        for file in self.event_files().await? {
+            if *runner.shutdown_rx.borrow() {
+                info!(
+                    "Shutdown detected, stopping the import before {}",
+                    file.display()
+                );
+                break;
+            }
+
+            let content = fs::read_to_string(&file).await?;
+            let lines: Vec<String> = content
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with("cursor: "))
+                .map(String::from)
+                .collect();
+            debug!("Importing {} events from {}", lines.len(), file.display());
+            summary.files += 1;
+            summary.events += lines.len();
+
+            // The cursor is only read by the event polling, which the import bypasses
+            let homeserver =
+                Homeserver::try_from_cursor(runner.default_homeserver.clone(), DEFAULT_CURSOR)?;
+            let mut processor = runner.build_processor(homeserver);
+            processor.event_log = None;
+            // The dump users may be hosted anywhere, their events are not polled from a homeserver
+            processor.check_user_homeserver = false;
+            processor.record_changes = false;
+            processor.process_event_lines(lines).await?;
+            outcomes.merge(&processor.outcomes);
+        }
+
+        summary.outcomes = outcomes.snapshot();
+        Ok(summary)
+    }
+
+    /// Event-line files of the dump, in file name order
+    async fn event_files(&self) -> Result<Vec<PathBuf>, DynError> {
+        let events_dir = self.dir.join(DUMP_EVENTS_DIR);
+        let mut entries = fs::read_dir(&events_dir).await.map_err(|e| {
+            format!(
+                "Cannot read the dump events at {}: {e}",
+                events_dir.display()
+            )
+        })?;
+
+        let mut files = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            if entry.file_type().await?.is_file() {
+                files.push(entry.path());
+            }
+        }
+        files.sort_unstable();
+        Ok(files)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_event_files_are_sorted_and_skip_directories() {
+        let tmp = tempfile::tempdir().unwrap();
+        let events = tmp.path().join(DUMP_EVENTS_DIR);
+        fs::create_dir_all(events.join("nested")).await.unwrap();
+        fs::write(events.join("0002.txt"), "").await.unwrap();
+        fs::write(events.join("0001.txt"), "").await.unwrap();
+
+        let import = EventImport {
+            dir: tmp.path().to_path_buf(),
+        };
+        let files = import.event_files().await.unwrap();
+        assert_eq!(
+            files,
+            vec![events.join("0001.txt"), events.join("0002.txt")]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_missing_events_dir_is_an_error() {
+        let tmp = tempfile::tempdir().unwrap();
+        let import = EventImport {
+            dir: tmp.path().to_path_buf(),
+        };
+        assert!(import.event_files().await.is_err());
+    }
+}
@@ -0,0 +1,241 @@
+use std::collections::BTreeSet;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::Utc;
+use nexus_common::db::kv::{lease, RedisResult};
+use tokio::sync::watch::Receiver;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use crate::service::PROCESSING_TIMEOUT_SECS;
+
+/// Live watcher instances, scored by the timestamp of their last heartbeat
+const INSTANCES_KEY: &str = "Watcher:Instances";
+/// Lease of the instance running the retry processor
+const LEADER_LEASE_KEY: &str = "Watcher:Leases:Leader";
+/// Prefix of the per-homeserver leases
+const HOMESERVER_LEASE_PREFIX: &str = "Watcher:Leases:Homeserver";
+/// Shortest lease TTL, so that the leases are renewed at most three times a second
+const MIN_LEASE_TTL: Duration = Duration::from_secs(1);
+
+/// Redis leases that let several watcher instances share the monitored homeservers,
+/// see [WatcherConfig::sharding].
+///
+/// Each instance holds a lease per homeserver it processes, and takes at most its fair share
+/// of the homeservers: their number divided by the number of live instances. Leases are
+/// renewed in the background and expire after `ttl`, so the homeservers of a crashed instance
+/// are picked up by the others. A separate leader lease elects the instance that runs the
+/// retry processor.
+///
+/// [WatcherConfig::sharding]: nexus_common::WatcherConfig::sharding
+pub struct WatcherLeases {
+    instance_id: String,
+    ttl: Duration,
+    /// Homeservers this instance holds a lease for
+    held: Mutex<BTreeSet<String>>,
+}
+
+impl WatcherLeases {
+    /// `ttl` is raised to a second if shorter, see [WatcherLeases::min_ttl] for the TTL of a
+    /// running watcher
+    pub fn new(instance_id: String, ttl: Duration) -> Self {
+        Self {
+            instance_id,
+            ttl: ttl.max(MIN_LEASE_TTL),
+            held: Mutex::new(BTreeSet::new()),
+        }
+    }
+
+    /// Shortest [WatcherConfig::lease_ttl] accepted by a running watcher, in whole seconds: it
+    /// outlasts the sleep between two runs and the timeout of a homeserver run, so a slow
+    /// renewal does not let a lease expire mid-run
+    ///
+    /// [WatcherConfig::lease_ttl]: nexus_common::WatcherConfig::lease_ttl
+    pub fn min_ttl(watcher_sleep: Duration) -> Duration {
+        let run = watcher_sleep + Duration::from_secs(PROCESSING_TIMEOUT_SECS);
+        Duration::from_secs(run.as_secs() + 1)
+    }
+
+    /// Instance id made of the host name, the process id and the start time, unique across the
+    /// instances sharing a Redis
+    pub fn default_instance_id() -> String {
+        let host = std::env::var("HOSTNAME").unwrap_or_else(|_| String::from("watcher"));
+        let started_at = Utc::now().timestamp_millis();
+        format!("{host}-{}-{started_at}", std::process::id())
+    }
+
+    /// Id of this watcher instance, as stored in the leases
+    pub fn instance_id(&self) -> &str {
+        &self.instance_id
+    }
+
+    /// Picks the homeservers this instance processes in the coming run, out of `hs_ids`.
+    ///
+    /// Leases already held are renewed and free ones are acquired, in priority order, up to the
+    /// fair share of this instance and `limit`. Leases beyond the share, e.g. after another
+    /// instance joined, are released so that instance can take them over.
+    ///
+    /// # Returns
+    /// The homeservers leased by this instance, in the order of `hs_ids`
+    pub async fn assign(&self, hs_ids: Vec<String>, limit: usize) -> RedisResult<Vec<String>> {
+        let live_instances = self.heartbeat().await?;
+        let share = hs_ids.len().div_ceil(live_instances.max(1)).min(limit);
+
+        let mut assigned = Vec::new();
+        for hs_id in hs_ids {
+            let key = homeserver_lease_key(&hs_id);
+            if assigned.len() >= share {
+                if self.is_held(&hs_id) {
+                    lease::release(&key, &self.instance_id).await?;
+                    debug!("Released the lease of HS {hs_id}, beyond the share of this instance");
+                }
+                continue;
+            }
+            if lease::acquire(&key, &self.instance_id, self.ttl_ms()).await? {
+                assigned.push(hs_id);
+            }
+        }
+
+        debug!(
+            "Leased {}/{share} homeservers, {live_instances} live watcher instances",
+            assigned.len()
+        );
+        *self.held.lock().expect("lease set lock poisoned") = assigned.iter().cloned().collect();
+        Ok(assigned)
+    }
+
+    /// Acquires or renews the leader lease.
+    ///
+    /// # Returns
+    /// `true` if this instance is the leader
+    pub async fn is_leader(&self) -> RedisResult<bool> {
+        lease::acquire(LEADER_LEASE_KEY, &self.instance_id, self.ttl_ms()).await
+    }
+
+    /// Renews the heartbeat and every lease held by this instance, forgetting the leases
+    /// that were lost, e.g. after a long pause
+    pub async fn renew(&self) -> RedisResult<()> {
+        self.heartbeat().await?;
+
+        for hs_id in self.held_leases() {
+            if !lease::acquire(
+                &homeserver_lease_key(&hs_id),
+                &self.instance_id,
+                self.ttl_ms(),
+            )
+            .await?
+            {
+                warn!("Lost the lease of HS {hs_id} to another watcher instance");
+                self.held
+                    .lock()
+                    .expect("lease set lock poisoned")
+                    .remove(&hs_id);
+            }
+        }
+        if lease::owner(LEADER_LEASE_KEY).await?.as_deref() == Some(self.instance_id.as_str()) {
+            lease::acquire(LEADER_LEASE_KEY, &self.instance_id, self.ttl_ms()).await?;
+        }
+        Ok(())
+    }
+
+    /// Releases every lease held by this instance, so the other instances take over right away
+    pub async fn release_all(&self) -> RedisResult<()> {
+        let held = std::mem::take(&mut *self.held.lock().expect("lease set lock poisoned"));
+        for hs_id in held {
+            lease::release(&homeserver_lease_key(&hs_id), &self.instance_id).await?;
+        }
+        lease::release(LEADER_LEASE_KEY, &self.instance_id).await?;
+        lease::leave(INSTANCES_KEY, &self.instance_id).await?;
+        info!(
+            "Released the leases of watcher instance {}",
+            self.instance_id
+        );
+        Ok(())
+    }
+
+    /// Renews the leases every third of their TTL until shutdown
+    pub fn spawn_renewal(self: Arc<Self>, mut shutdown_rx: Receiver<bool>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.ttl / 3);
+            loop {
+                tokio::select! {
+                    _ = shutdown_rx.changed() => break,
+                    _ = interval.tick() => {
+                        if let Err(e) = self.renew().await {
+                            warn!("Failed to renew the watcher leases: {e}");
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    /// Homeservers this instance currently holds a lease for
+    pub fn held_leases(&self) -> Vec<String> {
+        let held = self.held.lock().expect("lease set lock poisoned");
+        held.iter().cloned().collect()
+    }
+
+    /// Checks in Redis that this instance still holds the lease of the homeserver, e.g. before
+    /// committing a cursor
+    pub async fn holds(&self, hs_id: &str) -> RedisResult<bool> {
+        if !self.is_held(hs_id) {
+            return Ok(false);
+        }
+        let owner = lease::owner(&homeserver_lease_key(hs_id)).await?;
+        Ok(owner.as_deref() == Some(self.instance_id.as_str()))
+    }
+
+    /// Whether this instance held the lease of the homeserver at the last renewal
+    pub fn is_held(&self, hs_id: &str) -> bool {
+        self.held
+            .lock()
+            .expect("lease set lock poisoned")
+            .contains(hs_id)
+    }
+
+    /// Registers this instance as live and returns the number of live instances
+    async fn heartbeat(&self) -> RedisResult<usize> {
+        let now = Utc::now().timestamp_millis();
+        lease::heartbeat(INSTANCES_KEY, &self.instance_id, now, self.ttl_ms()).await
+    }
+
+    fn ttl_ms(&self) -> u64 {
+        self.ttl.as_millis() as u64
+    }
+}
+
+fn homeserver_lease_key(hs_id: &str) -> String {
+    format!("{HOMESERVER_LEASE_PREFIX}:{hs_id}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_short_ttl_does_not_stop_the_renewal() {
+        for ttl_ms in [0, 1, 2] {
+            let leases = Arc::new(WatcherLeases::new(
+                String::from("instance"),
+                Duration::from_millis(ttl_ms),
+            ));
+            assert_eq!(leases.ttl, MIN_LEASE_TTL);
+
+            // The renewal interval panics on a zero period
+            let (shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+            let renewal = leases.spawn_renewal(shutdown_rx);
+            shutdown_tx.send(true).unwrap();
+            assert!(renewal.await.is_ok());
+        }
+    }
+
+    #[test]
+    fn test_min_ttl_outlasts_a_run() {
+        let watcher_sleep = Duration::from_millis(5_500);
+        let min_ttl = WatcherLeases::min_ttl(watcher_sleep);
+        assert!(min_ttl > watcher_sleep + Duration::from_secs(PROCESSING_TIMEOUT_SECS));
+        assert_eq!(min_ttl.subsec_nanos(), 0);
+    }
+}
@@ -0,0 +1,172 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use nexus_common::db::PubkyConnector;
+use nexus_common::models::error::ModelError;
+use nexus_common::models::homeserver::Homeserver;
+use nexus_common::models::user::UserHomeserver;
+use nexus_common::types::DynError;
+use pubky_app_specs::PubkyId;
+use tokio::sync::watch::Receiver;
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, warn};
+
+use crate::service::leases::WatcherLeases;
+
+/// Outcome of the homeserver check of a single user
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UserCheck {
+    /// Still hosted on the recorded homeserver
+    Unchanged,
+    /// Homeserver recorded for the first time
+    Hosted,
+    /// Moved from the recorded homeserver to a new one
+    Migrated,
+    /// No homeserver published
+    Unresolved,
+    /// The new homeserver was refused by the homeserver policy
+    Rejected,
+}
+
+/// Result of a [MigrationCheck] batch
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MigrationCheckStats {
+    pub checked: usize,
+    pub hosted: usize,
+    pub migrated: usize,
+    pub unresolved: usize,
+    pub rejected: usize,
+    pub failed: usize,
+}
+
+/// Periodically re-resolves the homeserver of the indexed users from their pkarr record, to
+/// follow the users that migrated to another homeserver, see
+/// [WatcherConfig::migration_check_sleep].
+///
+/// The new homeserver of a migrated user is ingested, subject to the homeserver policy, and
+/// recorded as a [UserHomeserver], after which the event processors ignore the events of the
+/// user polled from any other homeserver.
+///
+/// [WatcherConfig::migration_check_sleep]: nexus_common::WatcherConfig::migration_check_sleep
+pub struct MigrationCheck {
+    batch_size: usize,
+    /// ID of the last checked user, the next batch starts after it
+    last_user_id: Mutex<String>,
+}
+
+impl MigrationCheck {
+    pub fn new(batch_size: usize) -> Self {
+        Self {
+            batch_size,
+            last_user_id: Mutex::new(String::new()),
+        }
+    }
+
+    /// Checks the next batch of users, starting over from the first user once all were checked
+    pub async fn run(&self) -> Result<MigrationCheckStats, DynError> {
+        let after = self
+            .last_user_id
+            .lock()
+            .expect("cursor lock poisoned")
+            .clone();
+        let users = UserHomeserver::get_page_from_graph(&after, self.batch_size).await?;
+        *self.last_user_id.lock().expect("cursor lock poisoned") = users
+            .last()
+            .map(|(user_id, _)| user_id.clone())
+            .unwrap_or_default();
+
+        let mut stats = MigrationCheckStats::default();
+        for (user_id, homeserver_id) in users {
+            stats.checked += 1;
+            match Self::check_user(&user_id, homeserver_id).await {
+                Ok(UserCheck::Unchanged) => {}
+                Ok(UserCheck::Hosted) => stats.hosted += 1,
+                Ok(UserCheck::Migrated) => stats.migrated += 1,
+                Ok(UserCheck::Unresolved) => stats.unresolved += 1,
+                Ok(UserCheck::Rejected) => stats.rejected += 1,
+                Err(e) => {
+                    warn!("Failed to check the homeserver of user {user_id}: {e}");
+                    stats.failed += 1;
+                }
+            }
+        }
+        Ok(stats)
+    }
+
+    /// Resolves the homeserver of `user_id` and records it if it differs from `homeserver_id`,
+    /// the homeserver currently recorded for the user
+    pub async fn check_user(
+        user_id: &str,
+        homeserver_id: Option<String>,
+    ) -> Result<UserCheck, DynError> {
+        let user_id = PubkyId::try_from(user_id)?;
+        let pubky = PubkyConnector::get()?;
+        let Some(resolved) = pubky.get_homeserver_of(&user_id.to_public_key()).await else {
+            return Ok(UserCheck::Unresolved);
+        };
+        Self::follow_user(&user_id, homeserver_id, PubkyId::from(resolved)).await
+    }
+
+    /// Records `resolved` as the homeserver of `user_id` if it differs from `homeserver_id`,
+    /// the homeserver currently recorded for the user, ingesting it if unknown
+    pub async fn follow_user(
+        user_id: &PubkyId,
+        homeserver_id: Option<String>,
+        resolved: PubkyId,
+    ) -> Result<UserCheck, DynError> {
+        if homeserver_id.as_deref() == Some(resolved.as_ref()) {
+            return Ok(UserCheck::Unchanged);
+        }
+
+        match Homeserver::maybe_ingest(resolved.clone(), user_id).await {
+            Err(ModelError::HomeserverRejected { .. }) => return Ok(UserCheck::Rejected),
+            result => result?,
+        }
+        if UserHomeserver::put(user_id, &resolved).await?.is_none() {
+            // The user was deleted in the meantime
+            return Ok(UserCheck::Unchanged);
+        }
+
+        match homeserver_id {
+            Some(previous) => {
+                info!("User {user_id} migrated from HS {previous} to HS {resolved}");
+                Ok(UserCheck::Migrated)
+            }
+            None => Ok(UserCheck::Hosted),
+        }
+    }
+
+    /// Checks a batch of users every `sleep` until shutdown. With sharding, only the leader
+    /// instance runs the check.
+    pub fn spawn(
+        self: Arc<Self>,
+        sleep: Duration,
+        leases: Option<Arc<WatcherLeases>>,
+        mut shutdown_rx: Receiver<bool>,
+    ) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(sleep);
+            loop {
+                tokio::select! {
+                    _ = shutdown_rx.changed() => break,
+                    _ = interval.tick() => {
+                        if let Some(ref leases) = leases {
+                            match leases.is_leader().await {
+                                Ok(true) => {}
+                                Ok(false) => continue,
+                                Err(e) => {
+                                    error!("Failed to acquire the leader lease: {e}");
+                                    continue;
+                                }
+                            }
+                        }
+                        match self.run().await {
+                            Ok(stats) => debug!("Homeserver migration check: {stats:?}"),
+                            Err(e) => error!("Failed to run the homeserver migration check: {e}"),
+                        }
+                    }
+                }
+            }
+        })
+    }
+}
@@ -1,28 +1,46 @@
 pub mod backoff;
 mod constants;
+mod import;
+mod leases;
+mod migrations;
 mod processor;
 mod processor_runner;
+mod rebuild;
+mod replay;
+mod resync;
 mod stats;
 mod traits;
+mod validation;
 
 /// Module exports
 pub use constants::{PROCESSING_TIMEOUT_SECS, WATCHER_CONFIG_FILE_NAME};
+pub use import::{EventImport, ImportSummary, DUMP_EVENTS_DIR};
+pub use leases::WatcherLeases;
+pub use migrations::{MigrationCheck, MigrationCheckStats, UserCheck};
 use nexus_common::types::DynError;
 pub use processor::EventProcessor;
 pub use processor_runner::EventProcessorRunner;
+pub use rebuild::{rebuild_from_log, RebuildSummary};
+pub use replay::{EventReplay, ReplaySummary};
+pub use resync::{ResyncSummary, UserResync};
+pub use stats::{EventOutcome, EventOutcomes, ResourceOutcomes};
 pub use traits::{TEventProcessor, TEventProcessorRunner};
+pub use validation::{validate_homeserver, InvalidEvent, ValidationReport};
 
 use crate::dispatcher::EventDispatcher;
 use crate::NexusWatcherBuilder;
 use nexus_common::file::ConfigLoader;
+use nexus_common::models::event::EntityChange;
 use nexus_common::models::homeserver::Homeserver;
+use nexus_common::models::webhook::Webhooks;
 use nexus_common::utils::create_shutdown_rx;
 use nexus_common::{DaemonConfig, WatcherConfig};
+use pubky_app_specs::PubkyId;
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::watch::Receiver;
 use tokio::time::Duration;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 pub struct NexusWatcher {}
 
@@ -81,6 +99,72 @@ impl NexusWatcher {
         .await
     }
 
+    /// Derives the [WatcherConfig] from [DaemonConfig] (nexusd service config) and replays a range
+    /// of homeserver events, see [EventReplay].
+    ///
+    /// ### Arguments
+    ///
+    /// - `config_dir`: the directory where the config file is expected to be
+    /// - `replay`: the homeserver and cursor range to replay
+    pub async fn replay_from_daemon(
+        config_dir: PathBuf,
+        replay: EventReplay,
+    ) -> Result<ReplaySummary, DynError> {
+        let daemon_config = DaemonConfig::read_or_create_config_file(config_dir).await?;
+        let watcher_config = WatcherConfig::from(daemon_config);
+        NexusWatcherBuilder {
+            config: watcher_config,
+            plugins: vec![],
+        }
+        .replay(replay)
+        .await
+    }
+
+    /// Loads the config from `config_dir` and resyncs a single user from their homeserver,
+    /// see [UserResync]
+    pub async fn resync_from_daemon(
+        config_dir: PathBuf,
+        resync: UserResync,
+    ) -> Result<ResyncSummary, DynError> {
+        let daemon_config = DaemonConfig::read_or_create_config_file(config_dir).await?;
+        let watcher_config = WatcherConfig::from(daemon_config);
+        NexusWatcherBuilder {
+            config: watcher_config,
+            plugins: vec![],
+        }
+        .resync(resync)
+        .await
+    }
+
+    /// Loads the config from `config_dir` and imports the events of a dump directory,
+    /// see [EventImport]
+    pub async fn import_from_daemon(
+        config_dir: PathBuf,
+        import: EventImport,
+    ) -> Result<ImportSummary, DynError> {
+        let daemon_config = DaemonConfig::read_or_create_config_file(config_dir).await?;
+        let watcher_config = WatcherConfig::from(daemon_config);
+        NexusWatcherBuilder {
+            config: watcher_config,
+            plugins: vec![],
+        }
+        .import(import)
+        .await
+    }
+
+    /// Loads the config from `config_dir` and rebuilds the indexes from the local event log,
+    /// see [rebuild_from_log]
+    pub async fn rebuild_from_daemon(config_dir: PathBuf) -> Result<RebuildSummary, DynError> {
+        let daemon_config = DaemonConfig::read_or_create_config_file(config_dir).await?;
+        let watcher_config = WatcherConfig::from(daemon_config);
+        NexusWatcherBuilder {
+            config: watcher_config,
+            plugins: vec![],
+        }
+        .rebuild_from_log()
+        .await
+    }
+
     pub async fn start(
         mut shutdown_rx: Receiver<bool>,
         config: WatcherConfig,
@@ -88,15 +172,68 @@ impl NexusWatcher {
     ) -> Result<(), DynError> {
         debug!(?config, "Running NexusWatcher with ");
 
+        if config.dry_run {
+            return NexusWatcher::dry_run(shutdown_rx, config, dispatcher).await;
+        }
+
         let config_hs = config.homeserver.clone();
         Homeserver::persist_if_unknown(config_hs).await?;
+        // Published so the API admits homeservers with the same policy
+        config.homeserver_policy.put_to_index().await?;
+        Webhooks::init(&config.webhooks);
+        EntityChange::set_max_len(config.entity_changes_max_len);
 
         let mut interval = tokio::time::interval(Duration::from_millis(config.watcher_sleep));
-        let ev_processor_runner =
+        let mut retry_interval = tokio::time::interval(Duration::from_millis(config.retry_sleep));
+        let mut ev_processor_runner =
             EventProcessorRunner::from_config(&config, shutdown_rx.clone(), dispatcher);
+        let retry_processor = ev_processor_runner.retry_processor.clone();
+        let leases = config.sharding.then(|| {
+            let min_ttl = WatcherLeases::min_ttl(Duration::from_millis(config.watcher_sleep));
+            let mut ttl = Duration::from_millis(config.lease_ttl);
+            if ttl < min_ttl {
+                warn!(
+                    "lease_ttl of {}ms is shorter than a homeserver run, raised to {}s",
+                    config.lease_ttl,
+                    min_ttl.as_secs()
+                );
+                ttl = min_ttl;
+            }
+            Arc::new(WatcherLeases::new(
+                WatcherLeases::default_instance_id(),
+                ttl,
+            ))
+        });
+        if let Some(ref leases) = leases {
+            info!(
+                "Sharding homeservers as watcher instance {}",
+                leases.instance_id()
+            );
+            leases.clone().spawn_renewal(shutdown_rx.clone());
+        }
+        ev_processor_runner.leases = leases.clone();
+        let ev_processor_runner = Arc::new(ev_processor_runner);
+        // Resyncs requested through the admin API
+        UserResync::spawn_queued(
+            ev_processor_runner.clone(),
+            Duration::from_millis(config.retry_sleep),
+            leases.clone(),
+            shutdown_rx.clone(),
+        );
+        if config.migration_check_batch_size > 0 {
+            Arc::new(MigrationCheck::new(config.migration_check_batch_size)).spawn(
+                Duration::from_millis(config.migration_check_sleep),
+                leases.clone(),
+                shutdown_rx.clone(),
+            );
+        }
         let mut backoff = crate::service::backoff::HomeserverBackoff::new(
             config.initial_backoff_secs,
             config.max_backoff_secs,
+        )
+        .with_poll_cadence(
+            Duration::from_millis(config.watcher_sleep),
+            Duration::from_millis(config.max_idle_sleep),
         );
 
         loop {
@@ -111,10 +248,72 @@ impl NexusWatcher {
                         .run_all(&mut backoff)
                         .await
                         .inspect_err(|e| error!("Failed to start event processors run: {e}"));
+                    // Re-poll the homeservers that returned a full page until they are drained
+                    if backoff.is_draining() {
+                        interval.reset_immediately();
+                    }
+                }
+                _ = retry_interval.tick() => {
+                    // With sharding, only the leader instance replays the retry queue and the
+                    // pending webhook deliveries
+                    if let Some(ref leases) = leases {
+                        match leases.is_leader().await {
+                            Ok(true) => {}
+                            Ok(false) => continue,
+                            Err(e) => {
+                                error!("Failed to acquire the leader lease: {e}");
+                                continue;
+                            }
+                        }
+                    }
+                    debug!("Retrying failed events…");
+                    _ = retry_processor
+                        .run()
+                        .await
+                        .inspect_err(|e| error!("Failed to run retry processor: {e}"));
+                    _ = Webhooks::run_pending()
+                        .await
+                        .inspect_err(|e| error!("Failed to run the pending webhook deliveries: {e}"));
                 }
             }
         }
+        if let Some(leases) = leases {
+            _ = leases
+                .release_all()
+                .await
+                .inspect_err(|e| error!("Failed to release the watcher leases: {e}"));
+        }
         info!("Nexus Watcher shut down gracefully");
         Ok(())
     }
+
+    /// Validates the events of the monitored homeservers without indexing them and logs
+    /// a [ValidationReport] per homeserver, see [WatcherConfig::dry_run]
+    async fn dry_run(
+        shutdown_rx: Receiver<bool>,
+        config: WatcherConfig,
+        dispatcher: Option<Arc<EventDispatcher>>,
+    ) -> Result<(), DynError> {
+        info!("Dry run: validating homeserver events without writing to the graph or Redis");
+
+        let ev_processor_runner =
+            EventProcessorRunner::from_config(&config, shutdown_rx, dispatcher);
+
+        // The default homeserver may not be in the graph yet, as nothing is persisted in a dry run
+        let mut hs_ids = ev_processor_runner.pre_run_all().await?;
+        if !hs_ids.contains(&config.homeserver.to_string()) {
+            hs_ids.insert(0, config.homeserver.to_string());
+            hs_ids.truncate(config.monitored_homeservers_limit.max(1));
+        }
+
+        for hs_id in hs_ids {
+            let report =
+                validation::validate_homeserver(&ev_processor_runner, PubkyId::try_from(&hs_id)?)
+                    .await?;
+            report.log();
+        }
+
+        info!("Dry run completed");
+        Ok(())
+    }
 }
@@ -1,17 +1,25 @@
 use nexus_common::models::event::{Event, EventProcessorError, EventType, ParseResult};
 
 use crate::dispatcher::EventDispatcher;
-use crate::events::handle;
+use crate::events::log::{event_cursors, EventLog};
 use crate::events::retry::event::RetryEvent;
+use crate::events::retry::processor::RetryProcessor;
 use crate::events::Moderation;
+use crate::events::{fetch_put_blob, handle_fetched};
+use crate::metrics::metrics;
+use crate::service::leases::WatcherLeases;
+use crate::service::stats::{EventOutcome, EventOutcomes};
 use crate::service::traits::TEventProcessor;
 use nexus_common::db::PubkyConnector;
 use nexus_common::models::homeserver::Homeserver;
+use nexus_common::models::user::UserHomeserver;
 use opentelemetry::trace::{FutureExt, Span, TraceContextExt, Tracer};
 use opentelemetry::{global, Context, KeyValue};
 use pubky::Method;
-use pubky_app_specs::PubkyId;
+use pubky_app_specs::{PubkyId, Resource};
+use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
 use tokio::sync::watch::Receiver;
 use tracing::{debug, error, info, warn};
@@ -26,6 +34,26 @@ pub struct EventProcessor {
     pub shutdown_rx: Receiver<bool>,
     /// Domain plugin dispatcher — intercepts events before social parsing.
     pub dispatcher: Option<Arc<EventDispatcher>>,
+    /// Replays the events waiting for a user or post indexed by this processor
+    pub retry_processor: Arc<RetryProcessor>,
+    /// Event lines processed so far, see [TEventProcessor::events_processed]
+    pub events_processed: AtomicUsize,
+    /// Whether the last poll returned `limit` events, see [TEventProcessor::page_full]
+    pub page_full: AtomicBool,
+    /// Outcome of every event line processed so far, by resource type
+    pub outcomes: EventOutcomes,
+    /// Local log every processed event line is appended to, see [WatcherConfig::event_log]
+    pub event_log: Option<Arc<EventLog>>,
+    /// Ignore the events of users recorded as hosted on another homeserver, i.e. that migrated
+    /// away from this one, see [UserHomeserver]
+    pub check_user_homeserver: bool,
+    /// Append the changes of the indexed entities to their stream, see [handle_fetched]
+    pub record_changes: bool,
+    /// Leases of this watcher instance, if the homeserver is processed under one. The run stops
+    /// once the lease is lost, see [WatcherConfig::sharding]
+    ///
+    /// [WatcherConfig::sharding]: nexus_common::WatcherConfig::sharding
+    pub leases: Option<Arc<WatcherLeases>>,
 }
 
 #[async_trait::async_trait]
@@ -34,6 +62,14 @@ impl TEventProcessor for EventProcessor {
         self.homeserver.id.clone()
     }
 
+    fn events_processed(&self) -> usize {
+        self.events_processed.load(Ordering::Relaxed)
+    }
+
+    fn page_full(&self) -> bool {
+        self.page_full.load(Ordering::Relaxed)
+    }
+
     async fn run_internal(self: Arc<Self>) -> Result<(), EventProcessorError> {
         let maybe_event_lines = self
             .poll_events()
@@ -43,6 +79,9 @@ impl TEventProcessor for EventProcessor {
         match maybe_event_lines {
             None => debug!("No new events"),
             Some(event_lines) => {
+                let events = event_lines.iter().filter(|l| !l.starts_with("cursor: "));
+                self.page_full
+                    .store(events.count() >= self.limit as usize, Ordering::Relaxed);
                 info!("Processing {} event lines", event_lines.len());
                 self.process_event_lines(event_lines).await?;
             }
@@ -60,7 +99,7 @@ impl EventProcessor {
     /// URIs in a newline-separated format, processes it into a vector of strings,
     /// and returns the result.
     #[tracing::instrument(name = "events.poll", skip_all, fields(homeserver = %self.homeserver.id))]
-    async fn poll_events(&self) -> Result<Option<Vec<String>>, EventProcessorError> {
+    pub(crate) async fn poll_events(&self) -> Result<Option<Vec<String>>, EventProcessorError> {
         debug!("Polling new events from homeserver");
 
         let response_text = {
@@ -87,8 +126,10 @@ impl EventProcessor {
         debug!("Homeserver response lines {:?}", lines);
 
         if lines.is_empty() || (lines.len() == 1 && lines[0].is_empty()) {
+            metrics().record_poll_batch_size(0);
             return Ok(None);
         }
+        metrics().record_poll_batch_size(lines.len());
 
         Ok(Some(lines))
     }
@@ -99,31 +140,63 @@ impl EventProcessor {
     /// - Lines starting with `cursor:` update the cursor for the homeserver and save it to the index.
     /// - Other lines are parsed into events and processed accordingly. If parsing fails, an error is logged.
     ///
+    /// Once the batch is processed, the last received cursor is checkpointed to the graph, so it
+    /// survives a Redis wipe.
+    ///
     /// # Parameters
     /// - `lines`: A vector of strings representing event lines retrieved from the homeserver.
     #[tracing::instrument(name = "event_batch.process", skip_all, fields(batch.size = lines.len()))]
     pub async fn process_event_lines(&self, lines: Vec<String>) -> Result<(), EventProcessorError> {
+        let mut checkpoint = None;
+        // Homeserver of the users seen in this batch
+        let mut user_homeservers = HashMap::new();
+        let mut cursors = event_cursors(&self.homeserver.cursor, &lines).into_iter();
+
         for line in &lines {
             let id = self.homeserver.id.clone();
 
             if *self.shutdown_rx.borrow() {
                 debug!("Shutdown detected while processing HS {id}, exiting event processing loop");
-                return Ok(());
+                break;
+            }
+            if self.leases.as_ref().is_some_and(|l| !l.is_held(&id)) {
+                warn!("Lost the lease of HS {id}, stopping its run");
+                break;
             }
 
             if let Some(cursor) = line.strip_prefix("cursor: ") {
                 info!("Received cursor for the next request: {cursor}");
+                // Another instance may have taken the homeserver over since the last renewal
+                if let Some(ref leases) = self.leases {
+                    if !leases.holds(&id).await? {
+                        warn!("Lost the lease of HS {id}, not committing cursor {cursor}");
+                        break;
+                    }
+                }
                 match Homeserver::try_from_cursor(id, cursor) {
-                    Ok(hs) => hs.put_to_index().await?,
+                    Ok(hs) => {
+                        hs.put_to_index().await?;
+                        checkpoint = Some(hs);
+                    }
                     Err(e) => warn!("{e}"),
                 }
             } else {
+                self.events_processed.fetch_add(1, Ordering::Relaxed);
+                let cursor = cursors.next();
+                let cursor = cursor.as_deref();
+
                 // Let domain plugins claim their events before social parsing.
                 if let Some(ref dispatcher) = self.dispatcher {
                     match dispatcher.try_dispatch(line).await {
-                        Ok(true) => continue,
+                        Ok(true) => {
+                            self.log_event(line, cursor, None).await;
+                            self.outcomes.record("plugin", EventOutcome::Indexed);
+                            continue;
+                        }
                         Ok(false) => {}
                         Err(e) => {
+                            self.log_event(line, cursor, None).await;
+                            self.outcomes.record("plugin", EventOutcome::Failed);
                             self.enqueue_plugin_retry(line, e).await;
                             continue;
                         }
@@ -131,20 +204,39 @@ impl EventProcessor {
                 }
 
                 match Event::parse_event(line, self.files_path.clone()) {
-                    Err(e) => error!("{e}"),
-                    Ok(ParseResult::Skipped) => {}
+                    Err(e) => {
+                        self.log_event(line, cursor, None).await;
+                        self.outcomes.record("unknown", EventOutcome::Failed);
+                        error!("{e}")
+                    }
+                    Ok(ParseResult::Skipped) => {
+                        self.log_event(line, cursor, None).await;
+                        self.outcomes.record("unknown", EventOutcome::Skipped)
+                    }
                     Ok(ParseResult::UnrecognizedUri {
                         event_type,
                         uri,
                         reason,
                     }) => {
+                        self.log_event(line, cursor, None).await;
                         if !self.try_handle_universal_tag(&event_type, &uri).await
                             && !self.try_handle_universal_file(&event_type, &uri).await
                         {
+                            self.outcomes.record("unknown", EventOutcome::Skipped);
                             error!("Cannot parse event URI: {reason}");
                         }
                     }
                     Ok(ParseResult::Parsed(event)) => {
+                        let event = event.with_homeserver(&self.homeserver.id);
+                        if self
+                            .is_migrated_away(&event.parsed_uri.user_id, &mut user_homeservers)
+                            .await?
+                        {
+                            debug!("Ignoring {line}, its user migrated to another homeserver");
+                            let resource = event.parsed_uri.resource.to_string();
+                            self.outcomes.record(&resource, EventOutcome::Skipped);
+                            continue;
+                        }
                         let tracer = global::tracer(self.tracer_name.clone());
                         let mut span = tracer.start(event.parsed_uri.resource.to_string());
                         span.set_attribute(KeyValue::new("event.uri", event.uri.clone()));
@@ -162,24 +254,103 @@ impl EventProcessor {
                         ));
                         let cx = Context::new().with_span(span);
                         debug!("Processing event: {:?}", event);
-                        self.handle_event(&event).with_context(cx).await?;
+                        self.handle_event(&event, cursor).with_context(cx).await?;
+                        self.record_user_homeserver(&event, &mut user_homeservers)
+                            .await;
                     }
                 }
             }
         }
 
+        if let Some(hs) = checkpoint {
+            debug!(
+                "Checkpointing cursor {} of HS {} to the graph",
+                hs.cursor, hs.id
+            );
+            hs.put_cursor_to_graph().await?;
+        }
+
         Ok(())
     }
 
+    /// Whether `user_id` is recorded as hosted on another homeserver than this one
+    ///
+    /// # Arguments
+    /// * `user_homeservers` - Homeservers of the users already looked up, by user ID
+    async fn is_migrated_away(
+        &self,
+        user_id: &PubkyId,
+        user_homeservers: &mut HashMap<String, Option<String>>,
+    ) -> Result<bool, EventProcessorError> {
+        if !self.check_user_homeserver {
+            return Ok(false);
+        }
+        let homeserver_id = match user_homeservers.get(user_id.as_ref()) {
+            Some(homeserver_id) => homeserver_id.clone(),
+            None => {
+                let homeserver_id = UserHomeserver::get_from_index(user_id)
+                    .await?
+                    .map(|user_homeserver| user_homeserver.homeserver_id);
+                user_homeservers.insert(user_id.to_string(), homeserver_id.clone());
+                homeserver_id
+            }
+        };
+        Ok(homeserver_id.is_some_and(|homeserver_id| homeserver_id != *self.homeserver.id))
+    }
+
+    /// Records this homeserver as the one hosting the user of an indexed profile, unless the user
+    /// is already associated with a homeserver
+    ///
+    /// # Arguments
+    /// * `user_homeservers` - Homeservers of the users already looked up, by user ID
+    async fn record_user_homeserver(
+        &self,
+        event: &Event,
+        user_homeservers: &mut HashMap<String, Option<String>>,
+    ) {
+        let user_id = &event.parsed_uri.user_id;
+        if !self.check_user_homeserver
+            || !matches!(event.event_type, EventType::Put)
+            || !matches!(event.parsed_uri.resource, Resource::User)
+            || !matches!(user_homeservers.get(user_id.as_ref()), Some(None))
+        {
+            return;
+        }
+        match UserHomeserver::put(user_id, &self.homeserver.id).await {
+            Ok(Some(_)) => {
+                user_homeservers.insert(user_id.to_string(), Some(self.homeserver.id.to_string()));
+            }
+            // The profile failed to index
+            Ok(None) => {}
+            Err(e) => warn!("Failed to record the homeserver of user {user_id}: {e}"),
+        }
+    }
+
     /// Attempts to handle an unrecognized URI as a universal tag at an app-specific path.
     /// Returns `true` if the event was claimed (regardless of success/failure).
     async fn try_handle_universal_tag(&self, event_type: &EventType, uri: &str) -> bool {
-        let result = crate::events::handlers::universal_tag::try_handle(event_type, uri).await;
+        let result = crate::events::handlers::universal_tag::try_handle(
+            event_type,
+            uri,
+            self.record_changes,
+        )
+        .await;
 
         let Some(result) = result else {
             return false;
         };
 
+        match &result {
+            Ok(()) => {
+                metrics().record_event("tag", event_type);
+                self.outcomes.record("tag", EventOutcome::Indexed);
+            }
+            Err(e) => {
+                metrics().record_failure("tag", e);
+                self.outcomes.record("tag", EventOutcome::Failed);
+            }
+        }
+
         if let Err(e) = result {
             match e {
                 EventProcessorError::InvalidEventLine(ref msg) => {
@@ -187,7 +358,8 @@ impl EventProcessor {
                 }
                 _ => {
                     let index_key = format!("{event_type}:{uri}");
-                    let retry_event = RetryEvent::new(e);
+                    let retry_event =
+                        RetryEvent::new(e, format!("{event_type} {uri}"), &self.homeserver.id);
                     error!("{}, {}", retry_event.error_type, index_key);
                     if let Err(err) = retry_event.put_to_index(index_key).await {
                         error!("Failed to enqueue universal tag retry: {err}");
@@ -210,6 +382,17 @@ impl EventProcessor {
             return false;
         };
 
+        match &result {
+            Ok(()) => {
+                metrics().record_event("file", event_type);
+                self.outcomes.record("file", EventOutcome::Indexed);
+            }
+            Err(e) => {
+                metrics().record_failure("file", e);
+                self.outcomes.record("file", EventOutcome::Failed);
+            }
+        }
+
         if let Err(e) = result {
             match e {
                 EventProcessorError::InvalidEventLine(ref msg) => {
@@ -217,7 +400,8 @@ impl EventProcessor {
                 }
                 _ => {
                     let index_key = format!("{event_type}:{uri}");
-                    let retry_event = RetryEvent::new(e);
+                    let retry_event =
+                        RetryEvent::new(e, format!("{event_type} {uri}"), &self.homeserver.id);
                     error!("{}, {}", retry_event.error_type, index_key);
                     if let Err(err) = retry_event.put_to_index(index_key).await {
                         error!("Failed to enqueue universal file retry: {err}");
@@ -230,12 +414,14 @@ impl EventProcessor {
     }
 
     async fn enqueue_plugin_retry(&self, line: &str, error: EventProcessorError) {
+        metrics().record_failure("plugin", &error);
+
         let Some((event_type, uri)) = line.split_once(' ') else {
             error!("Plugin dispatch error for malformed event line {line}: {error}");
             return;
         };
 
-        let retry_event = RetryEvent::new(error);
+        let retry_event = RetryEvent::new(error, line, &self.homeserver.id);
         let Some(index) = RetryEvent::generate_index_key(uri.trim()) else {
             error!("Plugin dispatch retry skipped for invalid URI: {uri}");
             return;
@@ -265,13 +451,22 @@ impl EventProcessor {
             otel.status_message = tracing::field::Empty,
         )
     )]
-    async fn handle_event(&self, event: &Event) -> Result<(), EventProcessorError> {
+    async fn handle_event(
+        &self,
+        event: &Event,
+        cursor: Option<&str>,
+    ) -> Result<(), EventProcessorError> {
         let span = tracing::Span::current();
-        if let Err(e) = handle(event, self.moderation.clone()).await {
+        let resource = event.parsed_uri.resource.to_string();
+        if let Err(e) = self.index_event(event, cursor).await {
             span.record("otel.status_code", "ERROR");
             span.record("otel.status_message", tracing::field::display(&e));
+            metrics().record_failure(&resource, &e);
+            self.outcomes.record(&resource, EventOutcome::Failed);
 
-            if let Some((index_key, retry_event)) = extract_retry_event_info(event, e) {
+            if let Some((index_key, retry_event)) =
+                extract_retry_event_info(event, e, &self.homeserver.id)
+            {
                 error!("{}, {}", retry_event.error_type, index_key);
                 if let Err(err) = retry_event.put_to_index(index_key).await {
                     error!("Failed to put event to retry index: {}", err);
@@ -279,9 +474,62 @@ impl EventProcessor {
             }
         } else {
             span.record("otel.status_code", "OK");
+            metrics().record_event(&resource, &event.event_type);
+            self.outcomes.record(&resource, EventOutcome::Indexed);
+
+            if let Some(dependency_key) = extract_dependency_key(event) {
+                if let Err(e) = self.retry_processor.retry_dependents(&dependency_key).await {
+                    error!("Failed to retry events waiting for {dependency_key}: {e}");
+                }
+            }
         }
         Ok(())
     }
+
+    /// Fetches the blob of the event, appends both to the event log and indexes the event
+    async fn index_event(
+        &self,
+        event: &Event,
+        cursor: Option<&str>,
+    ) -> Result<(), EventProcessorError> {
+        let blob = fetch_put_blob(event).await;
+        let fetched = blob.as_ref().ok().and_then(Option::as_deref);
+        self.log_event(event.event_line(), cursor, fetched).await;
+
+        handle_fetched(
+            event,
+            blob?.as_deref(),
+            self.moderation.clone(),
+            self.record_changes,
+        )
+        .await
+    }
+
+    /// Appends an event line to the event log, if enabled. Failures are logged and do not
+    /// affect the indexing of the event.
+    async fn log_event(&self, line: &str, cursor: Option<&str>, blob: Option<&[u8]>) {
+        let Some(ref event_log) = self.event_log else {
+            return;
+        };
+        let homeserver = Some(self.homeserver.id.as_ref());
+        if let Err(e) = event_log.append(homeserver, cursor, line, blob).await {
+            error!("Failed to append event line to the event log: {e}");
+        }
+    }
+}
+
+/// Returns the key under which events blocked by a `MissingDependency` wait for this event,
+/// if indexing it can unblock them (a new user profile or post)
+///
+/// # Parameters
+/// - `event`: Reference to the event that was just indexed
+fn extract_dependency_key(event: &Event) -> Option<String> {
+    match (&event.event_type, &event.parsed_uri.resource) {
+        (EventType::Put, Resource::User | Resource::Post(_)) => {
+            Some(RetryEvent::generate_index_key_from_uri(&event.parsed_uri))
+        }
+        _ => None,
+    }
 }
 
 /// Extracts retry-related information from an event and its associated error
@@ -289,9 +537,11 @@ impl EventProcessor {
 /// # Parameters
 /// - `event`: Reference to the event for which retry information is being extracted
 /// - `error`: Determines whether the event is eligible for a retry or should be discarded
+/// - `homeserver`: The homeserver the event was polled from
 fn extract_retry_event_info(
     event: &Event,
     error: EventProcessorError,
+    homeserver: &str,
 ) -> Option<(String, RetryEvent)> {
     let retry_event = match error {
         EventProcessorError::InvalidEventLine(ref message) => {
@@ -304,7 +554,7 @@ fn extract_retry_event_info(
             error!("SpecValidation: {}", reason);
             return None;
         }
-        _ => RetryEvent::new(error),
+        _ => RetryEvent::new(error, event.event_line(), homeserver),
     };
 
     // Generate a compress index to save in the cache
@@ -323,6 +573,8 @@ mod tests {
     use nexus_common::models::event::ParseResult;
     use tempfile::TempDir;
 
+    const HOMESERVER: &str = "8pinxxgqs41n4aididenw5apqp1urfmzdztr8jt4abrkdn435ewo";
+
     /// Build a syntactically-valid `Event` for classifier tests. The body of
     /// `extract_retry_event_info` only consults `event.uri` for the retry-eligible
     /// branch; the skip branches we test below short-circuit before reaching it.
@@ -346,6 +598,7 @@ mod tests {
         let result = extract_retry_event_info(
             &event,
             EventProcessorError::InvalidEventLine("malformed".into()),
+            HOMESERVER,
         );
         assert!(result.is_none(), "InvalidEventLine must skip retry");
     }
@@ -360,6 +613,7 @@ mod tests {
         let result = extract_retry_event_info(
             &event,
             EventProcessorError::SpecValidation("post kind is unknown".into()),
+            HOMESERVER,
         );
         assert!(result.is_none(), "SpecValidation must skip retry");
     }
@@ -372,7 +626,27 @@ mod tests {
         let result = extract_retry_event_info(
             &event,
             EventProcessorError::Generic("transient failure".into()),
+            HOMESERVER,
         );
         assert!(result.is_some(), "Generic errors must enqueue a retry");
     }
+
+    #[test]
+    fn test_extract_dependency_key_matches_missing_dependency_keys() {
+        // The key must match what `post::sync_put` stores in `MissingDependency`
+        // for a reply, so the reply is found once its parent is indexed.
+        let (event, _tmp) = fixture_event();
+        assert_eq!(
+            extract_dependency_key(&event),
+            RetryEvent::generate_index_key(&event.uri)
+        );
+
+        let tmp = tempfile::tempdir().expect("create temp files_path");
+        let line = "DEL pubky://4snwyct86m383rsduhw5xgcxpw7c63j3pq8x4ycqikxgik8y64ro/pub/pubky.app/posts/0034A0X7NJ52A";
+        let event = match Event::parse_event(line, tmp.path().to_path_buf()).unwrap() {
+            ParseResult::Parsed(event) => event,
+            other => panic!("expected Parsed event, got {:?}", other),
+        };
+        assert!(extract_dependency_key(&event).is_none());
+    }
 }
@@ -1,12 +1,17 @@
 use crate::dispatcher::EventDispatcher;
+use crate::events::log::EventLog;
+use crate::events::retry::processor::RetryProcessor;
 use crate::events::Moderation;
+use crate::service::leases::WatcherLeases;
 use crate::service::processor::EventProcessor;
+use crate::service::stats::EventOutcomes;
 use crate::service::traits::{TEventProcessor, TEventProcessorRunner};
-use nexus_common::models::homeserver::Homeserver;
+use nexus_common::models::homeserver::{Homeserver, HomeserverPolicy};
 use nexus_common::types::DynError;
 use nexus_common::WatcherConfig;
 use pubky_app_specs::PubkyId;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicUsize};
 use std::sync::Arc;
 use tokio::sync::watch::Receiver;
 
@@ -15,6 +20,8 @@ pub struct EventProcessorRunner {
     pub limit: u32,
     /// See [WatcherConfig::monitored_homeservers_limit]
     pub monitored_homeservers_limit: usize,
+    /// See [WatcherConfig::max_concurrent_homeservers]
+    pub max_concurrent_homeservers: usize,
     pub files_path: PathBuf,
     pub tracer_name: String,
     pub moderation: Arc<Moderation>,
@@ -23,6 +30,14 @@ pub struct EventProcessorRunner {
     pub default_homeserver: PubkyId,
     /// Domain plugin dispatcher (None when no plugins are registered).
     pub dispatcher: Option<Arc<EventDispatcher>>,
+    /// Shared with the event processors, to replay events once their dependency is indexed
+    pub retry_processor: Arc<RetryProcessor>,
+    /// See [WatcherConfig::event_log]
+    pub event_log: Option<Arc<EventLog>>,
+    /// Homeserver leases shared with the other watcher instances, see [WatcherConfig::sharding]
+    pub leases: Option<Arc<WatcherLeases>>,
+    /// See [WatcherConfig::homeserver_policy], denylisted homeservers are not polled
+    pub homeserver_policy: HomeserverPolicy,
 }
 
 impl EventProcessorRunner {
@@ -32,9 +47,20 @@ impl EventProcessorRunner {
         shutdown_rx: Receiver<bool>,
         dispatcher: Option<Arc<EventDispatcher>>,
     ) -> Self {
+        let event_log = config
+            .event_log
+            .then(|| Arc::new(EventLog::from_files_path(&config.stack.files_path)));
+        let retry_processor = Arc::new(RetryProcessor::from_config(
+            config,
+            shutdown_rx.clone(),
+            dispatcher.clone(),
+            event_log.clone(),
+        ));
+
         Self {
             limit: config.events_limit,
             monitored_homeservers_limit: config.monitored_homeservers_limit,
+            max_concurrent_homeservers: config.max_concurrent_homeservers,
             files_path: config.stack.files_path.clone(),
             tracer_name: config.stack.otlp.name.clone(),
             moderation: Arc::new(Moderation {
@@ -43,7 +69,32 @@ impl EventProcessorRunner {
             }),
             shutdown_rx,
             default_homeserver: config.homeserver.clone(),
+            retry_processor,
             dispatcher,
+            event_log,
+            leases: None,
+            homeserver_policy: config.homeserver_policy.clone(),
+        }
+    }
+
+    /// Creates a new event processor reading the events of `homeserver` from its current cursor
+    pub fn build_processor(&self, homeserver: Homeserver) -> EventProcessor {
+        EventProcessor {
+            homeserver,
+            limit: self.limit,
+            files_path: self.files_path.clone(),
+            tracer_name: self.tracer_name.clone(),
+            moderation: self.moderation.clone(),
+            shutdown_rx: self.shutdown_rx.clone(),
+            dispatcher: self.dispatcher.clone(),
+            retry_processor: self.retry_processor.clone(),
+            events_processed: AtomicUsize::new(0),
+            page_full: AtomicBool::new(false),
+            outcomes: EventOutcomes::default(),
+            event_log: self.event_log.clone(),
+            check_user_homeserver: true,
+            record_changes: true,
+            leases: None,
         }
     }
 }
@@ -62,6 +113,10 @@ impl TEventProcessorRunner for EventProcessorRunner {
         self.monitored_homeservers_limit
     }
 
+    fn max_concurrent_homeservers(&self) -> usize {
+        self.max_concurrent_homeservers
+    }
+
     async fn homeservers_by_priority(&self) -> Result<Vec<String>, DynError> {
         let mut hs_ids = Homeserver::get_all_from_graph().await?;
 
@@ -77,6 +132,21 @@ impl TEventProcessorRunner for EventProcessorRunner {
         Ok(hs_ids)
     }
 
+    /// With [WatcherConfig::sharding], only the homeservers leased by this instance are run
+    async fn pre_run_all(&self) -> Result<Vec<String>, DynError> {
+        let mut hs_ids = self.homeservers_by_priority().await?;
+        hs_ids.retain(|hs_id| !self.homeserver_policy.is_denylisted(hs_id));
+        match self.leases {
+            Some(ref leases) => Ok(leases
+                .assign(hs_ids, self.monitored_homeservers_limit)
+                .await?),
+            None => {
+                let max_index = std::cmp::min(self.monitored_homeservers_limit, hs_ids.len());
+                Ok(hs_ids[..max_index].to_vec())
+            }
+        }
+    }
+
     /// Creates and returns a new event processor instance for the specified homeserver
     async fn build(&self, homeserver_id: String) -> Result<Arc<dyn TEventProcessor>, DynError> {
         let homeserver_id = PubkyId::try_from(&homeserver_id)?;
@@ -84,15 +154,9 @@ impl TEventProcessorRunner for EventProcessorRunner {
             .await?
             .ok_or("Homeserver not found")?;
 
-        // Create a new event processor instance with the specified homeserver
-        Ok(Arc::new(EventProcessor {
-            homeserver,
-            limit: self.limit,
-            files_path: self.files_path.clone(),
-            tracer_name: self.tracer_name.clone(),
-            moderation: self.moderation.clone(),
-            shutdown_rx: self.shutdown_rx.clone(),
-            dispatcher: self.dispatcher.clone(),
-        }))
+        let mut processor = self.build_processor(homeserver);
+        // The run stops once the lease of the homeserver is lost
+        processor.leases = self.leases.clone();
+        Ok(Arc::new(processor))
     }
 }
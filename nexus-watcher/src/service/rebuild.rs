@@ -0,0 +1,177 @@
+use std::collections::BTreeMap;
+
+use nexus_common::models::event::{Event, EventType, ParseResult};
+use nexus_common::models::homeserver::Homeserver;
+use nexus_common::types::DynError;
+use pubky_app_specs::PubkyId;
+use tracing::{debug, info, warn};
+
+use crate::events::handle_fetched;
+use crate::events::handlers::{universal_file, universal_tag};
+use crate::events::log::{EventLog, EventLogEntry};
+use crate::service::processor_runner::EventProcessorRunner;
+use crate::service::stats::{EventOutcome, EventOutcomes, ResourceOutcomes};
+
+/// Result of a rebuild from the event log, see [rebuild_from_log]
+#[derive(Debug, Default)]
+pub struct RebuildSummary {
+    /// Number of log segments read
+    pub segments: usize,
+    /// Number of log entries read
+    pub entries: usize,
+    /// Cursor restored for each homeserver found in the log
+    pub cursors: BTreeMap<String, String>,
+    /// Outcome of the rebuilt events, by resource type
+    pub outcomes: BTreeMap<String, ResourceOutcomes>,
+    /// Logged event lines that cannot be rebuilt offline, as their blobs are not logged: the
+    /// PUTs of universal tags and files, and the domain plugin events. They can be replayed from
+    /// their homeserver with [EventReplay](crate::service::EventReplay).
+    pub refused: Vec<String>,
+}
+
+/// Rebuilds the graph and Redis indexes from the event log, see [WatcherConfig::event_log],
+/// without contacting any homeserver.
+///
+/// The entries are replayed in the order they were logged, with the blobs stored in the log.
+/// Only the blobs of the `pubky.app` resources are logged: domain plugin events and universal
+/// tag/file PUTs cannot be rebuilt offline, so they are refused and listed in
+/// [RebuildSummary::refused]. Failed events are reported, not retried.
+///
+/// Once the log is replayed, the cursor of every homeserver is restored to the cursor of its
+/// last logged event, so the watcher resumes right after it.
+///
+/// [WatcherConfig::event_log]: nexus_common::WatcherConfig::event_log
+pub async fn rebuild_from_log(runner: &EventProcessorRunner) -> Result<RebuildSummary, DynError> {
+    let event_log = EventLog::from_files_path(&runner.files_path);
+    let outcomes = EventOutcomes::default();
+    let mut summary = RebuildSummary::default();
+
+    'segments: for segment in event_log.segments().await? {
+        debug!("Rebuilding from event log segment {}", segment.display());
+        summary.segments += 1;
+
+        for entry in EventLog::read_segment(&segment).await? {
+            if *runner.shutdown_rx.borrow() {
+                info!("Shutdown detected, stopping the rebuild");
+                break 'segments;
+            }
+            summary.entries += 1;
+
+            if let (Some(homeserver), Some(cursor)) = (&entry.homeserver, &entry.cursor) {
+                summary.cursors.insert(homeserver.clone(), cursor.clone());
+            }
+            if is_refused(runner, &entry.line) {
+                warn!("Cannot rebuild {} without its homeserver", entry.line);
+                summary.refused.push(entry.line);
+                continue;
+            }
+            rebuild_entry(runner, &event_log, &entry, &outcomes).await;
+        }
+    }
+
+    for (homeserver_id, cursor) in &summary.cursors {
+        let homeserver =
+            Homeserver::try_from_cursor(PubkyId::try_from(homeserver_id.as_str())?, cursor)?;
+        homeserver.put_cursor_to_graph().await?;
+        homeserver.put_to_index().await?;
+    }
+
+    summary.outcomes = outcomes.snapshot();
+    Ok(summary)
+}
+
+/// Whether the logged event line was indexed from a blob that is not in the log: a domain plugin
+/// event or a universal tag/file PUT
+fn is_refused(runner: &EventProcessorRunner, line: &str) -> bool {
+    if let Some(ref dispatcher) = runner.dispatcher {
+        if dispatcher.matches(line) {
+            return true;
+        }
+    }
+    match Event::parse_event(line, runner.files_path.clone()) {
+        Ok(ParseResult::UnrecognizedUri {
+            event_type: EventType::Put,
+            uri,
+            ..
+        }) => {
+            universal_tag::try_parse_app_tag_path(&uri).is_some()
+                || universal_file::try_parse_app_file_path(&uri).is_some()
+        }
+        _ => false,
+    }
+}
+
+/// Indexes a single log entry, recording its outcome
+async fn rebuild_entry(
+    runner: &EventProcessorRunner,
+    event_log: &EventLog,
+    entry: &EventLogEntry,
+    outcomes: &EventOutcomes,
+) {
+    let line = entry.line.as_str();
+    let event = match Event::parse_event(line, runner.files_path.clone()) {
+        Err(e) => {
+            warn!("Failed to parse logged event line {line}: {e}");
+            return outcomes.record("unknown", EventOutcome::Failed);
+        }
+        Ok(ParseResult::Skipped) => return outcomes.record("unknown", EventOutcome::Skipped),
+        Ok(ParseResult::UnrecognizedUri {
+            event_type, uri, ..
+        }) => return rebuild_universal(runner, &event_type, &uri, outcomes).await,
+        Ok(ParseResult::Parsed(event)) => match &entry.homeserver {
+            Some(homeserver) => event.with_homeserver(homeserver),
+            None => event,
+        },
+    };
+
+    let resource = event.parsed_uri.resource.to_string();
+    let blob = match (&event.event_type, &entry.blob_hash) {
+        (EventType::Del, _) => None,
+        (EventType::Put, Some(hash)) => match event_log.read_blob(hash).await {
+            Ok(blob) => Some(blob),
+            Err(e) => {
+                warn!("Failed to read the logged blob {hash} of {line}: {e}");
+                return outcomes.record(&resource, EventOutcome::Failed);
+            }
+        },
+        // The blob could not be fetched when the event was first processed
+        (EventType::Put, None) => return outcomes.record(&resource, EventOutcome::Skipped),
+    };
+
+    match handle_fetched(&event, blob.as_deref(), runner.moderation.clone(), false).await {
+        Ok(()) => outcomes.record(&resource, EventOutcome::Indexed),
+        Err(e) => {
+            warn!("Failed to rebuild {line}: {e}");
+            outcomes.record(&resource, EventOutcome::Failed);
+        }
+    }
+}
+
+/// Rebuilds a universal tag/file DEL, see [is_refused] for their PUTs
+async fn rebuild_universal(
+    runner: &EventProcessorRunner,
+    event_type: &EventType,
+    uri: &str,
+    outcomes: &EventOutcomes,
+) {
+    let resource = if universal_tag::try_parse_app_tag_path(uri).is_some() {
+        "tag"
+    } else if universal_file::try_parse_app_file_path(uri).is_some() {
+        "file"
+    } else {
+        return outcomes.record("unknown", EventOutcome::Skipped);
+    };
+
+    let result = match resource {
+        "tag" => universal_tag::try_handle(event_type, uri, false).await,
+        _ => universal_file::try_handle(event_type, uri, &runner.files_path).await,
+    };
+    match result {
+        Some(Ok(())) => outcomes.record(resource, EventOutcome::Indexed),
+        Some(Err(e)) => {
+            warn!("Failed to rebuild DEL {uri}: {e}");
+            outcomes.record(resource, EventOutcome::Failed);
+        }
+        None => outcomes.record(resource, EventOutcome::Skipped),
+    }
+}
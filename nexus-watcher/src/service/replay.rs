@@ -0,0 +1,214 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use nexus_common::models::event::{Event, ParseResult};
+use nexus_common::models::homeserver::Homeserver;
+use nexus_common::types::DynError;
+use pubky_app_specs::PubkyId;
+use tracing::{debug, info};
+
+use crate::service::processor_runner::EventProcessorRunner;
+use crate::service::stats::{EventOutcome, EventOutcomes, ResourceOutcomes};
+
+/// Re-ingests a range of the events of a homeserver, e.g. after a handler bug fix.
+///
+/// The events are read page by page from the `/events` endpoint, starting at `from`, and
+/// fed to [`EventProcessor::process_event_lines`](crate::service::EventProcessor::process_event_lines).
+/// The cursor lines are consumed by the replay itself, so the cursor stored for the
+/// homeserver (index and graph checkpoint) is left untouched.
+#[derive(Debug, Clone)]
+pub struct EventReplay {
+    pub homeserver: PubkyId,
+    /// Cursor to start reading from, as passed to `/events?cursor=`
+    pub from: String,
+    /// Cursor of the last event to replay. The page that goes beyond it is read again one
+    /// event at a time, so that the replay stops at the first event past it. If `None`,
+    /// replays up to the latest event.
+    pub to: Option<String>,
+    /// Only replay the events of this user
+    pub user: Option<PubkyId>,
+    /// Read and classify the events without indexing them
+    pub dry_run: bool,
+}
+
+/// Result of an [`EventReplay`]
+#[derive(Debug, Default)]
+pub struct ReplaySummary {
+    /// Number of `/events` pages read
+    pub pages: usize,
+    /// Cursor of the last page read, `None` if no page was read
+    pub last_cursor: Option<String>,
+    /// Outcome of the replayed events, by resource type. In a dry run, every event
+    /// in the range is reported as skipped.
+    pub outcomes: BTreeMap<String, ResourceOutcomes>,
+}
+
+impl EventReplay {
+    /// Replays the range using event processors built by `runner`
+    pub async fn run(&self, runner: &EventProcessorRunner) -> Result<ReplaySummary, DynError> {
+        let outcomes = EventOutcomes::default();
+        let mut summary = ReplaySummary::default();
+        let mut cursor = self.from.clone();
+        // Set once a page went beyond the end of the range, to find where exactly
+        let mut one_by_one = false;
+
+        loop {
+            if *runner.shutdown_rx.borrow() {
+                info!("Shutdown detected, stopping the replay at cursor {cursor}");
+                break;
+            }
+
+            let homeserver = Homeserver::try_from_cursor(self.homeserver.clone(), &cursor)?;
+            let mut processor = runner.build_processor(homeserver);
+            // The events were streamed as entity changes when first indexed
+            processor.record_changes = false;
+            if one_by_one {
+                processor.limit = 1;
+            }
+            let Some(lines) = processor.poll_events().await? else {
+                break;
+            };
+            summary.pages += 1;
+
+            let mut next_cursor = None;
+            let mut event_lines = Vec::new();
+            for line in lines {
+                match line.strip_prefix("cursor: ") {
+                    Some(page_cursor) => next_cursor = Some(page_cursor.to_string()),
+                    None if self.matches_user(&line) => event_lines.push(line),
+                    None => {}
+                }
+            }
+
+            // The cursor of a page is the one of its last event, so only a page of a single
+            // event tells whether that event is beyond the end of the range
+            if next_cursor
+                .as_deref()
+                .is_some_and(|next| self.is_beyond_end(next))
+            {
+                if one_by_one {
+                    break;
+                }
+                debug!("Page after cursor {cursor} goes beyond the end, reading it event by event");
+                one_by_one = true;
+                continue;
+            }
+            debug!(
+                "Replaying {} events after cursor {cursor}",
+                event_lines.len()
+            );
+
+            if self.dry_run {
+                for line in &event_lines {
+                    outcomes.record(
+                        &resource_of(line, &runner.files_path),
+                        EventOutcome::Skipped,
+                    );
+                }
+            } else {
+                processor.process_event_lines(event_lines).await?;
+                outcomes.merge(&processor.outcomes);
+            }
+
+            // A page without a new cursor is the last one
+            let Some(next_cursor) = next_cursor.filter(|next| *next != cursor) else {
+                break;
+            };
+            summary.last_cursor = Some(next_cursor.clone());
+            if self.is_at_end(&next_cursor) {
+                break;
+            }
+            cursor = next_cursor;
+        }
+
+        summary.outcomes = outcomes.snapshot();
+        Ok(summary)
+    }
+
+    /// Whether the event line belongs to the user filter, if any
+    fn matches_user(&self, line: &str) -> bool {
+        let Some(user) = &self.user else {
+            return true;
+        };
+        line.split_once(' ')
+            .and_then(|(_, uri)| uri.trim().strip_prefix("pubky://"))
+            .and_then(|path| path.split('/').next())
+            .is_some_and(|user_id| user_id == &**user)
+    }
+
+    /// Whether `cursor` reached the end of the range. Numeric cursors are compared by value,
+    /// other cursors only match the end of the range exactly.
+    fn is_at_end(&self, cursor: &str) -> bool {
+        let Some(to) = &self.to else {
+            return false;
+        };
+        match (cursor.parse::<u64>(), to.parse::<u64>()) {
+            (Ok(cursor), Ok(to)) => cursor >= to,
+            _ => cursor == to,
+        }
+    }
+
+    /// Whether `cursor` is beyond the end of the range. Only numeric cursors can be.
+    fn is_beyond_end(&self, cursor: &str) -> bool {
+        let Some(to) = &self.to else {
+            return false;
+        };
+        match (cursor.parse::<u64>(), to.parse::<u64>()) {
+            (Ok(cursor), Ok(to)) => cursor > to,
+            _ => false,
+        }
+    }
+}
+
+/// Resource type of an event line, as reported in the replay summary
+fn resource_of(line: &str, files_path: &Path) -> String {
+    match Event::parse_event(line, files_path.to_path_buf()) {
+        Ok(ParseResult::Parsed(event)) => event.parsed_uri.resource.to_string(),
+        _ => String::from("unknown"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USER: &str = "4snwyct86m383rsduhw5xgcxpw7c63j3pq8x4ycqikxgik8y64ro";
+    const HOMESERVER: &str = "8pinxxgqs41n4aididenw5apqp1urfmzdztr8jt4abrkdn435ewo";
+
+    fn replay(to: Option<&str>, user: Option<&str>) -> EventReplay {
+        EventReplay {
+            homeserver: PubkyId::try_from(HOMESERVER).unwrap(),
+            from: String::from("0"),
+            to: to.map(String::from),
+            user: user.map(|user| PubkyId::try_from(user).unwrap()),
+            dry_run: true,
+        }
+    }
+
+    #[test]
+    fn test_matches_user() {
+        let line = format!("PUT pubky://{USER}/pub/pubky.app/posts/0034A0X7NJ52A");
+        assert!(replay(None, None).matches_user(&line));
+        assert!(replay(None, Some(USER)).matches_user(&line));
+        assert!(!replay(None, Some(HOMESERVER)).matches_user(&line));
+        assert!(!replay(None, Some(USER)).matches_user("malformed"));
+    }
+
+    #[test]
+    fn test_is_at_end() {
+        assert!(!replay(None, None).is_at_end("100"));
+        assert!(replay(Some("100"), None).is_at_end("100"));
+        assert!(replay(Some("100"), None).is_at_end("150"));
+        assert!(!replay(Some("100"), None).is_at_end("99"));
+        assert!(replay(Some("abc"), None).is_at_end("abc"));
+        assert!(!replay(Some("abc"), None).is_at_end("abd"));
+    }
+
+    #[test]
+    fn test_is_beyond_end() {
+        assert!(!replay(None, None).is_beyond_end("100"));
+        assert!(!replay(Some("100"), None).is_beyond_end("100"));
+        assert!(replay(Some("100"), None).is_beyond_end("101"));
+        assert!(!replay(Some("abc"), None).is_beyond_end("abd"));
+    }
+}
@@ -0,0 +1,339 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use nexus_common::db::{fetch_row_from_graph, queries, PubkyConnector};
+use nexus_common::models::homeserver::Homeserver;
+use nexus_common::models::user::{UserHomeserver, UserResyncRequest};
+use nexus_common::types::DynError;
+use pubky_app_specs::PubkyId;
+use tokio::sync::watch::Receiver;
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info};
+
+use crate::service::constants::USER_RESYNC_BATCH_SIZE;
+use crate::service::leases::WatcherLeases;
+use crate::service::migrations::{MigrationCheck, UserCheck};
+use crate::service::processor_runner::EventProcessorRunner;
+use crate::service::stats::ResourceOutcomes;
+
+/// Resources of the `/pub/pubky.app/` tree converged by a resync, in the order they are put.
+/// They are deleted in the reverse order, so the resources referencing others go first.
+const RESYNC_RESOURCES: [&str; 7] = [
+    "profile.json",
+    "files/",
+    "posts/",
+    "follows/",
+    "bookmarks/",
+    "tags/",
+    "feeds/",
+];
+
+/// Maximum number of entries requested per homeserver listing page
+const LIST_PAGE_SIZE: u16 = 500;
+
+/// Converges the graph with the `/pub/pubky.app/` tree of a single user on their homeserver,
+/// e.g. after a missed `DEL` or a batch that crashed halfway.
+///
+/// The profile, posts, tags, follows, bookmarks, files and feeds listed on the homeserver are
+/// diffed against the ones the graph holds for the user. The missing ones are fed as `PUT` event
+/// lines to [`EventProcessor::process_event_lines`](crate::service::EventProcessor::process_event_lines),
+/// the ones no longer on the homeserver as `DEL` lines, so they go through the usual handlers.
+#[derive(Debug, Clone)]
+pub struct UserResync {
+    pub user: PubkyId,
+    /// Compute the diff without applying it
+    pub dry_run: bool,
+}
+
+/// Result of a [`UserResync`]
+#[derive(Debug, Default)]
+pub struct ResyncSummary {
+    /// Homeserver the user was resynced from
+    pub homeserver: String,
+    /// URIs of the resources put, as they were missing from the graph
+    pub put: Vec<String>,
+    /// URIs of the resources deleted, as they are no longer on the homeserver
+    pub deleted: Vec<String>,
+    /// Outcome of the applied events, by resource type. Empty in a dry run.
+    pub outcomes: BTreeMap<String, ResourceOutcomes>,
+}
+
+impl UserResync {
+    /// Resyncs the user using an event processor built by `runner`
+    pub async fn run(&self, runner: &EventProcessorRunner) -> Result<ResyncSummary, DynError> {
+        let homeserver = self.resolve_homeserver().await?;
+        let listed = self.list_homeserver().await?;
+        let indexed = self.list_graph().await?;
+        let (put, deleted) = plan(&self.prefix(), &listed, &indexed);
+        debug!(
+            "Resync of user {}: {} resources to put, {} to delete",
+            self.user,
+            put.len(),
+            deleted.len()
+        );
+
+        let mut summary = ResyncSummary {
+            homeserver: homeserver.to_string(),
+            put,
+            deleted,
+            outcomes: BTreeMap::new(),
+        };
+        if self.dry_run || (summary.put.is_empty() && summary.deleted.is_empty()) {
+            return Ok(summary);
+        }
+
+        let lines = summary
+            .put
+            .iter()
+            .map(|uri| format!("PUT {uri}"))
+            .chain(summary.deleted.iter().map(|uri| format!("DEL {uri}")))
+            .collect();
+        let processor = runner.build_processor(Homeserver::new(homeserver));
+        processor.process_event_lines(lines).await?;
+        summary.outcomes = processor.outcomes.snapshot();
+
+        info!(
+            "Resynced user {}: {} resources put, {} deleted",
+            self.user,
+            summary.put.len(),
+            summary.deleted.len()
+        );
+        Ok(summary)
+    }
+
+    /// Runs the resyncs queued through the API, the oldest first, recording their outcome.
+    /// The resyncs abandoned mid-run by a stopped watcher are queued again first.
+    ///
+    /// # Returns
+    /// The number of resyncs run
+    pub async fn run_queued(runner: &EventProcessorRunner) -> Result<usize, DynError> {
+        let requeued = UserResyncRequest::requeue_stale().await?;
+        if requeued > 0 {
+            info!("Queued {requeued} stale user resyncs again");
+        }
+        let requests = UserResyncRequest::queued(USER_RESYNC_BATCH_SIZE).await?;
+        let count = requests.len();
+        for mut request in requests {
+            request.start().await?;
+            let result = match PubkyId::try_from(request.user_id.as_str()) {
+                Ok(user) => UserResync {
+                    user,
+                    dry_run: false,
+                }
+                .run(runner)
+                .await
+                .map(|summary| (summary.put.len(), summary.deleted.len()))
+                .map_err(|e| e.to_string()),
+                Err(e) => Err(e.to_string()),
+            };
+            if let Err(ref e) = result {
+                error!("Failed to resync user {}: {e}", request.user_id);
+            }
+            request.finish(result).await?;
+        }
+        Ok(count)
+    }
+
+    /// Runs the queued resyncs every `sleep` until shutdown, apart from the indexing loop so a
+    /// long resync does not hold back the homeservers. With sharding, only the leader instance
+    /// runs them.
+    pub fn spawn_queued(
+        runner: Arc<EventProcessorRunner>,
+        sleep: Duration,
+        leases: Option<Arc<WatcherLeases>>,
+        mut shutdown_rx: Receiver<bool>,
+    ) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(sleep);
+            loop {
+                tokio::select! {
+                    _ = shutdown_rx.changed() => break,
+                    _ = interval.tick() => {
+                        if let Some(ref leases) = leases {
+                            match leases.is_leader().await {
+                                Ok(true) => {}
+                                Ok(false) => continue,
+                                Err(e) => {
+                                    error!("Failed to acquire the leader lease: {e}");
+                                    continue;
+                                }
+                            }
+                        }
+                        _ = Self::run_queued(&runner)
+                            .await
+                            .inspect_err(|e| error!("Failed to run the queued user resyncs: {e}"));
+                    }
+                }
+            }
+        })
+    }
+
+    /// Resolves the homeserver of the user from their pkarr record, following a migration
+    /// like [MigrationCheck] does, and falls back to the recorded one if none is published
+    async fn resolve_homeserver(&self) -> Result<PubkyId, DynError> {
+        let recorded = UserHomeserver::get_by_id(&self.user)
+            .await?
+            .map(|user_homeserver| user_homeserver.homeserver_id);
+        let pubky = PubkyConnector::get()?;
+        let resolved = pubky
+            .get_homeserver_of(&self.user.to_public_key())
+            .await
+            .map(PubkyId::from);
+
+        match (resolved, recorded) {
+            (Some(resolved), recorded) => {
+                if MigrationCheck::follow_user(&self.user, recorded, resolved.clone()).await?
+                    == UserCheck::Rejected
+                {
+                    return Err(format!(
+                        "The homeserver of user {} is refused by the homeserver policy",
+                        self.user
+                    )
+                    .into());
+                }
+                Ok(resolved)
+            }
+            (None, Some(recorded)) => Ok(PubkyId::try_from(recorded.as_str())?),
+            (None, None) => Err(format!("No homeserver found for user {}", self.user).into()),
+        }
+    }
+
+    /// URIs of the resources under the `/pub/pubky.app/` tree of the user on their homeserver
+    async fn list_homeserver(&self) -> Result<BTreeSet<String>, DynError> {
+        let pubky = PubkyConnector::get()?;
+        let storage = pubky.public_storage();
+
+        let mut uris = BTreeSet::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let mut list = storage.list(self.prefix())?.limit(LIST_PAGE_SIZE);
+            if let Some(ref cursor) = cursor {
+                list = list.cursor(cursor);
+            }
+            let entries = list.send().await?;
+            let Some(last) = entries.last() else {
+                break;
+            };
+            cursor = Some(last.to_pubky_url());
+            let known = uris.len();
+            uris.extend(entries.iter().map(|entry| entry.to_pubky_url()));
+            // Guards against a homeserver ignoring the cursor
+            if uris.len() == known {
+                break;
+            }
+        }
+        Ok(uris)
+    }
+
+    /// URIs of the resources the graph holds for the user
+    async fn list_graph(&self) -> Result<BTreeSet<String>, DynError> {
+        let query = queries::get::get_user_resource_uris(&self.user);
+        let uris = match fetch_row_from_graph(query).await? {
+            Some(row) => row.get::<Vec<String>>("uris")?,
+            None => Vec::new(),
+        };
+        Ok(uris.into_iter().collect())
+    }
+
+    fn prefix(&self) -> String {
+        format!("pubky://{}/pub/pubky.app/", self.user)
+    }
+}
+
+/// Splits the resources that differ between the homeserver and the graph into the ones to put
+/// and the ones to delete, in the order of [RESYNC_RESOURCES]. Resources of other types are
+/// ignored.
+///
+/// # Arguments
+/// * `prefix` - URI of the `/pub/pubky.app/` tree of the user
+/// * `listed` - URIs listed on the homeserver
+/// * `indexed` - URIs held by the graph
+fn plan(
+    prefix: &str,
+    listed: &BTreeSet<String>,
+    indexed: &BTreeSet<String>,
+) -> (Vec<String>, Vec<String>) {
+    let ordered = |uris: Vec<&String>, reverse: bool| {
+        let mut ranked: Vec<(usize, String)> = uris
+            .into_iter()
+            .filter_map(|uri| Some((resource_rank(prefix, uri)?, uri.clone())))
+            .collect();
+        ranked.sort();
+        if reverse {
+            ranked.sort_by_key(|(rank, _)| std::cmp::Reverse(*rank));
+        }
+        ranked.into_iter().map(|(_, uri)| uri).collect()
+    };
+
+    let put = ordered(listed.difference(indexed).collect(), false);
+    let deleted = ordered(indexed.difference(listed).collect(), true);
+    (put, deleted)
+}
+
+/// Position of the resource type of `uri` in [RESYNC_RESOURCES], `None` for other resources
+fn resource_rank(prefix: &str, uri: &str) -> Option<usize> {
+    let path = uri.strip_prefix(prefix)?;
+    RESYNC_RESOURCES.iter().position(|resource| {
+        match path.strip_prefix(resource) {
+            // Directories hold a single level of resources
+            Some(id) if resource.ends_with('/') => !id.is_empty() && !id.contains('/'),
+            Some(rest) => rest.is_empty(),
+            None => false,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PREFIX: &str =
+        "pubky://4snwyct86m383rsduhw5xgcxpw7c63j3pq8x4ycqikxgik8y64ro/pub/pubky.app/";
+
+    fn uris(paths: &[&str]) -> BTreeSet<String> {
+        paths.iter().map(|path| format!("{PREFIX}{path}")).collect()
+    }
+
+    #[test]
+    fn test_resource_rank() {
+        assert_eq!(
+            resource_rank(PREFIX, &format!("{PREFIX}profile.json")),
+            Some(0)
+        );
+        assert_eq!(
+            resource_rank(PREFIX, &format!("{PREFIX}posts/0034A0X7NJ52A")),
+            Some(2)
+        );
+        assert_eq!(resource_rank(PREFIX, &format!("{PREFIX}tags/ABC")), Some(5));
+        assert_eq!(resource_rank(PREFIX, &format!("{PREFIX}mutes/ABC")), None);
+        assert_eq!(resource_rank(PREFIX, &format!("{PREFIX}posts/")), None);
+        assert_eq!(resource_rank(PREFIX, &format!("{PREFIX}posts/a/b")), None);
+        assert_eq!(
+            resource_rank(PREFIX, "pubky://other/pub/pubky.app/profile.json"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_plan() {
+        let listed = uris(&[
+            "tags/T1",
+            "posts/P1",
+            "posts/P2",
+            "profile.json",
+            "last_read",
+        ]);
+        let indexed = uris(&["profile.json", "posts/P1", "posts/P3", "follows/F1"]);
+
+        let (put, deleted) = plan(PREFIX, &listed, &indexed);
+        assert_eq!(
+            put,
+            vec![format!("{PREFIX}posts/P2"), format!("{PREFIX}tags/T1")]
+        );
+        assert_eq!(
+            deleted,
+            vec![format!("{PREFIX}follows/F1"), format!("{PREFIX}posts/P3")]
+        );
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
 use std::time::Duration;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -8,6 +10,8 @@ pub enum ProcessorRunStatus {
     Panic,
     Timeout,
     Skipped,
+    /// Not due for polling yet, see [HomeserverBackoff::is_due](crate::service::backoff::HomeserverBackoff::is_due)
+    Idle,
 }
 
 pub struct ProcessorRunStats {
@@ -69,7 +73,63 @@ impl RunAllProcessorsStats {
     pub fn count_skipped(&self) -> usize {
         self.count(ProcessorRunStatus::Skipped)
     }
+
+    /// Number of homeservers left idle, as they had no new events recently
+    pub fn count_idle(&self) -> usize {
+        self.count(ProcessorRunStatus::Idle)
+    }
 }
 
 /// Wrapper around `RunAllProcessorsStats` which indicates they've been processed
 pub struct ProcessedStats(pub RunAllProcessorsStats);
+
+/// Outcome of a single event line handled by an event processor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventOutcome {
+    /// The event was indexed
+    Indexed,
+    /// The event failed to index and was either queued for retry or discarded
+    Failed,
+    /// The event was not meant to be indexed (unknown or ignored resource)
+    Skipped,
+}
+
+/// Number of event lines per [EventOutcome] for a single resource type
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResourceOutcomes {
+    pub indexed: usize,
+    pub failed: usize,
+    pub skipped: usize,
+}
+
+/// Tally of the event outcomes of an event processor, grouped by resource type
+#[derive(Debug, Default)]
+pub struct EventOutcomes(Mutex<BTreeMap<String, ResourceOutcomes>>);
+
+impl EventOutcomes {
+    pub fn record(&self, resource: &str, outcome: EventOutcome) {
+        let mut outcomes = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = outcomes.entry(resource.to_string()).or_default();
+        match outcome {
+            EventOutcome::Indexed => entry.indexed += 1,
+            EventOutcome::Failed => entry.failed += 1,
+            EventOutcome::Skipped => entry.skipped += 1,
+        }
+    }
+
+    /// Adds the outcomes of another tally into this one
+    pub fn merge(&self, other: &EventOutcomes) {
+        let mut outcomes = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        for (resource, counts) in other.snapshot() {
+            let entry = outcomes.entry(resource).or_default();
+            entry.indexed += counts.indexed;
+            entry.failed += counts.failed;
+            entry.skipped += counts.skipped;
+        }
+    }
+
+    /// Returns a copy of the outcomes, ordered by resource type
+    pub fn snapshot(&self) -> BTreeMap<String, ResourceOutcomes> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
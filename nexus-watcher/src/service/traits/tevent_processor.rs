@@ -83,6 +83,19 @@ pub trait TEventProcessor: Send + Sync + 'static {
     /// Returns `Ok(())` on a clean exit, or `Err(EventProcessorError)` on failure.
     async fn run_internal(self: Arc<Self>) -> Result<(), EventProcessorError>;
 
+    /// Number of event lines processed by the last run, reported in the homeserver status.
+    fn events_processed(&self) -> usize {
+        0
+    }
+
+    /// Whether the last run returned a full page of events, i.e. more events are waiting.
+    /// Such homeservers are re-polled right away, see [HomeserverBackoff::record_poll].
+    ///
+    /// [HomeserverBackoff::record_poll]: crate::service::backoff::HomeserverBackoff::record_poll
+    fn page_full(&self) -> bool {
+        false
+    }
+
     /// Optional custom timeout for this event processor.
     ///
     /// If not set, the [`PROCESSING_TIMEOUT_SECS`] is applied.
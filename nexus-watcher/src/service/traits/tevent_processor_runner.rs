@@ -1,5 +1,11 @@
-use std::{sync::Arc, time::Instant};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use futures::stream::{self, StreamExt};
 
+use nexus_common::models::homeserver::HomeserverStatus;
 use nexus_common::types::DynError;
 use tokio::sync::watch::Receiver;
 use tracing::{debug, error, info, warn};
@@ -28,6 +34,13 @@ pub trait TEventProcessorRunner {
 
     fn monitored_homeservers_limit(&self) -> usize;
 
+    /// Maximum number of event processors run in parallel by [TEventProcessorRunner::run_all].
+    ///
+    /// Defaults to `1`, i.e. the homeservers are processed one after the other.
+    fn max_concurrent_homeservers(&self) -> usize {
+        1
+    }
+
     /// Returns the homeserver IDs relevant for this run, ordered by their priority.
     ///
     /// Contains all homeserver IDs from the graph, with the default homeserver prioritized at index 0.
@@ -72,6 +85,7 @@ pub trait TEventProcessorRunner {
         let count_timeout = stats.count_timeout();
         let count_failed_to_build = stats.count_failed_to_build();
         let count_skipped = stats.count_skipped();
+        let count_idle = stats.count_idle();
         let had_issues = count_error + count_panic + count_timeout + count_failed_to_build > 0;
 
         if had_issues {
@@ -79,61 +93,147 @@ pub trait TEventProcessorRunner {
         } else if count_skipped > 0 {
             info!("Run result: {count_ok} ok, {count_skipped} skipped (backoff)");
         } else {
-            debug!("Run result: {count_ok} ok");
+            debug!("Run result: {count_ok} ok, {count_idle} idle");
         }
 
         ProcessedStats(stats)
     }
 
+    /// Persists the outcome of a homeserver run as its [`HomeserverStatus`].
+    ///
+    /// Failing to persist the status is logged and does not affect the run.
+    async fn record_status(
+        &self,
+        hs_id: &str,
+        status: &ProcessorRunStatus,
+        events_processed: usize,
+        backoff: &HomeserverBackoff,
+    ) {
+        let recorded = HomeserverStatus::record_run(
+            hs_id,
+            format!("{status:?}"),
+            *status == ProcessorRunStatus::Ok,
+            backoff.consecutive_failures(hs_id),
+            backoff.backoff_until(hs_id),
+            events_processed,
+        )
+        .await;
+
+        if let Err(e) = recorded {
+            warn!("Failed to persist the status of homeserver {hs_id}: {e}");
+        }
+    }
+
+    /// Builds and runs the event processor of a homeserver, with timeout protection.
+    ///
+    /// # Returns
+    /// The status of the run, the number of event lines processed and whether the page was full
+    async fn run_processor(&self, hs_id: String) -> (ProcessorRunStatus, usize, bool) {
+        match self.build(hs_id.clone()).await {
+            Ok(event_processor) => {
+                let status = match event_processor.clone().run().await {
+                    Ok(_) => ProcessorRunStatus::Ok,
+                    Err(RunError::Internal(_)) => ProcessorRunStatus::Error,
+                    Err(RunError::Panicked) => ProcessorRunStatus::Panic,
+                    Err(RunError::TimedOut) => ProcessorRunStatus::Timeout,
+                };
+                let events_processed = event_processor.events_processed();
+                (status, events_processed, event_processor.page_full())
+            }
+            Err(e) => {
+                error!("Failed to build event processor for homeserver: {hs_id}: {e}");
+                (ProcessorRunStatus::FailedToBuild, 0, false)
+            }
+        }
+    }
+
     /// Runs event processors for all homeservers relevant for this run, with timeout protection.
     ///
+    /// Up to [TEventProcessorRunner::max_concurrent_homeservers] processors run in parallel. They are
+    /// started in the order of [TEventProcessorRunner::pre_run_all], so the default homeserver
+    /// always gets the first slot, and each processor keeps its own timeout.
+    ///
     /// # Parameters
-    /// * `backoff` - Tracks per-homeserver exponential backoff; homeservers in an active backoff
-    ///   window are skipped and their state is updated after each run.
+    /// * `backoff` - Tracks per-homeserver exponential backoff and polling cadence; homeservers in
+    ///   an active backoff window are skipped, homeservers not due for polling are left idle, and
+    ///   their state is updated after each run.
     ///
     /// # Returns
-    /// Statistics about the event processor run results, summarized as [`RunAllProcessorsStats`]
+    /// Statistics about the event processor run results, summarized as [`RunAllProcessorsStats`],
+    /// in the order of the homeservers priority
     async fn run_all(&self, backoff: &mut HomeserverBackoff) -> Result<ProcessedStats, DynError> {
         let hs_ids = self.pre_run_all().await?;
+        let shutdown_rx = self.shutdown_rx();
+        backoff.retain_polls(&hs_ids);
+
+        // Backoff windows are checked upfront, the backoff state is only updated once all runs are done
+        let planned: Vec<(String, Option<ProcessorRunStatus>)> = hs_ids
+            .into_iter()
+            .map(|hs_id| {
+                let skip = if backoff.should_skip(&hs_id) {
+                    Some(ProcessorRunStatus::Skipped)
+                } else if !backoff.is_due(&hs_id) {
+                    Some(ProcessorRunStatus::Idle)
+                } else {
+                    None
+                };
+                (hs_id, skip)
+            })
+            .collect();
+
+        // Results are gathered as they complete, so a slow homeserver never holds a free slot back,
+        // and put back in the priority order afterwards
+        let mut results: Vec<_> = stream::iter(planned.into_iter().enumerate())
+            .map(|(priority, (hs_id, skip))| {
+                let shutdown_rx = shutdown_rx.clone();
+                async move {
+                    if *shutdown_rx.borrow() {
+                        info!("Shutdown detected in homeserver {hs_id}, not starting its run");
+                        return None;
+                    }
+
+                    // Skip homeservers that are in a backoff window or not due for polling
+                    if let Some(status) = skip {
+                        debug!("Skipping homeserver {hs_id} ({status:?})");
+                        return Some((priority, hs_id, Duration::ZERO, status, 0, false));
+                    }
+
+                    let t0 = Instant::now();
+                    let (status, events_processed, page_full) =
+                        self.run_processor(hs_id.clone()).await;
+                    let duration = t0.elapsed();
+                    Some((
+                        priority,
+                        hs_id,
+                        duration,
+                        status,
+                        events_processed,
+                        page_full,
+                    ))
+                }
+            })
+            .buffer_unordered(self.max_concurrent_homeservers().max(1))
+            .collect()
+            .await;
+        results.sort_unstable_by_key(|result| result.as_ref().map(|(priority, ..)| *priority));
 
         let mut run_stats = RunAllProcessorsStats::default();
 
-        for hs_id in hs_ids {
-            if *self.shutdown_rx().borrow() {
-                info!("Shutdown detected in homeserver {hs_id}, exiting run_all loop");
-                break; // Exit loop
-            }
-
-            // Skip homeservers that are in a backoff window
-            if backoff.should_skip(&hs_id) {
-                debug!("Skipping homeserver {hs_id} (in backoff)");
-                run_stats.add_run_result(
-                    hs_id,
-                    std::time::Duration::ZERO,
-                    ProcessorRunStatus::Skipped,
-                );
-                continue;
-            }
-
-            let t0 = Instant::now();
-            let status = match self.build(hs_id.clone()).await {
-                Ok(event_processor) => match event_processor.run().await {
-                    Ok(_) => ProcessorRunStatus::Ok,
-                    Err(RunError::Internal(_)) => ProcessorRunStatus::Error,
-                    Err(RunError::Panicked) => ProcessorRunStatus::Panic,
-                    Err(RunError::TimedOut) => ProcessorRunStatus::Timeout,
-                },
-                Err(e) => {
-                    error!("Failed to build event processor for homeserver: {hs_id}: {e}");
-                    ProcessorRunStatus::FailedToBuild
+        for (_, hs_id, duration, status, events_processed, page_full) in
+            results.into_iter().flatten()
+        {
+            if !matches!(
+                status,
+                ProcessorRunStatus::Skipped | ProcessorRunStatus::Idle
+            ) {
+                if status == ProcessorRunStatus::Ok {
+                    backoff.record_success(&hs_id);
+                    backoff.record_poll(&hs_id, events_processed, page_full);
+                } else {
+                    backoff.record_failure(&hs_id);
                 }
-            };
-            let duration = t0.elapsed();
-
-            if status == ProcessorRunStatus::Ok {
-                backoff.record_success(&hs_id);
-            } else {
-                backoff.record_failure(&hs_id);
+                self.record_status(&hs_id, &status, events_processed, backoff)
+                    .await;
             }
 
             run_stats.add_run_result(hs_id, duration, status);
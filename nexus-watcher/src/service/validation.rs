@@ -0,0 +1,298 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use nexus_common::models::event::{Event, EventType, ParseResult};
+use nexus_common::models::homeserver::{Homeserver, DEFAULT_CURSOR};
+use nexus_common::types::DynError;
+use pubky_app_specs::{PubkyAppObject, PubkyId};
+use tracing::{debug, info, warn};
+
+use crate::dispatcher::EventDispatcher;
+use crate::events::handlers::{universal_file, universal_tag};
+use crate::events::{fetch_blob, fetch_event_blob};
+use crate::service::processor_runner::EventProcessorRunner;
+
+/// An event line rejected by the dry run, with the reason
+#[derive(Debug, Clone)]
+pub struct InvalidEvent {
+    pub line: String,
+    pub reason: String,
+}
+
+/// Report of a dry run over the events of a homeserver, see [WatcherConfig::dry_run]
+///
+/// [WatcherConfig::dry_run]: nexus_common::WatcherConfig::dry_run
+#[derive(Debug, Default)]
+pub struct ValidationReport {
+    pub homeserver: String,
+    /// Cursor of the last page read, `None` if the homeserver had no new events
+    pub last_cursor: Option<String>,
+    /// Number of valid events by resource type. Events claimed by a domain plugin are
+    /// counted as `plugin`, events of resources Nexus does not index as `skipped`.
+    pub resources: BTreeMap<String, usize>,
+    /// Event lines that `Event::parse_event` could not parse
+    pub parse_failures: Vec<InvalidEvent>,
+    /// Event blobs rejected by the `pubky-app-specs` validation
+    pub spec_validation_errors: Vec<InvalidEvent>,
+    /// Event blobs that could not be fetched from the homeserver
+    pub fetch_failures: Vec<InvalidEvent>,
+}
+
+impl ValidationReport {
+    fn new(homeserver: &PubkyId) -> Self {
+        Self {
+            homeserver: homeserver.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn count(&mut self, resource: impl Into<String>) {
+        *self.resources.entry(resource.into()).or_default() += 1;
+    }
+
+    /// Number of events that passed the validation
+    pub fn valid_events(&self) -> usize {
+        self.resources.values().sum()
+    }
+
+    /// Logs the report, with one warning per rejected event
+    pub fn log(&self) {
+        let resources = self
+            .resources
+            .iter()
+            .map(|(resource, count)| format!("{resource}={count}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        info!(
+            "Dry run of HS {} up to cursor {}: {} valid events [{resources}], {} parse failures, {} spec validation errors, {} fetch failures",
+            self.homeserver,
+            self.last_cursor.as_deref().unwrap_or("none"),
+            self.valid_events(),
+            self.parse_failures.len(),
+            self.spec_validation_errors.len(),
+            self.fetch_failures.len(),
+        );
+
+        for (kind, events) in [
+            ("Parse failure", &self.parse_failures),
+            ("Spec validation error", &self.spec_validation_errors),
+            ("Fetch failure", &self.fetch_failures),
+        ] {
+            for event in events {
+                warn!("{kind}: {}: {}", event.line, event.reason);
+            }
+        }
+    }
+}
+
+/// Reads the events of a homeserver from its stored cursor up to the latest one and validates
+/// them without indexing, see [WatcherConfig::dry_run].
+///
+/// The stored cursor is only read: neither the index nor the graph is written.
+///
+/// [WatcherConfig::dry_run]: nexus_common::WatcherConfig::dry_run
+pub async fn validate_homeserver(
+    runner: &EventProcessorRunner,
+    homeserver_id: PubkyId,
+) -> Result<ValidationReport, DynError> {
+    let mut report = ValidationReport::new(&homeserver_id);
+    let mut cursor = stored_cursor(&homeserver_id).await?;
+
+    loop {
+        if *runner.shutdown_rx.borrow() {
+            info!("Shutdown detected, stopping the dry run of HS {homeserver_id}");
+            break;
+        }
+
+        let homeserver = Homeserver::try_from_cursor(homeserver_id.clone(), &cursor)?;
+        let processor = runner.build_processor(homeserver);
+        let Some(lines) = processor.poll_events().await? else {
+            break;
+        };
+
+        let mut next_cursor = None;
+        for line in lines {
+            match line.strip_prefix("cursor: ") {
+                Some(page_cursor) => next_cursor = Some(page_cursor.to_string()),
+                None => {
+                    let dispatcher = runner.dispatcher.as_deref();
+                    validate_line(dispatcher, &runner.files_path, line, &mut report).await
+                }
+            }
+        }
+
+        // A page without a new cursor is the last one
+        let Some(next_cursor) = next_cursor.filter(|next| *next != cursor) else {
+            break;
+        };
+        debug!("Dry run of HS {homeserver_id} reached cursor {next_cursor}");
+        report.last_cursor = Some(next_cursor.clone());
+        cursor = next_cursor;
+    }
+
+    Ok(report)
+}
+
+/// Cursor of the homeserver in the index, else in the graph checkpoint, without re-seeding the index
+async fn stored_cursor(homeserver_id: &PubkyId) -> Result<String, DynError> {
+    if let Some(hs) = Homeserver::get_from_index(homeserver_id).await? {
+        return Ok(hs.cursor);
+    }
+    let cursor = Homeserver::get_from_graph(homeserver_id)
+        .await?
+        .map(|hs| hs.cursor)
+        .unwrap_or_else(|| DEFAULT_CURSOR.to_string());
+    Ok(cursor)
+}
+
+/// Runs an event line through the same parsing and validation steps as the event processor,
+/// stopping short of the handlers
+async fn validate_line(
+    dispatcher: Option<&EventDispatcher>,
+    files_path: &Path,
+    line: String,
+    report: &mut ValidationReport,
+) {
+    if dispatcher.is_some_and(|dispatcher| dispatcher.matches(&line)) {
+        report.count("plugin");
+        return;
+    }
+
+    let event = match Event::parse_event(&line, files_path.to_path_buf()) {
+        Err(e) => {
+            let reason = e.to_string();
+            report.parse_failures.push(InvalidEvent { line, reason });
+            return;
+        }
+        Ok(ParseResult::Skipped) => return report.count("skipped"),
+        Ok(ParseResult::UnrecognizedUri {
+            event_type,
+            uri,
+            reason,
+        }) => return validate_universal_line(event_type, &uri, line, reason, report).await,
+        Ok(ParseResult::Parsed(event)) => event,
+    };
+
+    if matches!(event.event_type, EventType::Put) {
+        let blob = match fetch_event_blob(&event).await {
+            Ok(blob) => blob,
+            Err(e) => {
+                let reason = e.to_string();
+                report.fetch_failures.push(InvalidEvent { line, reason });
+                return;
+            }
+        };
+        if let Err(e) = PubkyAppObject::from_resource(&event.parsed_uri.resource, &blob) {
+            let reason = e.to_string();
+            report
+                .spec_validation_errors
+                .push(InvalidEvent { line, reason });
+            return;
+        }
+    }
+
+    report.count(event.parsed_uri.resource.to_string());
+}
+
+/// Validates an event line with a URI unknown to `pubky-app-specs` as the universal tag and
+/// file handlers would: PUT blobs must deserialize as a tag or a file. The URIs no universal
+/// handler claims are parse failures.
+async fn validate_universal_line(
+    event_type: EventType,
+    uri: &str,
+    line: String,
+    reason: String,
+    report: &mut ValidationReport,
+) {
+    let resource = if universal_tag::try_parse_app_tag_path(uri).is_some() {
+        "tag"
+    } else if universal_file::try_parse_app_file_path(uri).is_some() {
+        "file"
+    } else {
+        report.parse_failures.push(InvalidEvent { line, reason });
+        return;
+    };
+
+    if matches!(event_type, EventType::Put) {
+        let blob = match fetch_blob(uri).await {
+            Ok(blob) => blob,
+            Err(e) => {
+                let reason = e.to_string();
+                report.fetch_failures.push(InvalidEvent { line, reason });
+                return;
+            }
+        };
+        let parsed = match resource {
+            "tag" => universal_tag::parse_blob(uri, &blob).map(|_| ()),
+            _ => universal_file::parse_blob(uri, &blob).map(|_| ()),
+        };
+        if let Err(e) = parsed {
+            let reason = e.to_string();
+            report
+                .spec_validation_errors
+                .push(InvalidEvent { line, reason });
+            return;
+        }
+    }
+
+    report.count(resource);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USER: &str = "4snwyct86m383rsduhw5xgcxpw7c63j3pq8x4ycqikxgik8y64ro";
+
+    async fn validate(lines: &[String]) -> ValidationReport {
+        let mut report = ValidationReport::default();
+        for line in lines {
+            validate_line(None, Path::new("/tmp"), line.clone(), &mut report).await;
+        }
+        report
+    }
+
+    #[tokio::test]
+    async fn test_validate_valid_lines() {
+        let report = validate(&[
+            format!("DEL pubky://{USER}/pub/pubky.app/posts/0034A0X7NJ52A"),
+            format!("DEL pubky://{USER}/pub/mapky/tags/ABC123"),
+            format!("DEL pubky://{USER}/pub/mapky/files/0034A0X7NJ52A"),
+        ])
+        .await;
+
+        assert_eq!(report.valid_events(), 3);
+        assert_eq!(report.resources.get("tag"), Some(&1));
+        assert_eq!(report.resources.get("file"), Some(&1));
+        assert!(report.parse_failures.is_empty());
+        assert!(report.spec_validation_errors.is_empty());
+        assert!(report.fetch_failures.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_validate_invalid_lines() {
+        let report = validate(&[
+            String::from("malformed"),
+            format!("PATCH pubky://{USER}/pub/pubky.app/posts/0034A0X7NJ52A"),
+            format!("DEL pubky://{USER}/pub/pubky.app/unknown/0034A0X7NJ52A"),
+        ])
+        .await;
+
+        assert_eq!(report.valid_events(), 0);
+        assert_eq!(report.parse_failures.len(), 3);
+        assert_eq!(report.parse_failures[0].line, "malformed");
+    }
+
+    #[tokio::test]
+    async fn test_validate_unknown_lines() {
+        // Resources Nexus does not index are valid, but only counted as skipped
+        let report = validate(&[format!(
+            "PUT pubky://{USER}/pub/pubky.app/blobs/0034A0X7NJ52A"
+        )])
+        .await;
+
+        assert_eq!(report.resources.get("skipped"), Some(&1));
+        assert!(report.parse_failures.is_empty());
+        assert!(report.fetch_failures.is_empty());
+    }
+}
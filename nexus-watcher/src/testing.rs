@@ -5,7 +5,7 @@ use nexus_common::db::PubkyConnector;
 use nexus_common::get_files_dir_pathbuf;
 use nexus_common::models::event::{Event, EventProcessorError, ParseResult};
 use nexus_common::models::file::FileDetails;
-use nexus_common::models::homeserver::Homeserver;
+use nexus_common::models::homeserver::{Homeserver, HomeserverPolicy};
 use nexus_common::models::traits::Collection;
 use nexus_common::plugin::NexusPlugin;
 use nexus_common::{StackConfig, StackManager};
@@ -26,6 +26,7 @@ use tracing::debug;
 
 use crate::dispatcher::EventDispatcher;
 use crate::events::retry::event::RetryEvent;
+use crate::events::retry::processor::RetryProcessor;
 use crate::events::{handle, Moderation};
 use crate::service::{EventProcessorRunner, TEventProcessorRunner};
 
@@ -65,20 +66,36 @@ impl WatcherTest {
     fn create_test_event_processor_runner(
         default_homeserver: PubkyId,
         files_path: PathBuf,
+        dispatcher: Option<Arc<EventDispatcher>>,
     ) -> EventProcessorRunner {
         let moderation = Arc::new(default_moderation_tests());
 
         let (_shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
 
+        let retry_processor = Arc::new(RetryProcessor {
+            batch_size: 100,
+            max_attempts: 10,
+            files_path: files_path.clone(),
+            moderation: moderation.clone(),
+            shutdown_rx: shutdown_rx.clone(),
+            dispatcher: dispatcher.clone(),
+            event_log: None,
+        });
+
         EventProcessorRunner {
             limit: 1000,
             monitored_homeservers_limit: 100,
+            max_concurrent_homeservers: 1,
             files_path,
             tracer_name: "test".to_string(),
             moderation,
             shutdown_rx,
             default_homeserver,
-            dispatcher: None,
+            dispatcher,
+            retry_processor,
+            event_log: None,
+            leases: None,
+            homeserver_policy: HomeserverPolicy::default(),
         }
     }
 
@@ -110,11 +127,9 @@ impl WatcherTest {
             Err(e) => panic!("WatcherTest: PubkyConnector initialization failed: {}", e),
         }
 
-        let mut event_processor_runner =
-            Self::create_test_event_processor_runner(homeserver_id.clone(), files_path);
-        if !plugins.is_empty() {
-            event_processor_runner.dispatcher = Some(Arc::new(EventDispatcher::new(plugins)));
-        }
+        let dispatcher = EventDispatcher::from_plugins(plugins);
+        let event_processor_runner =
+            Self::create_test_event_processor_runner(homeserver_id.clone(), files_path, dispatcher);
 
         Ok(Self {
             testnet,
@@ -125,6 +140,11 @@ impl WatcherTest {
         })
     }
 
+    /// The [RetryProcessor] shared with the event processors of this harness.
+    pub fn retry_processor(&self) -> Arc<RetryProcessor> {
+        self.event_processor_runner.retry_processor.clone()
+    }
+
     /// Disables automatic event processing after each write.
     pub async fn remove_event_processing(mut self) -> Self {
         self.ensure_event_processing = false;
@@ -0,0 +1,110 @@
+use crate::event_processor::utils::watcher::{HomeserverHashIdPath, WatcherTest};
+use anyhow::Result;
+use nexus_common::models::feed::FeedDetails;
+use pubky::Keypair;
+use pubky_app_specs::traits::HashId;
+use pubky_app_specs::{
+    PubkyAppFeed, PubkyAppFeedLayout, PubkyAppFeedReach, PubkyAppFeedSort, PubkyAppPostKind,
+    PubkyAppUser,
+};
+
+#[tokio_shared_rt::test(shared)]
+async fn test_homeserver_put_del_feed() -> Result<()> {
+    let mut test = WatcherTest::setup().await?;
+
+    let user_kp = Keypair::random();
+    let user = PubkyAppUser {
+        bio: Some("test_homeserver_put_del_feed".to_string()),
+        image: None,
+        links: None,
+        name: "Watcher:Feed:User".to_string(),
+        status: None,
+    };
+    let user_id = test.create_user(&user_kp, &user).await?;
+
+    let feed = PubkyAppFeed::new(
+        Some(vec!["rust".to_string(), "pubky".to_string()]),
+        PubkyAppFeedReach::Following,
+        PubkyAppFeedLayout::Columns,
+        PubkyAppFeedSort::Popularity,
+        Some(PubkyAppPostKind::Long),
+        "Watcher:Feed".to_string(),
+    );
+    let feed_id = feed.create_id();
+    let feed_path = feed.hs_path();
+    test.put(&user_kp, &feed_path, &feed).await?;
+
+    // GRAPH_OP
+    let graph_feeds = FeedDetails::get_from_graph(&user_id, Some(&feed_id)).await?;
+    assert_eq!(graph_feeds.len(), 1);
+    let graph_feed = &graph_feeds[0];
+    assert_eq!(graph_feed.name, feed.name);
+    assert_eq!(graph_feed.tags, feed.feed.tags);
+    assert_eq!(graph_feed.reach, PubkyAppFeedReach::Following);
+    assert_eq!(graph_feed.sort, PubkyAppFeedSort::Popularity);
+    assert_eq!(graph_feed.content, Some(PubkyAppPostKind::Long));
+    assert_eq!(graph_feed.created_at, feed.created_at);
+
+    // INDEX_OP
+    let indexed_feed = FeedDetails::get_by_id(&user_id, &feed_id)
+        .await?
+        .expect("The feed should be indexed");
+    assert_eq!(&indexed_feed, graph_feed);
+    let user_feeds = FeedDetails::get_by_owner(&user_id, None, None).await?;
+    assert_eq!(user_feeds, vec![indexed_feed]);
+
+    test.del(&user_kp, &feed_path).await?;
+
+    assert!(FeedDetails::get_from_graph(&user_id, Some(&feed_id))
+        .await?
+        .is_empty());
+    assert!(FeedDetails::get_by_id(&user_id, &feed_id).await?.is_none());
+    assert!(FeedDetails::get_by_owner(&user_id, None, None)
+        .await?
+        .is_empty());
+
+    Ok(())
+}
+
+#[tokio_shared_rt::test(shared)]
+async fn test_homeserver_del_user_deletes_feeds() -> Result<()> {
+    let mut test = WatcherTest::setup().await?;
+
+    let user_kp = Keypair::random();
+    let user = PubkyAppUser {
+        bio: Some("test_homeserver_del_user_deletes_feeds".to_string()),
+        image: None,
+        links: None,
+        name: "Watcher:Feed:DeletedUser".to_string(),
+        status: None,
+    };
+    let user_id = test.create_user(&user_kp, &user).await?;
+
+    let feed = PubkyAppFeed::new(
+        None,
+        PubkyAppFeedReach::All,
+        PubkyAppFeedLayout::Columns,
+        PubkyAppFeedSort::Recent,
+        None,
+        "Watcher:Feed:DeletedUser".to_string(),
+    );
+    let feed_id = feed.create_id();
+    test.put(&user_kp, &feed.hs_path(), &feed).await?;
+    assert_eq!(
+        FeedDetails::get_by_owner(&user_id, None, None).await?.len(),
+        1
+    );
+
+    // The feed is left on the homeserver, only the profile is deleted
+    test.cleanup_user(&user_kp).await?;
+
+    assert!(FeedDetails::get_from_graph(&user_id, None)
+        .await?
+        .is_empty());
+    assert!(FeedDetails::get_by_id(&user_id, &feed_id).await?.is_none());
+    assert!(FeedDetails::get_by_owner(&user_id, None, None)
+        .await?
+        .is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use crate::event_processor::utils::watcher::WatcherTest;
+use anyhow::Result;
+use chrono::Utc;
+use nexus_common::models::homeserver::Homeserver;
+use nexus_common::models::post::PostDetails;
+use nexus_watcher::service::validate_homeserver;
+use pubky::{Keypair, ResourcePath};
+use pubky_app_specs::traits::{HasIdPath, HashId};
+use pubky_app_specs::{PubkyAppPost, PubkyAppPostKind, PubkyAppTag, PubkyAppUser};
+
+#[tokio_shared_rt::test(shared)]
+async fn test_dry_run_validates_without_indexing() -> Result<()> {
+    let mut test = WatcherTest::setup().await?;
+
+    let user_kp = Keypair::random();
+    let user = PubkyAppUser {
+        bio: Some("test_dry_run".to_string()),
+        image: None,
+        links: None,
+        name: "Watcher:DryRun".to_string(),
+        status: None,
+    };
+    let user_id = test.create_user(&user_kp, &user).await?;
+    let cursor = Homeserver::get_from_index(&test.homeserver_id)
+        .await?
+        .expect("Homeserver should be indexed")
+        .cursor;
+
+    // The events written from now on are only read by the dry run
+    let mut test = test.remove_event_processing().await;
+
+    let post = PubkyAppPost {
+        content: "Watcher:DryRun:Post".to_string(),
+        kind: PubkyAppPostKind::Short,
+        parent: None,
+        embed: None,
+        attachments: None,
+    };
+    let (post_id, _) = test.create_post(&user_kp, &post).await?;
+
+    let tag = PubkyAppTag {
+        uri: "https://example.com/dry-run".to_string(),
+        label: "dryrun".to_string(),
+        created_at: Utc::now().timestamp_millis(),
+    };
+    let tag_path: ResourcePath = format!("/pub/mapky/tags/{}", tag.create_id()).parse()?;
+    test.put(&user_kp, &tag_path, &tag).await?;
+
+    // A post blob in a universal tag path
+    let invalid_tag_path: ResourcePath = "/pub/mapky/tags/INVALID".parse()?;
+    test.put(&user_kp, &invalid_tag_path, &post).await?;
+
+    // A user blob where a post is expected
+    let invalid_post_path: ResourcePath = PubkyAppPost::create_path("0034A0X7NJ52A").parse()?;
+    test.put(&user_kp, &invalid_post_path, &user).await?;
+
+    let report =
+        validate_homeserver(&test.event_processor_runner, test.homeserver_id.clone()).await?;
+
+    assert_eq!(report.homeserver, test.homeserver_id.to_string());
+    assert_eq!(report.resources.get("posts"), Some(&1));
+    assert_eq!(report.resources.get("tag"), Some(&1));
+    assert_eq!(report.valid_events(), 2);
+    assert!(report.parse_failures.is_empty());
+    assert!(report.fetch_failures.is_empty());
+
+    let mut rejected: Vec<_> = report
+        .spec_validation_errors
+        .iter()
+        .map(|invalid| invalid.line.clone())
+        .collect();
+    rejected.sort();
+    let mut expected = vec![
+        format!("PUT pubky://{user_id}{invalid_post_path}"),
+        format!("PUT pubky://{user_id}{invalid_tag_path}"),
+    ];
+    expected.sort();
+    assert_eq!(rejected, expected);
+
+    // Neither the index nor the graph is written, and the stored cursor does not move
+    assert!(PostDetails::get_by_id(&user_id, &post_id).await?.is_none());
+    let stored = Homeserver::get_from_index(&test.homeserver_id)
+        .await?
+        .expect("Homeserver should be indexed");
+    assert_eq!(stored.cursor, cursor);
+
+    Ok(())
+}
@@ -1,4 +1,6 @@
+mod dry_run;
 mod ingest_homeservers_from_follow_events;
 mod ingest_homeservers_from_post_events;
 mod ingest_homeservers_from_tag_events;
+mod user_migration;
 mod utils;
@@ -0,0 +1,64 @@
+use crate::event_processor::{
+    homeserver::utils::create_external_test_homeserver, utils::watcher::WatcherTest,
+};
+use anyhow::Result;
+use nexus_common::models::homeserver::Homeserver;
+use nexus_common::models::user::UserHomeserver;
+use nexus_watcher::service::{MigrationCheck, UserCheck};
+use pubky::Keypair;
+use pubky_app_specs::{PubkyAppUser, PubkyId};
+
+#[tokio_shared_rt::test(shared)]
+async fn test_user_migration_to_new_homeserver() -> Result<()> {
+    let mut test = WatcherTest::setup().await?;
+
+    let user_kp = Keypair::random();
+    let user = PubkyAppUser {
+        bio: Some("test_user_migration_to_new_homeserver".to_string()),
+        image: None,
+        links: None,
+        name: "Watcher:Homeserver:Migration".to_string(),
+        status: None,
+    };
+    let user_id = PubkyId::try_from(test.create_user(&user_kp, &user).await?.as_str()).unwrap();
+
+    // The first check records the homeserver the user signed up on
+    let check = MigrationCheck::check_user(&user_id, None).await.unwrap();
+    assert_eq!(check, UserCheck::Hosted);
+    let hosted_on = UserHomeserver::get_by_id(&user_id).await?.unwrap();
+    assert_eq!(hosted_on.homeserver_id, test.homeserver_id.to_string());
+
+    let check = MigrationCheck::check_user(&user_id, Some(hosted_on.homeserver_id))
+        .await
+        .unwrap();
+    assert_eq!(check, UserCheck::Unchanged);
+
+    // The user moves to a new homeserver
+    let new_hs_pk = create_external_test_homeserver(&mut test).await?;
+    let new_hs_id = PubkyId::try_from(&new_hs_pk.to_z32()).unwrap();
+    test.register_user_in_hs(&user_kp, &new_hs_pk).await?;
+
+    let check = MigrationCheck::check_user(&user_id, Some(test.homeserver_id.to_string()))
+        .await
+        .unwrap();
+    assert_eq!(check, UserCheck::Migrated);
+    let hosted_on = UserHomeserver::get_by_id(&user_id).await?.unwrap();
+    assert_eq!(hosted_on.homeserver_id, new_hs_id.to_string());
+    assert!(Homeserver::get_by_id(new_hs_id).await.unwrap().is_some());
+
+    // The events of the user polled from the previous homeserver are ignored
+    let processor = test
+        .event_processor_runner
+        .build_processor(Homeserver::new(test.homeserver_id.clone()));
+    processor
+        .process_event_lines(vec![format!(
+            "PUT pubky://{user_id}/pub/pubky.app/profile.json"
+        )])
+        .await?;
+    let outcomes = processor.outcomes.snapshot();
+    let skipped: usize = outcomes.values().map(|outcome| outcome.skipped).sum();
+    let indexed: usize = outcomes.values().map(|outcome| outcome.indexed).sum();
+    assert_eq!((skipped, indexed), (1, 0));
+
+    Ok(())
+}
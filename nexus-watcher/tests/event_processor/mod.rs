@@ -1,4 +1,5 @@
 mod bookmarks;
+mod feeds;
 mod files;
 mod follows;
 mod homeserver;
@@ -0,0 +1,71 @@
+use crate::event_processor::utils::watcher::{HomeserverHashIdPath, WatcherTest};
+use anyhow::Result;
+use nexus_common::{
+    models::notification::{Notification, NotificationPreferences, PostChangedSource},
+    types::Pagination,
+};
+use pubky::Keypair;
+use pubky_app_specs::{
+    post_uri_builder, PubkyAppBookmark, PubkyAppPost, PubkyAppPostKind, PubkyAppUser,
+};
+
+#[tokio_shared_rt::test(shared)]
+async fn test_edit_bookmarked_post_notification_muted() -> Result<()> {
+    let mut test = WatcherTest::setup().await?;
+
+    let author_kp = Keypair::random();
+    let author = PubkyAppUser {
+        bio: Some("test_edit_bookmarked_post_notification_muted".to_string()),
+        image: None,
+        links: None,
+        name: "Watcher:MutedPostEditNotification:Author".to_string(),
+        status: None,
+    };
+    let author_id = test.create_user(&author_kp, &author).await?;
+
+    let mut post = PubkyAppPost {
+        content: "Original post".to_string(),
+        kind: PubkyAppPostKind::Short,
+        parent: None,
+        embed: None,
+        attachments: None,
+    };
+    let (post_id, post_path) = test.create_post(&author_kp, &post).await?;
+
+    // Two users bookmark the post, only the first one muted the edits of bookmarked posts
+    let mut bookmarker_ids = Vec::new();
+    for name in ["Muting", "Notified"] {
+        let bookmarker_kp = Keypair::random();
+        let bookmarker = PubkyAppUser {
+            bio: Some("test_edit_bookmarked_post_notification_muted".to_string()),
+            image: None,
+            links: None,
+            name: format!("Watcher:MutedPostEditNotification:{name}"),
+            status: None,
+        };
+        let bookmarker_id = test.create_user(&bookmarker_kp, &bookmarker).await?;
+        let bookmark = PubkyAppBookmark {
+            uri: post_uri_builder(author_id.clone(), post_id.clone()),
+            created_at: 0,
+        };
+        test.put(&bookmarker_kp, &bookmark.hs_path(), bookmark)
+            .await?;
+        bookmarker_ids.push(bookmarker_id);
+    }
+    NotificationPreferences {
+        muted_edit_sources: vec![PostChangedSource::Bookmark],
+        ..Default::default()
+    }
+    .put_to_index(&bookmarker_ids[0])
+    .await?;
+
+    post.content = "Edited post".to_string();
+    test.put(&author_kp, &post_path, &post).await?;
+
+    let muting = Notification::get_by_id(&bookmarker_ids[0], Pagination::default()).await?;
+    assert!(muting.is_empty(), "The muted edit should not be notified");
+    let notified = Notification::get_by_id(&bookmarker_ids[1], Pagination::default()).await?;
+    assert_eq!(notified.len(), 1, "The edit should be notified");
+
+    Ok(())
+}
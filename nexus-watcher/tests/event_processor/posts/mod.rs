@@ -11,6 +11,7 @@ mod del_with_attachments;
 mod del_with_relations;
 mod del_without_relations;
 mod edit_bookmarked_notification;
+mod edit_muted_notification;
 mod edit_reply_parent_notification;
 mod edit_reposted_notification;
 mod edit_tagged_notification;
@@ -29,7 +30,9 @@ mod reply_repost;
 mod repost;
 mod repost_notification;
 mod retry_all;
+mod retry_dependency;
 mod retry_post;
+mod retry_replay;
 mod retry_reply;
 mod retry_repost;
 pub mod utils;
@@ -0,0 +1,169 @@
+use super::utils::find_post_details;
+use crate::event_processor::utils::watcher::{
+    assert_eventually_exists, generate_post_id, WatcherTest,
+};
+use anyhow::Result;
+use nexus_common::models::event::{EventProcessorError, EventType};
+use nexus_watcher::events::retry::event::RetryEvent;
+use pubky::{Keypair, ResourcePath};
+use pubky_app_specs::traits::HasIdPath;
+use pubky_app_specs::{post_uri_builder, PubkyAppPost, PubkyAppPostKind, PubkyAppUser};
+
+/// A chain of replies written before its root post is indexed as soon as the root post is,
+/// without waiting for the retry processor schedule
+#[tokio_shared_rt::test(shared)]
+async fn test_retry_dependents_when_parent_is_indexed() -> Result<()> {
+    let mut test = WatcherTest::setup().await?;
+
+    let user_kp = Keypair::random();
+    let user = PubkyAppUser {
+        bio: Some("test_retry_dependents_when_parent_is_indexed".to_string()),
+        image: None,
+        links: None,
+        name: "Watcher:Retry:Dependency:User".to_string(),
+        status: None,
+    };
+    let user_id = test.create_user(&user_kp, &user).await?;
+
+    // Reserve the root post ID, but write the replies first
+    let parent_id = generate_post_id();
+    let reply = PubkyAppPost {
+        content: "Watcher:Retry:Dependency:User:Reply".to_string(),
+        kind: PubkyAppPostKind::Short,
+        parent: Some(post_uri_builder(user_id.clone(), parent_id.clone())),
+        embed: None,
+        attachments: None,
+    };
+    let (reply_id, _) = test.create_post(&user_kp, &reply).await?;
+
+    let nested_reply = PubkyAppPost {
+        content: "Watcher:Retry:Dependency:User:NestedReply".to_string(),
+        kind: PubkyAppPostKind::Short,
+        parent: Some(post_uri_builder(user_id.clone(), reply_id.clone())),
+        embed: None,
+        attachments: None,
+    };
+    let (nested_reply_id, _) = test.create_post(&user_kp, &nested_reply).await?;
+
+    let index_keys: Vec<String> = [&reply_id, &nested_reply_id]
+        .iter()
+        .map(|post_id| {
+            let uri = post_uri_builder(user_id.clone(), post_id.to_string());
+            format!(
+                "{}:{}",
+                EventType::Put,
+                RetryEvent::generate_index_key(&uri).unwrap()
+            )
+        })
+        .collect();
+    for index_key in &index_keys {
+        assert_eventually_exists(index_key).await;
+    }
+
+    // Indexing the root post releases the reply, which in turn releases the nested reply
+    let parent = PubkyAppPost {
+        content: "Watcher:Retry:Dependency:User:Parent".to_string(),
+        kind: PubkyAppPostKind::Short,
+        parent: None,
+        embed: None,
+        attachments: None,
+    };
+    let parent_path: ResourcePath = PubkyAppPost::create_path(&parent_id).parse()?;
+    test.put(&user_kp, &parent_path, parent).await?;
+
+    for index_key in &index_keys {
+        assert!(RetryEvent::check_uri(index_key).await?.is_none());
+        assert!(RetryEvent::get_from_index(index_key).await?.is_none());
+    }
+
+    let reply_details = find_post_details(&user_id, &reply_id).await?;
+    assert_eq!(reply_details.id, reply_id);
+    let nested_reply_details = find_post_details(&user_id, &nested_reply_id).await?;
+    assert_eq!(nested_reply_details.id, nested_reply_id);
+
+    Ok(())
+}
+
+/// Dependents are released in the order they started waiting, so they are replayed oldest first
+#[tokio_shared_rt::test(shared)]
+async fn test_take_dependents_oldest_first() -> Result<()> {
+    WatcherTest::setup().await?;
+
+    let user_id = Keypair::random().public_key().to_z32();
+    let parent_uri = post_uri_builder(user_id.clone(), generate_post_id());
+    let dependency_key = RetryEvent::generate_index_key(&parent_uri).unwrap();
+
+    let mut index_keys = Vec::new();
+    for _ in 0..5 {
+        let uri = post_uri_builder(user_id.clone(), generate_post_id());
+        let index_key = format!(
+            "{}:{}",
+            EventType::Put,
+            RetryEvent::generate_index_key(&uri).unwrap()
+        );
+        let error = EventProcessorError::MissingDependency {
+            dependency: vec![dependency_key.clone()],
+        };
+        RetryEvent::new(error, format!("PUT {uri}"), "homeserver")
+            .put_to_index(index_key.clone())
+            .await?;
+        index_keys.push(index_key);
+        // Dependents are scored by the time (ms) they started waiting
+        tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+    }
+
+    assert_eq!(
+        RetryEvent::take_dependents(&dependency_key).await?,
+        index_keys
+    );
+
+    for index_key in &index_keys {
+        RetryEvent::remove_from_index(index_key).await?;
+    }
+    Ok(())
+}
+
+/// Processors releasing the same dependency at once never take the same dependents, so no
+/// event is replayed twice
+#[tokio_shared_rt::test(shared)]
+async fn test_take_dependents_is_atomic() -> Result<()> {
+    WatcherTest::setup().await?;
+
+    let user_id = Keypair::random().public_key().to_z32();
+    let parent_uri = post_uri_builder(user_id.clone(), generate_post_id());
+    let dependency_key = RetryEvent::generate_index_key(&parent_uri).unwrap();
+
+    let mut index_keys = Vec::new();
+    for _ in 0..20 {
+        let uri = post_uri_builder(user_id.clone(), generate_post_id());
+        let index_key = format!(
+            "{}:{}",
+            EventType::Put,
+            RetryEvent::generate_index_key(&uri).unwrap()
+        );
+        let error = EventProcessorError::MissingDependency {
+            dependency: vec![dependency_key.clone()],
+        };
+        RetryEvent::new(error, format!("PUT {uri}"), "homeserver")
+            .put_to_index(index_key.clone())
+            .await?;
+        index_keys.push(index_key);
+    }
+
+    let (first, second) = tokio::join!(
+        RetryEvent::take_dependents(&dependency_key),
+        RetryEvent::take_dependents(&dependency_key)
+    );
+    let mut taken = [first?, second?].concat();
+    taken.sort();
+    index_keys.sort();
+    assert_eq!(taken, index_keys);
+    assert!(RetryEvent::take_dependents(&dependency_key)
+        .await?
+        .is_empty());
+
+    for index_key in &index_keys {
+        RetryEvent::remove_from_index(index_key).await?;
+    }
+    Ok(())
+}
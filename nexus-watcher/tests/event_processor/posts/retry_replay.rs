@@ -0,0 +1,72 @@
+use super::utils::find_post_details;
+use crate::event_processor::utils::watcher::{
+    assert_eventually_exists, generate_post_id, WatcherTest,
+};
+use anyhow::Result;
+use nexus_common::models::event::EventType;
+use nexus_watcher::events::retry::event::RetryEvent;
+use pubky::{Keypair, ResourcePath};
+use pubky_app_specs::traits::HasIdPath;
+use pubky_app_specs::{post_uri_builder, PubkyAppPost, PubkyAppPostKind, PubkyAppUser};
+
+/// A reply indexed before its parent is replayed by the retry processor once the parent exists
+#[tokio_shared_rt::test(shared)]
+async fn test_retry_processor_indexes_reply_after_parent() -> Result<()> {
+    let mut test = WatcherTest::setup().await?;
+
+    let user_kp = Keypair::random();
+    let user = PubkyAppUser {
+        bio: Some("test_retry_processor_indexes_reply_after_parent".to_string()),
+        image: None,
+        links: None,
+        name: "Watcher:Retry:Replay:User".to_string(),
+        status: None,
+    };
+    let user_id = test.create_user(&user_kp, &user).await?;
+
+    // Reserve the parent post ID, but write the reply first
+    let parent_id = generate_post_id();
+    let parent_uri = post_uri_builder(user_id.clone(), parent_id.clone());
+
+    let reply = PubkyAppPost {
+        content: "Watcher:Retry:Replay:User:Reply".to_string(),
+        kind: PubkyAppPostKind::Short,
+        parent: Some(parent_uri),
+        embed: None,
+        attachments: None,
+    };
+    let (reply_id, _) = test.create_post(&user_kp, &reply).await?;
+
+    let index_key = format!(
+        "{}:{}",
+        EventType::Put,
+        RetryEvent::generate_index_key(&post_uri_builder(user_id.clone(), reply_id.clone()))
+            .unwrap()
+    );
+    assert_eventually_exists(&index_key).await;
+
+    // Replaying before the parent exists keeps the event queued with an increased retry count
+    test.retry_processor().retry(&index_key).await?;
+    let retry_event = RetryEvent::get_from_index(&index_key).await?.unwrap();
+    assert_eq!(retry_event.retry_count, 1);
+
+    let parent = PubkyAppPost {
+        content: "Watcher:Retry:Replay:User:Parent".to_string(),
+        kind: PubkyAppPostKind::Short,
+        parent: None,
+        embed: None,
+        attachments: None,
+    };
+    let parent_path: ResourcePath = PubkyAppPost::create_path(&parent_id).parse()?;
+    test.put(&user_kp, &parent_path, parent).await?;
+
+    // Once the parent is indexed, the replay succeeds and the event leaves the queue
+    test.retry_processor().retry(&index_key).await?;
+    assert!(RetryEvent::check_uri(&index_key).await?.is_none());
+    assert!(RetryEvent::get_from_index(&index_key).await?.is_none());
+
+    let reply_details = find_post_details(&user_id, &reply_id).await?;
+    assert_eq!(reply_details.id, reply_id);
+
+    Ok(())
+}
@@ -0,0 +1,151 @@
+use crate::event_processor::utils::watcher::{HomeserverHashIdPath, WatcherTest};
+use anyhow::Result;
+use chrono::Utc;
+use nexus_common::models::event::{EntityChange, EntityType, EventType};
+use pubky::{recovery_file, Keypair, ResourcePath};
+use pubky_app_specs::traits::HashId;
+use pubky_app_specs::{PubkyAppTag, PubkyAppUser};
+use tokio::fs;
+
+#[tokio_shared_rt::test(shared)]
+async fn test_homeserver_entity_changes() -> Result<()> {
+    let mut test = WatcherTest::setup().await?;
+
+    // Only read the changes appended from now on, other tests share the stream
+    let user_kp = Keypair::random();
+    let group = format!("test:{}", user_kp.public_key().to_z32());
+    let consumer = "consumer";
+    assert!(EntityChange::create_group(&group, false).await?);
+    assert!(!EntityChange::create_group(&group, false).await?);
+
+    let user = PubkyAppUser {
+        bio: Some("test_homeserver_entity_changes".to_string()),
+        image: None,
+        links: None,
+        name: "Watcher:EntityChanges:User".to_string(),
+        status: None,
+    };
+    let user_id = test.create_user(&user_kp, &user).await?;
+    test.cleanup_user(&user_kp).await?;
+
+    let changes = EntityChange::read_group(&group, consumer, 1000, None).await?;
+    let user_changes = changes
+        .iter()
+        .filter(|(_, change)| change.user_id == user_id)
+        .collect::<Vec<_>>();
+    assert_eq!(user_changes.len(), 2);
+    for (_, change) in &user_changes {
+        assert_eq!(change.entity, EntityType::User);
+        assert_eq!(change.id, user_id);
+    }
+    assert_eq!(user_changes[0].1.operation, EventType::Put);
+    assert_eq!(user_changes[1].1.operation, EventType::Del);
+
+    // Delivered changes stay pending until acknowledged
+    let pending = EntityChange::read_pending(&group, consumer, 1000).await?;
+    assert_eq!(pending.len(), changes.len());
+    let ids = changes
+        .iter()
+        .map(|(id, _)| id.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(EntityChange::ack(&group, &ids).await?, ids.len());
+    assert!(EntityChange::read_pending(&group, consumer, 1000)
+        .await?
+        .is_empty());
+
+    Ok(())
+}
+
+#[tokio_shared_rt::test(shared)]
+async fn test_homeserver_moderation_tag_appends_the_deletion() -> Result<()> {
+    let mut test = WatcherTest::setup().await?;
+
+    let user_kp = Keypair::random();
+    let group = format!("test:{}", user_kp.public_key().to_z32());
+    assert!(EntityChange::create_group(&group, false).await?);
+
+    let user = PubkyAppUser {
+        bio: Some("test_homeserver_moderation_tag_appends_the_deletion".to_string()),
+        image: None,
+        links: None,
+        name: "Watcher:EntityChanges:Moderated".to_string(),
+        status: None,
+    };
+    let user_id = test.create_user(&user_kp, &user).await?;
+
+    let mod_file = fs::read("./tests/event_processor/utils/moderator_key.pkarr").await?;
+    let mod_kp = recovery_file::decrypt_recovery_file(&mod_file, "password")?;
+    let moderator_id = test.create_user(&mod_kp, &user).await?;
+
+    // The moderation tag deletes the user instead of being indexed
+    let tag = PubkyAppTag {
+        uri: format!("pubky://{user_id}/pub/pubky.app/profile.json"),
+        label: "label_to_moderate".to_string(),
+        created_at: Utc::now().timestamp_millis(),
+    };
+    let tag_path = tag.hs_path();
+    let moderated_uri = tag.uri.clone();
+    test.put(&mod_kp, &tag_path, tag).await?;
+
+    // Consumers learn the user was deleted, not that the moderator tagged it
+    let tag_uri = format!("pubky://{moderator_id}{tag_path}");
+    let changes = EntityChange::read_group(&group, "consumer", 1000, None).await?;
+    assert!(changes.iter().all(|(_, change)| change.uri != tag_uri));
+    let deletion = changes
+        .iter()
+        .map(|(_, change)| change)
+        .find(|change| change.user_id == user_id && change.operation == EventType::Del)
+        .expect("The deletion of the moderated user should be appended");
+    assert_eq!(deletion.entity, EntityType::User);
+    assert_eq!(deletion.id, user_id);
+    assert_eq!(deletion.uri, moderated_uri);
+
+    Ok(())
+}
+
+#[tokio_shared_rt::test(shared)]
+async fn test_homeserver_universal_tag_changes() -> Result<()> {
+    let mut test = WatcherTest::setup().await?;
+
+    let user_kp = Keypair::random();
+    let group = format!("test:{}", user_kp.public_key().to_z32());
+    assert!(EntityChange::create_group(&group, false).await?);
+
+    let user = PubkyAppUser {
+        bio: Some("test_homeserver_universal_tag_changes".to_string()),
+        image: None,
+        links: None,
+        name: "Watcher:EntityChanges:UniversalTag".to_string(),
+        status: None,
+    };
+    let user_id = test.create_user(&user_kp, &user).await?;
+
+    let tag = PubkyAppTag {
+        uri: "https://example.com/entity-changes".to_string(),
+        label: "changes".to_string(),
+        created_at: Utc::now().timestamp_millis(),
+    };
+    let tag_id = tag.create_id();
+    let tag_path: ResourcePath = format!("/pub/mapky/tags/{tag_id}").parse()?;
+    test.put(&user_kp, &tag_path, &tag).await?;
+    test.del(&user_kp, &tag_path).await?;
+
+    let changes = EntityChange::read_group(&group, "consumer", 1000, None).await?;
+    let tag_changes = changes
+        .iter()
+        .map(|(_, change)| change)
+        .filter(|change| change.user_id == user_id && change.entity == EntityType::Tag)
+        .collect::<Vec<_>>();
+    assert_eq!(tag_changes.len(), 2);
+    for change in &tag_changes {
+        assert_eq!(change.id, tag_id);
+        assert_eq!(
+            change.uri,
+            format!("pubky://{user_id}/pub/mapky/tags/{tag_id}")
+        );
+    }
+    assert_eq!(tag_changes[0].operation, EventType::Put);
+    assert_eq!(tag_changes[1].operation, EventType::Del);
+
+    Ok(())
+}
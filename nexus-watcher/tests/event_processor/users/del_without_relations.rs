@@ -2,9 +2,10 @@ use crate::{
     event_processor::users::utils::find_user_details, event_processor::utils::watcher::WatcherTest,
 };
 use anyhow::Result;
+use nexus_common::models::notification::{LastRead, NotificationPreferences, NotificationType};
 use nexus_common::models::user::{UserCounts, UserSearch, UserView};
 use pubky::Keypair;
-use pubky_app_specs::PubkyAppUser;
+use pubky_app_specs::{PubkyAppLastRead, PubkyAppUser};
 
 #[tokio_shared_rt::test(shared)]
 async fn test_delete_user_without_relationships() -> Result<()> {
@@ -34,6 +35,17 @@ async fn test_delete_user_without_relationships() -> Result<()> {
         "User should be findable by ID in search index before deletion"
     );
 
+    let last_read = PubkyAppLastRead { timestamp: 1000 };
+    test.put(&user_kp, &PubkyAppLastRead::hs_path(), &last_read)
+        .await?;
+    assert!(LastRead::get_from_index(&user_id).await?.is_some());
+
+    let preferences = NotificationPreferences {
+        muted_types: vec![NotificationType::Follow],
+        ..Default::default()
+    };
+    preferences.put_to_index(&user_id).await?;
+
     // Delete the user
     test.cleanup_user(&user_kp).await?;
 
@@ -58,6 +70,16 @@ async fn test_delete_user_without_relationships() -> Result<()> {
         "User view should not be found after deletion"
     );
 
+    assert!(
+        LastRead::get_from_index(&user_id).await?.is_none(),
+        "Last read timestamp should not be found after deletion"
+    );
+    assert_eq!(
+        NotificationPreferences::get_by_id(&user_id).await?,
+        NotificationPreferences::default(),
+        "Notification preferences should be removed after deletion"
+    );
+
     // Search indexes should be cleared after deletion
     let by_id_after = UserSearch::get_by_id(&user_id, None, None).await?;
     assert!(
@@ -0,0 +1,62 @@
+use crate::event_processor::utils::watcher::{HomeserverPath, WatcherTest};
+use anyhow::Result;
+use nexus_common::models::notification::{LastRead, Notification};
+use pubky::Keypair;
+use pubky_app_specs::{PubkyAppLastRead, PubkyAppUser};
+
+#[tokio_shared_rt::test(shared)]
+async fn test_homeserver_last_read() -> Result<()> {
+    let mut test = WatcherTest::setup().await?;
+
+    let followee_kp = Keypair::random();
+    let followee = PubkyAppUser {
+        bio: Some("test_homeserver_last_read".to_string()),
+        image: None,
+        links: None,
+        name: "Watcher:LastRead:Followee".to_string(),
+        status: None,
+    };
+    let followee_id = test.create_user(&followee_kp, &followee).await?;
+
+    let follower_kp = Keypair::random();
+    let follower = PubkyAppUser {
+        bio: Some("test_homeserver_last_read".to_string()),
+        image: None,
+        links: None,
+        name: "Watcher:LastRead:Follower".to_string(),
+        status: None,
+    };
+    test.create_user(&follower_kp, &follower).await?;
+
+    // The follow notifies the followee, who never read their notifications
+    test.create_follow(&follower_kp, &followee_id).await?;
+    assert!(LastRead::get_from_index(&followee_id).await?.is_none());
+    assert_eq!(Notification::unread_count(&followee_id).await?, 1);
+
+    let notifications = Notification::get_by_id(&followee_id, Default::default()).await?;
+    let last_read = PubkyAppLastRead {
+        timestamp: notifications[0].timestamp,
+    };
+    let last_read_path = PubkyAppLastRead::hs_path();
+    test.put(&followee_kp, &last_read_path, &last_read).await?;
+
+    assert_eq!(
+        LastRead::get_from_index(&followee_id).await?,
+        Some(LastRead {
+            timestamp: last_read.timestamp
+        })
+    );
+    assert_eq!(Notification::unread_count(&followee_id).await?, 0);
+    assert!(
+        Notification::get_unread_by_id(&followee_id, Default::default())
+            .await?
+            .is_empty()
+    );
+
+    // Without a last read timestamp, all the notifications are unread again
+    test.del(&followee_kp, &last_read_path).await?;
+    assert!(LastRead::get_from_index(&followee_id).await?.is_none());
+    assert_eq!(Notification::unread_count(&followee_id).await?, 1);
+
+    Ok(())
+}
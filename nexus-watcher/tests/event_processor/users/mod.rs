@@ -1,8 +1,11 @@
 // mod avatar;
 mod batch_retrieval;
+mod changes;
 mod del_with_relations;
 mod del_without_relations;
 mod idempotent_del;
+mod last_read;
 mod moderated;
 mod raw;
+mod resync;
 pub mod utils;
@@ -0,0 +1,73 @@
+use crate::event_processor::utils::watcher::WatcherTest;
+use anyhow::Result;
+use nexus_common::models::post::PostDetails;
+use nexus_watcher::service::UserResync;
+use pubky::Keypair;
+use pubky_app_specs::{post_uri_builder, PubkyAppPost, PubkyAppPostKind, PubkyAppUser, PubkyId};
+
+#[tokio_shared_rt::test(shared)]
+async fn test_user_resync() -> Result<()> {
+    let mut test = WatcherTest::setup().await?;
+
+    let user_kp = Keypair::random();
+    let user = PubkyAppUser {
+        bio: Some("test_user_resync".to_string()),
+        image: None,
+        links: None,
+        name: "Watcher:User:Resync".to_string(),
+        status: None,
+    };
+    let user_id = test.create_user(&user_kp, &user).await?;
+
+    let post = PubkyAppPost {
+        content: "Watcher:User:Resync:Post".to_string(),
+        kind: PubkyAppPostKind::Short,
+        parent: None,
+        embed: None,
+        attachments: None,
+    };
+    let (deleted_post_id, deleted_post_path) = test.create_post(&user_kp, &post).await?;
+
+    // The watcher misses a PUT and a DEL of the user
+    let mut test = test.remove_event_processing().await;
+    let (missed_post_id, _) = test.create_post(&user_kp, &post).await?;
+    test.cleanup_post(&user_kp, &deleted_post_path).await?;
+
+    let resync = UserResync {
+        user: PubkyId::try_from(user_id.as_str()).unwrap(),
+        dry_run: true,
+    };
+    let summary = resync.run(&test.event_processor_runner).await.unwrap();
+    assert_eq!(summary.homeserver, test.homeserver_id.to_string());
+    assert_eq!(
+        summary.put,
+        vec![post_uri_builder(user_id.clone(), missed_post_id.clone())]
+    );
+    assert_eq!(
+        summary.deleted,
+        vec![post_uri_builder(user_id.clone(), deleted_post_id.clone())]
+    );
+    // Nothing is applied in a dry run
+    assert!(PostDetails::get_by_id(&user_id, &missed_post_id)
+        .await?
+        .is_none());
+
+    let resync = UserResync {
+        dry_run: false,
+        ..resync
+    };
+    let summary = resync.run(&test.event_processor_runner).await.unwrap();
+    assert_eq!((summary.put.len(), summary.deleted.len()), (1, 1));
+    assert!(PostDetails::get_by_id(&user_id, &missed_post_id)
+        .await?
+        .is_some());
+    assert!(PostDetails::get_by_id(&user_id, &deleted_post_id)
+        .await?
+        .is_none());
+
+    // The user is now in sync
+    let summary = resync.run(&test.event_processor_runner).await.unwrap();
+    assert!(summary.put.is_empty() && summary.deleted.is_empty());
+
+    Ok(())
+}
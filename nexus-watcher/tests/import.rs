@@ -0,0 +1,175 @@
+//! Imports a small dump and checks the graph and Redis state it leaves behind.
+//!
+//! Kept in its own test binary: once initialised from a dump, the `PubkyConnector` serves every
+//! blob of the process from it, which would break the homeserver-backed tests.
+
+use anyhow::{Error, Result};
+use nexus_common::db::PubkyConnector;
+use nexus_common::models::follow::{Following, UserFollows};
+use nexus_common::models::homeserver::HomeserverPolicy;
+use nexus_common::models::post::PostDetails;
+use nexus_common::models::traits::Collection;
+use nexus_common::models::user::UserDetails;
+use nexus_common::{StackConfig, StackManager};
+use nexus_watcher::events::retry::processor::RetryProcessor;
+use nexus_watcher::service::{EventImport, EventProcessorRunner, DUMP_EVENTS_DIR};
+use nexus_watcher::testing::{default_moderation_tests, generate_post_id};
+use pubky::Keypair;
+use pubky_app_specs::{PubkyAppFollow, PubkyAppPost, PubkyAppPostKind, PubkyAppUser, PubkyId};
+use serde::Serialize;
+use std::path::Path;
+use std::sync::Arc;
+use tempfile::TempDir;
+use tokio::fs;
+
+const HOMESERVER: &str = "8pinxxgqs41n4aididenw5apqp1urfmzdztr8jt4abrkdn435ewo";
+
+/// Writes the blob of a PUT event at its path in the dump and returns the event line
+async fn put_blob(dir: &Path, user_id: &str, path: &str, blob: impl Serialize) -> Result<String> {
+    let blob_path = dir.join(user_id).join(path.trim_start_matches('/'));
+    fs::create_dir_all(blob_path.parent().unwrap()).await?;
+    fs::write(&blob_path, serde_json::to_vec(&blob)?).await?;
+    Ok(format!("PUT pubky://{user_id}{path}"))
+}
+
+fn runner(files_path: &Path) -> EventProcessorRunner {
+    let shutdown_rx = tokio::sync::watch::channel(false).1;
+    let moderation = Arc::new(default_moderation_tests());
+    let retry_processor = Arc::new(RetryProcessor {
+        batch_size: 100,
+        max_attempts: 10,
+        files_path: files_path.to_path_buf(),
+        moderation: moderation.clone(),
+        shutdown_rx: shutdown_rx.clone(),
+        dispatcher: None,
+        event_log: None,
+    });
+
+    EventProcessorRunner {
+        default_homeserver: PubkyId::try_from(HOMESERVER).unwrap(),
+        shutdown_rx,
+        limit: 1000,
+        monitored_homeservers_limit: 1,
+        max_concurrent_homeservers: 1,
+        files_path: files_path.to_path_buf(),
+        tracer_name: "test".to_string(),
+        moderation,
+        dispatcher: None,
+        retry_processor,
+        event_log: None,
+        leases: None,
+        homeserver_policy: HomeserverPolicy::default(),
+    }
+}
+
+#[tokio::test]
+async fn test_import_fixture_dump() -> Result<()> {
+    if let Err(e) = StackManager::setup(&StackConfig::default()).await {
+        return Err(Error::msg(format!("could not initialise the stack, {e:?}")));
+    }
+
+    let dump = TempDir::new()?;
+    let files = TempDir::new()?;
+    let alice = Keypair::random().public_key().to_z32();
+    let bob = Keypair::random().public_key().to_z32();
+    let post_id = generate_post_id();
+
+    let profile = |name: &str| PubkyAppUser {
+        bio: Some("test_import_fixture_dump".to_string()),
+        image: None,
+        links: None,
+        name: name.to_string(),
+        status: None,
+    };
+    let post = PubkyAppPost {
+        content: "Watcher:Import:Post".to_string(),
+        kind: PubkyAppPostKind::Short,
+        parent: None,
+        embed: None,
+        attachments: None,
+    };
+    let follow = PubkyAppFollow {
+        created_at: chrono::Utc::now().timestamp_millis(),
+    };
+
+    let profile_path = "/pub/pubky.app/profile.json";
+    let first_page = [
+        put_blob(
+            dump.path(),
+            &alice,
+            profile_path,
+            profile("Watcher:Import:Alice"),
+        )
+        .await?,
+        put_blob(
+            dump.path(),
+            &bob,
+            profile_path,
+            profile("Watcher:Import:Bob"),
+        )
+        .await?,
+        "cursor: 2".to_string(),
+    ];
+    let second_page = [
+        put_blob(
+            dump.path(),
+            &alice,
+            &format!("/pub/pubky.app/posts/{post_id}"),
+            &post,
+        )
+        .await?,
+        put_blob(
+            dump.path(),
+            &alice,
+            &format!("/pub/pubky.app/follows/{bob}"),
+            &follow,
+        )
+        .await?,
+        // Without a blob in the dump, the PUT fails and the rest of the dump is still imported
+        format!(
+            "PUT pubky://{bob}/pub/pubky.app/posts/{}",
+            generate_post_id()
+        ),
+    ];
+    let events_dir = dump.path().join(DUMP_EVENTS_DIR);
+    fs::create_dir_all(&events_dir).await?;
+    fs::write(events_dir.join("0001.txt"), first_page.join("\n")).await?;
+    fs::write(events_dir.join("0002.txt"), second_page.join("\n")).await?;
+
+    PubkyConnector::init_from_dump(dump.path().to_path_buf()).await?;
+    let import = EventImport {
+        dir: dump.path().to_path_buf(),
+    };
+    let summary = import.run(&runner(files.path())).await.unwrap();
+
+    assert_eq!(summary.files, 2);
+    assert_eq!(summary.events, 5);
+    assert_eq!(summary.outcomes["profile.json"].indexed, 2);
+    assert_eq!(summary.outcomes["posts"].indexed, 1);
+    assert_eq!(summary.outcomes["posts"].failed, 1);
+    assert_eq!(summary.outcomes["follows"].indexed, 1);
+
+    // Graph
+    let (details, _) = PostDetails::get_from_graph(&alice, &post_id)
+        .await?
+        .expect("The imported post should be in the graph");
+    assert_eq!(details.content, post.content);
+    let following = Following::get_from_graph(&alice, None, None)
+        .await?
+        .expect("The imported follow should be in the graph");
+    assert_eq!(following.0, vec![bob.clone()]);
+
+    // Redis
+    let user = UserDetails::get_from_index(vec![&[bob.as_str()]])
+        .await?
+        .pop()
+        .flatten()
+        .expect("The imported user should be indexed");
+    assert_eq!(user.name, "Watcher:Import:Bob");
+    assert!(PostDetails::get_from_index(&alice, &post_id)
+        .await?
+        .is_some());
+    assert!(Following::check_in_index(&alice, &bob).await?);
+
+    Ok(())
+}
@@ -209,3 +209,86 @@ async fn test_multi_hs_event_processing_with_panic() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio_shared_rt::test(shared)]
+async fn test_multi_hs_event_processing_runs_homeservers_concurrently() -> Result<()> {
+    // Initialize the test
+    let mut event_processor_list = setup().await?;
+    let (_shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+
+    // Create 4 random homeservers that take 1s each to process their events
+    for _ in 0..4 {
+        create_random_homeservers_and_persist(
+            &mut event_processor_list,
+            Some(Duration::from_secs(1)),
+            MockEventProcessorResult::Success,
+            None,
+            shutdown_rx.clone(),
+        )
+        .await;
+    }
+    let hs_ids: Vec<String> = event_processor_list
+        .iter()
+        .map(|p| p.homeserver_id.to_string())
+        .collect();
+
+    let mut runner = MockEventProcessorRunner::new(event_processor_list, 4, shutdown_rx);
+    runner.max_concurrent_homeservers = 4;
+
+    let t0 = std::time::Instant::now();
+    let stats = runner
+        .run_all(&mut HomeserverBackoff::default())
+        .await
+        .unwrap()
+        .0;
+
+    // All 4 ran in parallel, and the stats keep the priority order
+    assert!(t0.elapsed() < Duration::from_secs(3));
+    assert_eq!(stats.count_ok(), 4);
+    let stats_hs_ids: Vec<String> = stats.stats.iter().map(|s| s.hs_id.clone()).collect();
+    assert_eq!(stats_hs_ids, hs_ids);
+
+    Ok(())
+}
+
+#[tokio_shared_rt::test(shared)]
+async fn test_multi_hs_event_processing_slow_head_does_not_block_the_rest() -> Result<()> {
+    // Initialize the test
+    let mut event_processor_list = setup().await?;
+    let (_shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+
+    // The first homeserver takes 3s, the 3 others 1s each
+    for sleep_secs in [3, 1, 1, 1] {
+        create_random_homeservers_and_persist(
+            &mut event_processor_list,
+            Some(Duration::from_secs(sleep_secs)),
+            MockEventProcessorResult::Success,
+            None,
+            shutdown_rx.clone(),
+        )
+        .await;
+    }
+    let hs_ids: Vec<String> = event_processor_list
+        .iter()
+        .map(|p| p.homeserver_id.to_string())
+        .collect();
+
+    let mut runner = MockEventProcessorRunner::new(event_processor_list, 4, shutdown_rx);
+    runner.max_concurrent_homeservers = 2;
+
+    let t0 = std::time::Instant::now();
+    let stats = runner
+        .run_all(&mut HomeserverBackoff::default())
+        .await
+        .unwrap()
+        .0;
+
+    // The 3 others ran one after the other in the second slot while the first one was still
+    // running, instead of waiting for it to free the slots
+    assert!(t0.elapsed() < Duration::from_millis(3_800));
+    assert_eq!(stats.count_ok(), 4);
+    let stats_hs_ids: Vec<String> = stats.stats.iter().map(|s| s.hs_id.clone()).collect();
+    assert_eq!(stats_hs_ids, hs_ids);
+
+    Ok(())
+}
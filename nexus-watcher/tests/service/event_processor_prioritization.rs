@@ -2,8 +2,9 @@ use crate::event_processor::utils::default_moderation_tests;
 use crate::service::utils::HS_IDS;
 use crate::service::utils::{create_mock_event_processors, setup, MockEventProcessorRunner};
 use anyhow::Result;
-use nexus_common::models::homeserver::Homeserver;
+use nexus_common::models::homeserver::{Homeserver, HomeserverPolicy};
 use nexus_common::types::DynError;
+use nexus_watcher::events::retry::processor::RetryProcessor;
 use nexus_watcher::service::EventProcessorRunner;
 use nexus_watcher::service::TEventProcessorRunner;
 use pubky_app_specs::PubkyId;
@@ -15,15 +16,33 @@ async fn test_event_processor_runner_default_homeserver_prioritization() -> Resu
     // Initialize the test
     setup().await?;
 
+    let shutdown_rx = tokio::sync::watch::channel(false).1;
+    let files_path = PathBuf::from("/tmp/nexus-watcher-test");
+    let moderation = Arc::new(default_moderation_tests());
+    let retry_processor = Arc::new(RetryProcessor {
+        batch_size: 100,
+        max_attempts: 10,
+        files_path: files_path.clone(),
+        moderation: moderation.clone(),
+        shutdown_rx: shutdown_rx.clone(),
+        dispatcher: None,
+        event_log: None,
+    });
+
     let runner = EventProcessorRunner {
         default_homeserver: PubkyId::try_from(HS_IDS[3]).unwrap(),
-        shutdown_rx: tokio::sync::watch::channel(false).1,
+        shutdown_rx,
         limit: 1000,
         monitored_homeservers_limit: HS_IDS.len(),
-        files_path: PathBuf::from("/tmp/nexus-watcher-test"),
+        max_concurrent_homeservers: 1,
+        files_path,
         tracer_name: "test".to_string(),
-        moderation: Arc::new(default_moderation_tests()),
+        moderation,
         dispatcher: None,
+        retry_processor,
+        event_log: None,
+        leases: None,
+        homeserver_policy: HomeserverPolicy::default(),
     };
 
     // Persist the homeservers
@@ -53,6 +72,7 @@ async fn test_mock_event_processor_runner_default_homeserver_prioritization() ->
     let runner = MockEventProcessorRunner {
         event_processors,
         monitored_homeservers_limit: 100,
+        max_concurrent_homeservers: 1,
         shutdown_rx: tokio::sync::watch::channel(false).1,
     };
 
@@ -0,0 +1,57 @@
+use crate::service::utils::{
+    create_random_homeservers_and_persist, setup, MockEventProcessorResult,
+    MockEventProcessorRunner,
+};
+use anyhow::Result;
+use nexus_common::models::homeserver::HomeserverStatus;
+use nexus_watcher::service::backoff::HomeserverBackoff;
+use nexus_watcher::service::TEventProcessorRunner;
+
+#[tokio_shared_rt::test(shared)]
+async fn test_run_all_persists_homeserver_status() -> Result<()> {
+    let mut event_processor_list = setup().await?;
+    let (_shutdown_tx, shutdown_rx) = tokio::sync::watch::channel(false);
+
+    create_random_homeservers_and_persist(
+        &mut event_processor_list,
+        None,
+        MockEventProcessorResult::Success,
+        None,
+        shutdown_rx.clone(),
+    )
+    .await;
+    create_random_homeservers_and_persist(
+        &mut event_processor_list,
+        None,
+        MockEventProcessorResult::Error("PubkyClient: timeout from HS".into()),
+        None,
+        shutdown_rx.clone(),
+    )
+    .await;
+
+    let ok_hs_id = event_processor_list[0].homeserver_id.to_string();
+    let failing_hs_id = event_processor_list[1].homeserver_id.to_string();
+
+    let runner = MockEventProcessorRunner::new(event_processor_list, 2, shutdown_rx);
+    let mut backoff = HomeserverBackoff::default();
+    runner.run_all(&mut backoff).await.unwrap();
+
+    let ok_status = HomeserverStatus::get_from_index(&ok_hs_id)
+        .await?
+        .expect("Status of the successful homeserver should be persisted");
+    assert_eq!(ok_status.last_run_status, "Ok");
+    assert_eq!(ok_status.consecutive_failures, 0);
+    assert!(ok_status.last_success_at.is_some());
+    assert!(ok_status.backoff_until.is_none());
+    assert_eq!(ok_status.cursor.as_deref(), Some("0000000000000"));
+
+    let failing_status = HomeserverStatus::get_from_index(&failing_hs_id)
+        .await?
+        .expect("Status of the failing homeserver should be persisted");
+    assert_eq!(failing_status.last_run_status, "Error");
+    assert_eq!(failing_status.consecutive_failures, 1);
+    assert!(failing_status.last_success_at.is_none());
+    assert!(failing_status.backoff_until.is_some());
+
+    Ok(())
+}
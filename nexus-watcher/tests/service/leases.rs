@@ -0,0 +1,44 @@
+use crate::service::utils::setup;
+use anyhow::Result;
+use nexus_watcher::service::WatcherLeases;
+use std::time::Duration;
+
+#[tokio_shared_rt::test(shared)]
+async fn test_watcher_leases_split_homeservers_and_take_over_expired_ones() -> Result<()> {
+    setup().await?;
+
+    let hs_ids: Vec<String> = (0..4).map(|i| format!("lease-test-hs-{i}")).collect();
+    let ttl = Duration::from_millis(1_000);
+    let instance_a = WatcherLeases::new(String::from("lease-test-a"), ttl);
+    let instance_b = WatcherLeases::new(String::from("lease-test-b"), ttl);
+
+    // A single live instance leases every homeserver
+    let assigned_a = instance_a.assign(hs_ids.clone(), 100).await?;
+    assert_eq!(assigned_a, hs_ids);
+
+    // A second instance joins: nothing is free until the first one gives up its excess leases
+    assert!(instance_b.assign(hs_ids.clone(), 100).await?.is_empty());
+    let assigned_a = instance_a.assign(hs_ids.clone(), 100).await?;
+    assert_eq!(assigned_a, hs_ids[..2]);
+    let assigned_b = instance_b.assign(hs_ids.clone(), 100).await?;
+    assert_eq!(assigned_b, hs_ids[2..]);
+
+    // Only one leader at a time
+    assert!(instance_a.is_leader().await?);
+    assert!(!instance_b.is_leader().await?);
+
+    // The first instance crashes: once its leases expire, the second one takes over
+    tokio::time::sleep(ttl + Duration::from_millis(200)).await;
+    let assigned_b = instance_b.assign(hs_ids.clone(), 100).await?;
+    assert_eq!(assigned_b, hs_ids);
+    assert!(instance_b.is_leader().await?);
+
+    // Until its next renewal, the first instance still believes it holds its leases, but
+    // must not commit a cursor
+    assert!(instance_a.is_held(&hs_ids[0]));
+    assert!(!instance_a.holds(&hs_ids[0]).await?);
+    assert!(instance_b.holds(&hs_ids[0]).await?);
+
+    instance_b.release_all().await?;
+    Ok(())
+}
@@ -1,5 +1,8 @@
 pub mod event_processing_multiple_homeservers;
 pub mod event_processor_prioritization;
+pub mod homeserver_status;
+pub mod leases;
 pub mod mock_event_processor;
 pub mod signal;
 pub mod utils;
+pub mod webhook_pending;
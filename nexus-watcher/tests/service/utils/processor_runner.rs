@@ -11,6 +11,8 @@ pub struct MockEventProcessorRunner {
     /// The event processors to be used by the runner
     pub event_processors: Vec<Arc<MockEventProcessor>>,
     pub monitored_homeservers_limit: usize,
+    /// Number of mock event processors run in parallel, `1` unless set by the test
+    pub max_concurrent_homeservers: usize,
     pub shutdown_rx: Receiver<bool>,
 }
 
@@ -27,6 +29,7 @@ impl MockEventProcessorRunner {
         Self {
             event_processors: arcs,
             monitored_homeservers_limit,
+            max_concurrent_homeservers: 1,
             shutdown_rx,
         }
     }
@@ -50,6 +53,10 @@ impl TEventProcessorRunner for MockEventProcessorRunner {
         self.monitored_homeservers_limit
     }
 
+    fn max_concurrent_homeservers(&self) -> usize {
+        self.max_concurrent_homeservers
+    }
+
     async fn homeservers_by_priority(&self) -> Result<Vec<String>, DynError> {
         let persistedhs_ids = Homeserver::get_all_from_graph().await?;
 
@@ -0,0 +1,40 @@
+use crate::service::utils::setup;
+use anyhow::Result;
+use chrono::Utc;
+use nexus_common::models::notification::{Notification, NotificationBody};
+use nexus_common::models::webhook::{PendingDelivery, WebhookPayload};
+
+#[tokio_shared_rt::test(shared)]
+async fn test_webhook_pending_deliveries_are_persisted_until_due() -> Result<()> {
+    setup().await?;
+
+    let webhook = format!("pending-test-{}", Utc::now().timestamp_micros());
+    let notification = Notification::new(NotificationBody::Follow {
+        followed_by: String::from("follower"),
+    });
+    let mut pending = PendingDelivery::new(&webhook, WebhookPayload::new("user", &notification));
+    let pending_of = |due: Vec<PendingDelivery>| {
+        due.into_iter()
+            .filter(|delivery| delivery.webhook == webhook)
+            .collect::<Vec<_>>()
+    };
+
+    // Not attempted before it is due
+    let now = Utc::now().timestamp_millis();
+    pending.schedule(now + 60_000).await?;
+    assert!(pending_of(PendingDelivery::get_due(now, 1_000).await?).is_empty());
+
+    // A failed attempt is scheduled again with its state
+    pending.attempts = 1;
+    pending.status_code = Some(503);
+    pending.schedule(now - 1).await?;
+    let due = pending_of(PendingDelivery::get_due(now, 1_000).await?);
+    assert_eq!(due.len(), 1);
+    assert_eq!(due[0].payload.id, pending.payload.id);
+    assert_eq!(due[0].attempts, 1);
+    assert_eq!(due[0].status_code, Some(503));
+
+    pending.remove().await?;
+    assert!(pending_of(PendingDelivery::get_due(now, 1_000).await?).is_empty());
+    Ok(())
+}
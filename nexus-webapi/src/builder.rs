@@ -222,8 +222,12 @@ impl NexusApi {
         extra_swagger_docs: Vec<(String, utoipa::openapi::OpenApi)>,
     ) -> Result<Self, DynError> {
         // Create all the routes of the API and merge any plugin routes
-        let router = routes::routes(ctx.api_config.stack.files_path.clone(), extra_swagger_docs)
-            .merge(extra_routes);
+        let router = routes::routes(
+            ctx.api_config.stack.files_path.clone(),
+            ctx.api_config.admin_token.clone(),
+            extra_swagger_docs,
+        )
+        .merge(extra_routes);
         debug!(?ctx.api_config, "Running NexusAPI with config");
 
         let (icann_http_handle, icann_http_socket) =
@@ -26,10 +26,25 @@ pub enum Error {
     InvalidInput { message: String },
     #[error("File not found.")]
     FileNotFound {},
+    #[error("Feed not found: {user_id} {feed_id}")]
+    FeedNotFound { user_id: String, feed_id: String },
     #[error("Tag {tag_id} of {tagger_id} not found")]
     TagNotFound { tag_id: String, tagger_id: String },
     #[error("Resource not found: {resource_id}")]
     ResourceNotFound { resource_id: String },
+    #[error("Dead-lettered event not found: {index_key}")]
+    DeadLetterNotFound { index_key: String },
+    #[error("Homeserver not found: {homeserver_id}")]
+    HomeserverNotFound { homeserver_id: String },
+    #[error("Homeserver {homeserver_id} rejected: {reason}")]
+    HomeserverRejected {
+        homeserver_id: String,
+        reason: String,
+    },
+    #[error("No resync requested for user {user_id}")]
+    ResyncNotFound { user_id: String },
+    #[error("Unauthorized")]
+    Unauthorized {},
     // Add other custom errors here
 }
 
@@ -69,8 +84,17 @@ impl Error {
 
 impl From<ModelError> for Error {
     fn from(source: ModelError) -> Self {
-        Error::InternalServerError {
-            source: source.into(),
+        match source {
+            ModelError::HomeserverRejected {
+                homeserver_id,
+                reason,
+            } => Error::HomeserverRejected {
+                homeserver_id,
+                reason: reason.to_string(),
+            },
+            source => Error::InternalServerError {
+                source: source.into(),
+            },
         }
     }
 }
@@ -120,11 +144,17 @@ impl IntoResponse for Error {
             Error::UserNotFound { .. } => StatusCode::NOT_FOUND,
             Error::PostNotFound { .. } => StatusCode::NOT_FOUND,
             Error::FileNotFound { .. } => StatusCode::NOT_FOUND,
+            Error::FeedNotFound { .. } => StatusCode::NOT_FOUND,
             Error::TagsNotFound { .. } => StatusCode::NOT_FOUND,
             Error::InvalidInput { .. } => StatusCode::BAD_REQUEST,
             Error::InternalServerError { .. } => StatusCode::INTERNAL_SERVER_ERROR,
             Error::TagNotFound { .. } => StatusCode::NOT_FOUND,
             Error::ResourceNotFound { .. } => StatusCode::NOT_FOUND,
+            Error::DeadLetterNotFound { .. } => StatusCode::NOT_FOUND,
+            Error::HomeserverNotFound { .. } => StatusCode::NOT_FOUND,
+            Error::HomeserverRejected { .. } => StatusCode::FORBIDDEN,
+            Error::ResyncNotFound { .. } => StatusCode::NOT_FOUND,
+            Error::Unauthorized { .. } => StatusCode::UNAUTHORIZED,
             // Map other errors to appropriate status codes
         };
 
@@ -137,6 +167,9 @@ impl IntoResponse for Error {
             Error::FileNotFound {} => {
                 error!("File not found.")
             }
+            Error::FeedNotFound { user_id, feed_id } => {
+                error!("Feed not found: {} {}", user_id, feed_id)
+            }
             Error::TagsNotFound { reach } => {
                 error!("Tags not found: {}", reach)
             }
@@ -149,6 +182,22 @@ impl IntoResponse for Error {
             Error::ResourceNotFound { resource_id } => {
                 error!("Resource not found: {}", resource_id)
             }
+            Error::DeadLetterNotFound { index_key } => {
+                error!("Dead-lettered event not found: {}", index_key)
+            }
+            Error::HomeserverNotFound { homeserver_id } => {
+                error!("Homeserver not found: {}", homeserver_id)
+            }
+            Error::HomeserverRejected {
+                homeserver_id,
+                reason,
+            } => {
+                error!("Homeserver {} rejected: {}", homeserver_id, reason)
+            }
+            Error::ResyncNotFound { user_id } => {
+                error!("No resync requested for user {}", user_id)
+            }
+            Error::Unauthorized {} => error!("Unauthorized request"),
             Error::InternalServerError { source } => error!("Internal server error: {:?}", source),
         };
 
@@ -71,14 +71,18 @@ where
 #[derive(Clone)]
 pub struct AppState {
     pub files_path: Arc<PathBuf>,
+    /// See [ApiConfig::admin_token](nexus_common::ApiConfig::admin_token)
+    pub admin_token: Option<Arc<str>>,
 }
 
 pub fn routes(
     files_path: PathBuf,
+    admin_token: Option<String>,
     extra_swagger_docs: Vec<(String, utoipa::openapi::OpenApi)>,
 ) -> Router {
     let state = AppState {
         files_path: Arc::new(files_path),
+        admin_token: admin_token.map(Arc::from),
     };
 
     let route_static = r#static::routes(state.clone());
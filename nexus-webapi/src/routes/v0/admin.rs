@@ -0,0 +1,157 @@
+use crate::models::PubkyId;
+use crate::routes::AppState;
+use crate::routes::Path;
+use crate::routes::Query;
+use crate::{Error, Result};
+use axum::extract::State;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::routing::get;
+use axum::{Json, Router};
+use nexus_common::models::notification::NotificationType;
+use nexus_common::models::user::{ResyncStatus, UserResyncRequest};
+use nexus_common::models::webhook::{DeliveryStatus, WebhookDelivery};
+use nexus_common::types::Pagination;
+use subtle::ConstantTimeEq;
+use tracing::{debug, info};
+use utoipa::OpenApi;
+
+use super::endpoints::{ADMIN_USER_RESYNC_ROUTE, ADMIN_WEBHOOK_DELIVERIES_ROUTE};
+
+/// Checks the `Authorization: Bearer` header against the configured admin token. Every request
+/// is refused if no token is configured. The tokens are compared in constant time.
+pub(crate) fn authorize(app_state: &AppState, headers: &HeaderMap) -> Result<()> {
+    let token = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    match (app_state.admin_token.as_deref(), token) {
+        (Some(admin_token), Some(token))
+            if bool::from(admin_token.as_bytes().ct_eq(token.as_bytes())) =>
+        {
+            Ok(())
+        }
+        _ => Err(Error::Unauthorized {}),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = ADMIN_USER_RESYNC_ROUTE,
+    tag = "Admin",
+    description = "Queue a resync of the user from their homeserver: the watcher diffs the user's /pub/pubky.app/ tree against the graph and applies the missing PUT and DEL events",
+    params(
+        ("user_id" = PubkyId, Path, description = "User Pubky ID"),
+        ("Authorization" = String, Header, description = "Bearer admin token")
+    ),
+    responses(
+        (status = 202, description = "Resync queued, or the already queued or running one", body = UserResyncRequest),
+        (status = 401, description = "Missing or invalid admin token"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn post_user_resync_handler(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Path(user_id): Path<PubkyId>,
+) -> Result<(StatusCode, Json<UserResyncRequest>)> {
+    debug!("POST {ADMIN_USER_RESYNC_ROUTE}, user_id:{user_id}");
+    authorize(&app_state, &headers)?;
+
+    let request = UserResyncRequest::enqueue(&user_id).await?;
+    if request.status == ResyncStatus::Queued {
+        info!("Queued a resync of user {user_id}");
+    }
+    Ok((StatusCode::ACCEPTED, Json(request)))
+}
+
+#[utoipa::path(
+    get,
+    path = ADMIN_USER_RESYNC_ROUTE,
+    tag = "Admin",
+    description = "Status of the last resync requested for the user",
+    params(
+        ("user_id" = PubkyId, Path, description = "User Pubky ID"),
+        ("Authorization" = String, Header, description = "Bearer admin token")
+    ),
+    responses(
+        (status = 200, description = "Resync request", body = UserResyncRequest),
+        (status = 401, description = "Missing or invalid admin token"),
+        (status = 404, description = "No resync requested for the user"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_user_resync_handler(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Path(user_id): Path<PubkyId>,
+) -> Result<Json<UserResyncRequest>> {
+    debug!("GET {ADMIN_USER_RESYNC_ROUTE}, user_id:{user_id}");
+    authorize(&app_state, &headers)?;
+
+    match UserResyncRequest::get_from_index(&user_id).await? {
+        Some(request) => Ok(Json(request)),
+        None => Err(Error::ResyncNotFound {
+            user_id: user_id.to_string(),
+        }),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = ADMIN_WEBHOOK_DELIVERIES_ROUTE,
+    tag = "Admin",
+    description = "Log of the deliveries of indexed notifications to a webhook configured in the watcher, the most recent first. Deliveries are kept for a week",
+    params(
+        ("name" = String, Path, description = "Webhook name"),
+        ("skip" = Option<usize>, Query, description = "Skip N deliveries"),
+        ("limit" = Option<usize>, Query, description = "Retrieve N deliveries"),
+        ("Authorization" = String, Header, description = "Bearer admin token")
+    ),
+    responses(
+        (status = 200, description = "Webhook deliveries", body = Vec<WebhookDelivery>),
+        (status = 401, description = "Missing or invalid admin token"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_webhook_deliveries_handler(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Path(name): Path<String>,
+    Query(query): Query<Pagination>,
+) -> Result<Json<Vec<WebhookDelivery>>> {
+    debug!("GET {ADMIN_WEBHOOK_DELIVERIES_ROUTE}, name:{name}");
+    authorize(&app_state, &headers)?;
+
+    let limit = query.limit.unwrap_or(20);
+    let deliveries = WebhookDelivery::get_by_webhook(&name, query.skip, Some(limit)).await?;
+    Ok(Json(deliveries))
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            ADMIN_USER_RESYNC_ROUTE,
+            get(get_user_resync_handler).post(post_user_resync_handler),
+        )
+        .route(
+            ADMIN_WEBHOOK_DELIVERIES_ROUTE,
+            get(get_webhook_deliveries_handler),
+        )
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        post_user_resync_handler,
+        get_user_resync_handler,
+        get_webhook_deliveries_handler
+    ),
+    components(schemas(
+        UserResyncRequest,
+        ResyncStatus,
+        WebhookDelivery,
+        DeliveryStatus,
+        NotificationType
+    ))
+)]
+pub struct AdminApiDoc;
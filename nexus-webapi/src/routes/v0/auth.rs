@@ -0,0 +1,27 @@
+use crate::models::PubkyId;
+use crate::{Error, Result};
+use axum::http::{header, HeaderMap};
+use pubky::AuthToken;
+
+/// Scheme of the `Authorization` header carrying a Pubky [AuthToken], as
+/// `Authorization: Pubky <hex encoded token>`
+pub const PUBKY_AUTH_SCHEME: &str = "Pubky";
+
+/// Checks that the request is signed by `user_id`, for the user-scoped writes.
+///
+/// The `Authorization` header holds an [AuthToken] signed with the keypair of the user, the same
+/// token users sign in to their homeserver with. Tokens are valid for a few minutes around their
+/// timestamp.
+pub(crate) fn authorize_user(headers: &HeaderMap, user_id: &PubkyId) -> Result<()> {
+    let token = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix(PUBKY_AUTH_SCHEME))
+        .and_then(|value| value.strip_prefix(' '))
+        .and_then(|value| hex::decode(value.trim()).ok())
+        .and_then(|bytes| AuthToken::verify(&bytes).ok());
+    match token {
+        Some(token) if *token.public_key() == user_id.to_public_key() => Ok(()),
+        _ => Err(Error::Unauthorized {}),
+    }
+}
@@ -45,6 +45,7 @@ pub async fn bootstrap_handler(
     ),
     responses(
         (status = 200, description = "Successfully added new homeserver"),
+        (status = 403, description = "Homeserver refused by the homeserver admission policy"),
         (status = 500, description = "Internal server error")
     )
 )]
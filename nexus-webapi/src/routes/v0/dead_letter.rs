@@ -0,0 +1,87 @@
+use crate::routes::AppState;
+use crate::routes::Query;
+use crate::{Error, Result};
+use axum::routing::get;
+use axum::{Json, Router};
+use nexus_common::models::event::DeadLetterEvent;
+use serde::Deserialize;
+use tracing::debug;
+use utoipa::OpenApi;
+
+use super::endpoints::{DEAD_LETTER_EVENT_ROUTE, DEAD_LETTER_ROUTE};
+
+#[derive(Deserialize)]
+pub struct DeadLetterListQuery {
+    skip: Option<usize>,
+    limit: Option<usize>,
+}
+
+#[derive(Deserialize)]
+pub struct DeadLetterEventQuery {
+    key: String,
+}
+
+#[utoipa::path(
+    get,
+    path = DEAD_LETTER_ROUTE,
+    tag = "Info",
+    description = "Events that exhausted their retries in the watcher, most recent first",
+    params(
+        ("skip" = Option<usize>, Query, description = "Skip N events"),
+        ("limit" = Option<usize>, Query, description = "Retrieve N events (default 20, maximum 100)")
+    ),
+    responses(
+        (status = 200, description = "Dead-lettered events", body = Vec<DeadLetterEvent>),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn list_dead_letter_handler(
+    Query(query): Query<DeadLetterListQuery>,
+) -> Result<Json<Vec<DeadLetterEvent>>> {
+    debug!("GET {DEAD_LETTER_ROUTE}");
+
+    let skip = query.skip.unwrap_or(0);
+    let limit = query.limit.unwrap_or(20).min(100);
+
+    Ok(Json(DeadLetterEvent::list(skip, limit).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = DEAD_LETTER_EVENT_ROUTE,
+    tag = "Info",
+    description = "A single dead-lettered event with its last error",
+    params(
+        ("key" = String, Query, description = "Index key of the event, e.g. PUT:{user_id}:{resource}:{id}")
+    ),
+    responses(
+        (status = 200, description = "Dead-lettered event", body = DeadLetterEvent),
+        (status = 404, description = "Event not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_dead_letter_handler(
+    Query(query): Query<DeadLetterEventQuery>,
+) -> Result<Json<DeadLetterEvent>> {
+    debug!("GET {DEAD_LETTER_EVENT_ROUTE} for key: {}", query.key);
+
+    match DeadLetterEvent::get_from_index(&query.key).await? {
+        Some(event) => Ok(Json(event)),
+        None => Err(Error::DeadLetterNotFound {
+            index_key: query.key,
+        }),
+    }
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route(DEAD_LETTER_ROUTE, get(list_dead_letter_handler))
+        .route(DEAD_LETTER_EVENT_ROUTE, get(get_dead_letter_handler))
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(list_dead_letter_handler, get_dead_letter_handler),
+    components(schemas(DeadLetterEvent))
+)]
+pub struct DeadLetterApiDoc;
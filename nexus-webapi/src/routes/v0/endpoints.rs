@@ -5,6 +5,10 @@ const VERSION_ROUTE: &str = "/v0";
 
 // Info routes
 pub const INFO_ROUTE: &str = concatcp!(VERSION_ROUTE, "/info");
+pub const INFO_HOMESERVERS_ROUTE: &str = concatcp!(INFO_ROUTE, "/homeservers");
+pub const INFO_REJECTED_HOMESERVERS_ROUTE: &str = concatcp!(INFO_HOMESERVERS_ROUTE, "/rejected");
+pub const DEAD_LETTER_ROUTE: &str = concatcp!(INFO_ROUTE, "/dead-letter");
+pub const DEAD_LETTER_EVENT_ROUTE: &str = concatcp!(DEAD_LETTER_ROUTE, "/event");
 
 // -- USER endpoints --
 const USER_PREFIX: &str = concatcp!(VERSION_ROUTE, "/user");
@@ -17,6 +21,7 @@ pub const USER_TAGGERS_ROUTE: &str = concatcp!(USER_ROUTE, "/taggers/{label}");
 pub const USER_FOLLOWERS_ROUTE: &str = concatcp!(USER_ROUTE, "/followers");
 pub const USER_FOLLOWING_ROUTE: &str = concatcp!(USER_ROUTE, "/following");
 pub const USER_FRIENDS_ROUTE: &str = concatcp!(USER_ROUTE, "/friends");
+pub const USER_FEEDS_ROUTE: &str = concatcp!(USER_ROUTE, "/feeds");
 
 // -- POST endpoints --
 pub const POST_PREFIX: &str = concatcp!(VERSION_ROUTE, "/post");
@@ -39,6 +44,8 @@ pub const STREAM_USERS_BY_IDS_ROUTE: &str = concatcp!(STREAM_USERS_ROUTE, "/by_i
 pub const STREAM_POSTS_ROUTE: &str = concatcp!(STREAM_PREFIX, "/posts");
 pub const STREAM_POSTS_BY_IDS_ROUTE: &str = concatcp!(STREAM_POSTS_ROUTE, "/by_ids");
 pub const STREAM_POST_KEYS_ROUTE: &str = concatcp!(STREAM_POSTS_ROUTE, "/keys");
+pub const STREAM_POSTS_FEED_ROUTE: &str =
+    concatcp!(STREAM_POSTS_ROUTE, "/feed/{user_id}/{feed_id}");
 // STREAM of Tags for posts
 pub const STREAM_TAGS_ROUTE: &str = concatcp!(STREAM_PREFIX, "/tags");
 pub const STREAM_TAGS_GLOBAL_ROUTE: &str = concatcp!(STREAM_TAGS_ROUTE, "/global");
@@ -66,11 +73,26 @@ pub const FILE_ROUTE: &str = concatcp!(FILE_PREFIX, "/file/{file_id}");
 
 // -- NOTIFICATION endpoints -
 pub const NOTIFICATION_ROUTE: &str = concatcp!(USER_ROUTE, "/notifications");
+pub const NOTIFICATION_UNREAD_ROUTE: &str = concatcp!(NOTIFICATION_ROUTE, "/unread");
+pub const NOTIFICATION_GROUPED_ROUTE: &str = concatcp!(NOTIFICATION_ROUTE, "/grouped");
+pub const NOTIFICATION_PREFERENCES_ROUTE: &str = concatcp!(NOTIFICATION_ROUTE, "/preferences");
+pub const NOTIFICATION_STREAM_ROUTE: &str = concatcp!(NOTIFICATION_ROUTE, "/stream");
+pub const NOTIFICATION_SOCKET_ROUTE: &str = concatcp!(NOTIFICATION_ROUTE, "/ws");
 
 // -- BOOTSTRAP endpoints -
 pub const BOOTSTRAP_ROUTE: &str = concatcp!(VERSION_ROUTE, "/bootstrap/{user_id}");
 pub const PUT_HOMESERVER_ROUTE: &str = concatcp!(VERSION_ROUTE, "/ingest/{user_id}");
 
+// -- HOMESERVER endpoints --
+pub const HOMESERVERS_ROUTE: &str = concatcp!(VERSION_ROUTE, "/homeservers");
+pub const HOMESERVER_ROUTE: &str = concatcp!(VERSION_ROUTE, "/homeserver/{homeserver_id}");
+
+// -- ADMIN endpoints --
+const ADMIN_PREFIX: &str = concatcp!(VERSION_ROUTE, "/admin");
+pub const ADMIN_USER_RESYNC_ROUTE: &str = concatcp!(ADMIN_PREFIX, "/user/{user_id}/resync");
+pub const ADMIN_WEBHOOK_DELIVERIES_ROUTE: &str =
+    concatcp!(ADMIN_PREFIX, "/webhooks/{name}/deliveries");
+
 // -- RESOURCE endpoints --
 const RESOURCE_PREFIX: &str = concatcp!(VERSION_ROUTE, "/resource");
 pub const RESOURCE_TAGS_ROUTE: &str = concatcp!(RESOURCE_PREFIX, "/{resource_id}/tags");
@@ -84,3 +106,4 @@ pub const STREAM_RESOURCE_IDS_ROUTE: &str = concatcp!(STREAM_RESOURCES_ROUTE, "/
 
 // -- EVENTS endpoints
 pub const EVENTS_ROUTE: &str = concatcp!(VERSION_ROUTE, "/events");
+pub const EVENTS_STREAM_ROUTE: &str = concatcp!(EVENTS_ROUTE, "/stream");
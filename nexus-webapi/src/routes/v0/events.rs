@@ -1,16 +1,27 @@
 use crate::routes::AppState;
-use nexus_common::models::event::Event;
+use futures_util::{stream, Stream, StreamExt};
+use nexus_common::models::event::{Event, EventFilter, EventType, StoredEvent};
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use tracing::{debug, warn};
 use utoipa::ToSchema;
 
-use super::endpoints::EVENTS_ROUTE;
+use super::endpoints::{EVENTS_ROUTE, EVENTS_STREAM_ROUTE};
 
 use crate::routes::Query;
 use crate::Error;
+use axum::http::{header, HeaderMap};
+use axum::response::sse::{self, KeepAlive, Sse};
 use axum::routing::get;
-use axum::{response::Response, Router};
+use axum::{response::Response, Json, Router};
 use utoipa::OpenApi;
 
+/// Number of stored events read at once by the events stream
+const STREAM_BATCH_SIZE: usize = 100;
+
+/// Header set by `EventSource` clients on reconnection, with the id of the last received event
+const LAST_EVENT_ID_HEADER: &str = "Last-Event-ID";
+
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema, utoipa::ToResponse)]
 #[schema(as = String)]
 pub struct EventsList {
@@ -27,10 +38,30 @@ impl std::fmt::Display for EventsList {
     }
 }
 
+/// Events list served with `Accept: application/json`
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct EventsJson {
+    cursor: u64,
+    events: Vec<StoredEvent>,
+}
+
 #[derive(Deserialize)]
 pub struct EventsQuery {
     cursor: Option<u64>,
     limit: Option<usize>,
+    user_id: Option<String>,
+    resource: Option<String>,
+    #[serde(rename = "type")]
+    event_type: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct EventsStreamQuery {
+    cursor: Option<u64>,
+    user_id: Option<String>,
+    resource: Option<String>,
+    #[serde(rename = "type")]
+    event_type: Option<String>,
 }
 
 #[utoipa::path(
@@ -39,27 +70,47 @@ pub struct EventsQuery {
     tag = "Events",
     params(
         ("cursor" = u64, Query, description = "Cursor"),
-        ("limit" = usize, Query, description = "Limit the number of results, (default 500, maximum 1000)")
+        ("limit" = usize, Query, description = "Limit the number of results, (default 500, maximum 1000)"),
+        ("user_id" = Option<String>, Query, description = "Only the events of this user"),
+        ("resource" = Option<String>, Query, description = "Only the events of this resource, named as in the homeserver paths: `profile.json`, `posts`, `follows`, `mutes`, `bookmarks`, `tags`, `files`, `feeds` or `last_read`"),
+        ("type" = Option<String>, Query, description = "Only the `PUT` or `DEL` events"),
+        ("Accept" = Option<String>, Header, description = "`application/json`, preferred over `text/plain` by its quality value, to list the events as JSON, with their stored timestamp and homeserver")
     ),
     responses(
         (
             status = 200,
             description = "Events list",
-            body = String,
-            description = "Events list as plain text with cursor",
-            content_type = "text/plain",
-            example = "PUT pubky://<pk>/<path>\nDEL pubky://<pk>/<path>\nPUT pubky://<pk>/<path>\ncursor: 2"
+            content(
+                (String = "text/plain", example = "PUT pubky://<pk>/<path>\nDEL pubky://<pk>/<path>\nPUT pubky://<pk>/<path>\ncursor: 2"),
+                (EventsJson = "application/json")
+            )
         ),
         (status = 400, description = "Bad request"),
         (status = 500, description = "Internal server error"),
 
     )
 )]
-pub async fn get_events_handler(Query(q): Query<EventsQuery>) -> Result<Response, Error> {
+pub async fn get_events_handler(
+    Query(q): Query<EventsQuery>,
+    headers: HeaderMap,
+) -> Result<Response, Error> {
     let (limit, cursor) = parse_query(&q)?;
-    let (events, next_cursor) = Event::get_events_from_redis(cursor, limit).await?;
+    let filter = parse_filter(&q.user_id, &q.resource, &q.event_type)?;
+    let (events, next_cursor) = Event::get_stored_events(cursor, limit, &filter).await?;
+    let events = events.into_iter().map(|(_, event)| event);
+
+    if accepts_json(&headers) {
+        let event_list = EventsJson {
+            events: events.collect(),
+            cursor: next_cursor,
+        };
+        return Ok(axum::response::IntoResponse::into_response(Json(
+            event_list,
+        )));
+    }
+
     let event_list = EventsList {
-        events,
+        events: events.map(|event| event.line).collect(),
         cursor: next_cursor,
     };
 
@@ -68,6 +119,105 @@ pub async fn get_events_handler(Query(q): Query<EventsQuery>) -> Result<Response
     Ok(response)
 }
 
+#[utoipa::path(
+    get,
+    path = EVENTS_STREAM_ROUTE,
+    tag = "Events",
+    description = "Server-Sent Events stream of the events, as they are stored. Each event has the `event` type, the cursor after it as id and the stored event as JSON data. The stored events after the `Last-Event-ID` header (or the `cursor` param) are sent first; without either, only the new events are sent.",
+    params(
+        ("cursor" = Option<u64>, Query, description = "Cursor to stream the events from. Overridden by the `Last-Event-ID` header"),
+        ("user_id" = Option<String>, Query, description = "Only the events of this user"),
+        ("resource" = Option<String>, Query, description = "Only the events of this resource, named as in the homeserver paths, e.g. `posts`"),
+        ("type" = Option<String>, Query, description = "Only the `PUT` or `DEL` events"),
+        ("Last-Event-ID" = Option<u64>, Header, description = "Id of the last received event, set by `EventSource` clients on reconnection")
+    ),
+    responses(
+        (status = 200, description = "Stream of events", content_type = "text/event-stream", body = StoredEvent),
+        (status = 400, description = "Bad request"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn stream_events_handler(
+    Query(q): Query<EventsStreamQuery>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<sse::Event, axum::Error>>>, Error> {
+    let filter = parse_filter(&q.user_id, &q.resource, &q.event_type)?;
+    let cursor = headers
+        .get(LAST_EVENT_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok())
+        .or(q.cursor);
+    debug!("GET {EVENTS_STREAM_ROUTE} from cursor: {:?}", cursor);
+
+    // Followed before reading the stored events, so none is missed in between
+    let (live_from, live) = Event::follow().await?;
+    let state = StreamState {
+        cursor: cursor.unwrap_or(live_from),
+        live_from,
+        filter,
+        live,
+        pending: VecDeque::new(),
+    };
+    // Clients resume from the last received event on reconnection, e.g. once the stream ended
+    // for lagging behind
+    let events = stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(event) = state.pending.pop_front() {
+                return Some((event, state));
+            }
+
+            // Catch up with the stored events up to the followed ones
+            let cursor = state.cursor;
+            if cursor < state.live_from {
+                let read =
+                    Event::get_stored_events(Some(cursor), STREAM_BATCH_SIZE, &state.filter).await;
+                let (events, next_cursor) = match read {
+                    Ok(read) => read,
+                    Err(e) => {
+                        warn!("Failed to read the stored events from cursor {cursor}: {e}");
+                        return None;
+                    }
+                };
+                let live_from = state.live_from;
+                state
+                    .pending
+                    .extend(events.into_iter().filter(|(index, _)| *index < live_from));
+                state.cursor = match next_cursor > cursor {
+                    true => next_cursor.min(live_from),
+                    false => live_from,
+                };
+                continue;
+            }
+
+            let (index, event) = state.live.next().await?;
+            if index >= state.cursor && state.filter.matches(&event) {
+                state.cursor = index + 1;
+                return Some(((index, event), state));
+            }
+        }
+    })
+    .map(|(index, event)| {
+        sse::Event::default()
+            .event("event")
+            .id((index + 1).to_string())
+            .json_data(&event)
+    });
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+/// Progress of an events stream
+struct StreamState<S> {
+    /// Cursor of the next event to send
+    cursor: u64,
+    /// Index of the first followed event, the ones before it are read from the events list
+    live_from: u64,
+    filter: EventFilter,
+    /// Events followed as they are stored, see [Event::follow]
+    live: S,
+    /// Events read but not sent yet, with their index
+    pending: VecDeque<(u64, StoredEvent)>,
+}
+
 fn parse_query(q: &EventsQuery) -> Result<(usize, Option<u64>), Error> {
     let limit = q.limit.unwrap_or(500).min(1000);
     let cursor = q.cursor;
@@ -75,10 +225,98 @@ fn parse_query(q: &EventsQuery) -> Result<(usize, Option<u64>), Error> {
     Ok((limit, cursor))
 }
 
+fn parse_filter(
+    user_id: &Option<String>,
+    resource: &Option<String>,
+    event_type: &Option<String>,
+) -> Result<EventFilter, Error> {
+    let event_type = match event_type.as_deref().map(str::to_uppercase).as_deref() {
+        None => None,
+        Some("PUT") => Some(EventType::Put),
+        Some("DEL") => Some(EventType::Del),
+        Some(_) => return Err(Error::invalid_input("type must be PUT or DEL")),
+    };
+    Ok(EventFilter {
+        user_id: user_id.clone(),
+        resource: resource.clone(),
+        event_type,
+    })
+}
+
+/// Whether the client prefers JSON to plain text, by the quality values of the `Accept` header.
+/// Plain text is served on ties and without the header.
+fn accepts_json(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|accept| quality(accept, "application/json") > quality(accept, "text/plain"))
+}
+
+/// Quality value given to `media_type` by an `Accept` header: the one of the most specific media
+/// range matching it, `0` if none does
+fn quality(accept: &str, media_type: &str) -> f32 {
+    let type_range = media_type
+        .split_once('/')
+        .map(|(type_, _)| format!("{type_}/*"));
+    let mut best: Option<(u8, f32)> = None;
+    for media_range in accept.to_ascii_lowercase().split(',') {
+        let mut params = media_range.split(';').map(str::trim);
+        let range = params.next().unwrap_or_default();
+        let specificity = if range == media_type {
+            2
+        } else if type_range.as_deref() == Some(range) {
+            1
+        } else if range == "*/*" {
+            0
+        } else {
+            continue;
+        };
+        let q = params
+            .find_map(|param| param.strip_prefix("q="))
+            .and_then(|q| q.parse::<f32>().ok())
+            .unwrap_or(1.0);
+        if best.is_none_or(|(best_specificity, _)| specificity > best_specificity) {
+            best = Some((specificity, q));
+        }
+    }
+    best.map_or(0.0, |(_, q)| q)
+}
+
 pub fn routes() -> Router<AppState> {
-    Router::new().route(EVENTS_ROUTE, get(get_events_handler))
+    Router::new()
+        .route(EVENTS_ROUTE, get(get_events_handler))
+        .route(EVENTS_STREAM_ROUTE, get(stream_events_handler))
 }
 
 #[derive(OpenApi)]
-#[openapi(paths(get_events_handler), components(schemas(EventsList)))]
+#[openapi(
+    paths(get_events_handler, stream_events_handler),
+    components(schemas(EventsList, EventsJson, StoredEvent))
+)]
 pub struct EventsApiDoc;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn accepts(accept: &str) -> bool {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, HeaderValue::from_str(accept).unwrap());
+        accepts_json(&headers)
+    }
+
+    #[test]
+    fn test_accepts_json() {
+        assert!(!accepts_json(&HeaderMap::new()));
+        assert!(accepts("application/json"));
+        assert!(accepts("Application/JSON"));
+        assert!(accepts("text/plain;q=0.5, application/json"));
+        assert!(accepts("application/*, text/plain;q=0.9"));
+        assert!(!accepts("*/*"));
+        assert!(!accepts("text/plain, application/json;q=0.9"));
+        assert!(!accepts("application/json;q=0, */*"));
+        assert!(!accepts("application/jsonl"));
+        assert!(!accepts("text/html, application/json;q=0.8, */*;q=0.8"));
+    }
+}
@@ -0,0 +1,83 @@
+use crate::models::PubkyId;
+use crate::routes::AppState;
+use crate::routes::{Path, Query};
+use crate::{Error, Result};
+use axum::routing::get;
+use axum::{Json, Router};
+use nexus_common::models::homeserver::HomeserverView;
+use serde::Deserialize;
+use tracing::debug;
+use utoipa::OpenApi;
+
+use super::endpoints::{HOMESERVERS_ROUTE, HOMESERVER_ROUTE};
+
+#[derive(Deserialize)]
+pub struct HomeserversQuery {
+    skip: Option<usize>,
+    limit: Option<usize>,
+}
+
+#[utoipa::path(
+    get,
+    path = HOMESERVERS_ROUTE,
+    tag = "Homeserver",
+    description = "Known homeservers with the number of indexed users they host, the ones hosting the most users first",
+    params(
+        ("skip" = Option<usize>, Query, description = "Skip N homeservers"),
+        ("limit" = Option<usize>, Query, description = "Retrieve N homeservers (default 20, maximum 100)")
+    ),
+    responses(
+        (status = 200, description = "Homeservers", body = Vec<HomeserverView>),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn homeservers_handler(
+    Query(query): Query<HomeserversQuery>,
+) -> Result<Json<Vec<HomeserverView>>> {
+    debug!("GET {HOMESERVERS_ROUTE}");
+
+    let skip = query.skip.unwrap_or(0);
+    let limit = query.limit.unwrap_or(20).min(100);
+
+    Ok(Json(HomeserverView::list(skip, limit).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = HOMESERVER_ROUTE,
+    tag = "Homeserver",
+    description = "A known homeserver with the number of indexed users it hosts",
+    params(
+        ("homeserver_id" = PubkyId, Path, description = "Homeserver Pubky ID")
+    ),
+    responses(
+        (status = 200, description = "Homeserver", body = HomeserverView),
+        (status = 404, description = "Homeserver not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn homeserver_handler(
+    Path(homeserver_id): Path<PubkyId>,
+) -> Result<Json<HomeserverView>> {
+    debug!("GET {HOMESERVER_ROUTE}, homeserver_id:{homeserver_id}");
+
+    match HomeserverView::get_by_id(&homeserver_id).await? {
+        Some(homeserver) => Ok(Json(homeserver)),
+        None => Err(Error::HomeserverNotFound {
+            homeserver_id: homeserver_id.to_string(),
+        }),
+    }
+}
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route(HOMESERVERS_ROUTE, get(homeservers_handler))
+        .route(HOMESERVER_ROUTE, get(homeserver_handler))
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(homeservers_handler, homeserver_handler),
+    components(schemas(HomeserverView))
+)]
+pub struct HomeserverApiDoc;
@@ -1,15 +1,25 @@
 use std::path::PathBuf;
 
-use super::endpoints::INFO_ROUTE;
+use super::endpoints::{INFO_HOMESERVERS_ROUTE, INFO_REJECTED_HOMESERVERS_ROUTE, INFO_ROUTE};
 use crate::models::info::ServerInfo;
-use crate::routes::AppState;
+use crate::routes::{AppState, Query};
+use crate::Result;
 
 use axum::extract::State;
 use axum::response::IntoResponse;
 use axum::routing::get;
 use axum::{Json, Router};
+use nexus_common::models::homeserver::{AdmissionRejection, HomeserverStatus, RejectedHomeserver};
+use serde::Deserialize;
+use tracing::debug;
 use utoipa::OpenApi;
 
+#[derive(Deserialize)]
+pub struct HomeserversQuery {
+    skip: Option<usize>,
+    limit: Option<usize>,
+}
+
 #[utoipa::path(
     get,
     path = INFO_ROUTE,
@@ -24,12 +34,70 @@ pub async fn info_handler(State(app_state): State<AppState>) -> impl IntoRespons
     Json(info)
 }
 
+#[utoipa::path(
+    get,
+    path = INFO_HOMESERVERS_ROUTE,
+    tag = "Info",
+    description = "Watcher progress per homeserver, the ones with the oldest successful poll first",
+    params(
+        ("skip" = Option<usize>, Query, description = "Skip N homeservers"),
+        ("limit" = Option<usize>, Query, description = "Retrieve N homeservers (default 100, maximum 1000)")
+    ),
+    responses(
+        (status = 200, description = "Homeserver statuses", body = Vec<HomeserverStatus>),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn homeservers_handler(
+    Query(query): Query<HomeserversQuery>,
+) -> Result<Json<Vec<HomeserverStatus>>> {
+    debug!("GET {INFO_HOMESERVERS_ROUTE}");
+
+    let skip = query.skip.unwrap_or(0);
+    let limit = query.limit.unwrap_or(100).min(1000);
+
+    Ok(Json(HomeserverStatus::list(skip, limit).await?))
+}
+
+#[utoipa::path(
+    get,
+    path = INFO_REJECTED_HOMESERVERS_ROUTE,
+    tag = "Info",
+    description = "Homeservers refused by the homeserver admission policy, the most recently rejected first",
+    params(
+        ("skip" = Option<usize>, Query, description = "Skip N homeservers"),
+        ("limit" = Option<usize>, Query, description = "Retrieve N homeservers (default 100, maximum 1000)")
+    ),
+    responses(
+        (status = 200, description = "Rejected homeservers", body = Vec<RejectedHomeserver>),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn rejected_homeservers_handler(
+    Query(query): Query<HomeserversQuery>,
+) -> Result<Json<Vec<RejectedHomeserver>>> {
+    debug!("GET {INFO_REJECTED_HOMESERVERS_ROUTE}");
+
+    let skip = query.skip.unwrap_or(0);
+    let limit = query.limit.unwrap_or(100).min(1000);
+
+    Ok(Json(RejectedHomeserver::list(skip, limit).await?))
+}
+
 pub fn routes(app_state: AppState) -> Router<AppState> {
     Router::new()
         .with_state(app_state)
         .route(INFO_ROUTE, get(info_handler))
+        .route(INFO_HOMESERVERS_ROUTE, get(homeservers_handler))
+        .route(
+            INFO_REJECTED_HOMESERVERS_ROUTE,
+            get(rejected_homeservers_handler),
+        )
 }
 
 #[derive(OpenApi)]
-#[openapi(paths(info_handler), components(schemas(ServerInfo)))]
+#[openapi(
+    paths(info_handler, homeservers_handler, rejected_homeservers_handler),
+    components(schemas(ServerInfo, HomeserverStatus, RejectedHomeserver, AdmissionRejection))
+)]
 pub struct InfoApiDoc;
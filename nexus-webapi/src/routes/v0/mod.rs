@@ -1,10 +1,14 @@
 use axum::Router;
 use utoipa::OpenApi;
 
+pub mod admin;
+mod auth;
 pub mod bootstrap;
+pub mod dead_letter;
 pub mod endpoints;
 pub mod events;
 pub mod file;
+pub mod homeserver;
 pub mod info;
 pub mod notification;
 pub mod post;
@@ -15,6 +19,7 @@ pub mod tag;
 mod types;
 pub mod user;
 
+pub use auth::PUBKY_AUTH_SCHEME;
 pub use types::{TaggersInfoResponse, TagsQuery};
 
 use super::AppState;
@@ -31,6 +36,9 @@ pub fn routes(app_state: AppState) -> Router<AppState> {
     let route_notification = notification::routes();
     let route_bootstrap = bootstrap::routes();
     let route_events = events::routes();
+    let route_dead_letter = dead_letter::routes();
+    let route_homeserver = homeserver::routes();
+    let route_admin = admin::routes();
 
     routes_post
         .merge(routes_info)
@@ -43,6 +51,9 @@ pub fn routes(app_state: AppState) -> Router<AppState> {
         .merge(route_notification)
         .merge(route_bootstrap)
         .merge(route_events)
+        .merge(route_dead_letter)
+        .merge(route_homeserver)
+        .merge(route_admin)
 }
 
 #[derive(OpenApi)]
@@ -63,6 +74,9 @@ impl ApiDoc {
         combined.merge(resource::ResourceApiDoc::openapi());
         combined.merge(notification::NotificationApiDoc::merge_docs());
         combined.merge(events::EventsApiDoc::openapi());
+        combined.merge(dead_letter::DeadLetterApiDoc::openapi());
+        combined.merge(homeserver::HomeserverApiDoc::openapi());
+        combined.merge(admin::AdminApiDoc::openapi());
 
         combined
     }
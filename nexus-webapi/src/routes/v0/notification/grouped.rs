@@ -0,0 +1,83 @@
+use super::list::parse_notification_types;
+use crate::models::PubkyId;
+use crate::routes::v0::endpoints::NOTIFICATION_GROUPED_ROUTE;
+use crate::routes::Path;
+use crate::routes::Query;
+use crate::{Error, Result};
+use axum::Json;
+use nexus_common::models::notification::{
+    NotificationGroup, NotificationType, DEFAULT_GROUP_WINDOW,
+};
+use nexus_common::types::Pagination;
+use serde::de::{self, Deserializer};
+use serde::Deserialize;
+use tracing::debug;
+use utoipa::OpenApi;
+
+#[derive(Deserialize, Debug)]
+pub struct GroupedNotificationsQuery {
+    #[serde(flatten)]
+    pub pagination: Pagination,
+    #[serde(default, deserialize_with = "parse_notification_types")]
+    pub types: Option<Vec<NotificationType>>,
+    #[serde(default, deserialize_with = "parse_string_to_i64")]
+    pub window: Option<i64>,
+}
+
+// Flattened query params are all buffered as strings
+fn parse_string_to_i64<'de, D>(deserializer: D) -> std::result::Result<Option<i64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s: Option<String> = Option::deserialize(deserializer)?;
+    s.map(|s| s.parse::<i64>().map_err(de::Error::custom))
+        .transpose()
+}
+
+#[utoipa::path(
+    get,
+    path = NOTIFICATION_GROUPED_ROUTE,
+    tag = "User",
+    description = "List of user notifications, grouped by type, target post and/or tag label, and time window",
+    params(
+        ("user_id" = PubkyId, Path, description = "User Pubky ID"),
+        ("skip" = Option<usize>, Query, description = "Skip N groups"),
+        ("limit" = Option<usize>, Query, description = "Retrieve N groups, at most 100"),
+        ("start" = Option<String>, Query, description = "The start of the notifications timeframe. Notifications with a timestamp greater than this value are not grouped"),
+        ("end" = Option<String>, Query, description = "The end of the notifications timeframe. Notifications with a timestamp less than this value are not grouped"),
+        ("types" = Option<String>, Query, description = "Comma separated list of notification types to group, e.g. `tag_post,reply`. All types are grouped by default"),
+        ("window" = Option<i64>, Query, description = "Length of the time windows notifications are grouped within, in milliseconds. Defaults to one day")
+    ),
+    responses(
+        (status = 200, description = "List of notification groups", body = Vec<NotificationGroup>),
+        (status = 400, description = "Invalid window"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn grouped_notifications_handler(
+    Path(user_id): Path<PubkyId>,
+    Query(mut query): Query<GroupedNotificationsQuery>,
+) -> Result<Json<Vec<NotificationGroup>>> {
+    debug!(
+        "GET {NOTIFICATION_GROUPED_ROUTE} for user_id: {}, types: {:?}, window: {:?}",
+        user_id, query.types, query.window
+    );
+
+    let window = query.window.unwrap_or(DEFAULT_GROUP_WINDOW);
+    if window <= 0 {
+        return Err(Error::invalid_input("window must be positive"));
+    }
+    query.pagination.limit = Some(query.pagination.limit.unwrap_or(20).min(100));
+
+    let groups =
+        NotificationGroup::get_by_id(&user_id, query.pagination, query.types.as_deref(), window)
+            .await?;
+    Ok(Json(groups))
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(grouped_notifications_handler),
+    components(schemas(NotificationGroup, NotificationType))
+)]
+pub struct GroupedNotificationsApiDocs;
@@ -4,11 +4,57 @@ use crate::routes::Path;
 use crate::routes::Query;
 use crate::Result;
 use axum::Json;
-use nexus_common::models::notification::{Notification, NotificationBody, PostChangedSource};
+use nexus_common::models::notification::{
+    Notification, NotificationBody, NotificationType, PostChangedSource,
+};
 use nexus_common::types::Pagination;
+use serde::de::{self, Deserializer, IntoDeserializer};
+use serde::Deserialize;
 use tracing::debug;
 use utoipa::OpenApi;
 
+#[derive(Deserialize, Debug)]
+pub struct NotificationsQuery {
+    #[serde(flatten)]
+    pub pagination: Pagination,
+    #[serde(default, deserialize_with = "parse_string_to_bool")]
+    pub unread_only: bool,
+    #[serde(default, deserialize_with = "parse_notification_types")]
+    pub types: Option<Vec<NotificationType>>,
+}
+
+// Flattened query params are all buffered as strings
+fn parse_string_to_bool<'de, D>(deserializer: D) -> std::result::Result<bool, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s: Option<String> = Option::deserialize(deserializer)?;
+    match s {
+        Some(s) => s.parse::<bool>().map_err(de::Error::custom),
+        None => Ok(false),
+    }
+}
+
+// Parses a comma separated list of notification types, e.g. `mention,reply`
+pub(super) fn parse_notification_types<'de, D>(
+    deserializer: D,
+) -> std::result::Result<Option<Vec<NotificationType>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s: Option<String> = Option::deserialize(deserializer)?;
+    let Some(s) = s else {
+        return Ok(None);
+    };
+    s.split(',')
+        .map(|notification_type| {
+            NotificationType::deserialize(notification_type.trim().into_deserializer())
+        })
+        .collect::<std::result::Result<Vec<_>, de::value::Error>>()
+        .map(Some)
+        .map_err(de::Error::custom)
+}
+
 #[utoipa::path(
     get,
     path = NOTIFICATION_ROUTE,
@@ -19,7 +65,9 @@ use utoipa::OpenApi;
         ("skip" = Option<usize>, Query, description = "Skip N notifications"),
         ("limit" = Option<usize>, Query, description = "Retrieve N notifications"),
         ("start" = Option<String>, Query, description = "The start of the notifications timeframe. Notifications with a timestamp greater than this value will be excluded from the results"),
-        ("end" = Option<String>, Query, description = "The end of the notifications timeframe. Notifications with a timestamp less than this value will be excluded from the results")
+        ("end" = Option<String>, Query, description = "The end of the notifications timeframe. Notifications with a timestamp less than this value will be excluded from the results"),
+        ("unread_only" = Option<bool>, Query, description = "Only list the notifications more recent than the last read timestamp of the user"),
+        ("types" = Option<String>, Query, description = "Comma separated list of notification types to list, e.g. `mention,reply`. All types are listed by default")
     ),
     responses(
         (status = 200, description = "List of notifications", body = Vec<Notification>),
@@ -28,16 +76,33 @@ use utoipa::OpenApi;
 )]
 pub async fn list_notifications_handler(
     Path(user_id): Path<PubkyId>,
-    Query(pagination): Query<Pagination>,
+    Query(query): Query<NotificationsQuery>,
 ) -> Result<Json<Vec<Notification>>> {
-    debug!("GET {NOTIFICATION_ROUTE} for user_id: {}", user_id);
+    debug!(
+        "GET {NOTIFICATION_ROUTE} for user_id: {}, unread_only: {}, types: {:?}",
+        user_id, query.unread_only, query.types
+    );
 
-    Ok(Json(Notification::get_by_id(&user_id, pagination).await?))
+    let pagination = match query.unread_only {
+        true => Notification::unread_pagination(&user_id, query.pagination).await?,
+        false => query.pagination,
+    };
+    let notifications = match query.types {
+        Some(types) => Notification::get_by_types(&user_id, pagination, &types).await?,
+        None => Notification::get_by_id(&user_id, pagination).await?,
+    };
+    Ok(Json(notifications))
 }
 
 #[derive(OpenApi)]
 #[openapi(
     paths(list_notifications_handler,),
-    components(schemas(Notification, NotificationBody, PostChangedSource, PubkyId))
+    components(schemas(
+        Notification,
+        NotificationBody,
+        NotificationType,
+        PostChangedSource,
+        PubkyId
+    ))
 )]
 pub struct NotificationsApiDocs;
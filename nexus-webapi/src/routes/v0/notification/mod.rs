@@ -1,14 +1,44 @@
-use crate::routes::v0::endpoints::NOTIFICATION_ROUTE;
+use crate::routes::v0::endpoints::{
+    NOTIFICATION_GROUPED_ROUTE, NOTIFICATION_PREFERENCES_ROUTE, NOTIFICATION_ROUTE,
+    NOTIFICATION_SOCKET_ROUTE, NOTIFICATION_STREAM_ROUTE, NOTIFICATION_UNREAD_ROUTE,
+};
 use crate::routes::AppState;
 
 use axum::routing::get;
 use axum::Router;
 use utoipa::OpenApi;
 
+mod grouped;
 mod list;
+mod preferences;
+mod socket;
+mod stream;
+mod unread;
 
 pub fn routes() -> Router<AppState> {
-    Router::new().route(NOTIFICATION_ROUTE, get(list::list_notifications_handler))
+    Router::new()
+        .route(NOTIFICATION_ROUTE, get(list::list_notifications_handler))
+        .route(
+            NOTIFICATION_UNREAD_ROUTE,
+            get(unread::unread_notifications_handler),
+        )
+        .route(
+            NOTIFICATION_GROUPED_ROUTE,
+            get(grouped::grouped_notifications_handler),
+        )
+        .route(
+            NOTIFICATION_PREFERENCES_ROUTE,
+            get(preferences::get_notification_preferences_handler)
+                .put(preferences::put_notification_preferences_handler),
+        )
+        .route(
+            NOTIFICATION_STREAM_ROUTE,
+            get(stream::stream_notifications_handler),
+        )
+        .route(
+            NOTIFICATION_SOCKET_ROUTE,
+            get(socket::notification_socket_handler),
+        )
 }
 
 #[derive(OpenApi)]
@@ -17,6 +47,12 @@ pub struct NotificationApiDoc;
 
 impl NotificationApiDoc {
     pub fn merge_docs() -> utoipa::openapi::OpenApi {
-        list::NotificationsApiDocs::openapi()
+        let mut combined = list::NotificationsApiDocs::openapi();
+        combined.merge(unread::UnreadNotificationsApiDocs::openapi());
+        combined.merge(grouped::GroupedNotificationsApiDocs::openapi());
+        combined.merge(preferences::NotificationPreferencesApiDocs::openapi());
+        combined.merge(stream::NotificationStreamApiDocs::openapi());
+        combined.merge(socket::NotificationSocketApiDocs::openapi());
+        combined
     }
 }
@@ -0,0 +1,79 @@
+use crate::models::PubkyId;
+use crate::routes::v0::auth::authorize_user;
+use crate::routes::v0::endpoints::NOTIFICATION_PREFERENCES_ROUTE;
+use crate::routes::Json as RequestJson;
+use crate::routes::Path;
+use crate::Result;
+use axum::http::HeaderMap;
+use axum::Json;
+use nexus_common::models::notification::{
+    NotificationPreferences, NotificationType, PostChangedSource,
+};
+use tracing::debug;
+use utoipa::OpenApi;
+
+#[utoipa::path(
+    get,
+    path = NOTIFICATION_PREFERENCES_ROUTE,
+    tag = "User",
+    description = "Notification preferences of the user. Without stored preferences, nothing is muted",
+    params(
+        ("user_id" = PubkyId, Path, description = "User Pubky ID")
+    ),
+    responses(
+        (status = 200, description = "Notification preferences", body = NotificationPreferences),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_notification_preferences_handler(
+    Path(user_id): Path<PubkyId>,
+) -> Result<Json<NotificationPreferences>> {
+    debug!(
+        "GET {NOTIFICATION_PREFERENCES_ROUTE} for user_id: {}",
+        user_id
+    );
+
+    Ok(Json(NotificationPreferences::get_by_id(&user_id).await?))
+}
+
+#[utoipa::path(
+    put,
+    path = NOTIFICATION_PREFERENCES_ROUTE,
+    tag = "User",
+    description = "Replace the notification preferences of the user. Muted notifications are not stored from now on, the existing ones are kept. The request is authorized by a Pubky auth token signed by the user",
+    params(
+        ("user_id" = PubkyId, Path, description = "User Pubky ID"),
+        ("Authorization" = String, Header, description = "`Pubky` followed by the hex encoded auth token of the user")
+    ),
+    request_body = NotificationPreferences,
+    responses(
+        (status = 200, description = "Updated notification preferences", body = NotificationPreferences),
+        (status = 400, description = "Invalid preferences"),
+        (status = 401, description = "Missing or invalid auth token of the user"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn put_notification_preferences_handler(
+    headers: HeaderMap,
+    Path(user_id): Path<PubkyId>,
+    RequestJson(preferences): RequestJson<NotificationPreferences>,
+) -> Result<Json<NotificationPreferences>> {
+    debug!(
+        "PUT {NOTIFICATION_PREFERENCES_ROUTE} for user_id: {}, preferences: {:?}",
+        user_id, preferences
+    );
+    authorize_user(&headers, &user_id)?;
+
+    preferences.put_to_index(&user_id).await?;
+    Ok(Json(preferences))
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        get_notification_preferences_handler,
+        put_notification_preferences_handler
+    ),
+    components(schemas(NotificationPreferences, NotificationType, PostChangedSource))
+)]
+pub struct NotificationPreferencesApiDocs;
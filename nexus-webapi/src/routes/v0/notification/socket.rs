@@ -0,0 +1,99 @@
+use super::stream::{notification_stream, LastEventId};
+use crate::models::PubkyId;
+use crate::routes::v0::endpoints::NOTIFICATION_SOCKET_ROUTE;
+use crate::routes::Path;
+use crate::routes::Query;
+use crate::Result;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::response::Response;
+use futures_util::{Stream, StreamExt};
+use nexus_common::models::notification::Notification;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+use utoipa::{OpenApi, ToSchema};
+
+#[derive(Deserialize, Debug)]
+pub struct NotificationSocketQuery {
+    pub since: Option<i64>,
+    pub last_event_id: Option<String>,
+}
+
+/// Message sent over the WebSocket for each notification
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct NotificationMessage {
+    /// Id of the notification, to resume from with `last_event_id` on reconnection
+    pub id: String,
+    #[serde(flatten)]
+    pub notification: Notification,
+}
+
+#[utoipa::path(
+    get,
+    path = NOTIFICATION_SOCKET_ROUTE,
+    tag = "User",
+    description = "WebSocket stream of the user notifications, as they are indexed, the counterpart of the Server-Sent Events stream for clients that prefer WebSockets. Each notification is sent as a JSON text message with its id. Messages from the client are ignored.",
+    params(
+        ("user_id" = PubkyId, Path, description = "User Pubky ID"),
+        ("since" = Option<i64>, Query, description = "Timestamp (ms) of the last received notification. More recent notifications are sent before the live ones. Overridden by `last_event_id`"),
+        ("last_event_id" = Option<String>, Query, description = "Id of the last received notification. The notifications received after it are sent before the live ones")
+    ),
+    responses(
+        (status = 101, description = "Switching to the WebSocket protocol", body = NotificationMessage),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn notification_socket_handler(
+    Path(user_id): Path<PubkyId>,
+    Query(query): Query<NotificationSocketQuery>,
+    socket: WebSocketUpgrade,
+) -> Result<Response> {
+    debug!(
+        "GET {NOTIFICATION_SOCKET_ROUTE} for user_id: {}, last_event_id: {:?}, since: {:?}",
+        user_id, query.last_event_id, query.since
+    );
+    let last_event_id = query.last_event_id.as_deref().and_then(LastEventId::parse);
+
+    // Subscribed before the upgrade, so failures are answered with an HTTP error
+    let notifications = notification_stream(&user_id, last_event_id, query.since).await?;
+    Ok(socket.on_upgrade(move |socket| relay(socket, notifications)))
+}
+
+/// Sends the notifications over the socket until either side closes it
+async fn relay(mut socket: WebSocket, notifications: impl Stream<Item = Notification> + Send) {
+    let mut notifications = std::pin::pin!(notifications);
+    loop {
+        tokio::select! {
+            notification = notifications.next() => {
+                let Some(notification) = notification else {
+                    break;
+                };
+                let message = NotificationMessage {
+                    id: notification.id(),
+                    notification,
+                };
+                let text = match serde_json::to_string(&message) {
+                    Ok(text) => text,
+                    Err(e) => {
+                        warn!("Failed to serialize notification {}: {e}", message.id);
+                        continue;
+                    }
+                };
+                if socket.send(Message::Text(text.into())).await.is_err() {
+                    break;
+                }
+            }
+            message = socket.recv() => match message {
+                // Pings are answered by axum, other messages are ignored
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            }
+        }
+    }
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(notification_socket_handler),
+    components(schemas(NotificationMessage))
+)]
+pub struct NotificationSocketApiDocs;
@@ -0,0 +1,148 @@
+use crate::models::PubkyId;
+use crate::routes::v0::endpoints::NOTIFICATION_STREAM_ROUTE;
+use crate::routes::Path;
+use crate::routes::Query;
+use crate::Result;
+use axum::http::HeaderMap;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use futures_util::{stream, Stream, StreamExt};
+use nexus_common::models::notification::Notification;
+use nexus_common::types::Pagination;
+use serde::Deserialize;
+use std::collections::HashSet;
+use tracing::debug;
+use utoipa::OpenApi;
+
+/// Maximum number of missed notifications sent when a client reconnects
+const MAX_MISSED_NOTIFICATIONS: usize = 100;
+
+/// Header set by `EventSource` clients on reconnection, with the id of the last received event
+const LAST_EVENT_ID_HEADER: &str = "Last-Event-ID";
+
+#[derive(Deserialize, Debug)]
+pub struct NotificationStreamQuery {
+    pub since: Option<i64>,
+}
+
+/// Value of the `Last-Event-ID` header: the id of a notification, see [Notification::id], or a
+/// bare timestamp
+#[derive(Debug, PartialEq)]
+pub(super) struct LastEventId {
+    timestamp: i64,
+    id: Option<String>,
+}
+
+impl LastEventId {
+    pub(super) fn parse(value: &str) -> Option<Self> {
+        let (timestamp, id) = match value.split_once('-') {
+            Some((timestamp, _)) => (timestamp, Some(value.to_string())),
+            None => (value, None),
+        };
+        let timestamp = timestamp.parse::<i64>().ok()?;
+        Some(Self { timestamp, id })
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = NOTIFICATION_STREAM_ROUTE,
+    tag = "User",
+    description = "Server-Sent Events stream of the user notifications, as they are indexed. Each event has the `notification` type, the notification id (its timestamp and a hash of its body, e.g. `1700000000000-3f2a9c0d1e4b5a67`) as id and the notification as JSON data. On reconnection, the notifications received after the one of the `Last-Event-ID` header (or more recent than the `since` param) are sent first.",
+    params(
+        ("user_id" = PubkyId, Path, description = "User Pubky ID"),
+        ("since" = Option<i64>, Query, description = "Timestamp (ms) of the last received notification. More recent notifications are sent before the live ones. Overridden by the `Last-Event-ID` header"),
+        ("Last-Event-ID" = Option<String>, Header, description = "Id of the last received event, set by `EventSource` clients on reconnection")
+    ),
+    responses(
+        (status = 200, description = "Stream of notifications", content_type = "text/event-stream", body = Notification),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn stream_notifications_handler(
+    Path(user_id): Path<PubkyId>,
+    Query(query): Query<NotificationStreamQuery>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = std::result::Result<Event, axum::Error>>>> {
+    let last_event_id = headers
+        .get(LAST_EVENT_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(LastEventId::parse);
+    debug!(
+        "GET {NOTIFICATION_STREAM_ROUTE} for user_id: {}, last_event_id: {:?}, since: {:?}",
+        user_id, last_event_id, query.since
+    );
+    let notifications = notification_stream(&user_id, last_event_id, query.since).await?;
+
+    let events = notifications.map(|notification| {
+        Event::default()
+            .event("notification")
+            .id(notification.id())
+            .json_data(&notification)
+    });
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+/// Notifications of the user as they are indexed, preceded by the ones received after
+/// `last_event_id`, or more recent than `since` if not given
+pub(super) async fn notification_stream(
+    user_id: &str,
+    last_event_id: Option<LastEventId>,
+    since: Option<i64>,
+) -> Result<impl Stream<Item = Notification> + Send> {
+    // A timestamp excludes the notifications of that timestamp, while a notification id only
+    // excludes the notification it identifies
+    let (since, mut sent) = match last_event_id {
+        Some(LastEventId {
+            timestamp,
+            id: Some(id),
+        }) => (Some(timestamp), HashSet::from([id])),
+        Some(LastEventId {
+            timestamp,
+            id: None,
+        }) => (Some(timestamp + 1), HashSet::new()),
+        None => (since.map(|since| since + 1), HashSet::new()),
+    };
+
+    // Subscribe before reading the missed notifications, so none is lost in between
+    let live = Notification::subscribe(user_id).await?;
+
+    let missed = match since {
+        Some(since) => {
+            let pagination = Pagination {
+                limit: Some(MAX_MISSED_NOTIFICATIONS),
+                end: Some(since as f64),
+                ..Default::default()
+            };
+            let mut missed = Notification::get_by_id(user_id, pagination).await?;
+            // Send the oldest first, like the live notifications
+            missed.reverse();
+            missed.retain(|notification| !sent.contains(&notification.id()));
+            missed
+        }
+        None => Vec::new(),
+    };
+
+    // Live notifications already sent are skipped: the ones older than the last sent one, and
+    // the sent ones sharing its timestamp
+    let floor = missed
+        .last()
+        .map(|notification| notification.timestamp)
+        .or(since);
+    sent.extend(
+        missed
+            .iter()
+            .filter(|notification| Some(notification.timestamp) == floor)
+            .map(Notification::id),
+    );
+    let live = live.filter(move |notification| {
+        let is_new = floor.is_none_or(|floor| notification.timestamp >= floor)
+            && !sent.contains(&notification.id());
+        async move { is_new }
+    });
+
+    Ok(stream::iter(missed).chain(live))
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(stream_notifications_handler))]
+pub struct NotificationStreamApiDocs;
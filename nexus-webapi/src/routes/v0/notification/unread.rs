@@ -0,0 +1,51 @@
+use crate::models::PubkyId;
+use crate::routes::v0::endpoints::NOTIFICATION_UNREAD_ROUTE;
+use crate::routes::Path;
+use crate::Result;
+use axum::Json;
+use nexus_common::models::notification::{LastRead, Notification};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+use utoipa::{OpenApi, ToSchema};
+
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+pub struct UnreadNotifications {
+    /// Number of notifications more recent than `last_read`
+    pub unread_count: usize,
+    /// Timestamp (ms) of the last read notification, as published by the user.
+    /// Without it, all the notifications of the user are unread.
+    pub last_read: Option<i64>,
+}
+
+#[utoipa::path(
+    get,
+    path = NOTIFICATION_UNREAD_ROUTE,
+    tag = "User",
+    description = "Number of unread user notifications",
+    params(
+        ("user_id" = PubkyId, Path, description = "User Pubky ID")
+    ),
+    responses(
+        (status = 200, description = "Unread notifications", body = UnreadNotifications),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn unread_notifications_handler(
+    Path(user_id): Path<PubkyId>,
+) -> Result<Json<UnreadNotifications>> {
+    debug!("GET {NOTIFICATION_UNREAD_ROUTE} for user_id: {}", user_id);
+
+    let last_read = LastRead::get_from_index(&user_id).await?;
+    let unread_count = Notification::unread_count(&user_id).await?;
+    Ok(Json(UnreadNotifications {
+        unread_count,
+        last_read: last_read.map(|last_read| last_read.timestamp),
+    }))
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(unread_notifications_handler),
+    components(schemas(UnreadNotifications))
+)]
+pub struct UnreadNotificationsApiDocs;
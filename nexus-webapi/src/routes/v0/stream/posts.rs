@@ -1,12 +1,13 @@
 use crate::models::{GlobalPostId, GlobalPostIds, PostId, PostStreamDetailed, PubkyId, Tags};
 use crate::routes::v0::endpoints::{
-    STREAM_POSTS_BY_IDS_ROUTE, STREAM_POSTS_ROUTE, STREAM_POST_KEYS_ROUTE,
+    STREAM_POSTS_BY_IDS_ROUTE, STREAM_POSTS_FEED_ROUTE, STREAM_POSTS_ROUTE, STREAM_POST_KEYS_ROUTE,
 };
 use crate::routes::Json as RequestJson;
-use crate::routes::Query;
+use crate::routes::{Path, Query};
 use crate::{Error, Result as AppResult};
 use axum::Json;
 use nexus_common::db::kv::SortOrder;
+use nexus_common::models::feed::FeedDetails;
 use nexus_common::types::StreamSorting;
 use nexus_common::{
     models::post::{PostKeyStream, PostStream, StreamSource},
@@ -302,6 +303,73 @@ pub async fn stream_post_keys_handler(
     }
 }
 
+#[derive(Deserialize)]
+pub struct FeedPath {
+    pub user_id: PubkyId,
+    pub feed_id: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct FeedStreamQuery {
+    pub viewer_id: Option<PubkyId>,
+    #[serde(flatten)]
+    pub pagination: Pagination,
+    #[serde(default)]
+    pub include_attachment_metadata: bool,
+}
+
+#[utoipa::path(
+    get,
+    path = STREAM_POSTS_FEED_ROUTE,
+    tag = "Stream",
+    description = "Stream Posts of a feed: runs the reach, tags, sort and content kind saved by the user in the feed, so clients get the same results on every device. The reach is taken from the owner of the feed.",
+    params(
+        ("user_id" = PubkyId, Path, description = "Pubky ID of the owner of the feed"),
+        ("feed_id" = String, Path, description = "Feed ID"),
+        ("viewer_id" = Option<PubkyId>, Query, description = "Viewer Pubky ID"),
+        ("skip" = Option<usize>, Query, description = "Skip N posts"),
+        ("limit" = Option<usize>, Query, description = "Retrieve N posts"),
+        ("start" = Option<usize>, Query, description = "The start of the stream timeframe or score. Posts with a timestamp/score greater than this value will be excluded from the results"),
+        ("end" = Option<usize>, Query, description = "The end of the stream timeframe or score. Posts with a timestamp/score less than this value will be excluded from the results"),
+        ("include_attachment_metadata" = Option<bool>, Query, description = "Include file metadata for post attachments"),
+    ),
+    responses(
+        (status = 200, description = "Posts stream", body = PostStreamDetailed),
+        (status = 404, description = "Feed not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn stream_feed_posts_handler(
+    Path(FeedPath { user_id, feed_id }): Path<FeedPath>,
+    Query(mut query): Query<FeedStreamQuery>,
+) -> AppResult<Json<PostStreamDetailed>> {
+    debug!(
+        "GET {STREAM_POSTS_FEED_ROUTE} user_id:{}, feed_id:{}",
+        user_id, feed_id
+    );
+
+    let Some(feed) = FeedDetails::get_by_id(&user_id, &feed_id).await? else {
+        return Err(Error::FeedNotFound {
+            user_id: user_id.to_string(),
+            feed_id,
+        });
+    };
+
+    query.pagination.skip.get_or_insert(0);
+    query.pagination.limit = Some(query.pagination.limit.unwrap_or(10).min(30));
+
+    match feed
+        .get_posts(query.pagination, query.viewer_id.as_deref())
+        .await?
+    {
+        Some(stream) => Ok(Json(
+            PostStreamDetailed::from_post_views(stream.0, query.include_attachment_metadata)
+                .await?,
+        )),
+        None => Ok(Json(PostStreamDetailed::default())),
+    }
+}
+
 #[derive(ToSchema, Deserialize)]
 pub struct PostStreamByIdsRequest {
     pub post_ids: GlobalPostIds,
@@ -345,7 +413,8 @@ pub async fn stream_posts_by_ids_handler(
     paths(
         stream_posts_handler,
         stream_post_keys_handler,
-        stream_posts_by_ids_handler
+        stream_posts_by_ids_handler,
+        stream_feed_posts_handler
     ),
     components(schemas(
         PostKeyStream,
@@ -0,0 +1,47 @@
+use crate::models::PubkyId;
+use crate::routes::v0::endpoints::USER_FEEDS_ROUTE;
+use crate::routes::Path;
+use crate::routes::Query;
+use crate::Result;
+use axum::Json;
+use nexus_common::models::feed::FeedDetails;
+use nexus_common::types::Pagination;
+use pubky_app_specs::{PubkyAppFeedLayout, PubkyAppFeedReach, PubkyAppFeedSort};
+use tracing::debug;
+use utoipa::OpenApi;
+
+#[utoipa::path(
+    get,
+    path = USER_FEEDS_ROUTE,
+    description = "List user's feeds, the most recently created first",
+    tag = "User",
+    params(
+        ("user_id" = PubkyId, Path, description = "User Pubky ID"),
+        ("skip" = Option<usize>, Query, description = "Skip N feeds"),
+        ("limit" = Option<usize>, Query, description = "Retrieve N feeds (default 50, max 100)")
+    ),
+    responses(
+        (status = 200, description = "User feeds", body = Vec<FeedDetails>),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn user_feeds_handler(
+    Path(user_id): Path<PubkyId>,
+    Query(query): Query<Pagination>,
+) -> Result<Json<Vec<FeedDetails>>> {
+    debug!("GET {USER_FEEDS_ROUTE} user_id:{}", user_id);
+
+    let skip = query.skip.unwrap_or(0);
+    let limit = query.limit.unwrap_or(50).min(100);
+
+    Ok(Json(
+        FeedDetails::get_by_owner(&user_id, Some(skip), Some(limit)).await?,
+    ))
+}
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(user_feeds_handler),
+    components(schemas(FeedDetails, PubkyAppFeedReach, PubkyAppFeedLayout, PubkyAppFeedSort))
+)]
+pub struct UserFeedsApiDoc;
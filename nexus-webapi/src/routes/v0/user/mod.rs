@@ -1,6 +1,7 @@
 use crate::routes::v0::endpoints::{
-    RELATIONSHIP_ROUTE, USER_COUNTS_ROUTE, USER_DETAILS_ROUTE, USER_FOLLOWERS_ROUTE,
-    USER_FOLLOWING_ROUTE, USER_FRIENDS_ROUTE, USER_ROUTE, USER_TAGGERS_ROUTE, USER_TAGS_ROUTE,
+    RELATIONSHIP_ROUTE, USER_COUNTS_ROUTE, USER_DETAILS_ROUTE, USER_FEEDS_ROUTE,
+    USER_FOLLOWERS_ROUTE, USER_FOLLOWING_ROUTE, USER_FRIENDS_ROUTE, USER_ROUTE, USER_TAGGERS_ROUTE,
+    USER_TAGS_ROUTE,
 };
 use crate::routes::AppState;
 
@@ -10,6 +11,7 @@ use utoipa::OpenApi;
 
 mod counts;
 mod details;
+mod feeds;
 mod follows;
 mod relationship;
 pub mod tags;
@@ -29,6 +31,7 @@ pub fn routes() -> Router<AppState> {
         .route(USER_FOLLOWERS_ROUTE, get(follows::user_followers_handler))
         .route(USER_FOLLOWING_ROUTE, get(follows::user_following_handler))
         .route(USER_FRIENDS_ROUTE, get(follows::user_friends_handler))
+        .route(USER_FEEDS_ROUTE, get(feeds::user_feeds_handler))
 }
 
 #[derive(OpenApi)]
@@ -43,6 +46,7 @@ impl UserApiDoc {
         combined.merge(relationship::RelationshipApiDoc::openapi());
         combined.merge(tags::UserTagsApiDoc::openapi());
         combined.merge(follows::UserFollowsApiDoc::openapi());
+        combined.merge(feeds::UserFeedsApiDoc::openapi());
         combined
     }
 }
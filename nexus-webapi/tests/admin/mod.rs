@@ -0,0 +1,23 @@
+use crate::utils::host_url;
+use crate::utils::server::TEST_ADMIN_TOKEN;
+use anyhow::Result;
+use axum::http::StatusCode;
+use serde_json::Value;
+
+mod resync;
+mod webhooks;
+
+/// Sends a request to the admin API with the test admin token
+async fn admin_request(post: bool, endpoint: &str) -> Result<(StatusCode, Value)> {
+    let url = format!("{}{endpoint}", host_url().await);
+    let client = httpc_test::new_client("")?;
+    let request = if post {
+        client.reqwest_client().post(url)
+    } else {
+        client.reqwest_client().get(url)
+    };
+    let response = request.bearer_auth(TEST_ADMIN_TOKEN).send().await?;
+    let status = StatusCode::from_u16(response.status().as_u16())?;
+    let body = serde_json::from_str(&response.text().await?).unwrap_or(Value::Null);
+    Ok((status, body))
+}
@@ -0,0 +1,42 @@
+use super::admin_request;
+use crate::utils::{invalid_get_request, invalid_post_request};
+use anyhow::Result;
+use axum::http::StatusCode;
+use pubky::Keypair;
+use serde_json::Value;
+
+#[tokio_shared_rt::test(shared)]
+async fn test_user_resync_requires_admin_token() -> Result<()> {
+    let user_id = Keypair::random().public_key().to_z32();
+    let endpoint = format!("/v0/admin/user/{user_id}/resync");
+
+    invalid_post_request(&endpoint, Value::Null, StatusCode::UNAUTHORIZED).await?;
+    invalid_get_request(&endpoint, StatusCode::UNAUTHORIZED).await?;
+
+    Ok(())
+}
+
+#[tokio_shared_rt::test(shared)]
+async fn test_user_resync_request() -> Result<()> {
+    let user_id = Keypair::random().public_key().to_z32();
+    let endpoint = format!("/v0/admin/user/{user_id}/resync");
+
+    let (status, _) = admin_request(false, &endpoint).await?;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, request) = admin_request(true, &endpoint).await?;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    assert_eq!(request["user_id"], user_id.as_str());
+    assert_eq!(request["status"], "queued");
+
+    // A queued resync is not queued again
+    let (status, requeued) = admin_request(true, &endpoint).await?;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    assert_eq!(requeued["requested_at"], request["requested_at"]);
+
+    let (status, fetched) = admin_request(false, &endpoint).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(fetched, request);
+
+    Ok(())
+}
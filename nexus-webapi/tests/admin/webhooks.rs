@@ -0,0 +1,73 @@
+use super::admin_request;
+use crate::utils::invalid_get_request;
+use anyhow::Result;
+use axum::http::StatusCode;
+use chrono::Utc;
+use nexus_common::models::notification::NotificationType;
+use nexus_common::models::webhook::{DeliveryStatus, WebhookDelivery};
+use pubky::Keypair;
+
+fn delivery(id: &str, status: DeliveryStatus, timestamp: i64) -> WebhookDelivery {
+    WebhookDelivery {
+        id: id.to_string(),
+        event_type: NotificationType::Mention,
+        user_id: Keypair::random().public_key().to_z32(),
+        status,
+        attempts: 1,
+        status_code: Some(200),
+        error: None,
+        timestamp,
+    }
+}
+
+#[tokio_shared_rt::test(shared)]
+async fn test_webhook_deliveries() -> Result<()> {
+    let name = format!("test-{}", Utc::now().timestamp_micros());
+    let endpoint = format!("/v0/admin/webhooks/{name}/deliveries");
+
+    invalid_get_request(&endpoint, StatusCode::UNAUTHORIZED).await?;
+
+    let (status, deliveries) = admin_request(false, &endpoint).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(deliveries, serde_json::json!([]));
+
+    let now = Utc::now().timestamp_millis();
+    let mut failed = delivery("failed", DeliveryStatus::Failed, now - 1000);
+    failed.attempts = 5;
+    failed.status_code = Some(503);
+    failed.error = Some("Unexpected status 503 Service Unavailable".to_string());
+    for delivery in [
+        failed,
+        delivery("delivered", DeliveryStatus::Delivered, now),
+        // Past the retention period, dropped from the log by the next delivery
+        delivery(
+            "expired",
+            DeliveryStatus::Delivered,
+            now - 8 * 24 * 60 * 60 * 1000,
+        ),
+        delivery("latest", DeliveryStatus::Delivered, now + 1000),
+    ] {
+        delivery
+            .put_to_index(&name)
+            .await
+            .map_err(|e| anyhow::anyhow!("{e}"))?;
+    }
+
+    let (status, deliveries) = admin_request(false, &endpoint).await?;
+    assert_eq!(status, StatusCode::OK);
+    let ids: Vec<&str> = deliveries
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|delivery| delivery["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(ids, vec!["latest", "delivered", "failed"]);
+    assert_eq!(deliveries[2]["status"], "failed");
+    assert_eq!(deliveries[2]["attempts"], 5);
+    assert_eq!(deliveries[2]["type"], "mention");
+
+    let (_, deliveries) = admin_request(false, &format!("{endpoint}?skip=1&limit=1")).await?;
+    assert_eq!(deliveries[0]["id"], "delivered");
+
+    Ok(())
+}
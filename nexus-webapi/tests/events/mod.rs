@@ -1,5 +1,12 @@
 use crate::utils::host_url;
+use crate::utils::sse::SseStream;
 use anyhow::Result;
+use nexus_common::models::event::{Event, ParseResult};
+use pubky::Keypair;
+use serde_json::Value;
+use std::path::PathBuf;
+
+const TEST_HOMESERVER: &str = "8um71us3fyw6h8wbcxb5ar3rwusy1a6u49956ikzojg3gcwd1dty";
 
 /// Regression tests for overflow-safe cursor/limit handling (PR #683).
 
@@ -52,3 +59,98 @@ async fn test_events_large_cursor_with_limit() -> Result<()> {
 
     Ok(())
 }
+
+/// Stores an event line as the watcher does once it is indexed
+async fn store_event(line: &str) -> Result<()> {
+    let ParseResult::Parsed(event) = Event::parse_event(line, PathBuf::from("/tmp"))? else {
+        anyhow::bail!("Unexpected event line {line}");
+    };
+    event.with_homeserver(TEST_HOMESERVER).store_event().await?;
+    Ok(())
+}
+
+#[tokio_shared_rt::test(shared)]
+async fn test_events_filtered() -> Result<()> {
+    let url = host_url().await;
+    let client = httpc_test::new_client("")?;
+
+    let user_id = Keypair::random().public_key().to_z32();
+    let profile_line = format!("PUT pubky://{user_id}/pub/pubky.app/profile.json");
+    let put_post_line = format!("PUT pubky://{user_id}/pub/pubky.app/posts/0032SSN7Q4EVG");
+    let del_post_line = format!("DEL pubky://{user_id}/pub/pubky.app/posts/0032SSN7Q4EVG");
+    let cursor = Event::count_stored_events().await?;
+    store_event(&profile_line).await?;
+    store_event(&put_post_line).await?;
+    store_event(&del_post_line).await?;
+
+    // The JSON format has the stored timestamp and homeserver of every line
+    let response = client
+        .reqwest_client()
+        .get(format!(
+            "{url}/v0/events?cursor={cursor}&user_id={user_id}&resource=posts"
+        ))
+        .header("Accept", "application/json")
+        .send()
+        .await?;
+    assert_eq!(response.status(), 200);
+    let body: Value = serde_json::from_str(&response.text().await?)?;
+    let events = body["events"].as_array().unwrap();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0]["line"], put_post_line);
+    assert_eq!(events[1]["line"], del_post_line);
+    assert_eq!(events[0]["homeserver"], TEST_HOMESERVER);
+    assert!(events[0]["timestamp"].as_i64().is_some());
+    assert!(body["cursor"].as_u64().unwrap() >= cursor + 3);
+
+    // The plain text format is filtered alike
+    let res = client
+        .do_get(&format!(
+            "{url}/v0/events?cursor={cursor}&user_id={user_id}&type=DEL"
+        ))
+        .await?;
+    assert_eq!(res.status(), 200);
+    let body = res.text_body()?;
+    let lines: Vec<&str> = body.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0], del_post_line);
+    assert!(lines[1].starts_with("cursor: "));
+
+    let res = client
+        .do_get(&format!("{url}/v0/events?type=PATCH"))
+        .await?;
+    assert_eq!(res.status(), 400);
+
+    Ok(())
+}
+
+/// Stores an event, opens the event stream of its user from before it, and verifies it is sent
+/// first, followed by a new event as it is stored.
+#[tokio_shared_rt::test(shared)]
+async fn test_stream_events() -> Result<()> {
+    // Starts the test server, connected to Redis
+    host_url().await;
+
+    let user_id = Keypair::random().public_key().to_z32();
+    let profile_line = format!("PUT pubky://{user_id}/pub/pubky.app/profile.json");
+    let post_line = format!("PUT pubky://{user_id}/pub/pubky.app/posts/0032SSN7Q4EVG");
+    let cursor = Event::count_stored_events().await?;
+    store_event(&profile_line).await?;
+
+    let endpoint = format!("/v0/events/stream?user_id={user_id}");
+    let last_event_id = cursor.to_string();
+    let mut stream =
+        SseStream::connect(&endpoint, &[("Last-Event-ID", last_event_id.as_str())]).await?;
+
+    // The event stored after the last received one is sent first
+    let events = stream.read_events(1).await?;
+    assert_eq!(events[0].data["line"], profile_line);
+    assert_eq!(events[0].data["homeserver"], TEST_HOMESERVER);
+    assert!(events[0].id.parse::<u64>()? > cursor);
+
+    // Then the events are pushed as they are stored
+    store_event(&post_line).await?;
+    let events = stream.read_events(1).await?;
+    assert_eq!(events[0].data["line"], post_line);
+
+    Ok(())
+}
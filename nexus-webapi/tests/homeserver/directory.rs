@@ -0,0 +1,54 @@
+use crate::utils::{get_request, invalid_get_request};
+use anyhow::Result;
+use axum::http::StatusCode;
+use nexus_common::models::homeserver::Homeserver;
+use pubky::Keypair;
+use pubky_app_specs::PubkyId;
+
+#[tokio_shared_rt::test(shared)]
+async fn test_get_homeserver() -> Result<()> {
+    // Ensure the test server (and its stack) is up before touching the graph directly
+    get_request("/v0/info").await?;
+
+    let hs_id = PubkyId::from(Keypair::random().public_key());
+    let homeserver = Homeserver::new(hs_id.clone());
+    homeserver.put_to_graph().await?;
+    homeserver.put_to_index().await?;
+
+    let body = get_request(&format!("/v0/homeserver/{hs_id}")).await?;
+    assert_eq!(body["id"], hs_id.to_string());
+    assert_eq!(body["users"], 0);
+    assert_eq!(body["cursor"], homeserver.cursor.as_str());
+    assert!(body["first_seen_at"].is_i64());
+
+    Ok(())
+}
+
+#[tokio_shared_rt::test(shared)]
+async fn test_get_homeserver_not_found() -> Result<()> {
+    let hs_id = Keypair::random().public_key().to_z32();
+    invalid_get_request(&format!("/v0/homeserver/{hs_id}"), StatusCode::NOT_FOUND).await?;
+
+    Ok(())
+}
+
+#[tokio_shared_rt::test(shared)]
+async fn test_get_homeservers() -> Result<()> {
+    get_request("/v0/info").await?;
+    Homeserver::new(PubkyId::from(Keypair::random().public_key()))
+        .put_to_graph()
+        .await?;
+
+    let body = get_request("/v0/homeservers?limit=100").await?;
+    let homeservers = body.as_array().expect("Homeservers should be an array");
+    assert!(!homeservers.is_empty());
+
+    // Homeservers are ordered by the number of users they host
+    let users: Vec<u64> = homeservers
+        .iter()
+        .map(|homeserver| homeserver["users"].as_u64().unwrap())
+        .collect();
+    assert!(users.windows(2).all(|pair| pair[0] >= pair[1]));
+
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use crate::utils::{get_request, invalid_get_request};
+use anyhow::Result;
+use axum::http::StatusCode;
+use nexus_common::models::event::{DeadLetterEvent, EventProcessorError};
+
+#[tokio_shared_rt::test(shared)]
+async fn test_get_dead_letter_event() -> Result<()> {
+    // Ensure the test server (and its stack) is up before touching Redis directly
+    get_request("/v0/info").await?;
+
+    let index_key = "PUT:test_get_dead_letter_event:post:0032QB10HCRHG";
+    let dead_letter = DeadLetterEvent::new(
+        index_key.to_string(),
+        Some("PUT pubky://test_get_dead_letter_event/pub/pubky.app/posts/0032QB10HCRHG".into()),
+        None,
+        EventProcessorError::missing_dependencies(vec!["parent".into()]),
+        10,
+        0,
+    );
+    dead_letter.put_to_index().await?;
+
+    let body = get_request(&format!("/v0/info/dead-letter/event?key={index_key}")).await?;
+    assert_eq!(body["index_key"], index_key);
+    assert_eq!(body["retry_count"], 10);
+    assert_eq!(body["reason"], "MissingDependency: Could not be indexed");
+
+    let body = get_request("/v0/info/dead-letter?limit=100").await?;
+    let listed = body
+        .as_array()
+        .expect("Dead-letter list should be an array");
+    assert!(listed.iter().any(|event| event["index_key"] == index_key));
+
+    assert_eq!(DeadLetterEvent::remove_from_index(index_key).await?, 1);
+    assert_eq!(DeadLetterEvent::remove_from_index(index_key).await?, 0);
+    invalid_get_request(
+        &format!("/v0/info/dead-letter/event?key={index_key}"),
+        StatusCode::NOT_FOUND,
+    )
+    .await?;
+
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use crate::utils::get_request;
+use anyhow::Result;
+use nexus_common::models::homeserver::{AdmissionRejection, HomeserverStatus, RejectedHomeserver};
+use pubky::Keypair;
+
+#[tokio_shared_rt::test(shared)]
+async fn test_get_homeserver_statuses() -> Result<()> {
+    // Ensure the test server (and its stack) is up before touching Redis directly
+    get_request("/v0/info").await?;
+
+    // A homeserver that never had a successful poll is listed first
+    let hs_id = Keypair::random().public_key().to_z32();
+    let status = HomeserverStatus {
+        id: hs_id.clone(),
+        cursor: Some("0000000000000".into()),
+        last_run_at: 1_700_000_000_000,
+        last_run_status: "Timeout".into(),
+        last_success_at: None,
+        consecutive_failures: 3,
+        backoff_until: Some(1_700_000_240_000),
+        events_processed: 0,
+    };
+    status.put_to_index().await?;
+
+    let body = get_request("/v0/info/homeservers?limit=1000").await?;
+    let statuses = body
+        .as_array()
+        .expect("Homeserver statuses should be an array");
+    let listed = statuses
+        .iter()
+        .find(|status| status["id"] == hs_id.as_str())
+        .expect("Homeserver status should be listed");
+
+    assert_eq!(listed["last_run_status"], "Timeout");
+    assert_eq!(listed["consecutive_failures"], 3);
+    assert_eq!(listed["backoff_until"], 1_700_000_240_000i64);
+    assert!(listed["last_success_at"].is_null());
+
+    Ok(())
+}
+
+#[tokio_shared_rt::test(shared)]
+async fn test_get_rejected_homeservers() -> Result<()> {
+    get_request("/v0/info").await?;
+
+    let hs_id = Keypair::random().public_key().to_z32();
+    let user_id = Keypair::random().public_key().to_z32();
+    RejectedHomeserver::record(&hs_id, AdmissionRejection::NotAllowlisted, &user_id).await?;
+    let rejected =
+        RejectedHomeserver::record(&hs_id, AdmissionRejection::Denylisted, &user_id).await?;
+    assert_eq!(rejected.attempts, 2);
+
+    // The most recently rejected homeserver is listed first
+    let body = get_request("/v0/info/homeservers/rejected?limit=1").await?;
+    let listed = body
+        .as_array()
+        .and_then(|rejected| rejected.first())
+        .expect("Rejected homeservers should be listed");
+
+    assert_eq!(listed["id"], hs_id.as_str());
+    assert_eq!(listed["reason"], "denylisted");
+    assert_eq!(listed["referenced_by"], user_id.as_str());
+    assert_eq!(listed["attempts"], 2);
+
+    Ok(())
+}
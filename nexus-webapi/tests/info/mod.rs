@@ -0,0 +1,2 @@
+mod dead_letter;
+mod homeservers;
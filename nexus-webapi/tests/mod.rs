@@ -1,6 +1,9 @@
+pub mod admin;
 pub mod endpoints;
 pub mod events;
 pub mod files;
+pub mod homeserver;
+pub mod info;
 pub mod post;
 pub mod resource;
 pub mod stream;
@@ -0,0 +1,69 @@
+use crate::utils::{get_request, invalid_get_request};
+use anyhow::Result;
+use axum::http::StatusCode;
+use nexus_common::models::feed::FeedDetails;
+use pubky_app_specs::{PubkyAppFeedLayout, PubkyAppFeedReach, PubkyAppFeedSort};
+
+use super::utils::search_tag_in_post;
+use super::{POST_A, POST_B, POST_C, POST_D, POST_E, POST_F};
+use super::{ROOT_PATH, TAG_LABEL_2, USER_ID};
+
+const FEED_ID: &str = "WEBAPISTREAMFEEDOPENSOURCE";
+
+/// Indexes a feed of posts tagged with `opensource`, as the watcher would
+async fn seed_feed() -> Result<()> {
+    crate::utils::server::TestServiceServer::get_test_server().await;
+    let feed = FeedDetails {
+        id: FEED_ID.to_string(),
+        owner_id: USER_ID.to_string(),
+        uri: format!("pubky://{USER_ID}/pub/pubky.app/feeds/{FEED_ID}"),
+        name: "Open source".to_string(),
+        tags: Some(vec![TAG_LABEL_2.to_string()]),
+        reach: PubkyAppFeedReach::All,
+        layout: PubkyAppFeedLayout::Columns,
+        sort: PubkyAppFeedSort::Recent,
+        content: None,
+        created_at: 1_700_000_000_000,
+        indexed_at: 1_700_000_000_000,
+    };
+    feed.put_to_graph()
+        .await
+        .map_err(|e| anyhow::anyhow!("{e}"))?;
+    feed.put_to_index()
+        .await
+        .map_err(|e| anyhow::anyhow!("{e}"))
+}
+
+#[tokio_shared_rt::test(shared)]
+async fn test_stream_feed_posts() -> Result<()> {
+    seed_feed().await?;
+
+    // Same results as the equivalent stream query, see `test_post_tag_search`
+    let post_order = vec![POST_C, POST_B, POST_A, POST_D, POST_E, POST_F];
+    let path = format!("{ROOT_PATH}/feed/{USER_ID}/{FEED_ID}?limit=6");
+    let body = get_request(&path).await?;
+    let posts = body.as_array().expect("Post stream should be an array");
+    assert_eq!(posts.len(), 6);
+    search_tag_in_post(posts, TAG_LABEL_2, post_order);
+
+    // The feed is listed among the feeds of its owner
+    let feeds = get_request(&format!("/v0/user/{USER_ID}/feeds")).await?;
+    let feed = feeds
+        .as_array()
+        .expect("User feeds should be an array")
+        .iter()
+        .find(|feed| feed["id"] == FEED_ID)
+        .expect("The seeded feed should be listed");
+    assert_eq!(feed["name"], "Open source");
+    assert_eq!(feed["reach"], "all");
+    assert_eq!(feed["tags"][0], TAG_LABEL_2);
+
+    Ok(())
+}
+
+#[tokio_shared_rt::test(shared)]
+async fn test_stream_feed_posts_not_found() -> Result<()> {
+    let path = format!("{ROOT_PATH}/feed/{USER_ID}/UNKNOWNFEED");
+    invalid_get_request(&path, StatusCode::NOT_FOUND).await?;
+    Ok(())
+}
@@ -3,6 +3,7 @@ use nexus_webapi::routes::v0::endpoints;
 pub mod author;
 pub mod author_replies;
 pub mod bookmarks;
+pub mod feed;
 pub mod kind;
 pub mod post_keys;
 pub mod post_replies;
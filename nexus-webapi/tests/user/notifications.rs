@@ -1,20 +1,32 @@
-use crate::utils::get_request;
+use crate::utils::{
+    get_request, host_url, invalid_get_request, invalid_put_request, put_request_as,
+};
 use anyhow::Result;
+use axum::http::StatusCode;
+use futures_util::{Stream, StreamExt};
 use nexus_common::{
     db::RedisOps,
-    models::notification::{Notification, NotificationBody},
+    models::follow::{Following, UserFollows},
+    models::notification::{LastRead, Notification, NotificationBody},
 };
+use pubky::Keypair;
+use serde_json::Value;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::Message;
 
 async fn env_init() {
     crate::utils::server::TestServiceServer::get_test_server().await;
 }
 
-/// Inserts a single follow notification into the Redis sorted set with an explicit
-/// timestamp, bypassing `new_follow` which would use `Utc::now()` internally.
-async fn seed_follow(recipient_id: &str, follower_id: &str, timestamp: i64) -> Result<()> {
-    let body = NotificationBody::Follow {
-        followed_by: follower_id.to_string(),
-    };
+/// Inserts a single notification into the Redis sorted set with an explicit timestamp,
+/// bypassing the `Notification` constructors which would use `Utc::now()` internally.
+async fn seed_notification(
+    recipient_id: &str,
+    body: NotificationBody,
+    timestamp: i64,
+) -> Result<()> {
     let json = serde_json::to_string(&body).unwrap();
     Notification::put_index_sorted_set(
         &["Notification", recipient_id],
@@ -26,6 +38,14 @@ async fn seed_follow(recipient_id: &str, follower_id: &str, timestamp: i64) -> R
     .map_err(|e| anyhow::anyhow!("{e}"))
 }
 
+/// Inserts a single follow notification with an explicit timestamp
+async fn seed_follow(recipient_id: &str, follower_id: &str, timestamp: i64) -> Result<()> {
+    let body = NotificationBody::Follow {
+        followed_by: follower_id.to_string(),
+    };
+    seed_notification(recipient_id, body, timestamp).await
+}
+
 /// Seeds 3 follow notifications (A oldest → C newest) for a dedicated test user, then
 /// verifies that limit=2 returns exactly the 2 newest items in the correct descending
 /// order with correct body content.
@@ -297,3 +317,374 @@ async fn test_get_notifications_with_limit_and_skip() -> Result<()> {
 
     Ok(())
 }
+
+/// Seeds 3 notifications, marks the oldest as read and verifies the unread count and the
+/// `unread_only` filter, alone and combined with a timeframe.
+#[tokio_shared_rt::test(shared)]
+async fn test_get_unread_notifications() -> Result<()> {
+    env_init().await;
+    const TEST_USER: &str = "799xoiqa9ebtxbz85ti3dtak7qj48qdft8grkd7ap3ge6h54dr6y";
+    const FOLLOWER_A: &str = "78s3myezjaphtmm3bm5tn4yk3ze7zwb9ufo7gaooz43d48z19i9o";
+    const FOLLOWER_B: &str = "iif4wc9bpjkc8r9k386bj66mktc75w6rnhfwyma4rcswp15uwxho";
+    const FOLLOWER_C: &str = "z76tfiz5bf3s57e18m1uxuoefh8sw7333gjhg66kazqx1mkf9ano";
+
+    seed_follow(TEST_USER, FOLLOWER_A, 1000).await?;
+    seed_follow(TEST_USER, FOLLOWER_B, 2000).await?;
+    seed_follow(TEST_USER, FOLLOWER_C, 3000).await?;
+
+    // Without a last read timestamp, all notifications are unread
+    LastRead::del_from_index(TEST_USER).await?;
+    let unread = get_request(&format!("/v0/user/{TEST_USER}/notifications/unread")).await?;
+    assert_eq!(unread["unread_count"], 3);
+    assert!(unread["last_read"].is_null());
+
+    LastRead { timestamp: 1000 }.put_to_index(TEST_USER).await?;
+    let unread = get_request(&format!("/v0/user/{TEST_USER}/notifications/unread")).await?;
+    assert_eq!(unread["unread_count"], 2);
+    assert_eq!(unread["last_read"], 1000_i64);
+
+    // unread_only excludes A(1000), which was read
+    let res = get_request(&format!(
+        "/v0/user/{TEST_USER}/notifications?unread_only=true"
+    ))
+    .await?;
+    let items = res.as_array().unwrap();
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0]["body"]["followed_by"], FOLLOWER_C);
+    assert_eq!(items[1]["body"]["followed_by"], FOLLOWER_B);
+
+    // unread_only=false lists them all
+    let res = get_request(&format!(
+        "/v0/user/{TEST_USER}/notifications?unread_only=false"
+    ))
+    .await?;
+    assert_eq!(res.as_array().unwrap().len(), 3);
+
+    // Combined with a timeframe, the narrowest lower bound applies
+    let res = get_request(&format!(
+        "/v0/user/{TEST_USER}/notifications?unread_only=true&end=500&start=2500"
+    ))
+    .await?;
+    let items = res.as_array().unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["body"]["followed_by"], FOLLOWER_B);
+
+    Ok(())
+}
+
+/// Reads the Server-Sent Events stream until `count` more notification events are received,
+/// returning their data
+async fn read_notification_events(stream: &mut TcpStream, count: usize) -> Result<Vec<Value>> {
+    let mut received = String::new();
+    let mut buf = [0u8; 4096];
+    loop {
+        // Only the complete events, which end with a blank line
+        let complete = received
+            .rsplit_once("\n\n")
+            .map_or("", |(complete, _)| complete);
+        let events: Vec<Value> = complete
+            .lines()
+            .filter_map(|line| line.strip_prefix("data: "))
+            .map(serde_json::from_str)
+            .collect::<std::result::Result<_, _>>()?;
+        if events.len() >= count {
+            return Ok(events);
+        }
+        let n = tokio::time::timeout(Duration::from_secs(5), stream.read(&mut buf)).await??;
+        anyhow::ensure!(n > 0, "Notification stream closed");
+        received.push_str(&String::from_utf8_lossy(&buf[..n]));
+    }
+}
+
+/// Seeds 2 notifications, opens the notification stream with the oldest as last received event,
+/// and verifies the missed one is sent first, followed by the new one as it is indexed.
+#[tokio_shared_rt::test(shared)]
+async fn test_stream_notifications() -> Result<()> {
+    env_init().await;
+    const TEST_USER: &str = "bskypm9tny1xfkwfj8a57n1pein9ja4rep41dguwjzxb896qf47o";
+    const FOLLOWER_A: &str = "njyi5k6pxw3o1ih5ikaotdkt4b8ze3dzobr3gn5k19moxwweruco";
+    const FOLLOWER_B: &str = "1nnft9r8319ptfkztuuiwfupez4hebxhohrhnod3omeywa7h7exy";
+    const FOLLOWER_C: &str = "137sxbqzd4mdwxgut5r8q4j71i9geh71r7awap3giem4noqxyk6y";
+
+    seed_follow(TEST_USER, FOLLOWER_A, 1000).await?;
+    seed_follow(TEST_USER, FOLLOWER_B, 2000).await?;
+
+    let host = host_url().await;
+    let host = host.trim_start_matches("http://");
+    let mut stream = TcpStream::connect(host).await?;
+    let request = format!(
+        "GET /v0/user/{TEST_USER}/notifications/stream HTTP/1.1\r\n\
+         Host: {host}\r\n\
+         Accept: text/event-stream\r\n\
+         Last-Event-ID: 1000\r\n\r\n"
+    );
+    stream.write_all(request.as_bytes()).await?;
+
+    // The notification missed since the last received event is sent first
+    let events = read_notification_events(&mut stream, 1).await?;
+    assert_eq!(events[0]["timestamp"], 2000);
+    assert_eq!(events[0]["body"]["followed_by"], FOLLOWER_B);
+
+    // Then the notifications are pushed as they are indexed
+    Notification::new_follow(FOLLOWER_C, TEST_USER, false)
+        .await
+        .map_err(|e| anyhow::anyhow!("{e}"))?;
+    let events = read_notification_events(&mut stream, 1).await?;
+    assert_eq!(events[0]["body"]["type"], "follow");
+    assert_eq!(events[0]["body"]["followed_by"], FOLLOWER_C);
+
+    Ok(())
+}
+
+/// Seeds 3 notifications, 2 of them sharing a timestamp, and verifies that reconnecting with the
+/// id of the first of the 2 as last received event only sends the other one, and that the events
+/// are identified by the notification ids.
+#[tokio_shared_rt::test(shared)]
+async fn test_stream_notifications_sharing_a_timestamp() -> Result<()> {
+    env_init().await;
+    let test_user = pubky::Keypair::random().public_key().to_z32();
+    const FOLLOWER_A: &str = "njyi5k6pxw3o1ih5ikaotdkt4b8ze3dzobr3gn5k19moxwweruco";
+    const FOLLOWER_B: &str = "1nnft9r8319ptfkztuuiwfupez4hebxhohrhnod3omeywa7h7exy";
+    const FOLLOWER_C: &str = "137sxbqzd4mdwxgut5r8q4j71i9geh71r7awap3giem4noqxyk6y";
+
+    seed_follow(&test_user, FOLLOWER_A, 1000).await?;
+    seed_follow(&test_user, FOLLOWER_B, 2000).await?;
+    seed_follow(&test_user, FOLLOWER_C, 2000).await?;
+    let follow = |follower: &str| Notification {
+        timestamp: 2000,
+        body: NotificationBody::Follow {
+            followed_by: follower.to_string(),
+        },
+    };
+
+    let host = host_url().await;
+    let host = host.trim_start_matches("http://");
+    let mut stream = TcpStream::connect(host).await?;
+    let request = format!(
+        "GET /v0/user/{test_user}/notifications/stream HTTP/1.1\r\n\
+         Host: {host}\r\n\
+         Accept: text/event-stream\r\n\
+         Last-Event-ID: {}\r\n\r\n",
+        follow(FOLLOWER_B).id()
+    );
+    stream.write_all(request.as_bytes()).await?;
+
+    let events = read_notification_events(&mut stream, 1).await?;
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0]["timestamp"], 2000);
+    assert_eq!(events[0]["body"]["followed_by"], FOLLOWER_C);
+
+    Ok(())
+}
+
+/// Reads the next message of the notification WebSocket as JSON
+async fn read_socket_message<S>(socket: &mut S) -> Result<Value>
+where
+    S: Stream<Item = std::result::Result<Message, tokio_tungstenite::tungstenite::Error>> + Unpin,
+{
+    let message = tokio::time::timeout(Duration::from_secs(5), socket.next())
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Notification socket closed"))??;
+    Ok(serde_json::from_str(message.to_text()?)?)
+}
+
+/// Seeds 2 notifications, opens the notification WebSocket with the oldest as last received one,
+/// and verifies the missed one is sent first, followed by the new one as it is indexed.
+#[tokio_shared_rt::test(shared)]
+async fn test_notification_socket() -> Result<()> {
+    env_init().await;
+    let test_user = pubky::Keypair::random().public_key().to_z32();
+    const FOLLOWER_A: &str = "njyi5k6pxw3o1ih5ikaotdkt4b8ze3dzobr3gn5k19moxwweruco";
+    const FOLLOWER_B: &str = "1nnft9r8319ptfkztuuiwfupez4hebxhohrhnod3omeywa7h7exy";
+    const FOLLOWER_C: &str = "137sxbqzd4mdwxgut5r8q4j71i9geh71r7awap3giem4noqxyk6y";
+
+    seed_follow(&test_user, FOLLOWER_A, 1000).await?;
+    seed_follow(&test_user, FOLLOWER_B, 2000).await?;
+
+    let host = host_url().await;
+    let url = format!(
+        "{}/v0/user/{test_user}/notifications/ws?since=1000",
+        host.replacen("http://", "ws://", 1)
+    );
+    let (mut socket, _) = tokio_tungstenite::connect_async(url).await?;
+
+    // The notification missed since the last received one is sent first
+    let message = read_socket_message(&mut socket).await?;
+    assert_eq!(message["timestamp"], 2000);
+    assert_eq!(message["body"]["followed_by"], FOLLOWER_B);
+    let missed = Notification {
+        timestamp: 2000,
+        body: NotificationBody::Follow {
+            followed_by: FOLLOWER_B.to_string(),
+        },
+    };
+    assert_eq!(message["id"], missed.id());
+
+    // Then the notifications are pushed as they are indexed
+    Notification::new_follow(FOLLOWER_C, &test_user, false)
+        .await
+        .map_err(|e| anyhow::anyhow!("{e}"))?;
+    let message = read_socket_message(&mut socket).await?;
+    assert_eq!(message["body"]["type"], "follow");
+    assert_eq!(message["body"]["followed_by"], FOLLOWER_C);
+
+    Ok(())
+}
+
+/// Seeds follows, tags on the same post and a mention, then verifies the `types` filter of the
+/// notification list and the grouping of notifications by type, target and time window.
+#[tokio_shared_rt::test(shared)]
+async fn test_get_notifications_by_type_and_grouped() -> Result<()> {
+    env_init().await;
+    const TEST_USER: &str = "6ffyzphruoczgjye44ypmqbim3gfmyin4srxmkzugakabrzpemuy";
+    const USER_A: &str = "fkud49ee71x6u474zyi4wuho6tow18ycty79j9o13ty3icetf9ey";
+    const USER_B: &str = "pdr97mss6o1o5z5xjywhz6roobtr531nx7u9fcksfixicimga8my";
+    const USER_C: &str = "aobcj1okn9jkanxiomttpyngnr9nku8irhtnjq4zeijcdpim8zoo";
+    let post_uri = format!("pubky://{TEST_USER}/pub/pubky.app/posts/0032SSN7Q4EVG");
+    let tag_post = |tagged_by: &str| NotificationBody::TagPost {
+        tagged_by: tagged_by.to_string(),
+        tag_label: "rust".to_string(),
+        post_uri: post_uri.clone(),
+    };
+
+    seed_follow(TEST_USER, USER_A, 1000).await?;
+    seed_follow(TEST_USER, USER_B, 2000).await?;
+    seed_notification(TEST_USER, tag_post(USER_C), 3000).await?;
+    seed_notification(TEST_USER, tag_post(USER_A), 4000).await?;
+    let mention = NotificationBody::Mention {
+        mentioned_by: USER_B.to_string(),
+        post_uri: format!("pubky://{USER_B}/pub/pubky.app/posts/0032SSN7Q4EVH"),
+    };
+    seed_notification(TEST_USER, mention, 5000).await?;
+
+    // Only the follows and the mention are listed
+    let res = get_request(&format!(
+        "/v0/user/{TEST_USER}/notifications?types=mention,follow"
+    ))
+    .await?;
+    let items = res.as_array().unwrap();
+    let timestamps: Vec<i64> = items
+        .iter()
+        .map(|n| n["timestamp"].as_i64().unwrap())
+        .collect();
+    assert_eq!(timestamps, vec![5000, 2000, 1000]);
+
+    // Pagination applies to the filtered notifications
+    let res = get_request(&format!(
+        "/v0/user/{TEST_USER}/notifications?types=follow&skip=1&limit=1"
+    ))
+    .await?;
+    let items = res.as_array().unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["body"]["followed_by"], USER_A);
+
+    invalid_get_request(
+        &format!("/v0/user/{TEST_USER}/notifications?types=unknown"),
+        StatusCode::BAD_REQUEST,
+    )
+    .await?;
+
+    // Within the default window of a day, the tags and the follows are grouped
+    let res = get_request(&format!("/v0/user/{TEST_USER}/notifications/grouped")).await?;
+    let groups = res.as_array().unwrap();
+    assert_eq!(groups.len(), 3);
+    assert_eq!(groups[0]["type"], "mention");
+    assert_eq!(groups[1]["type"], "tag_post");
+    assert_eq!(groups[1]["post_uri"], post_uri.as_str());
+    assert_eq!(groups[1]["tag_label"], "rust");
+    assert_eq!(groups[1]["actors"], serde_json::json!([USER_A, USER_C]));
+    assert_eq!(groups[1]["actors_count"], 2);
+    assert_eq!(groups[1]["count"], 2);
+    assert_eq!(groups[1]["timestamp"], 4000);
+    assert_eq!(groups[1]["oldest_timestamp"], 3000);
+    assert_eq!(groups[2]["type"], "follow");
+    assert_eq!(groups[2]["actors"], serde_json::json!([USER_B, USER_A]));
+
+    // Grouping only the selected types
+    let res = get_request(&format!(
+        "/v0/user/{TEST_USER}/notifications/grouped?types=tag_post"
+    ))
+    .await?;
+    assert_eq!(res.as_array().unwrap().len(), 1);
+
+    // Narrower windows split the follows, which are 1s apart
+    let res = get_request(&format!(
+        "/v0/user/{TEST_USER}/notifications/grouped?window=1500&skip=2"
+    ))
+    .await?;
+    let groups = res.as_array().unwrap();
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[0]["actors"], serde_json::json!([USER_B]));
+    assert_eq!(groups[1]["actors"], serde_json::json!([USER_A]));
+
+    invalid_get_request(
+        &format!("/v0/user/{TEST_USER}/notifications/grouped?window=0"),
+        StatusCode::BAD_REQUEST,
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Mutes untags and tags from unfollowed users through the preferences endpoints, then verifies
+/// only the allowed notifications are stored.
+#[tokio_shared_rt::test(shared)]
+async fn test_notification_preferences() -> Result<()> {
+    env_init().await;
+    // Preferences are set by the user, signing the request with their keypair
+    let user_kp = Keypair::random();
+    let test_user = &user_kp.public_key().to_z32();
+    const FOLLOWED: &str = "51biwmhce1oq9btuqrbnimommoaqyrxu5eac79d8bg1cbzydruny";
+    const STRANGER: &str = "ktrddf9d59qpycjmswx9xnep9bsqzwhcn561utzuthq9911t66ey";
+    let endpoint = format!("/v0/user/{test_user}/notifications/preferences");
+
+    Following(vec![FOLLOWED.to_string()])
+        .put_to_index(test_user)
+        .await
+        .map_err(|e| anyhow::anyhow!("{e}"))?;
+
+    // Without stored preferences, nothing is muted
+    let res = get_request(&endpoint).await?;
+    assert_eq!(res["muted_types"], serde_json::json!([]));
+    assert_eq!(res["tags_from_following_only"], false);
+
+    let preferences = serde_json::json!({
+        "muted_types": ["untag_profile"],
+        "tags_from_following_only": true
+    });
+    // Only the user sets their preferences
+    invalid_put_request(&endpoint, preferences.clone(), StatusCode::UNAUTHORIZED).await?;
+    let (status, _) = put_request_as(&Keypair::random(), &endpoint, preferences.clone()).await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, res) = put_request_as(&user_kp, &endpoint, preferences).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(res["muted_types"], serde_json::json!(["untag_profile"]));
+    assert_eq!(res["muted_edit_sources"], serde_json::json!([]));
+
+    let res = get_request(&endpoint).await?;
+    assert_eq!(res["muted_types"], serde_json::json!(["untag_profile"]));
+    assert_eq!(res["tags_from_following_only"], true);
+
+    let to_anyhow = |e| anyhow::anyhow!("{e}");
+    Notification::new_user_untag(FOLLOWED, test_user, "rust")
+        .await
+        .map_err(to_anyhow)?;
+    Notification::new_user_tag(STRANGER, test_user, "rust")
+        .await
+        .map_err(to_anyhow)?;
+    Notification::new_user_tag(FOLLOWED, test_user, "rust")
+        .await
+        .map_err(to_anyhow)?;
+    Notification::new_follow(STRANGER, test_user, false)
+        .await
+        .map_err(to_anyhow)?;
+
+    let res = get_request(&format!("/v0/user/{test_user}/notifications")).await?;
+    let items = res.as_array().unwrap();
+    assert_eq!(items.len(), 2);
+    assert_eq!(items[0]["body"]["type"], "follow");
+    assert_eq!(items[1]["body"]["type"], "tag_profile");
+    assert_eq!(items[1]["body"]["tagged_by"], FOLLOWED);
+
+    Ok(())
+}
@@ -1,8 +1,11 @@
 use axum::http::{Method, StatusCode};
+use nexus_webapi::routes::v0::PUBKY_AUTH_SCHEME;
+use pubky::{AuthToken, Capabilities, Keypair};
 use serde_json::Value;
 use server::TestServiceServer;
 
 pub mod server;
+pub mod sse;
 
 pub(crate) async fn host_url() -> String {
     let test_server = TestServiceServer::get_test_server().await;
@@ -56,6 +59,50 @@ pub async fn invalid_post_request(
     Ok(body)
 }
 
+/// Sends a PUT request authorized by an auth token signed by `keypair`, as user-scoped writes are
+///
+/// # Returns
+/// The status and the JSON body of the response
+pub async fn put_request_as(
+    keypair: &Keypair,
+    endpoint: &str,
+    data: Value,
+) -> anyhow::Result<(StatusCode, Value)> {
+    let url = host_url().await;
+    let client = httpc_test::new_client("")?;
+    let token = AuthToken::sign(keypair, Capabilities::default());
+    let response = client
+        .reqwest_client()
+        .put(format!("{url}{endpoint}"))
+        .header(
+            "authorization",
+            format!("{PUBKY_AUTH_SCHEME} {}", hex::encode(token.serialize())),
+        )
+        .header("content-type", "application/json")
+        .body(data.to_string())
+        .send()
+        .await?;
+    let status = StatusCode::from_u16(response.status().as_u16())?;
+    Ok((status, serde_json::from_str(&response.text().await?)?))
+}
+
+pub async fn invalid_put_request(
+    endpoint: &str,
+    data: Value,
+    error_code: StatusCode,
+) -> Result<Value, httpc_test::Error> {
+    let url = host_url().await;
+    let full_endpoint = format!("{url}{endpoint}");
+    let body = inner_make_request(
+        &full_endpoint,
+        Some(Method::PUT),
+        Some(data),
+        Some(error_code),
+    )
+    .await?;
+    Ok(body)
+}
+
 // Small helper function to send requests.
 async fn inner_make_request(
     endpoint: &str,
@@ -16,6 +16,9 @@ pub struct TestServiceServer {
     pub temp_dir: TempDir,
 }
 
+/// Admin token of the test servers
+pub const TEST_ADMIN_TOKEN: &str = "test-admin-token";
+
 /// [TestServiceServer] with no key republisher
 static TEST_SERVER: OnceCell<TestServiceServer> = OnceCell::const_new();
 /// [TestServiceServer] where the [NexusApi] is initialized with a key republisher
@@ -63,6 +66,7 @@ impl TestServiceServer {
             // When we define the sockets, use local port 0 so OS assigns an available port
             public_addr: SocketAddr::from(([127, 0, 0, 1], 0)),
             pubky_listen_socket: SocketAddr::from(([127, 0, 0, 1], 0)),
+            admin_token: Some(TEST_ADMIN_TOKEN.to_string()),
             ..Default::default()
         };
 
@@ -0,0 +1,82 @@
+use super::host_url;
+use anyhow::Result;
+use serde_json::Value;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// Event received on a Server-Sent Events stream
+pub struct SseEvent {
+    /// Id of the event, empty if it has none
+    pub id: String,
+    pub data: Value,
+}
+
+/// Server-Sent Events stream read from a raw connection, as the HTTP test client waits for the
+/// whole response
+pub struct SseStream {
+    stream: TcpStream,
+    /// Received bytes not parsed into events yet
+    received: String,
+}
+
+impl SseStream {
+    /// Opens the stream served at `endpoint`, sending the extra request `headers`
+    pub async fn connect(endpoint: &str, headers: &[(&str, &str)]) -> Result<Self> {
+        let url = host_url().await;
+        let host = url.trim_start_matches("http://");
+        let mut stream = TcpStream::connect(host).await?;
+
+        let mut request = format!(
+            "GET {endpoint} HTTP/1.1\r\n\
+             Host: {host}\r\n\
+             Accept: text/event-stream\r\n"
+        );
+        for (name, value) in headers {
+            request.push_str(&format!("{name}: {value}\r\n"));
+        }
+        request.push_str("\r\n");
+        stream.write_all(request.as_bytes()).await?;
+
+        Ok(Self {
+            stream,
+            received: String::new(),
+        })
+    }
+
+    /// Reads until `count` more events with data are received, and returns them
+    pub async fn read_events(&mut self, count: usize) -> Result<Vec<SseEvent>> {
+        let mut events = Vec::new();
+        let mut buf = [0u8; 4096];
+        loop {
+            // Only the complete events, which end with a blank line
+            while events.len() < count {
+                let Some((event, rest)) = self.received.split_once("\n\n") else {
+                    break;
+                };
+                let mut id = String::new();
+                let mut data = None;
+                for line in event.lines() {
+                    if let Some(event_id) = line.strip_prefix("id: ") {
+                        id = event_id.to_string();
+                    } else if let Some(event_data) = line.strip_prefix("data: ") {
+                        data = Some(serde_json::from_str(event_data)?);
+                    }
+                }
+                self.received = rest.to_string();
+                // Keep-alive comments have no data
+                if let Some(data) = data {
+                    events.push(SseEvent { id, data });
+                }
+            }
+            if events.len() >= count {
+                return Ok(events);
+            }
+
+            let n =
+                tokio::time::timeout(Duration::from_secs(5), self.stream.read(&mut buf)).await??;
+            anyhow::ensure!(n > 0, "Event stream closed");
+            self.received.push_str(&String::from_utf8_lossy(&buf[..n]));
+        }
+    }
+}
@@ -44,6 +44,14 @@ pub enum NexusCommands {
     #[command(subcommand)]
     Db(DbCommands),
 
+    /// Homeserver event operations
+    #[command(subcommand)]
+    Events(EventsCommands),
+
+    /// User operations
+    #[command(subcommand)]
+    User(UserCommands),
+
     /// Run both the API and the Watcher (default when no arguments are given)
     #[command(hide = true)]
     Run {
@@ -65,6 +73,77 @@ pub struct WatcherArgs {
     /// Optional configuration file for the watcher
     #[arg(short, long, default_value_os_t = default_config_dir_path(), value_parser = validate_config_dir_path)]
     pub config_dir: PathBuf,
+
+    /// Run a one-off watcher operation instead of the event watcher
+    #[command(subcommand)]
+    pub command: Option<WatcherCommands>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum WatcherCommands {
+    /// Re-ingest a cursor range of a homeserver's events, without moving its stored cursor
+    Replay(ReplayArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct ReplayArgs {
+    /// Homeserver to read the events from
+    #[arg(long, required = true)]
+    pub homeserver: String,
+
+    /// Cursor to start reading from
+    #[arg(long, required = true)]
+    pub from: String,
+
+    /// Cursor of the last event to replay (defaults to the latest event)
+    #[arg(long)]
+    pub to: Option<String>,
+
+    /// Only replay the events of this user
+    #[arg(long)]
+    pub user: Option<String>,
+
+    /// Read and summarize the events without indexing them
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum EventsCommands {
+    /// Import event lines and their blobs from a dump directory, without contacting any homeserver
+    Import(ImportArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct ImportArgs {
+    /// Dump directory: event-line files under `events/`, blobs at `<user_id>/pub/...`
+    #[arg(required = true)]
+    pub dir: PathBuf,
+
+    /// Directory containing `config.toml`
+    #[arg(short, long, default_value_os_t = default_config_dir_path(), value_parser = validate_config_dir_path)]
+    pub config_dir: PathBuf,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum UserCommands {
+    /// Converge the indexed posts, tags, follows, bookmarks and files of a user with their homeserver
+    Resync(ResyncArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct ResyncArgs {
+    /// User to resync
+    #[arg(required = true)]
+    pub user_id: String,
+
+    /// Directory containing `config.toml`
+    #[arg(short, long, default_value_os_t = default_config_dir_path(), value_parser = validate_config_dir_path)]
+    pub config_dir: PathBuf,
+
+    /// List the differences without applying them
+    #[arg(long)]
+    pub dry_run: bool,
 }
 
 #[derive(Subcommand, Debug)]
@@ -78,6 +157,24 @@ pub enum DbCommands {
     /// Manage database migrations
     #[command(subcommand)]
     Migration(MigrationCommands),
+
+    /// Inspect and replay events that exhausted their retries
+    DeadLetter(DeadLetterArgs),
+
+    /// Rebuild the graph and Redis indexes without contacting any homeserver.
+    /// Run `nexusd db clear` first for a full rebuild.
+    Rebuild(RebuildArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct RebuildArgs {
+    /// Directory containing `config.toml`
+    #[arg(short, long, default_value_os_t = default_config_dir_path(), value_parser = validate_config_dir_path)]
+    pub config_dir: PathBuf,
+
+    /// Replay the local event log written by the watcher (`event_log = true`)
+    #[arg(long, required = true)]
+    pub from_log: bool,
 }
 
 #[derive(Args, Debug)]
@@ -102,3 +199,57 @@ pub struct MigrationNewArgs {
     #[arg(required = true)]
     pub name: String,
 }
+
+#[derive(Args, Debug)]
+pub struct DeadLetterArgs {
+    /// Directory containing `config.toml`
+    #[arg(short, long, default_value_os_t = default_config_dir_path(), value_parser = validate_config_dir_path)]
+    pub config_dir: PathBuf,
+
+    #[command(subcommand)]
+    pub command: DeadLetterCommands,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum DeadLetterCommands {
+    /// List dead-lettered events, most recent first
+    List(DeadLetterListArgs),
+
+    /// Show a dead-lettered event with its last error
+    Show(DeadLetterKeyArgs),
+
+    /// Move dead-lettered events back to the retry queue
+    Requeue(DeadLetterSelectArgs),
+
+    /// Delete dead-lettered events
+    Purge(DeadLetterSelectArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct DeadLetterListArgs {
+    /// Number of events to skip
+    #[arg(long, default_value_t = 0)]
+    pub skip: usize,
+
+    /// Maximum number of events to list
+    #[arg(long, default_value_t = 20)]
+    pub limit: usize,
+}
+
+#[derive(Args, Debug)]
+pub struct DeadLetterKeyArgs {
+    /// Index key of the event, e.g. `PUT:{user_id}:{resource}:{id}`
+    #[arg(required = true)]
+    pub key: String,
+}
+
+#[derive(Args, Debug)]
+pub struct DeadLetterSelectArgs {
+    /// Index key of the event, e.g. `PUT:{user_id}:{resource}:{id}`
+    #[arg(required_unless_present = "all", conflicts_with = "all")]
+    pub key: Option<String>,
+
+    /// Apply to every dead-lettered event
+    #[arg(long)]
+    pub all: bool,
+}
@@ -0,0 +1,110 @@
+use crate::cli::{DeadLetterCommands, DeadLetterSelectArgs};
+use nexus_common::models::event::DeadLetterEvent;
+use nexus_common::types::DynError;
+use nexus_common::{DaemonConfig, StackManager};
+use nexus_watcher::events::retry::event::RetryEvent;
+use std::path::PathBuf;
+
+/// Operator tooling for the dead-letter index of the watcher
+pub struct DeadLetterManager {}
+
+impl DeadLetterManager {
+    /// Loads the [DaemonConfig] from `config_dir`, connects to the databases and runs the command
+    pub async fn run(config_dir: PathBuf, command: DeadLetterCommands) -> Result<(), DynError> {
+        let config = DaemonConfig::read_or_create_config_file(config_dir).await?;
+        StackManager::setup(&config.stack).await?;
+
+        match command {
+            DeadLetterCommands::List(args) => Self::list(args.skip, args.limit).await,
+            DeadLetterCommands::Show(args) => Self::show(&args.key).await,
+            DeadLetterCommands::Requeue(args) => Self::requeue(args).await,
+            DeadLetterCommands::Purge(args) => Self::purge(args).await,
+        }
+    }
+
+    async fn list(skip: usize, limit: usize) -> Result<(), DynError> {
+        let events = DeadLetterEvent::list(skip, limit).await?;
+        if events.is_empty() {
+            println!("No dead-lettered events");
+            return Ok(());
+        }
+
+        for event in events {
+            println!(
+                "{}\t{}\tretries={}\thomeserver={}\t{}",
+                event.dead_lettered_at,
+                event.index_key,
+                event.retry_count,
+                event.homeserver.as_deref().unwrap_or("unknown"),
+                event.reason
+            );
+        }
+        Ok(())
+    }
+
+    async fn show(key: &str) -> Result<(), DynError> {
+        let event = DeadLetterEvent::get_from_index(key)
+            .await?
+            .ok_or(format!("Dead-lettered event not found: {key}"))?;
+        println!("{}", serde_json::to_string_pretty(&event)?);
+        Ok(())
+    }
+
+    async fn requeue(args: DeadLetterSelectArgs) -> Result<(), DynError> {
+        let count = match args.key {
+            Some(key) => {
+                let event = DeadLetterEvent::get_from_index(&key)
+                    .await?
+                    .ok_or(format!("Dead-lettered event not found: {key}"))?;
+                Self::requeue_event(event).await? as usize
+            }
+            None => Self::requeue_all().await?,
+        };
+        println!("Requeued {count} dead-lettered events");
+        Ok(())
+    }
+
+    /// Moves the event back to the retry queue, unless it has no event line to replay
+    async fn requeue_event(event: DeadLetterEvent) -> Result<bool, DynError> {
+        if event.event_line.is_none() {
+            println!("Skipping {}: no event line to replay", event.index_key);
+            return Ok(false);
+        }
+        RetryEvent::requeue_dead_letter(event).await?;
+        Ok(true)
+    }
+
+    /// Requeues the whole dead-letter index, a page at a time
+    async fn requeue_all() -> Result<usize, DynError> {
+        const PAGE_SIZE: usize = 500;
+
+        let mut count = 0;
+        // Requeued events leave the index, only the skipped ones stay ahead of the next page
+        let mut skip = 0;
+        loop {
+            let keys = DeadLetterEvent::list_keys(skip, PAGE_SIZE).await?;
+            for key in &keys {
+                let requeued = match DeadLetterEvent::get_from_index(key).await? {
+                    Some(event) => Self::requeue_event(event).await?,
+                    None => false,
+                };
+                match requeued {
+                    true => count += 1,
+                    false => skip += 1,
+                }
+            }
+            if keys.len() < PAGE_SIZE {
+                return Ok(count);
+            }
+        }
+    }
+
+    async fn purge(args: DeadLetterSelectArgs) -> Result<(), DynError> {
+        let count = match args.key {
+            Some(key) => DeadLetterEvent::remove_from_index(&key).await?,
+            None => DeadLetterEvent::purge().await?,
+        };
+        println!("Purged {count} dead-lettered events");
+        Ok(())
+    }
+}
@@ -0,0 +1,34 @@
+use crate::cli::ImportArgs;
+use nexus_common::types::DynError;
+use nexus_watcher::service::{EventImport, NexusWatcher};
+
+/// Operator tooling to import the events of a dump directory
+pub struct ImportManager {}
+
+impl ImportManager {
+    /// Imports the dump with the watcher config derived from `config_dir`
+    /// and prints a per-resource summary of the outcomes
+    pub async fn run(args: ImportArgs) -> Result<(), DynError> {
+        let import = EventImport { dir: args.dir };
+
+        let summary = NexusWatcher::import_from_daemon(args.config_dir, import).await?;
+
+        println!(
+            "Read {} events from {} files",
+            summary.events, summary.files
+        );
+        if summary.outcomes.is_empty() {
+            println!("No events to import");
+            return Ok(());
+        }
+
+        println!("resource\tindexed\tfailed\tskipped");
+        for (resource, outcomes) in summary.outcomes {
+            println!(
+                "{resource}\t{}\t{}\t{}",
+                outcomes.indexed, outcomes.failed, outcomes.skipped
+            );
+        }
+        Ok(())
+    }
+}
@@ -1,5 +1,15 @@
 pub mod cli;
+mod dead_letter;
+mod import;
 mod launcher;
 pub mod migrations;
+mod rebuild;
+mod replay;
+mod resync;
 
+pub use dead_letter::DeadLetterManager;
+pub use import::ImportManager;
 pub use launcher::DaemonLauncher;
+pub use rebuild::RebuildManager;
+pub use replay::ReplayManager;
+pub use resync::ResyncManager;
@@ -4,9 +4,14 @@ use nexus_common::StackManager;
 use nexus_watcher::service::NexusWatcher;
 use nexus_webapi::mock::MockDb;
 use nexus_webapi::NexusApi;
-use nexusd::cli::{ApiArgs, Cli, DbCommands, MigrationCommands, NexusCommands, WatcherArgs};
+use nexusd::cli::{
+    ApiArgs, Cli, DbCommands, EventsCommands, MigrationCommands, NexusCommands, UserCommands,
+    WatcherArgs, WatcherCommands,
+};
 use nexusd::migrations::{import_migrations, MigrationBuilder, MigrationManager};
-use nexusd::DaemonLauncher;
+use nexusd::{
+    DaemonLauncher, DeadLetterManager, ImportManager, RebuildManager, ReplayManager, ResyncManager,
+};
 
 #[tokio::main]
 async fn main() -> Result<(), DynError> {
@@ -26,13 +31,23 @@ async fn main() -> Result<(), DynError> {
                     mm.run(&builder.migrations_backfill_ready()).await?;
                 }
             },
+            DbCommands::DeadLetter(args) => {
+                DeadLetterManager::run(args.config_dir, args.command).await?
+            }
+            DbCommands::Rebuild(args) => RebuildManager::run(args).await?,
         },
+        NexusCommands::Events(EventsCommands::Import(args)) => ImportManager::run(args).await?,
+        NexusCommands::User(UserCommands::Resync(args)) => ResyncManager::run(args).await?,
         NexusCommands::Api(ApiArgs { config_dir }) => {
             NexusApi::start_from_daemon(config_dir, None).await?;
         }
-        NexusCommands::Watcher(WatcherArgs { config_dir }) => {
-            NexusWatcher::start_from_daemon(config_dir, None).await?;
-        }
+        NexusCommands::Watcher(WatcherArgs {
+            config_dir,
+            command,
+        }) => match command {
+            None => NexusWatcher::start_from_daemon(config_dir, None).await?,
+            Some(WatcherCommands::Replay(args)) => ReplayManager::run(config_dir, args).await?,
+        },
         NexusCommands::Run { config_dir } => {
             DaemonLauncher::start(config_dir, None).await?;
         }
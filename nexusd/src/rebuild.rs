@@ -0,0 +1,47 @@
+use crate::cli::RebuildArgs;
+use nexus_common::types::DynError;
+use nexus_watcher::service::NexusWatcher;
+
+/// Operator tooling to rebuild the indexes from the local event log
+pub struct RebuildManager {}
+
+impl RebuildManager {
+    /// Rebuilds the indexes from the event log under the `files_path` of the config in
+    /// `config_dir` and prints a per-resource summary of the outcomes
+    pub async fn run(args: RebuildArgs) -> Result<(), DynError> {
+        // `--from-log` is the only rebuild source, clap enforces it
+        debug_assert!(args.from_log);
+
+        let summary = NexusWatcher::rebuild_from_daemon(args.config_dir).await?;
+
+        println!(
+            "Read {} entries from {} segments",
+            summary.entries, summary.segments
+        );
+        for (homeserver, cursor) in &summary.cursors {
+            println!("Restored cursor {cursor} for homeserver {homeserver}");
+        }
+        if !summary.refused.is_empty() {
+            println!(
+                "Refused {} events whose blobs are not logged, replay them from their homeserver:",
+                summary.refused.len()
+            );
+            for line in &summary.refused {
+                println!("{line}");
+            }
+        }
+        if summary.outcomes.is_empty() {
+            println!("No events to rebuild");
+            return Ok(());
+        }
+
+        println!("resource\tindexed\tfailed\tskipped");
+        for (resource, outcomes) in summary.outcomes {
+            println!(
+                "{resource}\t{}\t{}\t{}",
+                outcomes.indexed, outcomes.failed, outcomes.skipped
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,47 @@
+use crate::cli::ReplayArgs;
+use nexus_common::types::DynError;
+use nexus_watcher::service::{EventReplay, NexusWatcher};
+use pubky_app_specs::PubkyId;
+use std::path::PathBuf;
+
+/// Operator tooling to re-ingest a cursor range of homeserver events
+pub struct ReplayManager {}
+
+impl ReplayManager {
+    /// Replays the requested range with the watcher config derived from `config_dir`
+    /// and prints a per-resource summary of the outcomes
+    pub async fn run(config_dir: PathBuf, args: ReplayArgs) -> Result<(), DynError> {
+        let replay = EventReplay {
+            homeserver: PubkyId::try_from(args.homeserver.as_str())?,
+            from: args.from,
+            to: args.to,
+            user: args.user.as_deref().map(PubkyId::try_from).transpose()?,
+            dry_run: args.dry_run,
+        };
+        let dry_run = replay.dry_run;
+
+        let summary = NexusWatcher::replay_from_daemon(config_dir, replay).await?;
+
+        if dry_run {
+            println!("Dry run: no event was indexed, matched events are reported as skipped");
+        }
+        println!(
+            "Read {} pages, last cursor: {}",
+            summary.pages,
+            summary.last_cursor.as_deref().unwrap_or("none")
+        );
+        if summary.outcomes.is_empty() {
+            println!("No events to replay");
+            return Ok(());
+        }
+
+        println!("resource\tindexed\tfailed\tskipped");
+        for (resource, outcomes) in summary.outcomes {
+            println!(
+                "{resource}\t{}\t{}\t{}",
+                outcomes.indexed, outcomes.failed, outcomes.skipped
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,46 @@
+use crate::cli::ResyncArgs;
+use nexus_common::types::DynError;
+use nexus_watcher::service::{NexusWatcher, UserResync};
+use pubky_app_specs::PubkyId;
+
+/// Operator tooling to converge the indexed resources of a single user with their homeserver
+pub struct ResyncManager {}
+
+impl ResyncManager {
+    /// Resyncs the user with the watcher config derived from `config_dir` and prints the
+    /// resources put and deleted
+    pub async fn run(args: ResyncArgs) -> Result<(), DynError> {
+        let resync = UserResync {
+            user: PubkyId::try_from(args.user_id.as_str())?,
+            dry_run: args.dry_run,
+        };
+
+        let summary = NexusWatcher::resync_from_daemon(args.config_dir, resync).await?;
+
+        if args.dry_run {
+            println!("Dry run: no resource was indexed or deleted");
+        }
+        println!("Homeserver: {}", summary.homeserver);
+        if summary.put.is_empty() && summary.deleted.is_empty() {
+            println!("The graph is in sync with the homeserver");
+            return Ok(());
+        }
+
+        for uri in &summary.put {
+            println!("PUT {uri}");
+        }
+        for uri in &summary.deleted {
+            println!("DEL {uri}");
+        }
+        if !summary.outcomes.is_empty() {
+            println!("resource\tindexed\tfailed\tskipped");
+            for (resource, outcomes) in summary.outcomes {
+                println!(
+                    "{resource}\t{}\t{}\t{}",
+                    outcomes.indexed, outcomes.failed, outcomes.skipped
+                );
+            }
+        }
+        Ok(())
+    }
+}